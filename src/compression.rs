@@ -0,0 +1,141 @@
+use crate::{ACCOUNT_COMPRESSION_PROGRAM_ID, NOOP_PROGRAM_ID};
+use core::array;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction, Signer},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+/// SPL Account Compression `init_empty_merkle_tree`, making `authority` the only signer allowed to
+/// append to the tree
+///
+/// - The tree account must already be allocated and owned by the compression program, sized for
+///   `max_depth` and `max_buffer_size` (and an optional canopy)
+pub struct InitEmptyMerkleTree<'a> {
+    pub merkle_tree: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub noop_program: &'a AccountInfo,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+}
+
+impl InitEmptyMerkleTree<'_> {
+    /// `sha256("global:init_empty_merkle_tree")[..8]`
+    const DISCRIMINATOR: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        if self.noop_program.key().ne(&NOOP_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let account_metas = [
+            AccountMeta::writable(self.merkle_tree.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+            AccountMeta::readonly(self.noop_program.key()),
+        ];
+
+        // discriminator [8] | max_depth [4] | max_buffer_size [4]
+        let mut data = [0u8; 16];
+        data[0..8].copy_from_slice(&Self::DISCRIMINATOR);
+        data[8..12].copy_from_slice(&self.max_depth.to_le_bytes());
+        data[12..16].copy_from_slice(&self.max_buffer_size.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: &ACCOUNT_COMPRESSION_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(&instruction, &[self.merkle_tree, self.authority, self.noop_program], signers)
+    }
+}
+
+/// SPL Account Compression `append`, adding `leaf` at the next index of the tree
+///
+/// - The change is logged through the noop program, so indexers can rebuild the tree from the
+///   transaction history
+pub struct Append<'a> {
+    pub merkle_tree: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub noop_program: &'a AccountInfo,
+    pub leaf: &'a [u8; 32],
+}
+
+impl Append<'_> {
+    /// `sha256("global:append")[..8]`
+    const DISCRIMINATOR: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        if self.noop_program.key().ne(&NOOP_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let account_metas = [
+            AccountMeta::writable(self.merkle_tree.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+            AccountMeta::readonly(self.noop_program.key()),
+        ];
+
+        // discriminator [8] | leaf [32]
+        let mut data = [0u8; 40];
+        data[0..8].copy_from_slice(&Self::DISCRIMINATOR);
+        data[8..40].copy_from_slice(self.leaf);
+
+        let instruction = Instruction {
+            program_id: &ACCOUNT_COMPRESSION_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(&instruction, &[self.merkle_tree, self.authority, self.noop_program], signers)
+    }
+}
+
+/// SPL Account Compression `verify_leaf`, failing unless `leaf` sits at `index` under `root`
+///
+/// - `root` must be the current root or one still in the tree's buffer of recent changes
+/// - `proof` lists the sibling nodes from the leaf up, as accounts; nodes kept in the tree's canopy
+///   can be left out
+pub struct VerifyLeaf<'a> {
+    pub merkle_tree: &'a AccountInfo,
+    pub proof: &'a [AccountInfo],
+    pub root: &'a [u8; 32],
+    pub leaf: &'a [u8; 32],
+    pub index: u32,
+}
+
+impl VerifyLeaf<'_> {
+    /// `sha256("global:verify_leaf")[..8]`
+    const DISCRIMINATOR: [u8; 8] = [124, 220, 22, 223, 104, 10, 250, 224];
+
+    /// Invoke with `ACCOUNTS`, the tree and its proof nodes, fixed at compile time
+    pub fn invoke<const ACCOUNTS: usize>(&self) -> ProgramResult {
+        if self.proof.len() + 1 != ACCOUNTS {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let account_infos: [&AccountInfo; ACCOUNTS] = array::from_fn(|index| match index {
+            0 => self.merkle_tree,
+            _ => &self.proof[index - 1],
+        });
+        let account_metas: [AccountMeta; ACCOUNTS] =
+            array::from_fn(|index| AccountMeta::readonly(account_infos[index].key()));
+
+        // discriminator [8] | root [32] | leaf [32] | index [4]
+        let mut data = [0u8; 76];
+        data[0..8].copy_from_slice(&Self::DISCRIMINATOR);
+        data[8..40].copy_from_slice(self.root);
+        data[40..72].copy_from_slice(self.leaf);
+        data[72..76].copy_from_slice(&self.index.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: &ACCOUNT_COMPRESSION_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke(&instruction, &account_infos)
+    }
+}
@@ -1,7 +1,80 @@
+use pinocchio::pubkey::Pubkey;
+
 /// Maximum fee basis points (100%)
-const MAX_FEE_BPS: u16 = 10000;
+pub const MAX_FEE_BPS: u16 = 10000;
+
+/// Standard swap fee tiers in basis points (0.01%, 0.05%, 0.3%, 1%)
+pub const FEE_TIERS: [u16; 4] = [1, 5, 30, 100];
+
+/// Instruction flag shared by Deposit, Withdraw and Swap: `expiration` is a slot, not a unix timestamp
+pub const EXPIRATION_IN_SLOTS: u8 = 1 << 7;
+
+/// Instruction flag shared by Deposit, Withdraw and Swap: run the checks and the math, set the return
+/// data and fail with `AmmError::SimulationComplete` before moving any token, for exact quotes through
+/// `simulateTransaction`. Transfer fees can't be observed, so amounts are before transfer fees.
+pub const DRY_RUN: u8 = 1 << 6;
+
+/// Instruction flag shared by Deposit, Withdraw and Swap: the slippage bounds are tolerances in basis
+/// points, up to `MAX_FEE_BPS`, around the fair amounts at the reserves met on execution. The fair
+/// amounts are the pool share of the LP for Deposit and Withdraw, and the input at the spot price,
+/// before the fee and the price impact, for Swap. Can't be combined with `EXACT_AMOUNTS`.
+pub const SLIPPAGE_IN_BPS: u8 = 1 << 5;
+
+/// Token-2022 `PermanentDelegate` extension type, lets the delegate move tokens out of any account
+pub const PERMANENT_DELEGATE_EXTENSION: u16 = 12;
+
+/// Token-2022 mint extension types whose token accounts need an account extension, with the length
+/// of that account extension: `TransferFeeConfig`, `NonTransferable` and `TransferHook`
+pub const ACCOUNT_EXTENSIONS_REQUIRED: [(u16, usize); 3] = [(1, 8), (9, 0), (14, 1)];
+
+/// Size of a Token-2022 mint with only the `NonTransferable` extension: the base mint padded to
+/// the token account size, the account type and an empty TLV entry
+pub const NON_TRANSFERABLE_MINT_LEN: usize = 165 + 1 + 4;
 
 /// Seed literals as byte arrays for zero-cost conversion
-const CONFIG_SEED: &[u8] = b"config";
-const LP_SEED: &[u8] = b"lp";
-const AUTH_SEED: &[u8] = b"auth";
\ No newline at end of file
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const LP_SEED: &[u8] = b"lp";
+pub const AUTH_SEED: &[u8] = b"auth";
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = [
+    0x06, 0xdd, 0xf6, 0xe1, 0xee, 0x75, 0x8f, 0xde, 0x18, 0x42, 0x5d, 0xbc, 0xe4, 0x6c, 0xcd, 0xda,
+    0xb6, 0x1a, 0xfc, 0x4d, 0x83, 0xb9, 0x0d, 0x27, 0xfe, 0xbd, 0xf9, 0x28, 0xd8, 0xa1, 0x8b, 0xfc,
+];
+
+/// rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = [
+    0x0c, 0xb7, 0xfa, 0xbb, 0x52, 0xf7, 0xa6, 0x48, 0xbb, 0x5b, 0x31, 0x7d, 0x9a, 0x01, 0x8b, 0x90,
+    0x57, 0xcb, 0x02, 0x47, 0x74, 0xfa, 0xfe, 0x01, 0xe6, 0xc4, 0xdf, 0x98, 0xcc, 0x38, 0x58, 0x81,
+];
+
+/// MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr
+pub const MEMO_PROGRAM_ID: Pubkey = [
+    0x05, 0x4a, 0x53, 0x5a, 0x99, 0x29, 0x21, 0x06, 0x4d, 0x24, 0xe8, 0x71, 0x60, 0xda, 0x38, 0x7c,
+    0x7c, 0x35, 0xb5, 0xdd, 0xbc, 0x92, 0xbb, 0x81, 0xe4, 0x1f, 0xa8, 0x40, 0x41, 0x05, 0x44, 0x8d,
+];
+
+/// Oracle prices older than this (in seconds) are rejected
+pub const ORACLE_MAX_AGE: i64 = 60;
+
+/// Shortest `InsuranceFund::timelock` (in seconds) between requesting and withdrawing insurance
+pub const MIN_INSURANCE_TIMELOCK: i64 = 2 * 24 * 60 * 60;
+
+/// Ed25519SigVerify111111111111111111111111111
+pub const ED25519_PROGRAM_ID: Pubkey = [
+    0x03, 0x7d, 0x46, 0xd6, 0x7c, 0x93, 0xfb, 0xbe, 0x12, 0xf9, 0x42, 0x8f, 0x83, 0x8d, 0x40, 0xff,
+    0x05, 0x70, 0x74, 0x49, 0x27, 0xf4, 0x8a, 0x64, 0xfc, 0xca, 0x70, 0x44, 0x80, 0x00, 0x00, 0x00,
+];
+
+/// cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = [
+    0x09, 0x2a, 0x13, 0xee, 0x95, 0xc4, 0x1c, 0xba, 0x08, 0xa6, 0x7f, 0x5a, 0xc6, 0x7e, 0x8d, 0xf7,
+    0xe1, 0xda, 0x11, 0x62, 0x5e, 0x1d, 0x64, 0x13, 0x7f, 0x8f, 0x4f, 0x23, 0x83, 0x03, 0x7f, 0x14,
+];
+
+/// noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV
+pub const NOOP_PROGRAM_ID: Pubkey = [
+    0x0b, 0xbc, 0x0f, 0xc0, 0xbb, 0x47, 0xca, 0x2f, 0x74, 0xc4, 0x11, 0x2e, 0x94, 0xab, 0x13, 0xcf,
+    0xa3, 0xc6, 0x34, 0xe5, 0xdc, 0x17, 0xea, 0xcb, 0x03, 0xcd, 0x1a, 0x23, 0xcd, 0x7e, 0x78, 0x7c,
+];
@@ -2,12 +2,124 @@
 
 use pinocchio::program_error::ProgramError;
 
-/// Errors that may be returned by the Token program.
+/// Errors that may be returned by the Amm program.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub enum EscrowError {}
+pub enum AmmError {
+    /// The registry page has no room left for another pool
+    RegistryPageFull,
+    /// The mints are not ordered as `mint_x < mint_y`
+    InvalidMintOrder,
+    /// mint_x and mint_y are the same mint
+    IdenticalMints,
+    /// The account is not an initialized mint owned by a token program
+    InvalidMint,
+    /// The pool was not created with NFT positions enabled
+    NftPositionsDisabled,
+    /// The token account does not hold the position NFT for this owner
+    InvalidPositionHolder,
+    /// The position is locked
+    PositionLocked,
+    /// The fee is not one of the standard fee tiers
+    InvalidFeeTier,
+    /// Custom fee tiers can only be used by pools with an authority
+    CustomFeeTierRequiresAuthority,
+    /// The config or LP mint account for this pool already exists
+    PoolAlreadyExists,
+    /// A strict pool was given a mint with a freeze authority or permanent delegate
+    RiskyMint,
+    /// The protocol is paused by the global admin
+    ProtocolPaused,
+    /// The oracle account is not the configured, fresh and fully verified price feed
+    InvalidOracle,
+    /// The swap executes too far from the oracle price
+    OraclePriceDeviation,
+    /// The transaction contains more than one swap against a single-swap pool
+    MultipleSwaps,
+    /// The config account already holds this extension
+    ExtensionAlreadyExists,
+    /// The config account doesn't hold the extension this operation needs
+    MissingExtension,
+    /// The deposit would push the pool reserves above their cap
+    DepositCapExceeded,
+    /// The pool only accepts deposits from whitelisted users
+    DepositorNotWhitelisted,
+    /// The launch has reached its target or already graduated
+    LaunchNotTrading,
+    /// The launch hasn't reached its target or already graduated
+    LaunchNotGraduable,
+    /// The preceding instruction is not an ed25519 verification of this quote by the maker, or of
+    /// this `DepositWithPermit` permit by the owner
+    InvalidQuoteSignature,
+    /// The strategy allowance expired or doesn't cover this instruction
+    StrategyNotAllowed,
+    /// The strategy spent more than its allowance
+    AllowanceExceeded,
+    /// An account that must sign the transaction didn't
+    AccountNotSigner,
+    /// An account the instruction writes to was passed as readonly
+    AccountNotWritable,
+    /// The deployment would exceed the share of the reserves the vault strategy may use
+    StrategyCapExceeded,
+    /// The withdraw-only pool only allows swaps toward its `ExitSwaps` price
+    ExitSwapRejected,
+    /// A pool reserve is empty or not above `GlobalConfig::min_swap_reserve`
+    InsufficientLiquidity,
+    /// The swap is too small to pay out anything after fees
+    ZeroSwapOutput,
+    /// The pool fee is outside the `GlobalConfig` bounds for new pools
+    PoolFeeOutOfBounds,
+    /// A pool flag isn't allowed by `GlobalConfig::allowed_pool_flags`
+    PoolFlagsNotAllowed,
+    /// A pool asset lives in a token program not allowed by `GlobalConfig::allowed_token_programs`
+    TokenProgramNotAllowed,
+    /// A `DRY_RUN` instruction completed, its quote is in the return data
+    SimulationComplete,
+    /// The fee distributor was already checkpointed in this epoch
+    CheckpointTooEarly,
+    /// The stake was added less than a full epoch ago
+    StakeLocked,
+    /// The router isn't in the `TrustedRouter` registry
+    UntrustedRouter,
+    /// The swap wasn't issued by a program in the pool's `CallerAllowlist`
+    CallerNotAllowed,
+    /// An amount computation overflowed or underflowed
+    MathOverflow,
+    /// `AssertPoolHealthy` found a broken invariant, its failures are in the return data
+    PoolUnhealthy,
+    /// The signer neither owns the token account nor is its delegate for the amount moved
+    InsufficientDelegation,
+    /// The pool is inside its `MaintenanceWindow`, only withdrawals are allowed
+    MaintenanceWindow,
+    /// The pool's `TradingCalendar` market is closed
+    MarketClosed,
+    /// No insurance withdrawal is pending, or its timelock hasn't passed
+    InsuranceLocked,
+    /// The LBP hasn't started, has ended or was closed
+    LbpNotTrading,
+    /// The LBP sale hasn't ended or the pool was already closed
+    LbpNotClosable,
+    /// The signer already swapped the other way in this slot, see `SameSlotGuard`
+    SameSlotReversal,
+    /// The pool already appended an observation in this slot, see `ObservationTree`
+    ObservationAlreadyRecorded,
+    /// The `Arbitrage` profit left after the rebate is below the caller's minimum
+    ArbitrageUnprofitable,
+    /// The global admin froze the protocol for a program upgrade, only withdrawals are allowed
+    UpgradeFrozen,
+    /// A mint of the new pool has no `TokenBadge`, required by the `GlobalConfig`
+    MissingTokenBadge,
+    /// The `LongTermOrder` has nothing due to sell since its last crank
+    OrderNotDue,
+    /// `AssertBalanceChange` found the token account moved outside the given bounds
+    BalanceChangeOutOfBounds,
+    /// The swap tip exceeds the `GlobalConfig` cap
+    TipAboveCap,
+    /// The `BalanceSnapshot` wasn't taken by a top-level `SnapshotBalance` earlier in the transaction
+    InvalidBalanceSnapshot,
+}
 
-impl From<EscrowError> for ProgramError {
-    fn from(e: EscrowError) -> Self {
+impl From<AmmError> for ProgramError {
+    fn from(e: AmmError) -> Self {
         ProgramError::Custom(6000 + e as u32)
     }
 }
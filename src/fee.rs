@@ -0,0 +1,105 @@
+use crate::MAX_FEE_BPS;
+use pinocchio::program_error::ProgramError;
+
+/// A fee in basis points, from 0 to `MAX_FEE_BPS` (100%)
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fee(u16);
+
+impl Fee {
+    pub const ZERO: Fee = Fee(0);
+
+    /// Build a fee from basis points, failing above `MAX_FEE_BPS`.
+    #[inline(always)]
+    pub fn from_bps(bps: u16) -> Result<Self, ProgramError> {
+        if bps > MAX_FEE_BPS {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Self(bps))
+    }
+
+    #[inline(always)]
+    pub const fn bps(self) -> u16 {
+        self.0
+    }
+
+    /// The fee as a percentage, e.g. 0.3 for 30 bps
+    #[inline(always)]
+    pub fn percent(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    /// Part of `amount` taken by the fee, rounding down
+    #[inline(always)]
+    pub fn apply(self, amount: u64) -> u64 {
+        (amount as u128 * self.0 as u128 / MAX_FEE_BPS as u128) as u64
+    }
+
+    /// The fee with `discount_bps` (in bps of the fee) waived, rounding the discount down
+    #[inline(always)]
+    pub fn discounted(self, discount_bps: u16) -> Self {
+        let discount = self.0 as u32 * discount_bps.min(MAX_FEE_BPS) as u32 / MAX_FEE_BPS as u32;
+
+        Self(self.0 - discount as u16)
+    }
+
+    /// Part of `amount` left once the fee is taken, rounding down
+    #[inline(always)]
+    pub fn remainder(self, amount: u64) -> u64 {
+        (amount as u128 * (MAX_FEE_BPS - self.0) as u128 / MAX_FEE_BPS as u128) as u64
+    }
+}
+
+impl TryFrom<u16> for Fee {
+    type Error = ProgramError;
+
+    fn try_from(bps: u16) -> Result<Self, Self::Error> {
+        Self::from_bps(bps)
+    }
+}
+
+impl From<Fee> for u16 {
+    fn from(fee: Fee) -> Self {
+        fee.bps()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_bps_caps_at_max_fee() {
+        assert_eq!(Fee::from_bps(MAX_FEE_BPS).map(Fee::bps), Ok(MAX_FEE_BPS));
+        assert_eq!(Fee::from_bps(MAX_FEE_BPS + 1).map(Fee::bps), Err(ProgramError::InvalidArgument));
+        assert_eq!(Fee::try_from(u16::MAX).map(Fee::bps), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn apply_and_remainder_hold_at_u64_max() {
+        let full = Fee::from_bps(MAX_FEE_BPS).unwrap();
+
+        assert_eq!(full.apply(u64::MAX), u64::MAX);
+        assert_eq!(full.remainder(u64::MAX), 0);
+        assert_eq!(Fee::ZERO.apply(u64::MAX), 0);
+        assert_eq!(Fee::ZERO.remainder(u64::MAX), u64::MAX);
+
+        // Both round down, the fee taken by a swap as `amount - remainder` never underflows
+        for bps in [1, 30, 100, 2_500, MAX_FEE_BPS - 1] {
+            let fee = Fee::from_bps(bps).unwrap();
+            for amount in [1, 3, 10_001, u64::MAX - 1, u64::MAX] {
+                assert!(fee.apply(amount) as u128 + fee.remainder(amount) as u128 <= amount as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn discounted_stays_within_the_fee() {
+        let fee = Fee::from_bps(30).unwrap();
+
+        assert_eq!(fee.discounted(0).bps(), 30);
+        assert_eq!(fee.discounted(5_000).bps(), 15);
+        assert_eq!(fee.discounted(MAX_FEE_BPS).bps(), 0);
+        assert_eq!(fee.discounted(u16::MAX).bps(), 0);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::state::{get_extension, get_extension_mut, Config, GlobalConfig, ObservationTree};
+use crate::{bump_sequence, check_writable, pool_reserves, set_return_amounts, AmmError, Append};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #AppendObservation
+///
+/// Append the current reserves of a pool to its `ObservationTree`, at most once per slot
+///
+/// Anyone can run it, e.g. a keeper cranking high-frequency pools every slot. The leaf is logged
+/// through the noop program for indexers, see `ObservationTree` for its layout.
+///
+/// Accounts:
+///
+/// 1. config                       [mut]
+/// 2. mint_lp
+/// 3. vault_x
+/// 4. vault_y
+/// 5. merkle_tree                  [mut]   // `ObservationTree::merkle_tree`
+/// 6. compression_program          [executable]
+/// 7. noop_program                 [executable]
+/// 8. global_config
+///
+/// Return data:
+///
+/// 1. index: u64,                  // Leaf index of the observation
+pub struct AppendObservationAccounts<'a> {
+    pub config: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub merkle_tree: &'a AccountInfo,
+    pub noop_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AppendObservationAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, mint_lp, vault_x, vault_y, merkle_tree, _compression_program, noop_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the writable accounts
+        [config, merkle_tree].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            config,
+            mint_lp,
+            vault_x,
+            vault_y,
+            merkle_tree,
+            noop_program,
+            global_config,
+        })
+    }
+}
+
+pub struct AppendObservation<'a> {
+    pub accounts: AppendObservationAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for AppendObservation<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = AppendObservationAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AppendObservation<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &56;
+
+    /// `sha256("global:append_observation")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [152, 226, 35, 122, 227, 141, 99, 16];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let clock = Clock::get()?;
+
+        {
+            let config = Config::load(self.accounts.config)?;
+
+            let merkle_tree = *get_extension::<ObservationTree>(&self.accounts.config.try_borrow_data()?)?
+                .ok_or(AmmError::MissingExtension)?
+                .merkle_tree();
+
+            if merkle_tree.ne(self.accounts.merkle_tree.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // The observed reserves must be the pool's
+            let (reserve_x, reserve_y, _) = pool_reserves(
+                self.accounts.config,
+                &config,
+                (self.accounts.vault_x, self.accounts.vault_y),
+                self.accounts.mint_lp,
+            )?;
+
+            let leaf = ObservationTree::leaf(clock.slot, clock.unix_timestamp, reserve_x, reserve_y);
+
+            // The config is the tree authority
+            let config_bump = config.config_bump();
+            let seeds = [
+                Seed::from(b"config"),
+                Seed::from(config.seed_bytes()),
+                Seed::from(config.mint_x()),
+                Seed::from(config.mint_y()),
+                Seed::from(&config_bump),
+            ];
+
+            Append {
+                merkle_tree: self.accounts.merkle_tree,
+                authority: self.accounts.config,
+                noop_program: self.accounts.noop_program,
+                leaf: &leaf,
+            }
+            .invoke_signed(&[Signer::from(&seeds)])?;
+        }
+
+        // Fails a second observation in the same slot, the append above is rolled back with it
+        let index = get_extension_mut::<ObservationTree>(&mut self.accounts.config.try_borrow_mut_data()?)?
+            .ok_or(AmmError::MissingExtension)?
+            .record(clock.slot)?;
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        set_return_amounts(&[index]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use crate::derive_allowance;
+use crate::state::{Allowance, Config, GlobalConfig};
+use crate::{check_signer, check_writable};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #ApproveStrategy
+///
+/// Grant a keeper a bounded allowance to run swaps or deposits for the owner on one pool
+///
+/// The allowance only takes effect once the owner approves the allowance PDA as delegate of
+/// its token accounts, see `ExecuteStrategy`. Calling it again overwrites the allowance, and
+/// revoking the token delegate or setting zero amounts disables it.
+///
+/// Accounts:
+///
+/// 1. owner:                       [signer, mut]
+/// 2. keeper
+/// 3. config
+/// 4. allowance                    [init_if_needed]    // [b"allowance", config, owner, keeper]
+/// 5. system_program               [executable]
+/// 6. global_config
+///
+/// Parameters:
+///
+/// 1. amount_x: u64,               // X the keeper may spend in total
+/// 2. amount_y: u64,               // Y the keeper may spend in total
+/// 3. expiration: i64,             // Unix timestamp
+/// 4. permissions: u8,             // See `Allowance::ALLOW_SWAP` and `Allowance::ALLOW_DEPOSIT`
+pub struct ApproveStrategyAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub keeper: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub allowance: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ApproveStrategyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, keeper, config, allowance, _system_program, global_config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(owner)?;
+        [owner, allowance].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            owner,
+            keeper,
+            config,
+            allowance,
+            global_config,
+        })
+    }
+}
+
+pub struct ApproveStrategyInstructionData {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub expiration: i64,
+    pub permissions: u8,
+}
+
+impl TryFrom<&[u8]> for ApproveStrategyInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u64>() * 2 + size_of::<i64>() + size_of::<u8>())) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount_x = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let amount_y = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[16..24].try_into().unwrap());
+        let permissions = data[24];
+
+        if permissions & !(Allowance::ALLOW_SWAP | Allowance::ALLOW_DEPOSIT) != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount_x,
+            amount_y,
+            expiration,
+            permissions,
+        })
+    }
+}
+
+pub struct ApproveStrategy<'a> {
+    pub accounts: ApproveStrategyAccounts<'a>,
+    pub instruction_data: ApproveStrategyInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ApproveStrategy<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ApproveStrategyAccounts::try_from(accounts)?;
+        let instruction_data = ApproveStrategyInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ApproveStrategy<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &20;
+
+    /// `sha256("global:approve_strategy")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [7, 141, 162, 60, 71, 115, 26, 146];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // The allowance is scoped to an existing pool
+        Config::load(self.accounts.config)?;
+
+        // Check if the allowance is valid
+        let (allowance_key, allowance_bump) = derive_allowance(
+            self.accounts.config.key(),
+            self.accounts.owner.key(),
+            self.accounts.keeper.key(),
+        );
+
+        if allowance_key.ne(self.accounts.allowance.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the allowance on the first approval
+        let is_new = self.accounts.allowance.data_is_empty();
+        if is_new {
+            let allowance_bump = [allowance_bump];
+            let allowance_seeds = [
+                Seed::from(b"allowance"),
+                Seed::from(self.accounts.config.key()),
+                Seed::from(self.accounts.owner.key()),
+                Seed::from(self.accounts.keeper.key()),
+                Seed::from(&allowance_bump),
+            ];
+
+            CreateAccount {
+                from: self.accounts.owner,
+                to: self.accounts.allowance,
+                lamports: Rent::get()?.minimum_balance(Allowance::LEN),
+                space: Allowance::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&allowance_seeds)])?;
+        }
+
+        let mut allowance = match is_new {
+            true => Allowance::init(self.accounts.allowance)?,
+            false => Allowance::load_mut(self.accounts.allowance)?,
+        };
+
+        allowance.set_inner(
+            *self.accounts.owner.key(),
+            *self.accounts.keeper.key(),
+            *self.accounts.config.key(),
+            self.instruction_data.amount_x,
+            self.instruction_data.amount_y,
+            self.instruction_data.expiration,
+            self.instruction_data.permissions,
+            [allowance_bump],
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use crate::state::Config;
+use crate::{
+    check_expiration, check_signer, check_writable, mul_div, set_return_amounts, AmmError, CheckedUserAta,
+    EXPIRATION_IN_SLOTS, MAX_FEE_BPS, Rounding, Swap, SwapAccounts, SwapInstructionData, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #Arbitrage
+///
+/// Close the price gap between two pools of the same mint pair: sell the input token in pool A,
+/// buy it back in pool B, and keep the profit only if it clears `min_profit`
+///
+/// Both legs run exactly as `Swap`. `REBATE_BPS` of the profit is paid back to the input vaults of
+/// both pools, split evenly, so their LPs share the value the arbitrage takes from them. The
+/// profit is measured on the user's input account, net of transfer fees.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]        // Owner of the token accounts, or their delegate
+/// 2. user_x_ata                   [mut]
+/// 3. user_y_ata                   [mut]
+/// 4. mint_x
+/// 5. mint_y
+/// 6. token_program_x              [executable]
+/// 7. token_program_y              [executable]
+/// 8. global_config
+/// 9. config_a                     [mut]
+/// 10. vault_x_a                   [mut]
+/// 11. vault_y_a                   [mut]
+/// 12. config_b                    [mut]
+/// 13. vault_x_b                   [mut]
+/// 14. vault_y_b                   [mut]
+/// 15. oracle                      [optional]    // As in `Swap`, for both pools
+/// 16. instructions_sysvar         [optional]    // As in `Swap`, for both pools
+/// 17. mint_lp_a                   [optional]    // As in `Swap`
+/// 18. mint_lp_b                   [optional]
+///
+/// Optional accounts can be skipped by passing the program id in their place.
+///
+/// Parameters:
+///
+/// 1. is_x: bool,                  // Sell X in pool A and buy it back in pool B, or the same with Y
+/// 2. amount: u64,                 // Amount of the input token sold in pool A
+/// 3. min_profit: u64,             // Minimum profit to keep, after the rebate
+/// 4. expiration: i64,             // Unix timestamp (a slot with `EXPIRATION_IN_SLOTS`)
+/// 5. flags: u8,                   // Optional, only `EXPIRATION_IN_SLOTS`
+///
+/// Return data:
+///
+/// 1. amount_out: u64,             // Received from pool A, sold in pool B
+/// 2. profit: u64,                 // Kept by the user
+/// 3. rebate: u64,                 // Paid to both pools
+pub struct ArbitrageAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub config_a: &'a AccountInfo,
+    pub vault_x_a: &'a AccountInfo,
+    pub vault_y_a: &'a AccountInfo,
+    pub config_b: &'a AccountInfo,
+    pub vault_x_b: &'a AccountInfo,
+    pub vault_y_b: &'a AccountInfo,
+    pub oracle: Option<&'a AccountInfo>,
+    pub instructions_sysvar: Option<&'a AccountInfo>,
+    pub mint_lp_a: Option<&'a AccountInfo>,
+    pub mint_lp_b: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ArbitrageAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, user_x_ata, user_y_ata, mint_x, mint_y, token_program_x, token_program_y, global_config, config_a, vault_x_a, vault_y_a, config_b, vault_x_b, vault_y_b, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [
+            user_x_ata,
+            user_y_ata,
+            config_a,
+            vault_x_a,
+            vault_y_a,
+            config_b,
+            vault_x_b,
+            vault_y_b,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Arbitrage between a pool and itself can't profit
+        if config_a.key().eq(config_b.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut optional = remaining.iter().map(|account| account.key().ne(&crate::ID).then_some(account));
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            user_x_ata,
+            user_y_ata,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            global_config,
+            config_a,
+            vault_x_a,
+            vault_y_a,
+            config_b,
+            vault_x_b,
+            vault_y_b,
+            oracle: optional.next().flatten(),
+            instructions_sysvar: optional.next().flatten(),
+            mint_lp_a: optional.next().flatten(),
+            mint_lp_b: optional.next().flatten(),
+        })
+    }
+}
+
+pub struct ArbitrageInstructionData {
+    pub is_x: bool,
+    pub amount: u64,
+    pub min_profit: u64,
+    pub expiration: i64,
+    pub flags: u8,
+}
+
+impl TryFrom<&[u8]> for ArbitrageInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const ARBITRAGE_DATA_LEN: usize = size_of::<bool>() + size_of::<u64>() * 2 + size_of::<i64>();
+        const ARBITRAGE_DATA_LEN_WITH_FLAGS: usize = ARBITRAGE_DATA_LEN + size_of::<u8>();
+
+        let flags = match data.len() {
+            ARBITRAGE_DATA_LEN => 0,
+            ARBITRAGE_DATA_LEN_WITH_FLAGS => data[ARBITRAGE_DATA_LEN],
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let is_x = data[0] == 1;
+        let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let min_profit = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
+
+        if amount == 0 || flags & !EXPIRATION_IN_SLOTS != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_expiration(expiration, flags)?;
+
+        Ok(Self {
+            is_x,
+            amount,
+            min_profit,
+            expiration,
+            flags,
+        })
+    }
+}
+
+pub struct Arbitrage<'a> {
+    pub accounts: ArbitrageAccounts<'a>,
+    pub instruction_data: ArbitrageInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Arbitrage<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ArbitrageAccounts::try_from(accounts)?;
+        let instruction_data = ArbitrageInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Arbitrage<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &58;
+
+    /// `sha256("global:arbitrage")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [35, 187, 182, 132, 14, 49, 136, 132];
+
+    /// Indexes of the two configs among the accounts, see `Swap`
+    pub const CONFIG_INDEXES: [usize; 2] = [8, 11];
+
+    /// Share of the profit (in bps) paid back to the LPs of both pools
+    pub const REBATE_BPS: u16 = 2000;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let is_x = self.instruction_data.is_x;
+
+        let (user_in_ata, user_out_ata, mint_in, mint_out) = match is_x {
+            true => (self.accounts.user_x_ata, self.accounts.user_y_ata, self.accounts.mint_x, self.accounts.mint_y),
+            false => (self.accounts.user_y_ata, self.accounts.user_x_ata, self.accounts.mint_y, self.accounts.mint_x),
+        };
+        let user_in = CheckedUserAta::new(user_in_ata, mint_in.key())?;
+        let user_out = CheckedUserAta::new(user_out_ata, mint_out.key())?;
+
+        let user_in_before = user_in.amount();
+
+        // Sell in pool A
+        let user_out_before = user_out.amount();
+
+        self.swap(
+            (self.accounts.config_a, self.accounts.vault_x_a, self.accounts.vault_y_a),
+            self.accounts.mint_lp_a,
+            is_x,
+            self.instruction_data.amount,
+        )?;
+
+        let amount_out = user_out.amount().checked_sub(user_out_before).ok_or(AmmError::MathOverflow)?;
+
+        // Buy back in pool B with everything received
+        self.swap(
+            (self.accounts.config_b, self.accounts.vault_x_b, self.accounts.vault_y_b),
+            self.accounts.mint_lp_b,
+            !is_x,
+            amount_out,
+        )?;
+
+        let gross_profit = user_in
+            .amount()
+            .checked_sub(user_in_before)
+            .filter(|profit| *profit > 0)
+            .ok_or(AmmError::ArbitrageUnprofitable)?;
+
+        // Pay the LPs of both pools their share, as a donation to the input vaults
+        let rebate = mul_div(gross_profit, Self::REBATE_BPS as u64, MAX_FEE_BPS as u64, Rounding::Down)?;
+        let profit = gross_profit - rebate;
+
+        if profit < self.instruction_data.min_profit {
+            return Err(AmmError::ArbitrageUnprofitable.into());
+        }
+
+        let (decimals, token_program) = {
+            let config = Config::load(self.accounts.config_a)?;
+
+            match is_x {
+                true => (config.decimals_x(), *config.token_program_x()),
+                false => (config.decimals_y(), *config.token_program_y()),
+            }
+        };
+        let (vault_a, vault_b) = match is_x {
+            true => (self.accounts.vault_x_a, self.accounts.vault_x_b),
+            false => (self.accounts.vault_y_a, self.accounts.vault_y_b),
+        };
+        let rebate_a = rebate / 2;
+
+        for (vault, amount) in [(vault_a, rebate_a), (vault_b, rebate - rebate_a)] {
+            if amount == 0 {
+                continue;
+            }
+
+            TransferChecked {
+                from: user_in_ata,
+                mint: mint_in,
+                to: vault,
+                authority: self.accounts.user,
+                amount,
+                decimals,
+                token_program: &token_program,
+            }
+            .invoke()?;
+        }
+
+        set_return_amounts(&[amount_out, profit, rebate]);
+
+        Ok(())
+    }
+
+    /// Run one leg as a `Swap` on the pool at `config`, its output only bounded by the profit check
+    fn swap(
+        &self,
+        (config, vault_x, vault_y): (&'a AccountInfo, &'a AccountInfo, &'a AccountInfo),
+        mint_lp: Option<&'a AccountInfo>,
+        is_x: bool,
+        amount: u64,
+    ) -> ProgramResult {
+        Swap {
+            accounts: SwapAccounts {
+                user: self.accounts.user,
+                user_x: self.accounts.user_x_ata,
+                user_y: self.accounts.user_y_ata,
+                vault_x,
+                vault_y,
+                config,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                token_program_x: self.accounts.token_program_x,
+                token_program_y: self.accounts.token_program_y,
+                global_config: self.accounts.global_config,
+                host_fee: None,
+                oracle: self.accounts.oracle,
+                instructions_sysvar: self.accounts.instructions_sysvar,
+                mint_lp,
+                memo_program: None,
+                trading_calendar: None,
+                insurance_vault: None,
+                tip_account: None,
+                system_program: None,
+                user_stats: None,
+            },
+            instruction_data: SwapInstructionData {
+                is_x,
+                amount,
+                min: 1,
+                expiration: self.instruction_data.expiration,
+                flags: self.instruction_data.flags,
+                tip: 0,
+                memo: &[],
+            },
+        }
+        .process()
+    }
+}
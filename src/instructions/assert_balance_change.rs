@@ -0,0 +1,193 @@
+use crate::state::BalanceSnapshot;
+use crate::{set_return_amounts, AmmError, CheckedUserAta, SnapshotBalance};
+use core::mem::size_of;
+use pinocchio::sysvars::instructions::Instructions;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #AssertBalanceChange
+///
+/// Check that a token account moved by `min_delta` to `max_delta` from the balance recorded by a
+/// `SnapshotBalance` earlier in the transaction, and fail with `BalanceChangeOutOfBounds` otherwise
+///
+/// Wrapping a swap between the two, it bounds what the user ends up with however the swap was
+/// routed, so an adapter that skims can't get past it. The snapshot is only read if the top-level
+/// instruction it records is a `SnapshotBalance` into it that ran before this one, so a snapshot
+/// left by another transaction is rejected. Anyone can run it, it doesn't write anything.
+///
+/// Accounts:
+///
+/// 1. token_account
+/// 2. mint
+/// 3. snapshot                                         // [b"balance_snapshot", token_account]
+/// 4. instructions_sysvar
+///
+/// Parameters:
+///
+/// 1. min_delta: i64,              // Negative to bound what the account may lose
+/// 2. max_delta: i64,
+///
+/// Return data:
+///
+/// 1. after: u64,                  // Balance of the account when checked
+pub struct AssertBalanceChangeAccounts<'a> {
+    pub token_account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub snapshot: &'a AccountInfo,
+    pub instructions_sysvar: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AssertBalanceChangeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [token_account, mint, snapshot, instructions_sysvar] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Return the accounts
+        Ok(Self {
+            token_account,
+            mint,
+            snapshot,
+            instructions_sysvar,
+        })
+    }
+}
+
+pub struct AssertBalanceChangeInstructionData {
+    pub min_delta: i64,
+    pub max_delta: i64,
+}
+
+impl TryFrom<&[u8]> for AssertBalanceChangeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const ASSERT_BALANCE_CHANGE_DATA_LEN: usize = size_of::<i64>() * 2;
+
+        if data.len().ne(&ASSERT_BALANCE_CHANGE_DATA_LEN) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let min_delta = i64::from_le_bytes(data[0..8].try_into().unwrap());
+        let max_delta = i64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        if min_delta > max_delta {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { min_delta, max_delta })
+    }
+}
+
+pub struct AssertBalanceChange<'a> {
+    pub accounts: AssertBalanceChangeAccounts<'a>,
+    pub instruction_data: AssertBalanceChangeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for AssertBalanceChange<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = AssertBalanceChangeAccounts::try_from(accounts)?;
+        let instruction_data = AssertBalanceChangeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> AssertBalanceChange<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &64;
+
+    /// `sha256("global:assert_balance_change")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [251, 134, 55, 56, 247, 158, 28, 115];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let snapshot = BalanceSnapshot::load(self.accounts.snapshot)?;
+
+        if snapshot.token_account().ne(self.accounts.token_account.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check that the snapshot was taken earlier in this transaction
+        let instructions = Instructions::try_from(self.accounts.instructions_sysvar)?;
+
+        if snapshot.index() >= instructions.load_current_index()
+            || !SnapshotBalance::is_snapshot_into(
+                &instructions.load_instruction_at(snapshot.index() as usize)?,
+                self.accounts.snapshot.key(),
+            )
+        {
+            return Err(AmmError::InvalidBalanceSnapshot.into());
+        }
+
+        let after = CheckedUserAta::new(self.accounts.token_account, self.accounts.mint.key())?.amount();
+
+        set_return_amounts(&[after]);
+
+        check_balance_change(
+            snapshot.amount(),
+            after,
+            self.instruction_data.min_delta,
+            self.instruction_data.max_delta,
+        )
+    }
+}
+
+/// Check that the balance moved from `before` to `after` by `min_delta` to `max_delta`
+fn check_balance_change(before: u64, after: u64, min_delta: i64, max_delta: i64) -> ProgramResult {
+    // Any two balances differ by less than the range of an i128
+    let delta = after as i128 - before as i128;
+
+    if delta < min_delta as i128 || delta > max_delta as i128 {
+        return Err(AmmError::BalanceChangeOutOfBounds.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_balance_change_includes_both_bounds() {
+        assert_eq!(check_balance_change(100, 90, -10, 5), Ok(()));
+        assert_eq!(check_balance_change(100, 105, -10, 5), Ok(()));
+        assert_eq!(check_balance_change(100, 100, 0, 0), Ok(()));
+        assert_eq!(check_balance_change(100, 89, -10, 5), Err(AmmError::BalanceChangeOutOfBounds.into()));
+        assert_eq!(check_balance_change(100, 106, -10, 5), Err(AmmError::BalanceChangeOutOfBounds.into()));
+    }
+
+    #[test]
+    fn check_balance_change_bounds_losses_with_negative_deltas() {
+        // At least 50 must leave the account, at most 80
+        assert_eq!(check_balance_change(100, 50, -80, -50), Ok(()));
+        assert_eq!(check_balance_change(100, 20, -80, -50), Ok(()));
+        assert_eq!(check_balance_change(100, 51, -80, -50), Err(AmmError::BalanceChangeOutOfBounds.into()));
+        assert_eq!(check_balance_change(100, 19, -80, -50), Err(AmmError::BalanceChangeOutOfBounds.into()));
+    }
+
+    #[test]
+    fn check_balance_change_handles_deltas_past_i64() {
+        // u64::MAX apart, beyond what an i64 delta holds
+        assert_eq!(check_balance_change(0, u64::MAX, 0, i64::MAX), Err(AmmError::BalanceChangeOutOfBounds.into()));
+        assert_eq!(check_balance_change(u64::MAX, 0, i64::MIN, 0), Err(AmmError::BalanceChangeOutOfBounds.into()));
+        assert_eq!(check_balance_change(0, i64::MAX as u64, 0, i64::MAX), Ok(()));
+        assert_eq!(check_balance_change(i64::MAX as u64 + 1, 0, i64::MIN, 0), Ok(()));
+    }
+
+    #[test]
+    fn instruction_data_is_two_ordered_deltas() {
+        let data = [(-5i64).to_le_bytes(), 7i64.to_le_bytes()].concat();
+        let parsed = AssertBalanceChangeInstructionData::try_from(data.as_slice()).unwrap();
+        assert_eq!((parsed.min_delta, parsed.max_delta), (-5, 7));
+
+        let reversed = [7i64.to_le_bytes(), (-5i64).to_le_bytes()].concat();
+        assert!(AssertBalanceChangeInstructionData::try_from(reversed.as_slice()).is_err());
+        assert!(AssertBalanceChangeInstructionData::try_from(&data[..15]).is_err());
+    }
+}
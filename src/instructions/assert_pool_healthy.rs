@@ -0,0 +1,190 @@
+use crate::state::{get_extension, AmmState, Config, VaultStrategy};
+use crate::{pool_vault_addresses, set_return_amounts, AmmError, CheckedLpMint, CheckedVault};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #AssertPoolHealthy
+///
+/// Check the invariants of a pool and fail with `PoolUnhealthy` if any is broken
+///
+/// Anyone can run it, it doesn't write anything. Monitoring bots can simulate it or run it on
+/// chain, the return data is set either way.
+///
+/// Accounts:
+///
+/// 1. config
+/// 2. mint_lp
+/// 3. vault_x
+/// 4. vault_y
+/// 5. mint_x
+/// 6. mint_y
+///
+/// Return data:
+///
+/// 1. failures: u64,               // The broken invariants, see the `AssertPoolHealthy` flags
+/// 2. reserve_x: u64,
+/// 3. reserve_y: u64,
+/// 4. lp_supply: u64,              // Zero if the LP mint is invalid
+pub struct AssertPoolHealthyAccounts<'a> {
+    pub config: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AssertPoolHealthyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, mint_lp, vault_x, vault_y, mint_x, mint_y] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Return the accounts
+        Ok(Self {
+            config,
+            mint_lp,
+            vault_x,
+            vault_y,
+            mint_x,
+            mint_y,
+        })
+    }
+}
+
+pub struct AssertPoolHealthy<'a> {
+    pub accounts: AssertPoolHealthyAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for AssertPoolHealthy<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = AssertPoolHealthyAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AssertPoolHealthy<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &42;
+
+    /// `sha256("global:assert_pool_healthy")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [226, 109, 20, 92, 77, 27, 160, 113];
+
+    /* Failures */
+
+    /// The state byte isn't a known `AmmState`, or the pool is uninitialized
+    pub const INVALID_STATE: u64 = 1 << 0;
+    /// A mint isn't the pool's, or isn't owned by the pool's token program for its side
+    pub const INVALID_MINTS: u64 = 1 << 1;
+    /// The X vault isn't at the pool's address, or isn't a token account of X owned by the config
+    pub const INVALID_VAULT_X: u64 = 1 << 2;
+    /// Same for the Y vault
+    pub const INVALID_VAULT_Y: u64 = 1 << 3;
+    /// The LP mint isn't the pool's
+    pub const INVALID_LP_MINT: u64 = 1 << 4;
+    /// The uncollected protocol fees exceed what the vaults and the strategy hold
+    pub const UNBACKED_PROTOCOL_FEES: u64 = 1 << 5;
+    /// LP tokens are outstanding while a reserve is empty
+    pub const UNBACKED_LP_SUPPLY: u64 = 1 << 6;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        let mut failures = 0;
+
+        if !matches!(
+            config.amm_state(),
+            Ok(AmmState::Initialized | AmmState::Disabled | AmmState::WithdrawOnly)
+        ) {
+            failures |= Self::INVALID_STATE;
+        }
+
+        if self.accounts.mint_x.key().ne(config.mint_x())
+            || self.accounts.mint_y.key().ne(config.mint_y())
+            || self.accounts.mint_x.owner().ne(config.token_program_x())
+            || self.accounts.mint_y.owner().ne(config.token_program_y())
+        {
+            failures |= Self::INVALID_MINTS;
+        }
+
+        // Only the vaults at the pool's addresses are read, a missing one counts as empty
+        let (vault_x_key, vault_y_key) = pool_vault_addresses(self.accounts.config, &config)?;
+
+        let vault_x = vault_x_key
+            .eq(self.accounts.vault_x.key())
+            .then(|| {
+                CheckedVault::new(
+                    self.accounts.vault_x,
+                    config.mint_x(),
+                    self.accounts.config.key(),
+                    config.token_program_x(),
+                )
+                .ok()
+            })
+            .flatten();
+        let vault_y = vault_y_key
+            .eq(self.accounts.vault_y.key())
+            .then(|| {
+                CheckedVault::new(
+                    self.accounts.vault_y,
+                    config.mint_y(),
+                    self.accounts.config.key(),
+                    config.token_program_y(),
+                )
+                .ok()
+            })
+            .flatten();
+
+        if vault_x.is_none() {
+            failures |= Self::INVALID_VAULT_X;
+        }
+
+        if vault_y.is_none() {
+            failures |= Self::INVALID_VAULT_Y;
+        }
+
+        let lp_supply = CheckedLpMint::new(self.accounts.mint_lp, self.accounts.config.key())
+            .ok()
+            .map(|mint_lp| mint_lp.supply());
+
+        if lp_supply.is_none() {
+            failures |= Self::INVALID_LP_MINT;
+        }
+
+        // Reserves
+        let deployed = match get_extension::<VaultStrategy>(&self.accounts.config.try_borrow_data()?)? {
+            Some(strategy) => (strategy.deployed_x(), strategy.deployed_y()),
+            None => (0, 0),
+        };
+        let vault_x_amount = vault_x.map_or(0, |vault| vault.amount());
+        let vault_y_amount = vault_y.map_or(0, |vault| vault.amount());
+
+        if vault_x_amount.saturating_add(deployed.0) < config.protocol_fees_x()
+            || vault_y_amount.saturating_add(deployed.1) < config.protocol_fees_y()
+        {
+            failures |= Self::UNBACKED_PROTOCOL_FEES;
+        }
+
+        let (reserve_x, reserve_y) = config.reserves(vault_x_amount, vault_y_amount, deployed);
+        let lp_supply = lp_supply.unwrap_or(0);
+
+        if lp_supply > 0 && (reserve_x == 0 || reserve_y == 0) {
+            failures |= Self::UNBACKED_LP_SUPPLY;
+        }
+
+        set_return_amounts(&[failures, reserve_x, reserve_y, lp_supply]);
+
+        if failures != 0 {
+            return Err(AmmError::PoolUnhealthy.into());
+        }
+
+        Ok(())
+    }
+}
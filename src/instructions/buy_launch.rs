@@ -0,0 +1,221 @@
+use crate::state::{GlobalConfig, Launch};
+use crate::{
+    check_signer, check_writable, derive_ata, set_return_amounts, AmmError, CheckedVault, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #BuyLaunch
+///
+/// Buy launched tokens from the bonding curve with the quote token
+///
+/// The buy is trimmed so the raise stops at `Launch::target_raise`.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]
+/// 2. launch                       [mut]
+/// 3. launch_token_vault           [mut]
+/// 4. launch_quote_vault           [mut]
+/// 5. user_token_ata               [mut]
+/// 6. user_quote_ata               [mut]
+/// 7. mint
+/// 8. quote_mint
+/// 9. token_program                [executable]
+/// 10. quote_program               [executable]
+/// 11. global_config
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Quote to spend at most
+/// 2. min_out: u64,                // Minimum tokens to receive
+///
+/// Return data:
+///
+/// 1. quote_in: u64,               // Quote received by the curve
+/// 2. tokens_out: u64,             // Tokens sent to the user
+pub struct LaunchTradeAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub launch: &'a AccountInfo,
+    pub launch_token_vault: &'a AccountInfo,
+    pub launch_quote_vault: &'a AccountInfo,
+    pub user_token_ata: &'a AccountInfo,
+    pub user_quote_ata: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub quote_mint: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for LaunchTradeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, launch, launch_token_vault, launch_quote_vault, user_token_ata, user_quote_ata, mint, quote_mint, token_program, quote_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [
+            launch,
+            launch_token_vault,
+            launch_quote_vault,
+            user_token_ata,
+            user_quote_ata,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Deserialize the launch account
+        let launch_data = Launch::load(launch)?;
+
+        // Check the mints and their token programs
+        if mint.key().ne(launch_data.mint())
+            || quote_mint.key().ne(launch_data.quote_mint())
+            || token_program.key().ne(launch_data.token_program())
+            || quote_program.key().ne(launch_data.quote_program())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check if the launch vaults are valid
+        if derive_ata(launch.key(), token_program.key(), mint.key()).ne(launch_token_vault.key())
+            || derive_ata(launch.key(), quote_program.key(), quote_mint.key()).ne(launch_quote_vault.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            launch,
+            launch_token_vault,
+            launch_quote_vault,
+            user_token_ata,
+            user_quote_ata,
+            mint,
+            quote_mint,
+            global_config,
+        })
+    }
+}
+
+pub struct LaunchTradeInstructionData {
+    pub amount: u64,
+    pub min_out: u64,
+}
+
+impl TryFrom<&[u8]> for LaunchTradeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u64>() * 2)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let min_out = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount, min_out })
+    }
+}
+
+pub struct BuyLaunch<'a> {
+    pub accounts: LaunchTradeAccounts<'a>,
+    pub instruction_data: LaunchTradeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for BuyLaunch<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = LaunchTradeAccounts::try_from(accounts)?;
+        let instruction_data = LaunchTradeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> BuyLaunch<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &15;
+
+    /// `sha256("global:buy_launch")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [119, 46, 4, 171, 194, 234, 30, 160];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let (quote_in, tokens_out) = {
+            let launch = Launch::load(self.accounts.launch)?;
+
+            if !launch.is_trading() {
+                return Err(AmmError::LaunchNotTrading.into());
+            }
+
+            let launch_quote_vault = CheckedVault::new(
+                self.accounts.launch_quote_vault,
+                launch.quote_mint(),
+                self.accounts.launch.key(),
+                launch.quote_program(),
+            )?;
+            let quote_before = launch_quote_vault.amount();
+
+            let (quote_in, _) = launch.quote_buy(self.instruction_data.amount)?;
+
+            TransferChecked {
+                from: self.accounts.user_quote_ata,
+                mint: self.accounts.quote_mint,
+                to: self.accounts.launch_quote_vault,
+                authority: self.accounts.user,
+                amount: quote_in,
+                decimals: launch.quote_decimals(),
+                token_program: launch.quote_program(),
+            }
+            .invoke()?;
+
+            // Price the buy on what the vault received, in case of transfer fees
+            let quote_in = launch_quote_vault.amount() - quote_before;
+            let (quote_in, tokens_out) = launch.quote_buy(quote_in)?;
+
+            if tokens_out == 0 || tokens_out < self.instruction_data.min_out {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let launch_bump = launch.bump();
+            let launch_seeds = [
+                Seed::from(b"launch"),
+                Seed::from(launch.mint()),
+                Seed::from(&launch_bump),
+            ];
+
+            TransferChecked {
+                from: self.accounts.launch_token_vault,
+                mint: self.accounts.mint,
+                to: self.accounts.user_token_ata,
+                authority: self.accounts.launch,
+                amount: tokens_out,
+                decimals: launch.token_decimals(),
+                token_program: launch.token_program(),
+            }
+            .invoke_signed(&[Signer::from(&launch_seeds)])?;
+
+            (quote_in, tokens_out)
+        };
+
+        Launch::load_mut(self.accounts.launch)?.record_buy(quote_in, tokens_out)?;
+
+        set_return_amounts(&[quote_in, tokens_out]);
+
+        Ok(())
+    }
+}
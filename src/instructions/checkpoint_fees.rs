@@ -0,0 +1,102 @@
+use crate::state::{FeeDistributor, GlobalConfig};
+use crate::{check_writable, derive_ata, set_return_amounts, CheckedVault};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #CheckpointFees
+///
+/// Spread the rewards that reached a `FeeDistributor` since its last checkpoint over its stakers,
+/// at most once per epoch
+///
+/// Anyone can run it. Rewards that arrive while nothing is staked wait for a later checkpoint.
+///
+/// Accounts:
+///
+/// 1. fee_distributor              [mut]
+/// 2. reward_vault                 // ATA of fee_distributor for the reward mint
+/// 3. global_config
+///
+/// Return data:
+///
+/// 1. rewards: u64,                // Amount of rewards distributed
+pub struct CheckpointFeesAccounts<'a> {
+    pub fee_distributor: &'a AccountInfo,
+    pub reward_vault: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CheckpointFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [fee_distributor, reward_vault, global_config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_writable(fee_distributor)?;
+
+        // Return the accounts
+        Ok(Self {
+            fee_distributor,
+            reward_vault,
+            global_config,
+        })
+    }
+}
+
+pub struct CheckpointFees<'a> {
+    pub accounts: CheckpointFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for CheckpointFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = CheckpointFeesAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CheckpointFees<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &36;
+
+    /// `sha256("global:checkpoint_fees")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [22, 144, 220, 113, 240, 68, 215, 73];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let mut fee_distributor = FeeDistributor::load_mut(self.accounts.fee_distributor)?;
+
+        // Only the distributor's ATA counts, any account can be created with it as owner
+        if derive_ata(
+            self.accounts.fee_distributor.key(),
+            fee_distributor.reward_token_program(),
+            fee_distributor.reward_mint(),
+        )
+        .ne(self.accounts.reward_vault.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let reward_vault = CheckedVault::new(
+            self.accounts.reward_vault,
+            fee_distributor.reward_mint(),
+            self.accounts.fee_distributor.key(),
+            fee_distributor.reward_token_program(),
+        )?;
+
+        let rewards = fee_distributor.checkpoint(reward_vault.amount(), Clock::get()?.epoch)?;
+
+        set_return_amounts(&[rewards]);
+
+        Ok(())
+    }
+}
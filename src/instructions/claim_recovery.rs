@@ -0,0 +1,234 @@
+use crate::state::{Config, Recovery};
+use crate::{
+    check_signer, check_writable, derive_lp_mint, derive_recovery, set_return_amounts, AmmState, BurnChecked,
+    CheckedVault, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #ClaimRecovery
+///
+/// Burn LP of a `Disabled` pool for its pro-rata share of the recovery escrow, see
+/// `EmergencyWithdrawProtocolSide`
+///
+/// The share is taken from the `Recovery` snapshot, not the live escrow balances.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]        // Owner of user_lp_ata, or its delegate
+/// 2. config
+/// 3. recovery                     [mut]   // [b"recovery", config]
+/// 4. mint_lp                      [mut]
+/// 5. user_lp_ata                  [mut]
+/// 6. recovery_x                   [mut]   // ATA of recovery
+/// 7. recovery_y                   [mut]   // ATA of recovery
+/// 8. user_x_ata                   [mut]
+/// 9. user_y_ata                   [mut]
+/// 10. mint_x
+/// 11. mint_y
+/// 12. token_program               [executable]  // LP token program, see `Config::lp_token_program`
+/// 13. token_program_x             [executable]
+/// 14. token_program_y             [executable]
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Amount of LP token to burn
+///
+/// Return data:
+///
+/// 1. x: u64,                      // Amount of X sent from the escrow
+/// 2. y: u64,                      // Amount of Y sent from the escrow
+pub struct ClaimRecoveryAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub recovery: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub user_lp_ata: &'a AccountInfo,
+    pub recovery_x: &'a AccountInfo,
+    pub recovery_y: &'a AccountInfo,
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ClaimRecoveryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, config, recovery, mint_lp, user_lp_ata, recovery_x, recovery_y, user_x_ata, user_y_ata, mint_x, mint_y, _token_program, _token_program_x, _token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [recovery, mint_lp, user_lp_ata, recovery_x, recovery_y, user_x_ata, user_y_ata]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        // Check the LP mint and the recovery escrow of the pool
+        if derive_lp_mint(config.key()).0.ne(mint_lp.key()) || derive_recovery(config.key()).0.ne(recovery.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            config,
+            recovery,
+            mint_lp,
+            user_lp_ata,
+            recovery_x,
+            recovery_y,
+            user_x_ata,
+            user_y_ata,
+            mint_x,
+            mint_y,
+        })
+    }
+}
+
+pub struct ClaimRecoveryInstructionData {
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for ClaimRecoveryInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u64>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data.try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount })
+    }
+}
+
+pub struct ClaimRecovery<'a> {
+    pub accounts: ClaimRecoveryAccounts<'a>,
+    pub instruction_data: ClaimRecoveryInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ClaimRecovery<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ClaimRecoveryAccounts::try_from(accounts)?;
+        let instruction_data = ClaimRecoveryInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ClaimRecovery<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &32;
+
+    /// `sha256("global:claim_recovery")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [60, 85, 49, 148, 254, 62, 210, 222];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Deserialize the config account
+        let config = Config::load(self.accounts.config)?;
+
+        // Claims are only open while the pool stays disabled
+        if config.amm_state()?.ne(&AmmState::Disabled) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if self.accounts.mint_x.key().ne(config.mint_x()) || self.accounts.mint_y.key().ne(config.mint_y()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // The escrow accounts must be the recovery's, the snapshot was taken from their balances
+        CheckedVault::new(
+            self.accounts.recovery_x,
+            config.mint_x(),
+            self.accounts.recovery.key(),
+            config.token_program_x(),
+        )?;
+        CheckedVault::new(
+            self.accounts.recovery_y,
+            config.mint_y(),
+            self.accounts.recovery.key(),
+            config.token_program_y(),
+        )?;
+
+        // Pay out the LP share of the snapshot
+        let (x, y) = Recovery::load(self.accounts.recovery)?.quote_claim(self.instruction_data.amount)?;
+
+        if x == 0 && y == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Recovery::load_mut(self.accounts.recovery)?.claim(self.instruction_data.amount, x, y);
+
+        BurnChecked {
+            account: self.accounts.user_lp_ata,
+            mint: self.accounts.mint_lp,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount,
+            decimals: config.lp_decimals(),
+            token_program: config.lp_token_program(),
+        }
+        .invoke()?;
+
+        // Create the signer seeds
+        let recovery_bump = Recovery::load(self.accounts.recovery)?.bump();
+        let seeds = [
+            Seed::from(b"recovery"),
+            Seed::from(self.accounts.config.key()),
+            Seed::from(&recovery_bump),
+        ];
+        let signer_seeds = [Signer::from(&seeds)];
+
+        for (amount, recovery, mint, user_ata, decimals, token_program) in [
+            (
+                x,
+                self.accounts.recovery_x,
+                self.accounts.mint_x,
+                self.accounts.user_x_ata,
+                config.decimals_x(),
+                config.token_program_x(),
+            ),
+            (
+                y,
+                self.accounts.recovery_y,
+                self.accounts.mint_y,
+                self.accounts.user_y_ata,
+                config.decimals_y(),
+                config.token_program_y(),
+            ),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+
+            TransferChecked {
+                from: recovery,
+                mint,
+                to: user_ata,
+                authority: self.accounts.recovery,
+                amount,
+                decimals,
+                token_program,
+            }
+            .invoke_signed(&signer_seeds)?;
+        }
+
+        set_return_amounts(&[x, y]);
+
+        Ok(())
+    }
+}
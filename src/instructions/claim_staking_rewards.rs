@@ -0,0 +1,144 @@
+use crate::state::{FeeDistributor, Staker};
+use crate::{check_signer, check_writable, derive_ata, set_return_amounts, TransferChecked};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #ClaimStakingRewards
+///
+/// Claim the rewards a `Staker` earned from the checkpoints of its `FeeDistributor`
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]
+/// 2. fee_distributor              [mut]
+/// 3. staker                       [mut]   // [b"staker", fee_distributor, user]
+/// 4. user_reward_ata              [mut]
+/// 5. reward_vault                 [mut]   // ATA of fee_distributor for the reward mint
+/// 6. reward_mint
+/// 7. token_program                [executable]
+///
+/// Return data:
+///
+/// 1. claimed: u64,                // Amount of rewards sent by the reward vault
+pub struct ClaimStakingRewardsAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub fee_distributor: &'a AccountInfo,
+    pub staker: &'a AccountInfo,
+    pub user_reward_ata: &'a AccountInfo,
+    pub reward_vault: &'a AccountInfo,
+    pub reward_mint: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ClaimStakingRewardsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, fee_distributor, staker, user_reward_ata, reward_vault, reward_mint, _token_program] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [fee_distributor, staker, user_reward_ata, reward_vault]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            fee_distributor,
+            staker,
+            user_reward_ata,
+            reward_vault,
+            reward_mint,
+        })
+    }
+}
+
+pub struct ClaimStakingRewards<'a> {
+    pub accounts: ClaimStakingRewardsAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ClaimStakingRewards<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = ClaimStakingRewardsAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ClaimStakingRewards<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &39;
+
+    /// `sha256("global:claim_staking_rewards")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [229, 141, 170, 69, 111, 94, 6, 72];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut fee_distributor = FeeDistributor::load_mut(self.accounts.fee_distributor)?;
+        let mut staker = Staker::load_mut(self.accounts.staker)?;
+
+        // Check the staker, the reward mint and the reward vault
+        if staker.fee_distributor().ne(self.accounts.fee_distributor.key())
+            || staker.owner().ne(self.accounts.user.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if self.accounts.reward_mint.key().ne(fee_distributor.reward_mint())
+            || derive_ata(
+                self.accounts.fee_distributor.key(),
+                fee_distributor.reward_token_program(),
+                fee_distributor.reward_mint(),
+            )
+            .ne(self.accounts.reward_vault.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Settle the latest checkpoints and release the rewards
+        staker.settle(fee_distributor.reward_per_token());
+
+        let claimed = staker.take_owed();
+        if claimed == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        fee_distributor.pay(claimed);
+
+        // Release the mutable borrow before the distributor signs the transfer
+        drop(fee_distributor);
+        let fee_distributor = FeeDistributor::load(self.accounts.fee_distributor)?;
+
+        // Create the signer seeds
+        let bump = fee_distributor.bump();
+        let seeds = [
+            Seed::from(b"fee_distributor"),
+            Seed::from(fee_distributor.governance_mint()),
+            Seed::from(fee_distributor.reward_mint()),
+            Seed::from(&bump),
+        ];
+
+        TransferChecked {
+            from: self.accounts.reward_vault,
+            mint: self.accounts.reward_mint,
+            to: self.accounts.user_reward_ata,
+            authority: self.accounts.fee_distributor,
+            amount: claimed,
+            decimals: fee_distributor.reward_decimals(),
+            token_program: fee_distributor.reward_token_program(),
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        set_return_amounts(&[claimed]);
+
+        Ok(())
+    }
+}
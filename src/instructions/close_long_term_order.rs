@@ -0,0 +1,173 @@
+use crate::state::{Config, LongTermOrder};
+use crate::{check_signer, check_writable, set_return_amounts, CheckedUserAta, CloseAccount, TransferChecked};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #CloseLongTermOrder
+///
+/// Cancel a `LongTermOrder`, or close it once done: pay the owner the output bought and the input
+/// left unsold, then close the escrows and the order back to the owner
+///
+/// Accounts:
+///
+/// 1. owner:                       [signer, mut]
+/// 2. config
+/// 3. long_term_order              [mut]
+/// 4. escrow_x                     [mut]
+/// 5. escrow_y                     [mut]
+/// 6. owner_x_ata                  [mut]
+/// 7. owner_y_ata                  [mut]
+/// 8. mint_x
+/// 9. mint_y
+/// 10. token_program_x             [executable]
+/// 11. token_program_y             [executable]
+///
+/// Return data:
+///
+/// 1. amount_x: u64,               // Paid out of the order's X escrow
+/// 2. amount_y: u64,               // Paid out of the order's Y escrow
+pub struct CloseLongTermOrderAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub long_term_order: &'a AccountInfo,
+    pub escrow_x: &'a AccountInfo,
+    pub escrow_y: &'a AccountInfo,
+    pub owner_x_ata: &'a AccountInfo,
+    pub owner_y_ata: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CloseLongTermOrderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, config, long_term_order, escrow_x, escrow_y, owner_x_ata, owner_y_ata, mint_x, mint_y, _token_program_x, _token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(owner)?;
+        [owner, long_term_order, escrow_x, escrow_y, owner_x_ata, owner_y_ata]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            owner,
+            config,
+            long_term_order,
+            escrow_x,
+            escrow_y,
+            owner_x_ata,
+            owner_y_ata,
+            mint_x,
+            mint_y,
+        })
+    }
+}
+
+pub struct CloseLongTermOrder<'a> {
+    pub accounts: CloseLongTermOrderAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for CloseLongTermOrder<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = CloseLongTermOrderAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CloseLongTermOrder<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &63;
+
+    /// `sha256("global:close_long_term_order")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [56, 203, 88, 127, 130, 168, 217, 17];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (nonce, bump) = {
+            let order = LongTermOrder::load(self.accounts.long_term_order)?;
+
+            // Check if the owner is the order's
+            if order.owner().ne(self.accounts.owner.key()) || order.config().ne(self.accounts.config.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            (order.nonce().to_le_bytes(), order.bump())
+        };
+
+        let config = Config::load(self.accounts.config)?;
+
+        if config.mint_x().ne(self.accounts.mint_x.key()) || config.mint_y().ne(self.accounts.mint_y.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let long_term_order_seeds = [
+            Seed::from(b"long_term_order"),
+            Seed::from(self.accounts.config.key()),
+            Seed::from(self.accounts.owner.key()),
+            Seed::from(&nonce),
+            Seed::from(&bump),
+        ];
+        let signer = [Signer::from(&long_term_order_seeds)];
+
+        // Empty and close both escrows
+        let mut amounts = [0u64; 2];
+        for (amount, (escrow, owner_ata, mint, decimals, token_program)) in amounts.iter_mut().zip([
+            (
+                self.accounts.escrow_x,
+                self.accounts.owner_x_ata,
+                self.accounts.mint_x,
+                config.decimals_x(),
+                config.token_program_x(),
+            ),
+            (
+                self.accounts.escrow_y,
+                self.accounts.owner_y_ata,
+                self.accounts.mint_y,
+                config.decimals_y(),
+                config.token_program_y(),
+            ),
+        ]) {
+            *amount = CheckedUserAta::with_owner(escrow, mint.key(), self.accounts.long_term_order.key())?.amount();
+
+            if *amount > 0 {
+                TransferChecked {
+                    from: escrow,
+                    mint,
+                    to: owner_ata,
+                    authority: self.accounts.long_term_order,
+                    amount: *amount,
+                    decimals,
+                    token_program,
+                }
+                .invoke_signed(&signer)?;
+            }
+
+            CloseAccount {
+                account: escrow,
+                destination: self.accounts.owner,
+                authority: self.accounts.long_term_order,
+                token_program,
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        // Return the order's rent to the owner
+        *self.accounts.owner.try_borrow_mut_lamports()? += self.accounts.long_term_order.lamports();
+        self.accounts.long_term_order.close()?;
+
+        set_return_amounts(&amounts);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+use crate::state::{get_extension, Config, FeeBurn, FeeSplit, GlobalConfig};
+use crate::{
+    bump_sequence, check_signer, check_writable, set_return_amounts, validate_pool_accounts, AmmError, AmmState,
+    BurnChecked, CheckedVault, PoolAccounts, TransferChecked,
+};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, log::sol_log_data, program_error::ProgramError, ProgramResult};
+
+/// #CollectProtocolFees
+///
+/// Move the protocol fees accrued by a pool out of its vaults
+///
+/// With the `FeeBurn` extension, part of the fees in the governance token is burned instead. With
+/// the `FeeSplit` extension, the rest is split between its recipients, the treasury accounts being
+/// those of the first one.
+///
+/// Accounts:
+///
+/// 1. admin:                       [signer]
+/// 2. global_config
+/// 3. config                       [mut]
+/// 4. vault_x                      [mut]
+/// 5. vault_y                      [mut]
+/// 6. treasury_x                   [mut]   // Any X token account chosen by the admin
+/// 7. treasury_y                   [mut]   // Any Y token account chosen by the admin
+/// 8. mint_x                      [mut]   // Writable when it is the `FeeBurn` mint
+/// 9. mint_y                      [mut]
+/// 10. token_program_x             [executable]
+/// 11. token_program_y             [executable]
+/// 12. ..                          // With `FeeSplit`, per other recipient: recipient_x [mut], recipient_y [mut]
+///
+/// Return data:
+///
+/// 1. x: u64,                      // Amount of X collected
+/// 2. y: u64,                      // Amount of Y collected
+/// 3. burned_x: u64,               // Part of x burned
+/// 4. burned_y: u64,               // Part of y burned
+pub struct CollectProtocolFeesAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub treasury_x: &'a AccountInfo,
+    pub treasury_y: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub recipients: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CollectProtocolFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, global_config, config, vault_x, vault_y, treasury_x, treasury_y, mint_x, mint_y, token_program_x, token_program_y, recipients @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(admin)?;
+        [config, vault_x, vault_y, treasury_x, treasury_y].into_iter().try_for_each(check_writable)?;
+        recipients.iter().try_for_each(check_writable)?;
+
+        // Deserialize the global config account, fees aren't collected while upgrade frozen
+        let global_config = GlobalConfig::load_unfrozen(global_config)?;
+
+        // Check if the admin is the correct admin
+        if global_config.admin().ne(admin.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            admin,
+            config,
+            vault_x,
+            vault_y,
+            treasury_x,
+            treasury_y,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            recipients,
+        })
+    }
+}
+
+pub struct CollectProtocolFees<'a> {
+    pub accounts: CollectProtocolFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for CollectProtocolFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = CollectProtocolFeesAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CollectProtocolFees<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &11;
+
+    /// `sha256("global:collect_protocol_fees")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [22, 67, 23, 98, 150, 178, 70, 220];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Reset the accrued fees, the borrow is released before the config signs the transfers
+        let (x, y) = Config::load_mut(self.accounts.config)?.take_protocol_fees();
+
+        // Order the change for off-chain consumers, the fee burns are logged with it
+        let sequence = bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        // Check the config, the pool mints and the vaults
+        let PoolAccounts { config, .. } = validate_pool_accounts(
+            self.accounts.config,
+            (self.accounts.vault_x, self.accounts.vault_y),
+            (self.accounts.mint_x, self.accounts.mint_y),
+            (self.accounts.token_program_x, self.accounts.token_program_y),
+            &[AmmState::Initialized, AmmState::Disabled, AmmState::WithdrawOnly],
+        )?;
+
+        // Create the signer seeds
+        let config_bump = config.config_bump();
+        let seeds = [
+            Seed::from(b"config"),
+            Seed::from(config.seed_bytes()),
+            Seed::from(config.mint_x()),
+            Seed::from(config.mint_y()),
+            Seed::from(&config_bump),
+        ];
+        let signer_seeds = [Signer::from(&seeds)];
+
+        // Split off the governance token share to burn
+        let (burned_x, burned_y) = match get_extension::<FeeBurn>(&self.accounts.config.try_borrow_data()?)? {
+            Some(fee_burn) => fee_burn.burn_amounts(config.mint_x(), config.mint_y(), x, y)?,
+            None => (0, 0),
+        };
+
+        if burned_x > 0 {
+            check_writable(self.accounts.mint_x)?;
+
+            BurnChecked {
+                account: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                authority: self.accounts.config,
+                amount: burned_x,
+                decimals: config.decimals_x(),
+                token_program: config.token_program_x(),
+            }
+            .invoke_signed(&signer_seeds)?;
+
+            sol_log_data(&[b"fee_burn", config.mint_x(), &burned_x.to_le_bytes(), &sequence.to_le_bytes()]);
+        }
+
+        if burned_y > 0 {
+            check_writable(self.accounts.mint_y)?;
+
+            BurnChecked {
+                account: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                authority: self.accounts.config,
+                amount: burned_y,
+                decimals: config.decimals_y(),
+                token_program: config.token_program_y(),
+            }
+            .invoke_signed(&signer_seeds)?;
+
+            sol_log_data(&[b"fee_burn", config.mint_y(), &burned_y.to_le_bytes(), &sequence.to_le_bytes()]);
+        }
+
+        // Pay the rest to the treasury, or split it between the `FeeSplit` recipients
+        let (fee_x, fee_y) = (
+            x.checked_sub(burned_x).ok_or(AmmError::MathOverflow)?,
+            y.checked_sub(burned_y).ok_or(AmmError::MathOverflow)?,
+        );
+        let fee_split = get_extension::<FeeSplit>(&self.accounts.config.try_borrow_data()?)?
+            .filter(|fee_split| fee_split.is_enabled())
+            .map(|fee_split| fee_split.payouts(fee_x, fee_y))
+            .transpose()?;
+
+        match fee_split {
+            Some((payouts, count)) => {
+                if self.accounts.recipients.len() != (count - 1) * 2 {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                }
+
+                let recipient_accounts = [(self.accounts.treasury_x, self.accounts.treasury_y)]
+                    .into_iter()
+                    .chain(self.accounts.recipients.chunks_exact(2).map(|pair| (&pair[0], &pair[1])));
+
+                for ((recipient, amount_x, amount_y), (recipient_x, recipient_y)) in
+                    payouts[..count].iter().zip(recipient_accounts)
+                {
+                    // Each recipient is paid in token accounts it owns
+                    CheckedVault::new(recipient_x, config.mint_x(), recipient, config.token_program_x())?;
+                    CheckedVault::new(recipient_y, config.mint_y(), recipient, config.token_program_y())?;
+
+                    self.pay(&config, &signer_seeds, (recipient_x, recipient_y), (*amount_x, *amount_y))?;
+                }
+            }
+            None => self.pay(
+                &config,
+                &signer_seeds,
+                (self.accounts.treasury_x, self.accounts.treasury_y),
+                (fee_x, fee_y),
+            )?,
+        }
+
+        set_return_amounts(&[x, y, burned_x, burned_y]);
+
+        Ok(())
+    }
+    /// Transfer collected fees out of the vaults, signed by the config
+    fn pay(
+        &self,
+        config: &Config,
+        signer_seeds: &[Signer],
+        (to_x, to_y): (&AccountInfo, &AccountInfo),
+        (amount_x, amount_y): (u64, u64),
+    ) -> ProgramResult {
+        if amount_x > 0 {
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: to_x,
+                authority: self.accounts.config,
+                amount: amount_x,
+                decimals: config.decimals_x(),
+                token_program: config.token_program_x(),
+            }
+            .invoke_signed(signer_seeds)?;
+        }
+
+        if amount_y > 0 {
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: to_y,
+                authority: self.accounts.config,
+                amount: amount_y,
+                decimals: config.decimals_y(),
+                token_program: config.token_program_y(),
+            }
+            .invoke_signed(signer_seeds)?;
+        }
+
+        Ok(())
+    }
+}
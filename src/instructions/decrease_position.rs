@@ -0,0 +1,146 @@
+use crate::state::NftPosition;
+use crate::{check_signer, check_writable, AmmError};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_token::instructions::Transfer;
+
+/// #DecreasePosition
+///
+/// Move LP tokens out of an unlocked NFT position
+///
+/// Accounts:
+///
+/// 1. owner:                       [signer]
+/// 2. user_lp_ata                  [mut]
+/// 3. position                     [mut]
+/// 4. position_lp_vault            [mut]
+/// 5. owner_position_ata
+/// 6. token_program                [executable]
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Amount of LP token to remove
+pub struct DecreasePositionAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub user_lp_ata: &'a AccountInfo,
+    pub position: &'a AccountInfo,
+    pub position_lp_vault: &'a AccountInfo,
+    pub owner_position_ata: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for DecreasePositionAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, user_lp_ata, position, position_lp_vault, owner_position_ata, _token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(owner)?;
+        [user_lp_ata, position, position_lp_vault].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            owner,
+            user_lp_ata,
+            position,
+            position_lp_vault,
+            owner_position_ata,
+        })
+    }
+}
+
+pub struct DecreasePositionInstructionData {
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for DecreasePositionInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u64>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount })
+    }
+}
+
+pub struct DecreasePosition<'a> {
+    pub accounts: DecreasePositionAccounts<'a>,
+    pub instruction_data: DecreasePositionInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DecreasePosition<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = DecreasePositionAccounts::try_from(accounts)?;
+        let instruction_data = DecreasePositionInstructionData::try_from(data)?;
+
+        // Return the initialized struct
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DecreasePosition<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &8;
+
+    /// `sha256("global:decrease_position")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [57, 125, 21, 59, 200, 137, 179, 108];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (position_mint, position_bump) = {
+            let mut position = NftPosition::load_mut(self.accounts.position)?;
+
+            // Only the NFT holder can manage the position
+            position.check_holder(self.accounts.owner_position_ata, self.accounts.owner.key())?;
+
+            if position.lp_vault().ne(self.accounts.position_lp_vault.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if position.locked_until() > Clock::get()?.unix_timestamp {
+                return Err(AmmError::PositionLocked.into());
+            }
+
+            let liquidity = position
+                .liquidity()
+                .checked_sub(self.instruction_data.amount)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            position.set_liquidity(liquidity);
+
+            // The position borrow is released here, before the position signs the transfer
+            (*position.position_mint(), position.bump())
+        };
+
+        let position_seeds = [
+            Seed::from(b"nft_position"),
+            Seed::from(&position_mint),
+            Seed::from(&position_bump),
+        ];
+
+        Transfer {
+            from: self.accounts.position_lp_vault,
+            to: self.accounts.user_lp_ata,
+            authority: self.accounts.position,
+            amount: self.instruction_data.amount,
+        }
+        .invoke_signed(&[Signer::from(&position_seeds)])?;
+
+        Ok(())
+    }
+}
@@ -1,22 +1,29 @@
-use crate::state::Config;
-use crate::AmmState;
-use constant_product_curve::ConstantProduct;
+use crate::state::{
+    get_extension, Caps, Config, ExitPenalty, FeeState, GlobalConfig, MaintenanceWindow, Position, Whitelist,
+};
+use crate::{
+    bump_sequence, check_expiration, check_signer, check_writable, debug_log, deposit_amounts, derive_position,
+    mul_div, set_return_amounts, slippage_bound, validate_pool_accounts, AmmError, AmmState, CheckedLpMint,
+    CheckedUserAta, DRY_RUN, MAX_FEE_BPS, MintTo, PoolAccounts, Rounding, SLIPPAGE_IN_BPS, TransferChecked,
+};
 use core::mem::size_of;
 use pinocchio::instruction::{Seed, Signer};
-use pinocchio::pubkey::find_program_address;
 use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::rent::Rent;
 use pinocchio::sysvars::Sysvar;
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
-use pinocchio_token::instructions::{MintTo, Transfer};
-use pinocchio_token::state::{Mint, TokenAccount};
+use pinocchio_system::instructions::CreateAccount;
 
 /// #Deposit
 ///
 /// Deposit tokens into the Amm
 ///
+/// The LP minted is capped by what the vaults actually received, so transfer-fee tokens mint
+/// less than `amount` unless `EXACT_AMOUNTS` bounds it.
+///
 /// Accounts:
 ///
-/// 1. user:                         [signer, mut]
+/// 1. user:                        [signer]        // Owner of user_x_ata/user_y_ata, or their delegate
 /// 2. mint_lp                      [mut]
 /// 3. vault_x                      [mut]
 /// 4. vault_y                      [mut]
@@ -24,14 +31,40 @@ use pinocchio_token::state::{Mint, TokenAccount};
 /// 6. user_y_ata                   [mut]
 /// 7. user_lp_ata                  [init_if_needed]
 /// 8. config                       [mut]
-/// 9. token_program                [executable]
+/// 9. token_program                [executable]  // LP token program, see `Config::lp_token_program`
+/// 10. mint_x
+/// 11. mint_y
+/// 12. token_program_x             [executable]
+/// 13. token_program_y             [executable]
+/// 14. global_config
+/// 15. position                    [init_if_needed, optional]
+/// 16. system_program              [executable, optional]  // Required with position
+/// 17. payer                       [signer, mut, optional] // Pays the position rent, user if not passed
+///
+/// The position and the whitelist are keyed by `user`, so a delegate deposits for itself. The LP
+/// goes to `user_lp_ata` whoever owns it, so a program can deposit tokens its users delegated to
+/// it, e.g. a vault strategy, and mint the LP straight to them.
+///
+/// Pools with the `MaintenanceWindow` extension refuse deposits inside the window, and pools with the
+/// `ExitPenalty` extension deposits without a position.
+///
+/// Pools with the `FeeState` extension hold `deposit_fee_bps` of the amounts received back as
+/// protocol fees and mint LP for the rest only. Without `EXACT_AMOUNTS`, `amount` is the LP before
+/// the fee.
 ///
 /// Parameters:
 ///
-/// 1. amount: u64,        // Amount of LP token to claim
-/// 2. max_x: u64,         // Max amount of X we are willing to deposit
-/// 3. max_y: u64,         // Max amount of Y we are willing to deposit
-/// 4. expiration: i64     // Expiration of the offer
+/// 1. amount: u64,        // Amount of LP token to claim (minimum LP with `EXACT_AMOUNTS`)
+/// 2. max_x: u64,         // Max amount of X we are willing to deposit (a tolerance in bps with `SLIPPAGE_IN_BPS`)
+/// 3. max_y: u64,         // Max amount of Y we are willing to deposit (a tolerance in bps with `SLIPPAGE_IN_BPS`)
+/// 4. expiration: i64     // Expiration of the offer (a slot with `EXPIRATION_IN_SLOTS`)
+/// 5. flags: u8           // Optional, see `DepositInstructionData` and the shared flags in `constants`
+///
+/// Return data:
+///
+/// 1. x: u64,             // Amount of X deposited
+/// 2. y: u64,             // Amount of Y deposited
+/// 3. lp: u64,            // Amount of LP token minted
 pub struct DepositAccounts<'a> {
     pub user: &'a AccountInfo,
     pub mint_lp: &'a AccountInfo,
@@ -42,18 +75,53 @@ pub struct DepositAccounts<'a> {
     pub user_lp_ata: &'a AccountInfo,
     pub config: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub position: Option<&'a AccountInfo>,
+    pub payer: &'a AccountInfo,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for DepositAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program] =
+        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program, mint_x, mint_y, token_program_x, token_program_y, global_config, remaining @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [
+            mint_lp,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            user_lp_ata,
+            config,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // The position is optional, but creating it needs the system program
+        let (position, payer) = match remaining {
+            [] => (None, user),
+            [position, _system_program] => (Some(position), user),
+            [position, _system_program, payer, ..] => (Some(position), payer),
+            _ => return Err(ProgramError::NotEnoughAccountKeys),
+        };
+
+        // The position and its rent payer are written when a position is passed
+        if let Some(position) = position {
+            check_signer(payer)?;
+            [position, payer].into_iter().try_for_each(check_writable)?;
+        }
+
         // Return the accounts
         Ok(Self {
             user,
@@ -65,6 +133,13 @@ impl<'a> TryFrom<&'a [AccountInfo]> for DepositAccounts<'a> {
             user_lp_ata,
             config,
             token_program,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            global_config,
+            position,
+            payer,
         })
     }
 }
@@ -74,34 +149,53 @@ pub struct DepositInstructionData {
     pub max_x: u64,
     pub max_y: u64,
     pub expiration: i64,
+    pub flags: u8,
+}
+
+impl DepositInstructionData {
+    /// Deposit up to `max_x`/`max_y` and mint as much LP as they are worth, at least `amount`.
+    /// The first deposit into an empty pool still mints exactly `amount`.
+    pub const EXACT_AMOUNTS: u8 = 1 << 0;
 }
 
 impl<'a> TryFrom<&'a [u8]> for DepositInstructionData {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len().ne(&size_of::<DepositInstructionData>()) {
-            return Err(ProgramError::InvalidInstructionData);
-        }
+        const DEPOSIT_DATA_LEN: usize = size_of::<u64>() * 3 + size_of::<i64>();
+        const DEPOSIT_DATA_LEN_WITH_FLAGS: usize = DEPOSIT_DATA_LEN + size_of::<u8>();
+
+        let flags = match data.len() {
+            DEPOSIT_DATA_LEN => 0,
+            DEPOSIT_DATA_LEN_WITH_FLAGS => data[DEPOSIT_DATA_LEN],
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
 
         let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
         let max_x = u64::from_le_bytes(data[8..16].try_into().unwrap());
         let max_y = u64::from_le_bytes(data[16..24].try_into().unwrap());
         let expiration = i64::from_le_bytes(data[24..32].try_into().unwrap());
 
-        if amount == 0
-            || max_x == 0
-            || max_y == 0
-            || expiration < Clock::get()?.unix_timestamp
-        {
+        // Tolerances in bps may be zero, but not beyond 100% or on top of exact amounts
+        let max_invalid = match flags & SLIPPAGE_IN_BPS != 0 {
+            true => {
+                max_x > MAX_FEE_BPS as u64 || max_y > MAX_FEE_BPS as u64 || flags & Self::EXACT_AMOUNTS != 0
+            }
+            false => max_x == 0 || max_y == 0,
+        };
+
+        if amount == 0 || max_invalid {
             return Err(ProgramError::InvalidInstructionData);
         }
 
+        check_expiration(expiration, flags)?;
+
         Ok(Self {
             amount,
             max_x,
             max_y,
             expiration,
+            flags,
         })
     }
 }
@@ -129,107 +223,289 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Deposit<'a> {
 impl<'a> Deposit<'a> {
     pub const DISCRIMINATOR: &'a u8 = &1;
 
+    /// `sha256("global:deposit")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+
     pub fn process(&mut self) -> ProgramResult {
-        // Deserialize the config account
-        let config = Config::load(self.accounts.config)?;
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
 
-        // Check if we can deposit to the Amm
-        if config.state().ne(&(AmmState::Initialized as u8)) {
-            return Err(ProgramError::InvalidAccountData);
-        }
+        // Check the config, the pool mints and the vaults
+        let PoolAccounts { config, vault_x, vault_y, deployed } = validate_pool_accounts(
+            self.accounts.config,
+            (self.accounts.vault_x, self.accounts.vault_y),
+            (self.accounts.mint_x, self.accounts.mint_y),
+            (self.accounts.token_program_x, self.accounts.token_program_y),
+            &[AmmState::Initialized],
+        )?;
 
-        // Check if the vault_x is valid
-        let (vault_x, _) = find_program_address(
-            &[
-                self.accounts.config.key(),
-                self.accounts.token_program.key(),
-                config.mint_x(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-
-        if vault_x.ne(self.accounts.vault_x.key()) {
-            return Err(ProgramError::InvalidAccountData);
-        }
+        // Check the LP mint, its supply prices the LP
+        let mint_lp = CheckedLpMint::new(self.accounts.mint_lp, self.accounts.config.key())?;
+
+        // Uncollected protocol fees sit in the vaults but don't back the LP supply
+        let (reserve_x, reserve_y) = config.reserves(vault_x.amount(), vault_y.amount(), deployed);
+
+        let is_empty = mint_lp.supply() == 0 && reserve_x == 0 && reserve_y == 0;
+
+        debug_log!(Self::DISCRIMINATOR, 0, reserve_x, reserve_y, mint_lp.supply());
+
+        // Bound the deposit around the pool share of the LP at these reserves
+        if self.instruction_data.flags & SLIPPAGE_IN_BPS != 0 {
+            if is_empty || mint_lp.supply() == 0 {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let lp = self.instruction_data.amount as u128;
+            let supply = mint_lp.supply() as u128;
+            let (fair_x, fair_y) = (lp * reserve_x as u128 / supply, lp * reserve_y as u128 / supply);
 
-        // Check if the vault_y is valid
-        let (vault_y, _) = find_program_address(
-            &[
-                self.accounts.config.key(),
-                self.accounts.token_program.key(),
-                config.mint_y(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-
-        if vault_y.ne(self.accounts.vault_y.key()) {
-            return Err(ProgramError::InvalidAccountData);
+            self.instruction_data.max_x = slippage_bound(fair_x, self.instruction_data.max_x, true);
+            self.instruction_data.max_y = slippage_bound(fair_y, self.instruction_data.max_y, true);
         }
 
-        // Deserialize the token accounts
-        let mint_lp = unsafe { Mint::from_account_info_unchecked(self.accounts.mint_lp)? };
-        let vault_x = unsafe { TokenAccount::from_account_info_unchecked(self.accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_info_unchecked(self.accounts.vault_y)? };
+        let exact_amounts = self.instruction_data.flags & DepositInstructionData::EXACT_AMOUNTS != 0;
 
-        // Grab the amounts to deposit
-        let (x, y) = match mint_lp.supply() == 0 && vault_x.amount() == 0 && vault_y.amount() == 0 {
-            true => (self.instruction_data.max_x, self.instruction_data.max_y),
-            false => {
-                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                    vault_x.amount(),
-                    vault_y.amount(),
-                    mint_lp.supply(),
-                    self.instruction_data.amount,
-                    6,
-                )
-                .map_err(|_| ProgramError::InvalidArgument)?;
-
-                (amounts.x, amounts.y)
+        // Grab the amounts to deposit and the LP to mint
+        let (x, y, lp) = match (is_empty, exact_amounts) {
+            (true, _) => (
+                self.instruction_data.max_x,
+                self.instruction_data.max_y,
+                self.instruction_data.amount,
+            ),
+            (false, false) => {
+                let (x, y) = deposit_amounts(reserve_x, reserve_y, mint_lp.supply(), self.instruction_data.amount)?;
+
+                (x, y, self.instruction_data.amount)
+            }
+            (false, true) => {
+                let supply = mint_lp.supply();
+
+                if supply == 0 || reserve_x == 0 || reserve_y == 0 {
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                // The LP is capped by the scarcer side, rounding down in favor of the pool
+                let lp = mul_div(self.instruction_data.max_x, supply, reserve_x, Rounding::Down)?
+                    .min(mul_div(self.instruction_data.max_y, supply, reserve_y, Rounding::Down)?);
+
+                if lp < self.instruction_data.amount {
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                // Pay for the LP at the pool ratio, rounding up in favor of the pool
+                let (x, y) = deposit_amounts(reserve_x, reserve_y, supply, lp)?;
+
+                (x, y, lp)
             }
         };
 
+        debug_log!(Self::DISCRIMINATOR, 1, x, y, lp);
+
         // Check for slippage
         if !(x <= self.instruction_data.max_x && y <= self.instruction_data.max_y) {
             return Err(ProgramError::InvalidArgument);
         }
 
+        // Apply the optional deposit restrictions and fee
+        let deposit_fee_bps = {
+            let config_data = self.accounts.config.try_borrow_data()?;
+
+            if let Some(whitelist) = get_extension::<Whitelist>(&config_data)? {
+                if !whitelist.contains(self.accounts.user.key()) {
+                    return Err(AmmError::DepositorNotWhitelisted.into());
+                }
+            }
+
+            if let Some(caps) = get_extension::<Caps>(&config_data)? {
+                caps.check(reserve_x.saturating_add(x), reserve_y.saturating_add(y))?;
+            }
+
+            if let Some(window) = get_extension::<MaintenanceWindow>(&config_data)? {
+                window.check(Clock::get()?.unix_timestamp)?;
+            }
+
+            // Every deposit restarts the decay of the early-exit penalty, which needs the position
+            if get_extension::<ExitPenalty>(&config_data)?.is_some_and(ExitPenalty::is_enabled)
+                && self.accounts.position.is_none()
+            {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            }
+
+            match get_extension::<FeeState>(&config_data)? {
+                Some(fee_state) => fee_state.deposit_fee_bps() as u64,
+                None => 0,
+            }
+        };
+
+        // The deposit fee is held back from what the vaults receive, owed to the protocol, and only
+        // the rest mints LP. The fee rounds up and the LP down, in favor of the pool.
+        let charge_deposit_fee = |received_x: u64, received_y: u64, lp: u64| -> Result<_, ProgramError> {
+            if deposit_fee_bps == 0 {
+                return Ok((0, 0, lp));
+            }
+
+            let fee_x = mul_div(received_x, deposit_fee_bps, MAX_FEE_BPS as u64, Rounding::Up)?;
+            let fee_y = mul_div(received_y, deposit_fee_bps, MAX_FEE_BPS as u64, Rounding::Up)?;
+
+            let lp = match is_empty {
+                true => mul_div(lp, MAX_FEE_BPS as u64 - deposit_fee_bps, MAX_FEE_BPS as u64, Rounding::Down)?,
+                false => {
+                    let supply = mint_lp.supply();
+
+                    lp.min(mul_div(received_x - fee_x, supply, reserve_x, Rounding::Down)?)
+                        .min(mul_div(received_y - fee_y, supply, reserve_y, Rounding::Down)?)
+                }
+            };
+
+            Ok((fee_x, fee_y, lp))
+        };
+
+        // Report the quote and roll back before any transfer
+        if self.instruction_data.flags & DRY_RUN != 0 {
+            let (_, _, lp) = charge_deposit_fee(x, y, lp)?;
+
+            set_return_amounts(&[x, y, lp]);
+            return Err(AmmError::SimulationComplete.into());
+        }
+
         // Create the seeds
-        let seeds_binding = config.seed().to_le_bytes();
         let config_bump = config.config_bump();
         let seeds = [
             Seed::from(b"config"),
-            Seed::from(&seeds_binding),
+            Seed::from(config.seed_bytes()),
             Seed::from(config.mint_x()),
             Seed::from(config.mint_y()),
             Seed::from(&config_bump),
         ];
 
+        // A delegate must be approved for both amounts
+        CheckedUserAta::new(self.accounts.user_x_ata, config.mint_x())?.check_authority(self.accounts.user.key(), x)?;
+        CheckedUserAta::new(self.accounts.user_y_ata, config.mint_y())?.check_authority(self.accounts.user.key(), y)?;
+
         // Transfer the tokens to the vault
-        Transfer {
+        let (vault_x_before, vault_y_before) = (vault_x.amount(), vault_y.amount());
+
+        TransferChecked {
             from: self.accounts.user_x_ata,
+            mint: self.accounts.mint_x,
             to: self.accounts.vault_x,
             authority: self.accounts.user,
             amount: x,
+            decimals: config.decimals_x(),
+            token_program: config.token_program_x(),
         }
         .invoke()?;
 
-        Transfer {
+        TransferChecked {
             from: self.accounts.user_y_ata,
+            mint: self.accounts.mint_y,
             to: self.accounts.vault_y,
             authority: self.accounts.user,
             amount: y,
+            decimals: config.decimals_y(),
+            token_program: config.token_program_y(),
         }
         .invoke()?;
 
+        // With Token-2022 transfer fees the vaults receive less than was sent, so only mint
+        // the LP that the received amounts are worth
+        let received_x = vault_x.amount() - vault_x_before;
+        let received_y = vault_y.amount() - vault_y_before;
+
+        let lp = match is_empty || (received_x == x && received_y == y) {
+            true => lp,
+            false => {
+                let supply = mint_lp.supply() as u128;
+                let backed_lp = (received_x as u128 * supply / reserve_x as u128)
+                    .min(received_y as u128 * supply / reserve_y as u128);
+
+                lp.min(backed_lp as u64)
+            }
+        };
+
+        let (fee_x, fee_y, lp) = charge_deposit_fee(received_x, received_y, lp)?;
+
+        debug_log!(Self::DISCRIMINATOR, 2, received_x, received_y, lp);
+
+        if lp == 0 || (exact_amounts && lp < self.instruction_data.amount) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
         // Mint the LP tokens to the user
         MintTo {
             mint: self.accounts.mint_lp,
             account: self.accounts.user_lp_ata,
             mint_authority: self.accounts.config,
-            amount: self.instruction_data.amount,
+            amount: lp,
+            token_program: config.lp_token_program(),
         }
         .invoke_signed(&[Signer::from(&seeds)])?;
 
+        // The fee never leaves the vaults, only its owner changes
+        drop(config);
+
+        if fee_x > 0 || fee_y > 0 {
+            let mut config = Config::load_mut(self.accounts.config)?;
+            config.accrue_protocol_fee(true, fee_x)?;
+            config.accrue_protocol_fee(false, fee_y)?;
+        }
+
+        let config = Config::load(self.accounts.config)?;
+
+        // Snapshot the deposit in the user's position
+        if let Some(position) = self.accounts.position {
+            let (position_key, position_bump) =
+                derive_position(self.accounts.config.key(), self.accounts.user.key());
+
+            if position_key.ne(position.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // Create the position on the first deposit
+            if position.data_is_empty() {
+                let position_bump = [position_bump];
+                let position_seeds = [
+                    Seed::from(b"position"),
+                    Seed::from(self.accounts.config.key()),
+                    Seed::from(self.accounts.user.key()),
+                    Seed::from(&position_bump),
+                ];
+
+                CreateAccount {
+                    from: self.accounts.payer,
+                    to: position,
+                    lamports: Rent::get()?.minimum_balance(Position::LEN),
+                    space: Position::LEN as u64,
+                    owner: &crate::ID,
+                }
+                .invoke_signed(&[Signer::from(&position_seeds)])?;
+
+                Position::init(position)?.set_inner(
+                    *self.accounts.user.key(),
+                    *self.accounts.config.key(),
+                    position_bump,
+                );
+            }
+
+            let (reserve_x, reserve_y) = config.reserves(vault_x.amount(), vault_y.amount(), deployed);
+
+            Position::load_mut(position)?.record_deposit(
+                received_x,
+                received_y,
+                lp,
+                reserve_x,
+                reserve_y,
+                mint_lp.supply(),
+                Clock::get()?.unix_timestamp,
+            );
+        }
+
+        drop(config);
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        set_return_amounts(&[x, y, lp]);
+
         Ok(())
     }
 }
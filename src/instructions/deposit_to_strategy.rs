@@ -0,0 +1,285 @@
+use crate::state::{get_extension, get_extension_mut, Config, GlobalConfig, VaultStrategy};
+use crate::{
+    bump_sequence, check_signer, check_writable, set_return_amounts, validate_pool_accounts, AmmError, AmmState,
+    PoolAccounts,
+};
+use core::array;
+use pinocchio::instruction::{AccountMeta, Instruction, Seed, Signer};
+use pinocchio::program::invoke_signed;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// Most accounts a lending instruction can take
+const MAX_LENDING_ACCOUNTS: usize = 16;
+
+/// #DepositToStrategy
+///
+/// Lend idle reserves of one side through the `VaultStrategy` lending program
+///
+/// The lending instruction is invoked with the config signing as the owner of the vaults.
+/// Whatever the vault loses is tracked as deployed and keeps counting as reserves, up to
+/// `VaultStrategy::max_deployed_bps`, and the other vault must not change.
+///
+/// Accounts:
+///
+/// 1. authority:                   [signer]
+/// 2. config                       [mut]
+/// 3. vault_x                      [mut]
+/// 4. vault_y                      [mut]
+/// 5. mint_x
+/// 6. mint_y
+/// 7. token_program_x              [executable]
+/// 8. token_program_y              [executable]
+/// 9. lending_program              [executable]    // `VaultStrategy::lending_program`
+/// 10. global_config
+/// 11. ..                          // Accounts of the lending instruction, at most 16
+///
+/// Parameters:
+///
+/// 1. is_x: [bool]
+/// 2. settle: [bool]               // Must be unset, see `WithdrawFromStrategy`
+/// 3. instruction: [u8]            // Data of the lending instruction
+///
+/// Return data:
+///
+/// 1. deployed: u64,               // Amount of the side now deployed
+pub struct StrategyCpiAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub lending_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub lending_accounts: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for StrategyCpiAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, vault_x, vault_y, mint_x, mint_y, token_program_x, token_program_y, lending_program, global_config, lending_accounts @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        [config, vault_x, vault_y].into_iter().try_for_each(check_writable)?;
+
+        // Deserialize the config account
+        let config_data = Config::load(config)?;
+
+        // Check if the authority is the correct authority
+        if config_data.has_authority().ne(&Some(*authority.key())) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if lending_accounts.is_empty() || lending_accounts.len() > MAX_LENDING_ACCOUNTS {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            authority,
+            config,
+            vault_x,
+            vault_y,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            lending_program,
+            global_config,
+            lending_accounts,
+        })
+    }
+}
+
+pub struct StrategyCpiInstructionData<'a> {
+    pub is_x: bool,
+    pub settle: bool,
+    pub lending_data: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for StrategyCpiInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let [is_x, settle, lending_data @ ..] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        Ok(Self {
+            is_x: *is_x > 0,
+            settle: *settle > 0,
+            lending_data,
+        })
+    }
+}
+
+impl<'a> StrategyCpiAccounts<'a> {
+    /// Check the pool and its lending program, then run the lending instruction with the config
+    /// signing and return the balance of the `is_x` vault before and after.
+    pub fn invoke_lending(&self, is_x: bool, lending_data: &[u8]) -> Result<(u64, u64), ProgramError> {
+        // Check the config, the pool mints and the vaults, deployed reserves can always be recalled
+        let PoolAccounts { config, vault_x, vault_y, .. } = validate_pool_accounts(
+            self.config,
+            (self.vault_x, self.vault_y),
+            (self.mint_x, self.mint_y),
+            (self.token_program_x, self.token_program_y),
+            &[AmmState::Initialized, AmmState::Disabled, AmmState::WithdrawOnly],
+        )?;
+
+        {
+            let config_data = self.config.try_borrow_data()?;
+            let vault_strategy = get_extension::<VaultStrategy>(&config_data)?.ok_or(AmmError::MissingExtension)?;
+
+            if vault_strategy.lending_program().eq(&[0u8; 32])
+                || vault_strategy.lending_program().ne(self.lending_program.key())
+            {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+        }
+
+        let (vault, other_vault) = match is_x {
+            true => (vault_x, vault_y),
+            false => (vault_y, vault_x),
+        };
+        let (before, other_before) = (vault.amount(), other_vault.amount());
+
+        // Create the signer seeds
+        let config_bump = config.config_bump();
+        let seeds = [
+            Seed::from(b"config"),
+            Seed::from(config.seed_bytes()),
+            Seed::from(config.mint_x()),
+            Seed::from(config.mint_y()),
+            Seed::from(&config_bump),
+        ];
+        let signer = [Signer::from(&seeds)];
+
+        // `invoke_signed` needs the account count at compile time
+        match self.lending_accounts.len() {
+            1 => self.invoke_lending_with::<1>(lending_data, &signer)?,
+            2 => self.invoke_lending_with::<2>(lending_data, &signer)?,
+            3 => self.invoke_lending_with::<3>(lending_data, &signer)?,
+            4 => self.invoke_lending_with::<4>(lending_data, &signer)?,
+            5 => self.invoke_lending_with::<5>(lending_data, &signer)?,
+            6 => self.invoke_lending_with::<6>(lending_data, &signer)?,
+            7 => self.invoke_lending_with::<7>(lending_data, &signer)?,
+            8 => self.invoke_lending_with::<8>(lending_data, &signer)?,
+            9 => self.invoke_lending_with::<9>(lending_data, &signer)?,
+            10 => self.invoke_lending_with::<10>(lending_data, &signer)?,
+            11 => self.invoke_lending_with::<11>(lending_data, &signer)?,
+            12 => self.invoke_lending_with::<12>(lending_data, &signer)?,
+            13 => self.invoke_lending_with::<13>(lending_data, &signer)?,
+            14 => self.invoke_lending_with::<14>(lending_data, &signer)?,
+            15 => self.invoke_lending_with::<15>(lending_data, &signer)?,
+            _ => self.invoke_lending_with::<MAX_LENDING_ACCOUNTS>(lending_data, &signer)?,
+        }
+
+        // The lending program only moves the side being rebalanced
+        if other_vault.amount().ne(&other_before) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok((before, vault.amount()))
+    }
+
+    fn invoke_lending_with<const ACCOUNTS: usize>(&self, lending_data: &[u8], signer: &[Signer]) -> ProgramResult {
+        let lending_accounts = self.lending_accounts;
+
+        let account_metas: [AccountMeta; ACCOUNTS] = array::from_fn(|index| {
+            let account = &lending_accounts[index];
+            // The config stays borrowed for the signer seeds, and only this program writes it anyway
+            let is_config = account.key().eq(self.config.key());
+            AccountMeta::new(account.key(), account.is_writable() && !is_config, account.is_signer() || is_config)
+        });
+        let account_infos: [&AccountInfo; ACCOUNTS] = array::from_fn(|index| &lending_accounts[index]);
+
+        invoke_signed(
+            &Instruction {
+                program_id: self.lending_program.key(),
+                accounts: &account_metas,
+                data: lending_data,
+            },
+            &account_infos,
+            signer,
+        )
+    }
+}
+
+pub struct DepositToStrategy<'a> {
+    pub accounts: StrategyCpiAccounts<'a>,
+    pub instruction_data: StrategyCpiInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositToStrategy<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = StrategyCpiAccounts::try_from(accounts)?;
+        let instruction_data = StrategyCpiInstructionData::try_from(data)?;
+
+        if instruction_data.settle {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositToStrategy<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &23;
+
+    /// `sha256("global:deposit_to_strategy")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [193, 61, 195, 221, 81, 161, 9, 170];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let is_x = self.instruction_data.is_x;
+
+        let (before, after) = self.accounts.invoke_lending(is_x, self.instruction_data.lending_data)?;
+        let lent = before.checked_sub(after).ok_or(ProgramError::InvalidAccountData)?;
+
+        let deployed = {
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+
+            // Uncollected protocol fees don't back the curve and stay in the vault
+            let protocol_fees = {
+                let config = unsafe { Config::from_bytes_unchecked(&config_data) };
+                match is_x {
+                    true => config.protocol_fees_x(),
+                    false => config.protocol_fees_y(),
+                }
+            };
+            let liquid = after.checked_sub(protocol_fees).ok_or(ProgramError::InsufficientFunds)?;
+
+            let vault_strategy =
+                get_extension_mut::<VaultStrategy>(&mut config_data)?.ok_or(AmmError::MissingExtension)?;
+
+            vault_strategy.deploy(is_x, lent)?;
+            vault_strategy.check_cap(is_x, liquid)?;
+
+            match is_x {
+                true => vault_strategy.deployed_x(),
+                false => vault_strategy.deployed_y(),
+            }
+        };
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        set_return_amounts(&[deployed]);
+
+        Ok(())
+    }
+}
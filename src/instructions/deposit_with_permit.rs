@@ -0,0 +1,229 @@
+use crate::{
+    check_ed25519_signature, check_signer, check_writable, derive_permit_authority, derive_permit_nonce, CheckedUserAta,
+    Deposit, DepositInstructionData,
+};
+use core::array;
+use core::mem::size_of;
+use pinocchio::instruction::{AccountMeta, Instruction, Seed, Signer};
+use pinocchio::program::invoke_signed;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_system::instructions::CreateAccount;
+
+/// Number of accounts of the permitted `Deposit`
+const DEPOSIT_ACCOUNTS: usize = 14;
+
+/// #DepositWithPermit
+///
+/// Run a `Deposit` the owner signed off-chain, submitted by anyone without the owner signing the
+/// transaction
+///
+/// The owner approves the `[b"permit", owner]` PDA once as delegate of their X and Y accounts.
+/// Each deposit is then authorized by an ed25519 signature of the owner over
+/// `DepositWithPermitInstructionData::message`, verified by the instruction right before this
+/// one. The `Deposit` is invoked with the permit PDA signing as its `user`, and each permit can be
+/// used once, by creating the `[b"permit_nonce", owner, nonce]` account.
+///
+/// Accounts:
+///
+/// 1. relayer:                     [signer, mut]   // Submits the transaction, pays the permit_nonce rent
+/// 2. owner                        // Signer of the permit
+/// 3. permit_nonce                 [init]
+/// 4. instructions_sysvar
+/// 5. system_program               [executable]
+/// 6. ..                           // Accounts of the `Deposit`, with the permit PDA as `user`
+///
+/// The `Deposit` takes its 14 accounts without a position, with token accounts of the owner.
+///
+/// Parameters:
+///
+/// 1. nonce: u64,
+/// 2. deposit: [u8; 33]            // The `Deposit` data, with the flags byte always present
+///
+/// Return data: the `Deposit`'s
+pub struct DepositWithPermitAccounts<'a> {
+    pub relayer: &'a AccountInfo,
+    pub owner: &'a AccountInfo,
+    pub permit_nonce: &'a AccountInfo,
+    pub instructions_sysvar: &'a AccountInfo,
+    pub inner: &'a [AccountInfo; DEPOSIT_ACCOUNTS],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for DepositWithPermitAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [relayer, owner, permit_nonce, instructions_sysvar, _system_program, inner @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let inner: &[AccountInfo; DEPOSIT_ACCOUNTS] =
+            inner.try_into().map_err(|_| ProgramError::NotEnoughAccountKeys)?;
+
+        // Check the signers and the writable accounts
+        check_signer(relayer)?;
+        [relayer, permit_nonce].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            relayer,
+            owner,
+            permit_nonce,
+            instructions_sysvar,
+            inner,
+        })
+    }
+}
+
+pub struct DepositWithPermitInstructionData {
+    pub nonce: u64,
+    /// The `Deposit` data, checked by `DepositInstructionData` and signed as is
+    pub deposit: [u8; Self::DEPOSIT_DATA_LEN],
+}
+
+impl DepositWithPermitInstructionData {
+    /// Size of the `Deposit` data, with the flags
+    pub const DEPOSIT_DATA_LEN: usize = size_of::<u64>() * 3 + size_of::<i64>() + size_of::<u8>();
+
+    /// Size of the message signed by the owner
+    pub const MESSAGE_LEN: usize = size_of::<Pubkey>() + size_of::<u64>() + Self::DEPOSIT_DATA_LEN;
+
+    /// The message signed by the owner: config [32] | nonce [8] | amount [8] | max_x [8] | max_y [8] |
+    /// expiration [8] | flags [1]
+    pub fn message(&self, config: &Pubkey) -> [u8; Self::MESSAGE_LEN] {
+        let mut message = [0u8; Self::MESSAGE_LEN];
+        message[0..32].copy_from_slice(config);
+        message[32..40].copy_from_slice(&self.nonce.to_le_bytes());
+        message[40..].copy_from_slice(&self.deposit);
+        message
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositWithPermitInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u64>() + Self::DEPOSIT_DATA_LEN)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let nonce = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let deposit: [u8; Self::DEPOSIT_DATA_LEN] = data[8..].try_into().unwrap();
+
+        // Check the `Deposit` data up front
+        DepositInstructionData::try_from(&deposit[..])?;
+
+        Ok(Self { nonce, deposit })
+    }
+}
+
+pub struct DepositWithPermit<'a> {
+    pub accounts: DepositWithPermitAccounts<'a>,
+    pub instruction_data: DepositWithPermitInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositWithPermit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = DepositWithPermitAccounts::try_from(accounts)?;
+        let instruction_data = DepositWithPermitInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositWithPermit<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &65;
+
+    /// `sha256("global:deposit_with_permit")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [107, 47, 58, 93, 158, 107, 183, 5];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let inner = self.accounts.inner;
+        let owner = self.accounts.owner.key();
+
+        // Check the owner signed this exact deposit on this pool
+        check_ed25519_signature(
+            self.accounts.instructions_sysvar,
+            owner,
+            &self.instruction_data.message(inner[7].key()),
+        )?;
+
+        // The permit PDA signs as `user`
+        let (permit_authority, permit_authority_bump) = derive_permit_authority(owner);
+
+        if inner[0].key().ne(&permit_authority) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Take the tokens from the owner and mint the LP to them
+        CheckedUserAta::with_owner(&inner[4], inner[9].key(), owner)?;
+        CheckedUserAta::with_owner(&inner[5], inner[10].key(), owner)?;
+        CheckedUserAta::with_owner(&inner[6], inner[1].key(), owner)?;
+
+        // Burn the permit, creating the nonce account fails if it was already used
+        let (permit_nonce, permit_nonce_bump) = derive_permit_nonce(owner, self.instruction_data.nonce);
+
+        if permit_nonce.ne(self.accounts.permit_nonce.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let nonce = self.instruction_data.nonce.to_le_bytes();
+        let permit_nonce_bump = [permit_nonce_bump];
+        let permit_nonce_seeds = [
+            Seed::from(b"permit_nonce"),
+            Seed::from(owner),
+            Seed::from(&nonce),
+            Seed::from(&permit_nonce_bump),
+        ];
+
+        CreateAccount {
+            from: self.accounts.relayer,
+            to: self.accounts.permit_nonce,
+            lamports: Rent::get()?.minimum_balance(0),
+            space: 0,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&permit_nonce_seeds)])?;
+
+        // The `Deposit` encoding of the permit
+        let mut data = [0u8; 8 + DepositWithPermitInstructionData::DEPOSIT_DATA_LEN];
+        data[..8].copy_from_slice(&Deposit::HASH_DISCRIMINATOR);
+        data[8..].copy_from_slice(&self.instruction_data.deposit);
+
+        let permit_authority_bump = [permit_authority_bump];
+        let permit_authority_seeds = [
+            Seed::from(b"permit"),
+            Seed::from(owner),
+            Seed::from(&permit_authority_bump),
+        ];
+
+        self.invoke_deposit(&data, &[Signer::from(&permit_authority_seeds)])
+    }
+
+    /// Invoke the `Deposit` with the permit PDA signing
+    fn invoke_deposit(&self, data: &[u8], signer: &[Signer]) -> ProgramResult {
+        let inner = self.accounts.inner;
+
+        let account_metas: [AccountMeta; DEPOSIT_ACCOUNTS] = array::from_fn(|index| {
+            let account = &inner[index];
+            AccountMeta::new(account.key(), account.is_writable(), account.is_signer() || index == 0)
+        });
+        let account_infos: [&AccountInfo; DEPOSIT_ACCOUNTS] = array::from_fn(|index| &inner[index]);
+
+        invoke_signed(
+            &Instruction {
+                program_id: &crate::ID,
+                accounts: &account_metas,
+                data,
+            },
+            &account_infos,
+            signer,
+        )
+    }
+}
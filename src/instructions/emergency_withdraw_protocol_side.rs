@@ -0,0 +1,265 @@
+use crate::state::Recovery;
+use crate::{
+    bump_sequence, check_signer, check_writable, derive_ata, derive_recovery, set_return_amounts,
+    validate_pool_accounts, AmmState, CheckedLpMint, CheckedVault, PoolAccounts, TransferChecked,
+};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_associated_token_account::instructions::CreateIdempotent;
+use pinocchio_system::instructions::CreateAccount;
+
+/// #EmergencyWithdrawProtocolSide
+///
+/// Move the reserves of a `Disabled` pool into its recovery escrow, pending LP claims
+///
+/// The escrow is owned by the pool's `Recovery` PDA, never by the authority, and no fee is charged.
+/// Uncollected protocol fees stay in the vaults, and amounts deployed to a `VaultStrategy` can be
+/// moved once they are back by running this again. Each run retakes the `Recovery` snapshot that
+/// `ClaimRecovery` pays out against.
+///
+/// Accounts:
+///
+/// 1. authority:                   [signer, mut]   // Pool authority, pays for the escrow accounts
+/// 2. config                       [mut with a `Sequence`]
+/// 3. recovery                     [init_if_needed]    // [b"recovery", config]
+/// 4. vault_x                      [mut]
+/// 5. vault_y                      [mut]
+/// 6. recovery_x                   [init_if_needed]    // ATA of recovery
+/// 7. recovery_y                   [init_if_needed]    // ATA of recovery
+/// 8. mint_x
+/// 9. mint_y
+/// 10. mint_lp
+/// 11. token_program_x             [executable]
+/// 12. token_program_y             [executable]
+/// 13. system_program              [executable]
+/// 14. associated_token_program    [executable]
+///
+/// Return data:
+///
+/// 1. x: u64,                      // Amount of X moved to the escrow
+/// 2. y: u64,                      // Amount of Y moved to the escrow
+pub struct EmergencyWithdrawProtocolSideAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub recovery: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub recovery_x: &'a AccountInfo,
+    pub recovery_y: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for EmergencyWithdrawProtocolSideAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, recovery, vault_x, vault_y, recovery_x, recovery_y, mint_x, mint_y, mint_lp, token_program_x, token_program_y, system_program, _associated_token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        [authority, recovery, vault_x, vault_y, recovery_x, recovery_y].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            authority,
+            config,
+            recovery,
+            vault_x,
+            vault_y,
+            recovery_x,
+            recovery_y,
+            mint_x,
+            mint_y,
+            mint_lp,
+            token_program_x,
+            token_program_y,
+            system_program,
+        })
+    }
+}
+
+pub struct EmergencyWithdrawProtocolSide<'a> {
+    pub accounts: EmergencyWithdrawProtocolSideAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for EmergencyWithdrawProtocolSide<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = EmergencyWithdrawProtocolSideAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> EmergencyWithdrawProtocolSide<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &31;
+
+    /// `sha256("global:emergency_withdraw_protocol_side")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [63, 113, 150, 227, 184, 163, 227, 167];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Only a disabled pool can be emptied
+        let PoolAccounts { config, vault_x, vault_y, .. } = validate_pool_accounts(
+            self.accounts.config,
+            (self.accounts.vault_x, self.accounts.vault_y),
+            (self.accounts.mint_x, self.accounts.mint_y),
+            (self.accounts.token_program_x, self.accounts.token_program_y),
+            &[AmmState::Disabled],
+        )?;
+
+        // Check if the authority is the correct authority
+        if config.has_authority().ne(&Some(*self.accounts.authority.key())) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check the LP mint, the recovery escrow and its token accounts
+        let mint_lp = CheckedLpMint::new(self.accounts.mint_lp, self.accounts.config.key())?;
+
+        let (recovery_key, recovery_bump) = derive_recovery(self.accounts.config.key());
+        if recovery_key.ne(self.accounts.recovery.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if derive_ata(self.accounts.recovery.key(), config.token_program_x(), config.mint_x())
+            .ne(self.accounts.recovery_x.key())
+            || derive_ata(self.accounts.recovery.key(), config.token_program_y(), config.mint_y())
+                .ne(self.accounts.recovery_y.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the recovery on the first emergency withdrawal
+        if self.accounts.recovery.data_is_empty() {
+            let recovery_bump = [recovery_bump];
+            let recovery_seeds = [
+                Seed::from(b"recovery"),
+                Seed::from(self.accounts.config.key()),
+                Seed::from(&recovery_bump),
+            ];
+
+            CreateAccount {
+                from: self.accounts.authority,
+                to: self.accounts.recovery,
+                lamports: Rent::get()?.minimum_balance(Recovery::LEN),
+                space: Recovery::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&recovery_seeds)])?;
+
+            Recovery::init(self.accounts.recovery)?.set_inner(*self.accounts.config.key(), recovery_bump);
+        }
+
+        for (account, mint, token_program) in [
+            (self.accounts.recovery_x, self.accounts.mint_x, self.accounts.token_program_x),
+            (self.accounts.recovery_y, self.accounts.mint_y, self.accounts.token_program_y),
+        ] {
+            CreateIdempotent {
+                funding_account: self.accounts.authority,
+                account,
+                wallet: self.accounts.recovery,
+                mint,
+                system_program: self.accounts.system_program,
+                token_program,
+            }
+            .invoke()?;
+        }
+
+        // Uncollected protocol fees aren't owed to the LP holders
+        let x = vault_x.amount().saturating_sub(config.protocol_fees_x());
+        let y = vault_y.amount().saturating_sub(config.protocol_fees_y());
+
+        if x == 0 && y == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Create the signer seeds
+        let config_bump = config.config_bump();
+        let seeds = [
+            Seed::from(b"config"),
+            Seed::from(config.seed_bytes()),
+            Seed::from(config.mint_x()),
+            Seed::from(config.mint_y()),
+            Seed::from(&config_bump),
+        ];
+        let signer_seeds = [Signer::from(&seeds)];
+
+        for (amount, vault, mint, recovery, decimals, token_program) in [
+            (
+                x,
+                self.accounts.vault_x,
+                self.accounts.mint_x,
+                self.accounts.recovery_x,
+                config.decimals_x(),
+                config.token_program_x(),
+            ),
+            (
+                y,
+                self.accounts.vault_y,
+                self.accounts.mint_y,
+                self.accounts.recovery_y,
+                config.decimals_y(),
+                config.token_program_y(),
+            ),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+
+            TransferChecked {
+                from: vault,
+                mint,
+                to: recovery,
+                authority: self.accounts.config,
+                amount,
+                decimals,
+                token_program,
+            }
+            .invoke_signed(&signer_seeds)?;
+        }
+
+        // Snapshot what the escrow holds for the outstanding LP, net of transfer fees
+        let recovery_x = CheckedVault::new(
+            self.accounts.recovery_x,
+            config.mint_x(),
+            self.accounts.recovery.key(),
+            config.token_program_x(),
+        )?;
+        let recovery_y = CheckedVault::new(
+            self.accounts.recovery_y,
+            config.mint_y(),
+            self.accounts.recovery.key(),
+            config.token_program_y(),
+        )?;
+
+        Recovery::load_mut(self.accounts.recovery)?.snapshot(
+            mint_lp.supply(),
+            recovery_x.amount(),
+            recovery_y.amount(),
+        );
+
+        drop(config);
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        set_return_amounts(&[x, y]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use crate::state::LongTermOrder;
+use crate::{check_writable, set_return_amounts, AmmError, CheckedUserAta, Swap};
+use core::array;
+use core::mem::size_of;
+use pinocchio::instruction::{AccountMeta, Instruction, Seed, Signer};
+use pinocchio::program::invoke_signed;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// Number of accounts of the order's `Swap`
+const SWAP_ACCOUNTS: usize = 15;
+
+/// #ExecuteLongTermOrder
+///
+/// Sell the part of a `LongTermOrder` due since its last crank, pro rata of the slots elapsed
+///
+/// Anyone can crank an order. The due input is swapped as a `Swap` invoked with the order signing
+/// as its `user`, out of and into its escrows, so every pool check applies to each crank. The swap
+/// must keep the order's output at or above its pro rata share of `min_amount_out`, which bounds
+/// what a sandwich around any crank can take.
+///
+/// Accounts:
+///
+/// 1. long_term_order              [mut]
+/// 2. ..                           // Accounts of the `Swap`, with the order as `user`
+///
+/// The `Swap` takes its first 15 accounts, optional ones set to the program id, with the order's
+/// escrows as `user_x` and `user_y`.
+///
+/// Return data:
+///
+/// 1. sold: u64,                   // Input sold by this crank
+/// 2. bought: u64,                 // Output bought by this crank, net of transfer fees
+pub struct ExecuteLongTermOrderAccounts<'a> {
+    pub long_term_order: &'a AccountInfo,
+    pub inner: &'a [AccountInfo; SWAP_ACCOUNTS],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteLongTermOrderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [long_term_order, inner @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let inner: &[AccountInfo; SWAP_ACCOUNTS] =
+            inner.try_into().map_err(|_| ProgramError::NotEnoughAccountKeys)?;
+
+        // Check the writable accounts
+        check_writable(long_term_order)?;
+
+        // Return the accounts
+        Ok(Self { long_term_order, inner })
+    }
+}
+
+pub struct ExecuteLongTermOrder<'a> {
+    pub accounts: ExecuteLongTermOrderAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ExecuteLongTermOrder<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = ExecuteLongTermOrderAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ExecuteLongTermOrder<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &62;
+
+    /// `sha256("global:execute_long_term_order")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [53, 120, 53, 231, 172, 192, 13, 142];
+
+    /// Index of the config among the accounts, see `Swap`
+    pub const CONFIG_INDEX: usize = 6;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let inner = self.accounts.inner;
+
+        let (config, owner, nonce, bump, is_x, due, min) = {
+            let order = LongTermOrder::load(self.accounts.long_term_order)?;
+
+            // The order signs as `user` on its own pool
+            if inner[0].key().ne(self.accounts.long_term_order.key()) || inner[5].key().ne(order.config()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let due = order.due(Clock::get()?.slot)?;
+
+            if due == 0 {
+                return Err(AmmError::OrderNotDue.into());
+            }
+
+            // Whatever keeps the order on track for its minimum output, at least 1 as `Swap` requires
+            let min = order
+                .min_bought(order.sold() + due)?
+                .saturating_sub(order.bought())
+                .max(1);
+
+            (*order.config(), *order.owner(), order.nonce().to_le_bytes(), order.bump(), order.is_x(), due, min)
+        };
+
+        // Keep the proceeds in the order's escrows
+        let escrow_x = CheckedUserAta::with_owner(&inner[1], inner[6].key(), self.accounts.long_term_order.key())?;
+        let escrow_y = CheckedUserAta::with_owner(&inner[2], inner[7].key(), self.accounts.long_term_order.key())?;
+        let (escrow_in, escrow_out) = match is_x {
+            true => (escrow_x, escrow_y),
+            false => (escrow_y, escrow_x),
+        };
+        let (in_before, out_before) = (escrow_in.amount(), escrow_out.amount());
+
+        // The `Swap` encoding of the crank, never expiring
+        let mut data = [0u8; 8 + size_of::<bool>() + size_of::<u64>() * 2 + size_of::<i64>()];
+        data[..8].copy_from_slice(&Swap::HASH_DISCRIMINATOR);
+        data[8] = is_x as u8;
+        data[9..17].copy_from_slice(&due.to_le_bytes());
+        data[17..25].copy_from_slice(&min.to_le_bytes());
+        data[25..33].copy_from_slice(&i64::MAX.to_le_bytes());
+
+        let long_term_order_seeds = [
+            Seed::from(b"long_term_order"),
+            Seed::from(&config),
+            Seed::from(&owner),
+            Seed::from(&nonce),
+            Seed::from(&bump),
+        ];
+
+        self.invoke_swap(&data, &[Signer::from(&long_term_order_seeds)])?;
+
+        let sold = in_before.checked_sub(escrow_in.amount()).ok_or(AmmError::MathOverflow)?;
+        let bought = escrow_out.amount().checked_sub(out_before).ok_or(AmmError::MathOverflow)?;
+
+        LongTermOrder::load_mut(self.accounts.long_term_order)?.fill(sold, bought)?;
+
+        set_return_amounts(&[sold, bought]);
+
+        Ok(())
+    }
+
+    /// Invoke the `Swap` with the order signing
+    fn invoke_swap(&self, data: &[u8], signer: &[Signer]) -> ProgramResult {
+        let inner = self.accounts.inner;
+
+        let account_metas: [AccountMeta; SWAP_ACCOUNTS] = array::from_fn(|index| {
+            let account = &inner[index];
+            AccountMeta::new(
+                account.key(),
+                account.is_writable(),
+                account.is_signer() || account.key().eq(self.accounts.long_term_order.key()),
+            )
+        });
+        let account_infos: [&AccountInfo; SWAP_ACCOUNTS] = array::from_fn(|index| &inner[index]);
+
+        invoke_signed(
+            &Instruction {
+                program_id: &crate::ID,
+                accounts: &account_metas,
+                data,
+            },
+            &account_infos,
+            signer,
+        )
+    }
+}
@@ -0,0 +1,179 @@
+use crate::state::Allowance;
+use crate::{check_signer, check_writable, split_discriminator, CheckedUserAta, Deposit, Swap};
+use core::array;
+use pinocchio::instruction::{AccountMeta, Instruction, Seed, Signer};
+use pinocchio::program::invoke_signed;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// Number of accounts of a strategy `Swap`
+const SWAP_ACCOUNTS: usize = 15;
+/// Number of accounts of a strategy `Deposit`
+const DEPOSIT_ACCOUNTS: usize = 14;
+
+/// #ExecuteStrategy
+///
+/// Run a `Swap` or `Deposit` for an owner within the allowance it granted the keeper
+///
+/// The inner instruction is invoked with the allowance PDA signing as its `user`, so the owner's
+/// token accounts must have approved it as delegate. Whatever the owner's X and Y accounts lose
+/// is charged to the allowance, and everything the inner instruction pays out must go to
+/// accounts of the owner.
+///
+/// Accounts:
+///
+/// 1. keeper:                      [signer]
+/// 2. allowance                    [mut]
+/// 3. ..                           // Accounts of the inner instruction, with the allowance as `user`
+///
+/// `Swap` takes all 15 accounts, optional ones set to the program id, and `Deposit` the 14
+/// accounts without a position.
+///
+/// Parameters:
+///
+/// 1. instruction: [u8]            // Discriminator and data of the inner instruction
+///
+/// Return data: the inner instruction's
+pub struct ExecuteStrategyAccounts<'a> {
+    pub keeper: &'a AccountInfo,
+    pub allowance: &'a AccountInfo,
+    pub inner: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteStrategyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [keeper, allowance, inner @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(keeper)?;
+        check_writable(allowance)?;
+
+        // Return the accounts
+        Ok(Self {
+            keeper,
+            allowance,
+            inner,
+        })
+    }
+}
+
+pub struct ExecuteStrategy<'a> {
+    pub accounts: ExecuteStrategyAccounts<'a>,
+    pub instruction_data: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ExecuteStrategy<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ExecuteStrategyAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data: data,
+        })
+    }
+}
+
+impl<'a> ExecuteStrategy<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &21;
+
+    /// `sha256("global:execute_strategy")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [164, 197, 251, 183, 219, 177, 85, 161];
+
+    /// Index of the config among the accounts when running a `Swap`, see `Swap`
+    pub const CONFIG_INDEX: usize = 2 + 5;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let inner = self.accounts.inner;
+
+        // Indexes of the config, then of the owner's X and Y accounts and any other receiving account
+        // with their mints
+        let (permission, config, x, y, lp) = match split_discriminator(self.instruction_data) {
+            Some((Swap::DISCRIMINATOR, _)) if inner.len() == SWAP_ACCOUNTS => {
+                (Allowance::ALLOW_SWAP, 5, (1, 6), (2, 7), None)
+            }
+            Some((Deposit::DISCRIMINATOR, _)) if inner.len() == DEPOSIT_ACCOUNTS => {
+                (Allowance::ALLOW_DEPOSIT, 7, (4, 9), (5, 10), Some((6, 1)))
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let (owner, bump) = {
+            let allowance = Allowance::load(self.accounts.allowance)?;
+
+            if self.accounts.keeper.key().ne(allowance.keeper()) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+
+            allowance.check(permission, Clock::get()?.unix_timestamp)?;
+
+            // The allowance signs as `user` on the pool it was granted for
+            if inner[0].key().ne(self.accounts.allowance.key()) || inner[config].key().ne(allowance.config()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            (*allowance.owner(), allowance.bump())
+        };
+
+        // Keep the proceeds with the owner
+        if let Some((lp, mint_lp)) = lp {
+            CheckedUserAta::with_owner(&inner[lp], inner[mint_lp].key(), &owner)?;
+        }
+
+        let owner_x = CheckedUserAta::with_owner(&inner[x.0], inner[x.1].key(), &owner)?;
+        let owner_y = CheckedUserAta::with_owner(&inner[y.0], inner[y.1].key(), &owner)?;
+        let (x_before, y_before) = (owner_x.amount(), owner_y.amount());
+
+        let config_key = *inner[config].key();
+        let keeper_key = *self.accounts.keeper.key();
+        let allowance_seeds = [
+            Seed::from(b"allowance"),
+            Seed::from(&config_key),
+            Seed::from(&owner),
+            Seed::from(&keeper_key),
+            Seed::from(&bump),
+        ];
+        let signer = [Signer::from(&allowance_seeds)];
+
+        match inner.len() {
+            SWAP_ACCOUNTS => self.invoke_inner::<SWAP_ACCOUNTS>(&signer)?,
+            _ => self.invoke_inner::<DEPOSIT_ACCOUNTS>(&signer)?,
+        }
+
+        // Charge what left the owner's accounts
+        let x_spent = x_before.saturating_sub(owner_x.amount());
+        let y_spent = y_before.saturating_sub(owner_y.amount());
+
+        Allowance::load_mut(self.accounts.allowance)?.spend(x_spent, y_spent)
+    }
+
+    /// Invoke the inner instruction with the allowance signing
+    fn invoke_inner<const ACCOUNTS: usize>(&self, signer: &[Signer]) -> ProgramResult {
+        let inner = self.accounts.inner;
+
+        let account_metas: [AccountMeta; ACCOUNTS] = array::from_fn(|index| {
+            let account = &inner[index];
+            AccountMeta::new(
+                account.key(),
+                account.is_writable(),
+                account.is_signer() || account.key().eq(self.accounts.allowance.key()),
+            )
+        });
+        let account_infos: [&AccountInfo; ACCOUNTS] = array::from_fn(|index| &inner[index]);
+
+        invoke_signed(
+            &Instruction {
+                program_id: &crate::ID,
+                accounts: &account_metas,
+                data: self.instruction_data,
+            },
+            &account_infos,
+            signer,
+        )
+    }
+}
@@ -0,0 +1,175 @@
+use crate::state::Lbp;
+use crate::{check_signer, check_writable, derive_ata, set_return_amounts, CheckedVault, TransferChecked};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #ExitLbp
+///
+/// Close a liquidity bootstrapping pool once its sale has ended and return both vaults to the
+/// creator
+///
+/// Accounts:
+///
+/// 1. creator:                     [signer]
+/// 2. lbp                          [mut]
+/// 3. lbp_token_vault              [mut]
+/// 4. lbp_quote_vault              [mut]
+/// 5. creator_token_ata            [mut]
+/// 6. creator_quote_ata            [mut]
+/// 7. mint
+/// 8. quote_mint
+/// 9. token_program                [executable]
+/// 10. quote_program               [executable]
+///
+/// Return data:
+///
+/// 1. token_amount: u64,           // Sent to the creator
+/// 2. quote_amount: u64,
+pub struct ExitLbpAccounts<'a> {
+    pub creator: &'a AccountInfo,
+    pub lbp: &'a AccountInfo,
+    pub lbp_token_vault: &'a AccountInfo,
+    pub lbp_quote_vault: &'a AccountInfo,
+    pub creator_token_ata: &'a AccountInfo,
+    pub creator_quote_ata: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub quote_mint: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExitLbpAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [creator, lbp, lbp_token_vault, lbp_quote_vault, creator_token_ata, creator_quote_ata, mint, quote_mint, token_program, quote_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(creator)?;
+        [
+            lbp,
+            lbp_token_vault,
+            lbp_quote_vault,
+            creator_token_ata,
+            creator_quote_ata,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Deserialize the lbp account
+        let lbp_data = Lbp::load(lbp)?;
+
+        // Only the creator takes the liquidity back
+        if creator.key().ne(lbp_data.creator()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check the mints and their token programs
+        if mint.key().ne(lbp_data.mint())
+            || quote_mint.key().ne(lbp_data.quote_mint())
+            || token_program.key().ne(lbp_data.token_program())
+            || quote_program.key().ne(lbp_data.quote_program())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check if the lbp vaults are valid
+        if derive_ata(lbp.key(), token_program.key(), mint.key()).ne(lbp_token_vault.key())
+            || derive_ata(lbp.key(), quote_program.key(), quote_mint.key()).ne(lbp_quote_vault.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            creator,
+            lbp,
+            lbp_token_vault,
+            lbp_quote_vault,
+            creator_token_ata,
+            creator_quote_ata,
+            mint,
+            quote_mint,
+        })
+    }
+}
+
+pub struct ExitLbp<'a> {
+    pub accounts: ExitLbpAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ExitLbp<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = ExitLbpAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ExitLbp<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &53;
+
+    /// `sha256("global:exit_lbp")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [119, 109, 227, 40, 125, 11, 100, 145];
+
+    pub fn process(&mut self) -> ProgramResult {
+        Lbp::load_mut(self.accounts.lbp)?.close(Clock::get()?.unix_timestamp)?;
+
+        let lbp = Lbp::load(self.accounts.lbp)?;
+
+        let lbp_bump = lbp.bump();
+        let lbp_seeds = [Seed::from(b"lbp"), Seed::from(lbp.mint()), Seed::from(&lbp_bump)];
+        let signer_seeds = [Signer::from(&lbp_seeds)];
+
+        // The vaults are emptied in full, donations included
+        let mut amounts = [0u64; 2];
+
+        for (amount, (vault, mint, to, decimals, token_program)) in amounts.iter_mut().zip([
+            (
+                self.accounts.lbp_token_vault,
+                self.accounts.mint,
+                self.accounts.creator_token_ata,
+                lbp.token_decimals(),
+                lbp.token_program(),
+            ),
+            (
+                self.accounts.lbp_quote_vault,
+                self.accounts.quote_mint,
+                self.accounts.creator_quote_ata,
+                lbp.quote_decimals(),
+                lbp.quote_program(),
+            ),
+        ]) {
+            *amount = CheckedVault::new(vault, mint.key(), self.accounts.lbp.key(), token_program)?.amount();
+
+            if *amount == 0 {
+                continue;
+            }
+
+            TransferChecked {
+                from: vault,
+                mint,
+                to,
+                authority: self.accounts.lbp,
+                amount: *amount,
+                decimals,
+                token_program,
+            }
+            .invoke_signed(&signer_seeds)?;
+        }
+
+        set_return_amounts(&amounts);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use crate::state::{find_extension, Config, ConfigExtension, GlobalConfig};
+use crate::{bump_sequence, check_signer, check_writable, AmmError};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::Transfer;
+
+/// #ExtendConfig
+///
+/// Grow the config account with a zeroed `ConfigExtension` so optional features can be enabled
+/// on an existing pool
+///
+/// Accounts:
+///
+/// 1. authority:                   [signer, mut]   // Pays the extra rent
+/// 2. config                       [mut]
+/// 3. global_config
+/// 4. system_program               [executable]
+///
+/// Parameters:
+///
+/// 1. extension_type: [u16]       // See `ConfigExtension`
+pub struct ExtendConfigAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExtendConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, global_config, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        [authority, config].into_iter().try_for_each(check_writable)?;
+
+        // Deserialize the config account
+        let config_data = Config::load(config)?;
+
+        // Check if the authority is the correct authority
+        if config_data.has_authority().ne(&Some(*authority.key())) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self { authority, config, global_config })
+    }
+}
+
+pub struct ExtendConfigInstructionData {
+    pub extension_type: ConfigExtension,
+}
+
+impl TryFrom<&[u8]> for ExtendConfigInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u16>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let extension_type = ConfigExtension::try_from(u16::from_le_bytes(data[0..2].try_into().unwrap()))?;
+
+        // The vault bumps come with the vaults, see `InitializeVaults`, and the observation tree with
+        // the tree, see `InitializeObservationTree`
+        if extension_type == ConfigExtension::VaultBumps || extension_type == ConfigExtension::ObservationTree {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { extension_type })
+    }
+}
+
+pub struct ExtendConfig<'a> {
+    pub accounts: ExtendConfigAccounts<'a>,
+    pub instruction_data: ExtendConfigInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ExtendConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ExtendConfigAccounts::try_from(accounts)?;
+        let instruction_data = ExtendConfigInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ExtendConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &13;
+
+    /// `sha256("global:extend_config")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [218, 152, 7, 231, 210, 124, 139, 74];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // Check the extension isn't already present (this also validates the existing TLV area)
+        if find_extension(
+            &self.accounts.config.try_borrow_data()?,
+            self.instruction_data.extension_type as u16,
+        )?
+        .is_some()
+        {
+            return Err(AmmError::ExtensionAlreadyExists.into());
+        }
+
+        let length = self.instruction_data.extension_type.value_len();
+        let offset = self.accounts.config.data_len();
+        let new_len = offset + ConfigExtension::HEADER_LEN + length;
+
+        // Top up the rent for the new size
+        let lamports = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(self.accounts.config.lamports());
+
+        if lamports > 0 {
+            Transfer {
+                from: self.accounts.authority,
+                to: self.accounts.config,
+                lamports,
+            }
+            .invoke()?;
+        }
+
+        // Grow the account, the new value is zeroed
+        self.accounts.config.realloc(new_len, true)?;
+
+        {
+            let mut data = self.accounts.config.try_borrow_mut_data()?;
+            data[offset..offset + 2].copy_from_slice(&(self.instruction_data.extension_type as u16).to_le_bytes());
+            data[offset + 2..offset + 4].copy_from_slice(&(length as u16).to_le_bytes());
+        }
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use crate::state::{get_extension, Config, OracleState};
+use crate::{fair_lp_value, pool_reserves, set_return_amounts, AmmError, PythPriceUpdate};
+use core::mem::size_of;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #GetFairLpValue
+///
+/// Publish the value of an amount of LP priced from the pool invariant and its oracle, see
+/// `fair_lp_value`
+///
+/// Read-only, for lending protocols taking the LP as collateral: unlike the spot reserves, the
+/// value can't be pushed around within a transaction. The pool needs the `OracleState` extension.
+///
+/// Accounts:
+///
+/// 1. config
+/// 2. mint_lp
+/// 3. vault_x
+/// 4. vault_y
+/// 5. oracle                       // `OracleState::oracle`
+///
+/// Parameters:
+///
+/// 1. lp: u64,                     // Amount of LP to price
+///
+/// Return data:
+///
+/// 1. value: u64,                  // In Y base units
+pub struct GetFairLpValueAccounts<'a> {
+    pub config: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub oracle: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for GetFairLpValueAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, mint_lp, vault_x, vault_y, oracle] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Return the accounts
+        Ok(Self {
+            config,
+            mint_lp,
+            vault_x,
+            vault_y,
+            oracle,
+        })
+    }
+}
+
+pub struct GetFairLpValueInstructionData {
+    pub lp: u64,
+}
+
+impl TryFrom<&[u8]> for GetFairLpValueInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u64>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            lp: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+        })
+    }
+}
+
+pub struct GetFairLpValue<'a> {
+    pub accounts: GetFairLpValueAccounts<'a>,
+    pub instruction_data: GetFairLpValueInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for GetFairLpValue<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = GetFairLpValueAccounts::try_from(accounts)?;
+        let instruction_data = GetFairLpValueInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> GetFairLpValue<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &50;
+
+    /// `sha256("global:get_fair_lp_value")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [86, 190, 207, 135, 252, 139, 84, 211];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+
+        let (reserve_x, reserve_y, lp_supply) = pool_reserves(
+            self.accounts.config,
+            &config,
+            (self.accounts.vault_x, self.accounts.vault_y),
+            self.accounts.mint_lp,
+        )?;
+
+        // Read the pool's oracle
+        {
+            let config_data = self.accounts.config.try_borrow_data()?;
+            let oracle_state = get_extension::<OracleState>(&config_data)?.ok_or(AmmError::MissingExtension)?;
+
+            if self.accounts.oracle.key().ne(oracle_state.oracle()) {
+                return Err(AmmError::InvalidOracle.into());
+            }
+        }
+
+        let price = PythPriceUpdate::read(self.accounts.oracle, Clock::get()?.unix_timestamp)?
+            .raw_ratio(config.decimals_x(), config.decimals_y())?;
+
+        let value = fair_lp_value((reserve_x, reserve_y), lp_supply, self.instruction_data.lp, price)?;
+
+        set_return_amounts(&[value]);
+
+        Ok(())
+    }
+}
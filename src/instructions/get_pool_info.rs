@@ -0,0 +1,108 @@
+use crate::state::Config;
+use crate::{mul_div, pool_reserves, set_return_amounts, Rounding};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #GetPoolInfo
+///
+/// Publish the reserves, LP supply, mid price, fee and state of a pool in the return data
+///
+/// Read-only, so on-chain consumers (e.g. lending protocols pricing LP collateral) can CPI into it
+/// instead of fetching and decoding the config, the vaults and the LP mint themselves.
+///
+/// Accounts:
+///
+/// 1. config
+/// 2. mint_lp
+/// 3. vault_x
+/// 4. vault_y
+///
+/// Return data:
+///
+/// 1. reserve_x: u64,              // Excluding uncollected protocol fees, including deployed amounts
+/// 2. reserve_y: u64,
+/// 3. lp_supply: u64,
+/// 4. price: u64,                  // Y per X in base units, scaled by `PRICE_SCALE`
+/// 5. fee: u64,                    // Swap fee in bps
+/// 6. state: u64,                  // See `AmmState`
+pub struct GetPoolInfoAccounts<'a> {
+    pub config: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for GetPoolInfoAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, mint_lp, vault_x, vault_y] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Return the accounts
+        Ok(Self {
+            config,
+            mint_lp,
+            vault_x,
+            vault_y,
+        })
+    }
+}
+
+pub struct GetPoolInfo<'a> {
+    pub accounts: GetPoolInfoAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for GetPoolInfo<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = GetPoolInfoAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> GetPoolInfo<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &49;
+
+    /// `sha256("global:get_pool_info")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [9, 48, 220, 101, 22, 240, 78, 200];
+
+    /// Fixed-point scale of the price (Q32.32), which saturates at `u64::MAX` and is zero for an
+    /// empty pool
+    pub const PRICE_SCALE: u64 = 1 << 32;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+
+        // The reported values must be the pool's
+        let (reserve_x, reserve_y, lp_supply) = pool_reserves(
+            self.accounts.config,
+            &config,
+            (self.accounts.vault_x, self.accounts.vault_y),
+            self.accounts.mint_lp,
+        )?;
+
+        let price = match reserve_x {
+            0 => 0,
+            _ => mul_div(reserve_y, Self::PRICE_SCALE, reserve_x, Rounding::Down).unwrap_or(u64::MAX),
+        };
+
+        set_return_amounts(&[
+            reserve_x,
+            reserve_y,
+            lp_supply,
+            price,
+            config.fee() as u64,
+            config.state() as u64,
+        ]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+use crate::state::{Launch, LaunchState};
+use crate::{
+    check_signer, check_writable, isqrt, set_return_amounts, sort_mints, AmmError, CheckedVault,
+    Initialize, InitializeInstructionData, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::{AccountMeta, Instruction, Seed, Signer};
+use pinocchio::program::invoke;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_associated_token_account::instructions::{Create, CreateIdempotent};
+use pinocchio_token::instructions::MintTo;
+
+/// #GraduateLaunch
+///
+/// Permissionlessly turn a launch that reached its target into a constant-product pool
+///
+/// The pool is created through `Initialize` in the same instruction and seeded with the raise
+/// and `Launch::pool_token_amount`, so nobody can front-run the first deposit. The LP is
+/// minted to the launch PDA, which never moves it, and unsold curve tokens stay in the launch
+/// vault.
+///
+/// Accounts:
+///
+/// 1. payer:                       [signer, mut]   // Pays rent and the pool creation fee
+/// 2. launch                       [mut]
+/// 3. launch_token_vault           [mut]
+/// 4. launch_quote_vault           [mut]
+/// 5. launch_lp_ata                [init]          // LP ATA of the launch PDA
+/// 6. mint_lp                      [init]
+/// 7. config                       [init]
+/// 8. mint_x                       // The launch mint and quote mint, sorted
+/// 9. mint_y
+/// 10. vault_x                     [init_if_needed]
+/// 11. vault_y                     [init_if_needed]
+/// 12. token_program_x             [executable]
+/// 13. token_program_y             [executable]
+/// 14. global_config
+/// 15. treasury                    [mut]
+/// 16. system_program              [executable]
+/// 17. token_program               [executable]    // SPL Token, for the LP mint
+/// 18. associated_token_program    [executable]
+///
+/// Parameters:
+///
+/// 1. seed: [u64]                  // Pool seed, see `Initialize`
+/// 2. config_bump: [u8]
+/// 3. lp_bump: [u8]
+///
+/// Return data:
+///
+/// 1. x: u64,                      // Amount of X seeded
+/// 2. y: u64,                      // Amount of Y seeded
+/// 3. lp: u64,                     // Amount of LP token locked
+pub struct GraduateLaunchAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub launch: &'a AccountInfo,
+    pub launch_token_vault: &'a AccountInfo,
+    pub launch_quote_vault: &'a AccountInfo,
+    pub launch_lp_ata: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub treasury: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for GraduateLaunchAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, launch, launch_token_vault, launch_quote_vault, launch_lp_ata, mint_lp, config, mint_x, mint_y, vault_x, vault_y, token_program_x, token_program_y, global_config, treasury, system_program, token_program, _associated_token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(payer)?;
+        [
+            payer,
+            launch,
+            launch_token_vault,
+            launch_quote_vault,
+            launch_lp_ata,
+            mint_lp,
+            config,
+            vault_x,
+            vault_y,
+            treasury,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            payer,
+            launch,
+            launch_token_vault,
+            launch_quote_vault,
+            launch_lp_ata,
+            mint_lp,
+            config,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program_x,
+            token_program_y,
+            global_config,
+            treasury,
+            system_program,
+            token_program,
+        })
+    }
+}
+
+pub struct GraduateLaunchInstructionData {
+    pub seed: u64,
+    pub config_bump: [u8; 1],
+    pub lp_bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for GraduateLaunchInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u64>() + size_of::<[u8; 1]>() * 2)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            seed: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+            config_bump: [data[8]],
+            lp_bump: [data[9]],
+        })
+    }
+}
+
+pub struct GraduateLaunch<'a> {
+    pub accounts: GraduateLaunchAccounts<'a>,
+    pub instruction_data: GraduateLaunchInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for GraduateLaunch<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = GraduateLaunchAccounts::try_from(accounts)?;
+        let instruction_data = GraduateLaunchInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> GraduateLaunch<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &17;
+
+    /// `sha256("global:graduate_launch")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [49, 31, 22, 11, 201, 190, 128, 183];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let launch = Launch::load(self.accounts.launch)?;
+
+        if launch.state().ne(&(LaunchState::Trading as u8)) || launch.quote_reserve() < launch.target_raise() {
+            return Err(AmmError::LaunchNotGraduable.into());
+        }
+
+        // The pool pairs the launch mint with the quote mint, in pool order
+        let (mint_x, mint_y) = sort_mints(launch.mint(), launch.quote_mint());
+        if self.accounts.mint_x.key().ne(mint_x) || self.accounts.mint_y.key().ne(mint_y) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the pool, `Initialize` validates the mints, fee and creation fee
+        self.initialize_pool(launch.fee())?;
+
+        // Create the pool vaults, anyone may have created them already
+        for (vault, mint, token_program) in [
+            (self.accounts.vault_x, self.accounts.mint_x, self.accounts.token_program_x),
+            (self.accounts.vault_y, self.accounts.mint_y, self.accounts.token_program_y),
+        ] {
+            CreateIdempotent {
+                funding_account: self.accounts.payer,
+                account: vault,
+                wallet: self.accounts.config,
+                mint,
+                system_program: self.accounts.system_program,
+                token_program,
+            }
+            .invoke()?;
+        }
+
+        // Move the raise and the pool allocation out of the launch
+        let launch_bump = launch.bump();
+        let launch_seeds = [
+            Seed::from(b"launch"),
+            Seed::from(launch.mint()),
+            Seed::from(&launch_bump),
+        ];
+        let launch_signer = [Signer::from(&launch_seeds)];
+
+        let token_is_x = launch.mint().eq(mint_x);
+        let (token_vault, quote_vault) = match token_is_x {
+            true => (self.accounts.vault_x, self.accounts.vault_y),
+            false => (self.accounts.vault_y, self.accounts.vault_x),
+        };
+        let (token_mint, quote_mint) = match token_is_x {
+            true => (self.accounts.mint_x, self.accounts.mint_y),
+            false => (self.accounts.mint_y, self.accounts.mint_x),
+        };
+
+        TransferChecked {
+            from: self.accounts.launch_token_vault,
+            mint: token_mint,
+            to: token_vault,
+            authority: self.accounts.launch,
+            amount: launch.pool_token_amount(),
+            decimals: launch.token_decimals(),
+            token_program: launch.token_program(),
+        }
+        .invoke_signed(&launch_signer)?;
+
+        TransferChecked {
+            from: self.accounts.launch_quote_vault,
+            mint: quote_mint,
+            to: quote_vault,
+            authority: self.accounts.launch,
+            amount: launch.quote_reserve(),
+            decimals: launch.quote_decimals(),
+            token_program: launch.quote_program(),
+        }
+        .invoke_signed(&launch_signer)?;
+
+        // Lock the LP in the launch PDA
+        Create {
+            funding_account: self.accounts.payer,
+            account: self.accounts.launch_lp_ata,
+            wallet: self.accounts.launch,
+            mint: self.accounts.mint_lp,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.token_program,
+        }
+        .invoke()?;
+
+        // Mint the geometric mean of what the vaults received, as for a first deposit
+        let x = CheckedVault::new(
+            self.accounts.vault_x,
+            self.accounts.mint_x.key(),
+            self.accounts.config.key(),
+            self.accounts.token_program_x.key(),
+        )?
+        .amount();
+        let y = CheckedVault::new(
+            self.accounts.vault_y,
+            self.accounts.mint_y.key(),
+            self.accounts.config.key(),
+            self.accounts.token_program_y.key(),
+        )?
+        .amount();
+        let lp = u64::try_from(isqrt(x as u128 * y as u128)).map_err(|_| AmmError::MathOverflow)?;
+
+        if lp == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let seed_bytes = self.instruction_data.seed.to_le_bytes();
+        let config_seeds = [
+            Seed::from(b"config"),
+            Seed::from(&seed_bytes),
+            Seed::from(mint_x),
+            Seed::from(mint_y),
+            Seed::from(&self.instruction_data.config_bump),
+        ];
+
+        MintTo {
+            mint: self.accounts.mint_lp,
+            account: self.accounts.launch_lp_ata,
+            mint_authority: self.accounts.config,
+            amount: lp,
+        }
+        .invoke_signed(&[Signer::from(&config_seeds)])?;
+
+        drop(launch);
+
+        Launch::load_mut(self.accounts.launch)?.graduate(*self.accounts.config.key())?;
+
+        set_return_amounts(&[x, y, lp]);
+
+        Ok(())
+    }
+
+    /// Run `Initialize` for the graduated pool through a CPI into this program
+    fn initialize_pool(&self, fee: u16) -> ProgramResult {
+        // discriminator [8] | seed [8] | fee [2] | config_bump [1] | lp_bump [1] | flags [1] | lp_decimals [1]
+        let mut instruction_data = [0u8; 22];
+        instruction_data[0..8].copy_from_slice(&Initialize::HASH_DISCRIMINATOR);
+        instruction_data[8..16].copy_from_slice(&self.instruction_data.seed.to_le_bytes());
+        instruction_data[16..18].copy_from_slice(&fee.to_le_bytes());
+        instruction_data[18] = self.instruction_data.config_bump[0];
+        instruction_data[19] = self.instruction_data.lp_bump[0];
+        instruction_data[20] = 0;
+        instruction_data[21] = InitializeInstructionData::DEFAULT_LP_DECIMALS;
+
+        let account_metas = [
+            AccountMeta::writable_signer(self.accounts.payer.key()),
+            AccountMeta::writable(self.accounts.mint_lp.key()),
+            AccountMeta::writable(self.accounts.config.key()),
+            AccountMeta::readonly(self.accounts.mint_x.key()),
+            AccountMeta::readonly(self.accounts.mint_y.key()),
+            AccountMeta::readonly(self.accounts.system_program.key()),
+            AccountMeta::readonly(self.accounts.token_program.key()),
+            AccountMeta::readonly(self.accounts.global_config.key()),
+            AccountMeta::writable(self.accounts.treasury.key()),
+        ];
+
+        invoke(
+            &Instruction {
+                program_id: &crate::ID,
+                accounts: &account_metas,
+                data: &instruction_data,
+            },
+            &[
+                self.accounts.payer,
+                self.accounts.mint_lp,
+                self.accounts.config,
+                self.accounts.mint_x,
+                self.accounts.mint_y,
+                self.accounts.system_program,
+                self.accounts.token_program,
+                self.accounts.global_config,
+                self.accounts.treasury,
+            ],
+        )
+    }
+}
@@ -0,0 +1,157 @@
+use crate::state::{GlobalConfig, NftPosition};
+use crate::{check_signer, check_writable, AmmError};
+use core::mem::size_of;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_token::instructions::Transfer;
+
+/// #IncreasePosition
+///
+/// Move LP tokens into an NFT position, optionally extending its lock
+///
+/// Accounts:
+///
+/// 1. owner:                       [signer]
+/// 2. user_lp_ata                  [mut]
+/// 3. position                     [mut]
+/// 4. position_lp_vault            [mut]
+/// 5. owner_position_ata
+/// 6. token_program                [executable]
+/// 7. global_config
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Amount of LP token to add
+/// 2. locked_until: Option<i64>    // Lock the position until this timestamp
+pub struct IncreasePositionAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub user_lp_ata: &'a AccountInfo,
+    pub position: &'a AccountInfo,
+    pub position_lp_vault: &'a AccountInfo,
+    pub owner_position_ata: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for IncreasePositionAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, user_lp_ata, position, position_lp_vault, owner_position_ata, _token_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(owner)?;
+        [user_lp_ata, position, position_lp_vault].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            owner,
+            user_lp_ata,
+            position,
+            position_lp_vault,
+            owner_position_ata,
+            global_config,
+        })
+    }
+}
+
+pub struct IncreasePositionInstructionData {
+    pub amount: u64,
+    pub locked_until: Option<i64>,
+}
+
+impl<'a> TryFrom<&'a [u8]> for IncreasePositionInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const INCREASE_POSITION_DATA_LEN: usize = size_of::<u64>();
+        const INCREASE_POSITION_DATA_LEN_WITH_LOCK: usize = INCREASE_POSITION_DATA_LEN + size_of::<i64>();
+
+        let locked_until = match data.len() {
+            INCREASE_POSITION_DATA_LEN => None,
+            INCREASE_POSITION_DATA_LEN_WITH_LOCK => {
+                Some(i64::from_le_bytes(data[8..16].try_into().unwrap()))
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount,
+            locked_until,
+        })
+    }
+}
+
+pub struct IncreasePosition<'a> {
+    pub accounts: IncreasePositionAccounts<'a>,
+    pub instruction_data: IncreasePositionInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for IncreasePosition<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = IncreasePositionAccounts::try_from(accounts)?;
+        let instruction_data = IncreasePositionInstructionData::try_from(data)?;
+
+        // Return the initialized struct
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> IncreasePosition<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &7;
+
+    /// `sha256("global:increase_position")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [253, 234, 128, 104, 192, 188, 45, 91];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let mut position = NftPosition::load_mut(self.accounts.position)?;
+
+        // Only the NFT holder can manage the position
+        position.check_holder(self.accounts.owner_position_ata, self.accounts.owner.key())?;
+
+        if position.lp_vault().ne(self.accounts.position_lp_vault.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Transfer {
+            from: self.accounts.user_lp_ata,
+            to: self.accounts.position_lp_vault,
+            authority: self.accounts.owner,
+            amount: self.instruction_data.amount,
+        }
+        .invoke()?;
+
+        let liquidity = position
+            .liquidity()
+            .checked_add(self.instruction_data.amount)
+            .ok_or(AmmError::MathOverflow)?;
+        position.set_liquidity(liquidity);
+
+        // Locks can only be extended
+        if let Some(locked_until) = self.instruction_data.locked_until {
+            if locked_until < position.locked_until() || locked_until < Clock::get()?.unix_timestamp {
+                return Err(AmmError::PositionLocked.into());
+            }
+            position.set_locked_until(locked_until);
+        }
+
+        Ok(())
+    }
+}
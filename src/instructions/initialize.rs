@@ -1,51 +1,96 @@
-use crate::state::Config;
+use crate::state::{Config, GlobalConfig, Registry, TokenBadge};
+use crate::{
+    check_signer, check_writable, derive_config_address, derive_lp_mint, derive_registry, load_mint,
+    mint_has_extension, AmmError, InitializeMint2, InitializeNonTransferableMint, NON_TRANSFERABLE_MINT_LEN,
+    PERMANENT_DELEGATE_EXTENSION,
+};
 use core::mem::size_of;
 use pinocchio::{
     account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
 };
-use pinocchio_system::instructions::CreateAccount;
-use pinocchio_token::{instructions::InitializeMint2, state::Mint};
+use pinocchio_system::instructions::{CreateAccount, Transfer};
+use pinocchio_token::state::Mint;
 
 /// #Initialize
 ///
 /// Initialize the Amm
 ///
+/// Unless the initializer is the `GlobalConfig` admin, the fee, the flags and the token programs of
+/// the assets must stay within the deployment bounds, see `GlobalConfig::check_pool_params`. With
+/// `GlobalConfig::FLAG_REQUIRE_TOKEN_BADGES`, both mints need a `TokenBadge`, admin included.
+///
 /// Accounts:
 ///
 /// 1. initializer:                 [signer, mut]
 /// 2. mint_lp:                     [init]
 /// 3. config                       [init]
-/// 4. system_program               [executable]
-/// 5. token_program                [executable]
+/// 4. mint_x                       // Must sort before mint_y, see `sort_mints`
+/// 5. mint_y
+/// 6. system_program               [executable]
+/// 7. token_program                [executable]  // Token-2022 with `Config::FLAG_SOULBOUND_LP`, else SPL Token
+/// 8. global_config
+/// 9. treasury                     [mut]  // Receives `GlobalConfig::creation_fee` lamports
+/// 10. registry                    [mut, optional]
+/// 11. token_badge_x               [optional]  // Required with `GlobalConfig::FLAG_REQUIRE_TOKEN_BADGES`
+/// 12. token_badge_y               [optional]
+///
+/// Optional accounts can be skipped by passing the program id in their place.
 ///
 /// Parameters:
 ///
 /// 1. seed:          [u64]
-/// 2. fee:           [u16]
-/// 3. mint_x:        [Pubkey]
-/// 4. mint_y:        [Pubkey]
-/// 5. config_bump:   [u8]
-/// 6. lp_bump:       [u8]
-/// 5. authority:     [Option<Pubkey>]
+/// 2. fee:           [u16]           // One of `FEE_TIERS` unless `Config::FLAG_CUSTOM_FEE_TIER` is set
+/// 3. config_bump:   [u8]            // Canonical bump of the config, see `derive_config_address`
+/// 4. lp_bump:       [u8]            // Canonical bump of the LP mint, see `derive_lp_mint`
+/// 5. flags:         [u8]            // Pool options, see `Config::FLAG_*`
+/// 6. lp_decimals:   [u8]            // `DEFAULT_LP_DECIMALS` uses the larger of the two mints' decimals
+/// 7. authority:     [Option<Pubkey>]
 pub struct InitializeAccounts<'a> {
     pub initializer: &'a AccountInfo,
     pub mint_lp: &'a AccountInfo,
     pub config: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub treasury: &'a AccountInfo,
+    pub registry: Option<&'a AccountInfo>,
+    pub token_badge_x: Option<&'a AccountInfo>,
+    pub token_badge_y: Option<&'a AccountInfo>,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for InitializeAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [initializer, mint_lp, config, _system_program, _token_program] = accounts else {
+        let [initializer, mint_lp, config, mint_x, mint_y, _system_program, _token_program, global_config, treasury, remaining @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // Check the signers and the writable accounts
+        check_signer(initializer)?;
+        [initializer, mint_lp, config, treasury].into_iter().try_for_each(check_writable)?;
+
+        let mut optional = remaining.iter().map(|account| account.key().ne(&crate::ID).then_some(account));
+        let registry = optional.next().flatten();
+        let token_badge_x = optional.next().flatten();
+        let token_badge_y = optional.next().flatten();
+
+        if let Some(registry) = registry {
+            check_writable(registry)?;
+        }
+
         // Return the accounts
         Ok(Self {
             initializer,
             mint_lp,
             config,
+            mint_x,
+            mint_y,
+            global_config,
+            treasury,
+            registry,
+            token_badge_x,
+            token_badge_y,
         })
     }
 }
@@ -53,55 +98,60 @@ impl<'a> TryFrom<&'a [AccountInfo]> for InitializeAccounts<'a> {
 pub struct InitializeInstructionData {
     pub seed: u64,
     pub fee: u16,
-    pub mint_x: [u8; 32],
-    pub mint_y: [u8; 32],
     pub config_bump: [u8; 1],
     pub lp_bump: [u8; 1],
+    pub flags: u8,
+    pub lp_decimals: u8,
     pub authority: Option<[u8; 32]>,
 }
 
+impl InitializeInstructionData {
+    /// `lp_decimals` value requesting the larger of the two mints' decimals
+    pub const DEFAULT_LP_DECIMALS: u8 = u8::MAX;
+}
+
 impl TryFrom<&[u8]> for InitializeInstructionData {
     type Error = ProgramError;
 
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        const INITIALIZE_DATA_LEN_WITHOUT_AUTHORITY: usize = size_of::<u64>() + size_of::<u16>() + size_of::<[u8; 32]>() * 2 + size_of::<[u8; 1]>() * 2;
+        const INITIALIZE_DATA_LEN_WITHOUT_AUTHORITY: usize = size_of::<u64>() + size_of::<u16>() + size_of::<[u8; 1]>() * 2 + size_of::<u8>() * 2;
         const INITIALIZE_DATA_LEN_WITH_AUTHORITY: usize = INITIALIZE_DATA_LEN_WITHOUT_AUTHORITY + size_of::<[u8; 32]>();
 
         match data.len() {
             INITIALIZE_DATA_LEN_WITH_AUTHORITY => {
                 let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
                 let fee = u16::from_le_bytes(data[8..10].try_into().unwrap());
-                let mint_x = data[10..42].try_into().unwrap();
-                let mint_y = data[42..74].try_into().unwrap();
-                let config_bump: [u8; 1] = data[74..75].try_into().unwrap();
-                let lp_bump: [u8; 1] = data[75..76].try_into().unwrap();
-                let authority = data[76..108].try_into().unwrap();
+                let config_bump: [u8; 1] = data[10..11].try_into().unwrap();
+                let lp_bump: [u8; 1] = data[11..12].try_into().unwrap();
+                let flags = data[12];
+                let lp_decimals = data[13];
+                let authority = data[14..46].try_into().unwrap();
 
                 Ok(Self {
                     seed,
                     fee,
-                    mint_x,
-                    mint_y,
                     config_bump,
                     lp_bump,
+                    flags,
+                    lp_decimals,
                     authority: Some(authority),
                 })
             }
             INITIALIZE_DATA_LEN_WITHOUT_AUTHORITY => {
                 let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
                 let fee = u16::from_le_bytes(data[8..10].try_into().unwrap());
-                let mint_x = data[10..42].try_into().unwrap();
-                let mint_y = data[42..74].try_into().unwrap();
-                let config_bump: [u8; 1] = data[74..75].try_into().unwrap();
-                let lp_bump: [u8; 1] = data[75..76].try_into().unwrap();
+                let config_bump: [u8; 1] = data[10..11].try_into().unwrap();
+                let lp_bump: [u8; 1] = data[11..12].try_into().unwrap();
+                let flags = data[12];
+                let lp_decimals = data[13];
 
                 Ok(Self {
                     seed,
                     fee,
-                    mint_x,
-                    mint_y,
                     config_bump,
                     lp_bump,
+                    flags,
+                    lp_decimals,
                     authority: None,
                 })
             }
@@ -132,14 +182,159 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Initialize<'a> {
 impl<'a> Initialize<'a> {
     pub const DISCRIMINATOR: &'a u8 = &0;
 
+    /// `sha256("global:initialize")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [175, 175, 109, 31, 13, 152, 155, 237];
+
     pub fn process(&mut self) -> ProgramResult {
+        // Fail early with a clear error instead of deep inside CreateAccount
+        for account in [self.accounts.config, self.accounts.mint_lp] {
+            if !account.data_is_empty() || account.owner().ne(&pinocchio_system::ID) {
+                return Err(AmmError::PoolAlreadyExists.into());
+            }
+        }
+
+        // Pool creation is halted while the protocol is paused or frozen for an upgrade
+        let (protocol_fee_bps, creation_fee) = {
+            let global_config = GlobalConfig::load(self.accounts.global_config)?;
+
+            if global_config.is_paused() {
+                return Err(AmmError::ProtocolPaused.into());
+            }
+
+            global_config.check_not_frozen()?;
+
+            global_config.check_treasury(self.accounts.treasury.key())?;
+
+            // Permissionless creators must stay within the deployment bounds, the admin overrides them
+            if global_config.admin().ne(self.accounts.initializer.key()) {
+                global_config.check_pool_params(
+                    self.instruction_data.fee,
+                    self.instruction_data.flags,
+                    [self.accounts.mint_x.owner(), self.accounts.mint_y.owner()],
+                )?;
+            }
+
+            // Permissioned deployments only pool the mints the admin badged
+            if global_config.requires_token_badges() {
+                for (mint, token_badge) in [
+                    (self.accounts.mint_x, self.accounts.token_badge_x),
+                    (self.accounts.mint_y, self.accounts.token_badge_y),
+                ] {
+                    let token_badge = TokenBadge::load(token_badge.ok_or(AmmError::MissingTokenBadge)?)?;
+
+                    if token_badge.mint().ne(mint.key()) || !token_badge.is_badged() {
+                        return Err(AmmError::MissingTokenBadge.into());
+                    }
+                }
+            }
+
+            (global_config.protocol_fee_bps(), global_config.creation_fee())
+        };
+
+        // Charge the pool creation fee to deter spam pools
+        if creation_fee > 0 {
+            Transfer {
+                from: self.accounts.initializer,
+                to: self.accounts.treasury,
+                lamports: creation_fee,
+            }
+            .invoke()?;
+        }
+
+        let mint_x = self.accounts.mint_x.key();
+        let mint_y = self.accounts.mint_y.key();
+
+        // A pool needs two distinct assets
+        if mint_x.eq(mint_y) {
+            return Err(AmmError::IdenticalMints.into());
+        }
+
+        // Enforce a single ordering of the pair so (A,B) and (B,A) pools can't both exist
+        if mint_x.gt(mint_y) {
+            return Err(AmmError::InvalidMintOrder.into());
+        }
+
+        // Both sides must be real, initialized mints
+        let (mint_x_decimals, mint_x_freezable) = {
+            let mint = load_mint(self.accounts.mint_x)?;
+            (mint.decimals(), mint.has_freeze_authority())
+        };
+        let (mint_y_decimals, mint_y_freezable) = {
+            let mint = load_mint(self.accounts.mint_y)?;
+            (mint.decimals(), mint.has_freeze_authority())
+        };
+
+        // Record assets that could freeze or drain the vaults
+        let mut risk_flags = 0;
+        if mint_x_freezable {
+            risk_flags |= Config::RISK_FREEZABLE_X;
+        }
+        if mint_y_freezable {
+            risk_flags |= Config::RISK_FREEZABLE_Y;
+        }
+        if mint_has_extension(self.accounts.mint_x, PERMANENT_DELEGATE_EXTENSION)? {
+            risk_flags |= Config::RISK_CLAWBACK_X;
+        }
+        if mint_has_extension(self.accounts.mint_y, PERMANENT_DELEGATE_EXTENSION)? {
+            risk_flags |= Config::RISK_CLAWBACK_Y;
+        }
+
+        if self.instruction_data.flags & Config::FLAG_STRICT != 0 && risk_flags != 0 {
+            return Err(AmmError::RiskyMint.into());
+        }
+
+        let lp_decimals = match self.instruction_data.lp_decimals {
+            InitializeInstructionData::DEFAULT_LP_DECIMALS => mint_x_decimals.max(mint_y_decimals),
+            lp_decimals => lp_decimals,
+        };
+
+        // Canonical pools are derived from the mint pair only, so there is at most one per pair
+        let is_canonical = self.instruction_data.flags & Config::FLAG_CANONICAL != 0;
+        if is_canonical && self.instruction_data.seed != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Only the canonical bumps are accepted, so a seed and pair map to a single config, and the
+        // LP mint sits where `derive_lp_mint` finds it
+        let seed = (!is_canonical).then_some(self.instruction_data.seed);
+        let (config_key, config_bump) = derive_config_address(seed, mint_x, mint_y);
+        let (mint_lp_key, lp_bump) = derive_lp_mint(&config_key);
+
+        if config_key.ne(self.accounts.config.key())
+            || mint_lp_key.ne(self.accounts.mint_lp.key())
+            || [config_bump].ne(&self.instruction_data.config_bump)
+            || [lp_bump].ne(&self.instruction_data.lp_bump)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // The oracle guard needs a feed, so it can only be enabled through UpdateConfig
+        if self.instruction_data.flags & Config::FLAG_ORACLE_GUARD != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Soulbound LP can't be moved into a position vault
+        if self.instruction_data.flags & Config::FLAG_SOULBOUND_LP != 0
+            && self.instruction_data.flags & Config::FLAG_NFT_POSITIONS != 0
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Custom fee tiers are reserved for pools with an authority
+        if self.instruction_data.flags & Config::FLAG_CUSTOM_FEE_TIER != 0
+            && self.instruction_data.authority.is_none()
+        {
+            return Err(AmmError::CustomFeeTierRequiresAuthority.into());
+        }
+
         // Create the config account
         let seed_binding = self.instruction_data.seed.to_le_bytes();
+        let seed_bytes: &[u8] = if is_canonical { &[] } else { &seed_binding };
         let config_seeds = [
             Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(&self.instruction_data.mint_x),
-            Seed::from(&self.instruction_data.mint_y),
+            Seed::from(seed_bytes),
+            Seed::from(mint_x),
+            Seed::from(mint_y),
             Seed::from(&self.instruction_data.config_bump),
         ];
 
@@ -154,18 +349,23 @@ impl<'a> Initialize<'a> {
         }
         .invoke_signed(&[Signer::from(&config_seeds)])?;
 
-        let config = unsafe {
-            Config::load_mut_unchecked(self.accounts.config)
-        }?;
+        let mut config = Config::init(self.accounts.config)?;
 
         config.set_inner(
             self.instruction_data.seed,
             self.instruction_data.authority.unwrap_or_default(),
-            self.instruction_data.mint_x,
-            self.instruction_data.mint_y,
+            *mint_x,
+            *mint_y,
             self.instruction_data.fee,
             self.instruction_data.config_bump,
+            self.instruction_data.flags,
+            lp_decimals,
         )?;
+        config.set_risk_flags(risk_flags);
+        config.set_decimals(mint_x_decimals, mint_y_decimals);
+        // `load_mint` checked that each mint is owned by SPL Token or Token-2022
+        config.set_token_programs(*self.accounts.mint_x.owner(), *self.accounts.mint_y.owner());
+        config.set_protocol_fee_bps(protocol_fee_bps)?;
 
         // Create the mint_lp account
         let mint_lp_seeds = [
@@ -174,7 +374,11 @@ impl<'a> Initialize<'a> {
             Seed::from(&self.instruction_data.lp_bump),
         ];
 
-        let mint_size = Mint::LEN;
+        let lp_token_program = config.lp_token_program();
+        let mint_size = match config.has_soulbound_lp() {
+            true => NON_TRANSFERABLE_MINT_LEN,
+            false => Mint::LEN,
+        };
         let mint_lamports = Rent::get()?.minimum_balance(mint_size);
 
         CreateAccount {
@@ -182,17 +386,37 @@ impl<'a> Initialize<'a> {
             to: self.accounts.mint_lp,
             lamports: mint_lamports,
             space: mint_size as u64,
-            owner: &pinocchio_token::ID,
+            owner: lp_token_program,
         }
         .invoke_signed(&[Signer::from(&mint_lp_seeds)])?;
 
+        // Extensions are initialized before the mint itself
+        if config.has_soulbound_lp() {
+            InitializeNonTransferableMint {
+                mint: self.accounts.mint_lp,
+            }
+            .invoke()?;
+        }
+
         InitializeMint2 {
             mint: self.accounts.mint_lp,
-            decimals: 6,
+            decimals: lp_decimals,
             mint_authority: self.accounts.config.key(),
             freeze_authority: None,
+            token_program: lp_token_program,
+        }
+        .invoke()?;
+
+        // List the pool in the registry page, if one was provided, only at its canonical address
+        if let Some(registry) = self.accounts.registry {
+            let mut registry_data = Registry::load_mut(registry)?;
+
+            if derive_registry(registry_data.page()).0.ne(registry.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            registry_data.push(*self.accounts.config.key(), *mint_x, *mint_y)?;
         }
-        .invoke_signed(&[Signer::from(&mint_lp_seeds)])?;
 
         Ok(())
     }
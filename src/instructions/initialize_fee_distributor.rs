@@ -0,0 +1,189 @@
+use crate::state::{FeeDistributor, GlobalConfig};
+use crate::{check_signer, check_writable, derive_ata, derive_fee_distributor, load_mint};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_associated_token_account::instructions::CreateIdempotent;
+use pinocchio_system::instructions::CreateAccount;
+
+/// #InitializeFeeDistributor
+///
+/// Create a `FeeDistributor` sharing the protocol fees collected in `reward_mint` among the
+/// stakers of `governance_mint`, with its stake and reward vaults
+///
+/// The first checkpoint can run in the next epoch.
+///
+/// Accounts:
+///
+/// 1. admin:                       [signer, mut]   // `GlobalConfig` admin, pays for the accounts
+/// 2. global_config
+/// 3. fee_distributor              [init]  // [b"fee_distributor", governance_mint, reward_mint]
+/// 4. governance_mint
+/// 5. reward_mint
+/// 6. stake_vault                  [init_if_needed]    // ATA of fee_distributor for governance_mint
+/// 7. reward_vault                 [init_if_needed]    // ATA of fee_distributor for reward_mint
+/// 8. governance_token_program     [executable]
+/// 9. reward_token_program         [executable]
+/// 10. system_program              [executable]
+/// 11. associated_token_program    [executable]
+pub struct InitializeFeeDistributorAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub fee_distributor: &'a AccountInfo,
+    pub governance_mint: &'a AccountInfo,
+    pub reward_mint: &'a AccountInfo,
+    pub stake_vault: &'a AccountInfo,
+    pub reward_vault: &'a AccountInfo,
+    pub governance_token_program: &'a AccountInfo,
+    pub reward_token_program: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeFeeDistributorAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, global_config, fee_distributor, governance_mint, reward_mint, stake_vault, reward_vault, governance_token_program, reward_token_program, system_program, _associated_token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(admin)?;
+        [admin, fee_distributor, stake_vault, reward_vault].into_iter().try_for_each(check_writable)?;
+
+        // Check if the admin is the correct admin
+        let global_config = GlobalConfig::load(global_config)?;
+
+        if global_config.admin().ne(admin.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        global_config.check_not_frozen()?;
+
+        // Return the accounts
+        Ok(Self {
+            admin,
+            fee_distributor,
+            governance_mint,
+            reward_mint,
+            stake_vault,
+            reward_vault,
+            governance_token_program,
+            reward_token_program,
+            system_program,
+        })
+    }
+}
+
+pub struct InitializeFeeDistributor<'a> {
+    pub accounts: InitializeFeeDistributorAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeFeeDistributor<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = InitializeFeeDistributorAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> InitializeFeeDistributor<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &35;
+
+    /// `sha256("global:initialize_fee_distributor")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [120, 65, 70, 200, 207, 217, 63, 107];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Check the mints and their token programs
+        if self.accounts.governance_mint.key().eq(self.accounts.reward_mint.key()) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let governance_decimals = load_mint(self.accounts.governance_mint)?.decimals();
+        let reward_decimals = load_mint(self.accounts.reward_mint)?.decimals();
+
+        if self.accounts.governance_mint.owner().ne(self.accounts.governance_token_program.key())
+            || self.accounts.reward_mint.owner().ne(self.accounts.reward_token_program.key())
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Check the distributor and its vaults
+        let (fee_distributor_key, bump) =
+            derive_fee_distributor(self.accounts.governance_mint.key(), self.accounts.reward_mint.key());
+        if fee_distributor_key.ne(self.accounts.fee_distributor.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if derive_ata(
+            self.accounts.fee_distributor.key(),
+            self.accounts.governance_token_program.key(),
+            self.accounts.governance_mint.key(),
+        )
+        .ne(self.accounts.stake_vault.key())
+            || derive_ata(
+                self.accounts.fee_distributor.key(),
+                self.accounts.reward_token_program.key(),
+                self.accounts.reward_mint.key(),
+            )
+            .ne(self.accounts.reward_vault.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the distributor
+        let bump = [bump];
+        let seeds = [
+            Seed::from(b"fee_distributor"),
+            Seed::from(self.accounts.governance_mint.key()),
+            Seed::from(self.accounts.reward_mint.key()),
+            Seed::from(&bump),
+        ];
+
+        CreateAccount {
+            from: self.accounts.admin,
+            to: self.accounts.fee_distributor,
+            lamports: Rent::get()?.minimum_balance(FeeDistributor::LEN),
+            space: FeeDistributor::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        FeeDistributor::init(self.accounts.fee_distributor)?.set_inner(
+            *self.accounts.governance_mint.key(),
+            *self.accounts.reward_mint.key(),
+            *self.accounts.governance_token_program.key(),
+            *self.accounts.reward_token_program.key(),
+            governance_decimals,
+            reward_decimals,
+            Clock::get()?.epoch,
+            bump,
+        );
+
+        // Create the vaults, anyone may have created them already
+        for (vault, mint, token_program) in [
+            (self.accounts.stake_vault, self.accounts.governance_mint, self.accounts.governance_token_program),
+            (self.accounts.reward_vault, self.accounts.reward_mint, self.accounts.reward_token_program),
+        ] {
+            CreateIdempotent {
+                funding_account: self.accounts.admin,
+                account: vault,
+                wallet: self.accounts.fee_distributor,
+                mint,
+                system_program: self.accounts.system_program,
+                token_program,
+            }
+            .invoke()?;
+        }
+
+        Ok(())
+    }
+}
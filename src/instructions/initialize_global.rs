@@ -0,0 +1,134 @@
+use crate::state::GlobalConfig;
+use crate::{check_signer, check_writable};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #InitializeGlobal
+///
+/// Create the program-wide GlobalConfig, once per deployment
+///
+/// Accounts:
+///
+/// 1. admin:                       [signer, mut]
+/// 2. global_config:               [init]
+/// 3. system_program               [executable]
+///
+/// Parameters:
+///
+/// 1. protocol_fee_bps: [u16]      // Default share of the swap fee kept for the protocol
+/// 2. creation_fee:     [u64]      // Lamports charged to create a pool
+/// 3. global_bump:      [u8]
+/// 4. treasury_bump:    [u8]       // Bump of the [b"treasury"] PDA receiving creation fees
+pub struct InitializeGlobalAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeGlobalAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, global_config, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(admin)?;
+        [admin, global_config].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            admin,
+            global_config,
+        })
+    }
+}
+
+pub struct InitializeGlobalInstructionData {
+    pub protocol_fee_bps: u16,
+    pub creation_fee: u64,
+    pub global_bump: [u8; 1],
+    pub treasury_bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for InitializeGlobalInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u16>() + size_of::<u64>() + size_of::<[u8; 1]>() * 2)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let protocol_fee_bps = u16::from_le_bytes(data[0..2].try_into().unwrap());
+        let creation_fee = u64::from_le_bytes(data[2..10].try_into().unwrap());
+        let global_bump: [u8; 1] = data[10..11].try_into().unwrap();
+        let treasury_bump: [u8; 1] = data[11..12].try_into().unwrap();
+
+        Ok(Self {
+            protocol_fee_bps,
+            creation_fee,
+            global_bump,
+            treasury_bump,
+        })
+    }
+}
+
+pub struct InitializeGlobal<'a> {
+    pub accounts: InitializeGlobalAccounts<'a>,
+    pub instruction_data: InitializeGlobalInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeGlobal<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = InitializeGlobalAccounts::try_from(accounts)?;
+        let instruction_data = InitializeGlobalInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> InitializeGlobal<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &9;
+
+    /// `sha256("global:initialize_global")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [47, 225, 15, 112, 86, 51, 190, 231];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Create the global config
+        let global_seeds = [
+            Seed::from(b"global"),
+            Seed::from(&self.instruction_data.global_bump),
+        ];
+
+        let global_lamports = Rent::get()?.minimum_balance(GlobalConfig::LEN);
+
+        CreateAccount {
+            from: self.accounts.admin,
+            to: self.accounts.global_config,
+            lamports: global_lamports,
+            space: GlobalConfig::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&global_seeds)])?;
+
+        let mut global_config = GlobalConfig::init(self.accounts.global_config)?;
+
+        global_config.set_inner(
+            *self.accounts.admin.key(),
+            self.instruction_data.protocol_fee_bps,
+            self.instruction_data.creation_fee,
+            self.instruction_data.global_bump,
+            self.instruction_data.treasury_bump,
+        )?;
+
+        Ok(())
+    }
+}
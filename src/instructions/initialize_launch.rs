@@ -0,0 +1,263 @@
+use crate::state::{GlobalConfig, Launch};
+use crate::{check_signer, check_writable, load_mint, AmmError, CheckedVault, FEE_TIERS, TransferChecked};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_associated_token_account::instructions::Create;
+use pinocchio_system::instructions::CreateAccount;
+
+/// #InitializeLaunch
+///
+/// Start a bonding-curve sale of `mint` against `quote_mint`, see `Launch`
+///
+/// Accounts:
+///
+/// 1. creator:                     [signer, mut]
+/// 2. launch                       [init]
+/// 3. mint                         // Token being launched
+/// 4. quote_mint                   // Token raised
+/// 5. launch_token_vault           [init]      // Token ATA of the launch PDA
+/// 6. launch_quote_vault           [init]      // Quote ATA of the launch PDA
+/// 7. creator_token_ata            [mut]
+/// 8. system_program               [executable]
+/// 9. token_program                [executable]  // Owner of mint
+/// 10. quote_program               [executable]  // Owner of quote_mint
+/// 11. associated_token_program    [executable]
+/// 12. global_config
+///
+/// Parameters:
+///
+/// 1. virtual_quote_reserve: [u64] // Sets the starting price with token_amount
+/// 2. token_amount: [u64]          // Tokens sold on the curve
+/// 3. pool_token_amount: [u64]     // Tokens paired with the raise at graduation
+/// 4. target_raise: [u64]
+/// 5. fee: [u16]                   // Fee of the graduated pool, one of `FEE_TIERS`
+/// 6. launch_bump: [u8]
+pub struct InitializeLaunchAccounts<'a> {
+    pub creator: &'a AccountInfo,
+    pub launch: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub quote_mint: &'a AccountInfo,
+    pub launch_token_vault: &'a AccountInfo,
+    pub launch_quote_vault: &'a AccountInfo,
+    pub creator_token_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub quote_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeLaunchAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [creator, launch, mint, quote_mint, launch_token_vault, launch_quote_vault, creator_token_ata, system_program, token_program, quote_program, _associated_token_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(creator)?;
+        [
+            creator,
+            launch,
+            launch_token_vault,
+            launch_quote_vault,
+            creator_token_ata,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Each mint is moved by the token program that owns it
+        if mint.owner().ne(token_program.key()) || quote_mint.owner().ne(quote_program.key()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            creator,
+            launch,
+            mint,
+            quote_mint,
+            launch_token_vault,
+            launch_quote_vault,
+            creator_token_ata,
+            system_program,
+            token_program,
+            quote_program,
+            global_config,
+        })
+    }
+}
+
+pub struct InitializeLaunchInstructionData {
+    pub virtual_quote_reserve: u64,
+    pub token_amount: u64,
+    pub pool_token_amount: u64,
+    pub target_raise: u64,
+    pub fee: u16,
+    pub launch_bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for InitializeLaunchInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const INITIALIZE_LAUNCH_DATA_LEN: usize = size_of::<u64>() * 4 + size_of::<u16>() + size_of::<[u8; 1]>();
+
+        if data.len().ne(&INITIALIZE_LAUNCH_DATA_LEN) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let virtual_quote_reserve = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let token_amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let pool_token_amount = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let target_raise = u64::from_le_bytes(data[24..32].try_into().unwrap());
+        let fee = u16::from_le_bytes(data[32..34].try_into().unwrap());
+        let launch_bump = [data[34]];
+
+        if virtual_quote_reserve == 0 || token_amount == 0 || pool_token_amount == 0 || target_raise == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // The graduated pool is permissionless, so it must use a standard tier
+        if !FEE_TIERS.contains(&fee) {
+            return Err(AmmError::InvalidFeeTier.into());
+        }
+
+        Ok(Self {
+            virtual_quote_reserve,
+            token_amount,
+            pool_token_amount,
+            target_raise,
+            fee,
+            launch_bump,
+        })
+    }
+}
+
+pub struct InitializeLaunch<'a> {
+    pub accounts: InitializeLaunchAccounts<'a>,
+    pub instruction_data: InitializeLaunchInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeLaunch<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = InitializeLaunchAccounts::try_from(accounts)?;
+        let instruction_data = InitializeLaunchInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> InitializeLaunch<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &14;
+
+    /// `sha256("global:initialize_launch")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [90, 201, 220, 142, 112, 253, 100, 13];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // The launch graduates into a pool, which needs two distinct assets
+        if self.accounts.mint.key().eq(self.accounts.quote_mint.key()) {
+            return Err(AmmError::IdenticalMints.into());
+        }
+
+        let token_decimals = load_mint(self.accounts.mint)?.decimals();
+        let quote_decimals = load_mint(self.accounts.quote_mint)?.decimals();
+
+        // Create the launch account
+        let launch_seeds = [
+            Seed::from(b"launch"),
+            Seed::from(self.accounts.mint.key()),
+            Seed::from(&self.instruction_data.launch_bump),
+        ];
+
+        CreateAccount {
+            from: self.accounts.creator,
+            to: self.accounts.launch,
+            lamports: Rent::get()?.minimum_balance(Launch::LEN),
+            space: Launch::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&launch_seeds)])?;
+
+        // Create the vaults owned by the launch
+        Create {
+            funding_account: self.accounts.creator,
+            account: self.accounts.launch_token_vault,
+            wallet: self.accounts.launch,
+            mint: self.accounts.mint,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.token_program,
+        }
+        .invoke()?;
+
+        Create {
+            funding_account: self.accounts.creator,
+            account: self.accounts.launch_quote_vault,
+            wallet: self.accounts.launch,
+            mint: self.accounts.quote_mint,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.quote_program,
+        }
+        .invoke()?;
+
+        // Fund the curve and the pool allocation
+        let amount = self
+            .instruction_data
+            .token_amount
+            .checked_add(self.instruction_data.pool_token_amount)
+            .ok_or(AmmError::MathOverflow)?;
+
+        TransferChecked {
+            from: self.accounts.creator_token_ata,
+            mint: self.accounts.mint,
+            to: self.accounts.launch_token_vault,
+            authority: self.accounts.creator,
+            amount,
+            decimals: token_decimals,
+            token_program: self.accounts.token_program.key(),
+        }
+        .invoke()?;
+
+        // Transfer-fee tokens arrive short, which comes out of the curve side
+        let received = CheckedVault::new(
+            self.accounts.launch_token_vault,
+            self.accounts.mint.key(),
+            self.accounts.launch.key(),
+            self.accounts.token_program.key(),
+        )?
+        .amount();
+        let token_reserve = received
+            .checked_sub(self.instruction_data.pool_token_amount)
+            .filter(|token_reserve| *token_reserve > 0)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        Launch::init(self.accounts.launch)?.set_inner(
+            *self.accounts.creator.key(),
+            *self.accounts.mint.key(),
+            *self.accounts.quote_mint.key(),
+            *self.accounts.token_program.key(),
+            *self.accounts.quote_program.key(),
+            token_decimals,
+            quote_decimals,
+            self.instruction_data.virtual_quote_reserve,
+            token_reserve,
+            self.instruction_data.pool_token_amount,
+            self.instruction_data.target_raise,
+            self.instruction_data.fee,
+            self.instruction_data.launch_bump,
+        );
+
+        Ok(())
+    }
+}
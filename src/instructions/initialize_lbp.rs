@@ -0,0 +1,280 @@
+use crate::state::{GlobalConfig, Lbp};
+use crate::{check_signer, check_writable, load_mint, AmmError, CheckedVault, MAX_FEE_BPS, TransferChecked};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_associated_token_account::instructions::Create;
+use pinocchio_system::instructions::CreateAccount;
+
+/// #InitializeLbp
+///
+/// Create a liquidity bootstrapping pool of `mint` against `quote_mint`, funded by the creator,
+/// see `Lbp`
+///
+/// Accounts:
+///
+/// 1. creator:                     [signer, mut]
+/// 2. lbp                          [init]
+/// 3. mint                         // Token being launched
+/// 4. quote_mint                   // Token it is priced in
+/// 5. lbp_token_vault              [init]      // Token ATA of the lbp PDA
+/// 6. lbp_quote_vault              [init]      // Quote ATA of the lbp PDA
+/// 7. creator_token_ata            [mut]
+/// 8. creator_quote_ata            [mut]
+/// 9. system_program               [executable]
+/// 10. token_program               [executable]  // Owner of mint
+/// 11. quote_program               [executable]  // Owner of quote_mint
+/// 12. associated_token_program    [executable]
+/// 13. global_config
+///
+/// Parameters:
+///
+/// 1. token_amount: [u64]
+/// 2. quote_amount: [u64]          // Sets the starting price with token_amount and start_weight
+/// 3. start_weight: [u16]          // Token weight in bps, within `Lbp::MIN_WEIGHT..=Lbp::MAX_WEIGHT`
+/// 4. end_weight: [u16]
+/// 5. start_time: [i64]
+/// 6. end_time: [i64]
+/// 7. fee: [u16]                   // Swap fee in bps
+/// 8. lbp_bump: [u8]
+pub struct InitializeLbpAccounts<'a> {
+    pub creator: &'a AccountInfo,
+    pub lbp: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub quote_mint: &'a AccountInfo,
+    pub lbp_token_vault: &'a AccountInfo,
+    pub lbp_quote_vault: &'a AccountInfo,
+    pub creator_token_ata: &'a AccountInfo,
+    pub creator_quote_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub quote_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeLbpAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [creator, lbp, mint, quote_mint, lbp_token_vault, lbp_quote_vault, creator_token_ata, creator_quote_ata, system_program, token_program, quote_program, _associated_token_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(creator)?;
+        [
+            creator,
+            lbp,
+            lbp_token_vault,
+            lbp_quote_vault,
+            creator_token_ata,
+            creator_quote_ata,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Each mint is moved by the token program that owns it
+        if mint.owner().ne(token_program.key()) || quote_mint.owner().ne(quote_program.key()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            creator,
+            lbp,
+            mint,
+            quote_mint,
+            lbp_token_vault,
+            lbp_quote_vault,
+            creator_token_ata,
+            creator_quote_ata,
+            system_program,
+            token_program,
+            quote_program,
+            global_config,
+        })
+    }
+}
+
+pub struct InitializeLbpInstructionData {
+    pub token_amount: u64,
+    pub quote_amount: u64,
+    pub start_weight: u16,
+    pub end_weight: u16,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub fee: u16,
+    pub lbp_bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for InitializeLbpInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const INITIALIZE_LBP_DATA_LEN: usize = size_of::<u64>() * 2
+            + size_of::<u16>() * 2
+            + size_of::<i64>() * 2
+            + size_of::<u16>()
+            + size_of::<[u8; 1]>();
+
+        if data.len().ne(&INITIALIZE_LBP_DATA_LEN) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let token_amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let quote_amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let start_weight = u16::from_le_bytes(data[16..18].try_into().unwrap());
+        let end_weight = u16::from_le_bytes(data[18..20].try_into().unwrap());
+        let start_time = i64::from_le_bytes(data[20..28].try_into().unwrap());
+        let end_time = i64::from_le_bytes(data[28..36].try_into().unwrap());
+        let fee = u16::from_le_bytes(data[36..38].try_into().unwrap());
+        let lbp_bump = [data[38]];
+
+        // Both sides are needed to price the first swap
+        if token_amount == 0 || quote_amount == 0 || start_time >= end_time || fee >= MAX_FEE_BPS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let weights = Lbp::MIN_WEIGHT..=Lbp::MAX_WEIGHT;
+        if !weights.contains(&start_weight) || !weights.contains(&end_weight) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            token_amount,
+            quote_amount,
+            start_weight,
+            end_weight,
+            start_time,
+            end_time,
+            fee,
+            lbp_bump,
+        })
+    }
+}
+
+pub struct InitializeLbp<'a> {
+    pub accounts: InitializeLbpAccounts<'a>,
+    pub instruction_data: InitializeLbpInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeLbp<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = InitializeLbpAccounts::try_from(accounts)?;
+        let instruction_data = InitializeLbpInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> InitializeLbp<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &51;
+
+    /// `sha256("global:initialize_lbp")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [119, 247, 64, 213, 235, 101, 68, 207];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        if self.accounts.mint.key().eq(self.accounts.quote_mint.key()) {
+            return Err(AmmError::IdenticalMints.into());
+        }
+
+        let token_decimals = load_mint(self.accounts.mint)?.decimals();
+        let quote_decimals = load_mint(self.accounts.quote_mint)?.decimals();
+
+        // Create the lbp account
+        let lbp_seeds = [
+            Seed::from(b"lbp"),
+            Seed::from(self.accounts.mint.key()),
+            Seed::from(&self.instruction_data.lbp_bump),
+        ];
+
+        CreateAccount {
+            from: self.accounts.creator,
+            to: self.accounts.lbp,
+            lamports: Rent::get()?.minimum_balance(Lbp::LEN),
+            space: Lbp::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&lbp_seeds)])?;
+
+        // Create the vaults owned by the lbp and fund them, the only deposit the pool takes
+        let mut reserves = [0u64; 2];
+
+        for (reserve, (vault, mint, from, amount, decimals, token_program)) in reserves.iter_mut().zip([
+            (
+                self.accounts.lbp_token_vault,
+                self.accounts.mint,
+                self.accounts.creator_token_ata,
+                self.instruction_data.token_amount,
+                token_decimals,
+                self.accounts.token_program,
+            ),
+            (
+                self.accounts.lbp_quote_vault,
+                self.accounts.quote_mint,
+                self.accounts.creator_quote_ata,
+                self.instruction_data.quote_amount,
+                quote_decimals,
+                self.accounts.quote_program,
+            ),
+        ]) {
+            Create {
+                funding_account: self.accounts.creator,
+                account: vault,
+                wallet: self.accounts.lbp,
+                mint,
+                system_program: self.accounts.system_program,
+                token_program,
+            }
+            .invoke()?;
+
+            TransferChecked {
+                from,
+                mint,
+                to: vault,
+                authority: self.accounts.creator,
+                amount,
+                decimals,
+                token_program: token_program.key(),
+            }
+            .invoke()?;
+
+            // Transfer-fee tokens arrive short, the reserves are what the vaults received
+            *reserve = CheckedVault::new(vault, mint.key(), self.accounts.lbp.key(), token_program.key())?.amount();
+
+            if *reserve == 0 {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        Lbp::init(self.accounts.lbp)?.set_inner(
+            *self.accounts.creator.key(),
+            *self.accounts.mint.key(),
+            *self.accounts.quote_mint.key(),
+            *self.accounts.token_program.key(),
+            *self.accounts.quote_program.key(),
+            token_decimals,
+            quote_decimals,
+            reserves[0],
+            reserves[1],
+            self.instruction_data.start_weight,
+            self.instruction_data.end_weight,
+            self.instruction_data.start_time,
+            self.instruction_data.end_time,
+            self.instruction_data.fee,
+            self.instruction_data.lbp_bump,
+        );
+
+        Ok(())
+    }
+}
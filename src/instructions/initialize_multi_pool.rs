@@ -0,0 +1,237 @@
+use crate::state::{GlobalConfig, MultiPool};
+use crate::{check_signer, check_writable, load_mint, AmmError};
+use core::{array, mem::size_of};
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::{CreateAccount, Transfer};
+use pinocchio_token::{instructions::InitializeMint2, state::Mint};
+
+/// #InitializeMultiPool
+///
+/// Initialize a pool of 2 to `MultiPool::MAX_TOKENS` assets, see `MultiPool`
+///
+/// The vaults are the pool's ATAs, created by anyone before the first deposit.
+///
+/// Accounts:
+///
+/// 1. initializer:                 [signer, mut]
+/// 2. mint_lp:                     [init]
+/// 3. multi_pool                   [init]
+/// 4. system_program               [executable]
+/// 5. token_program                [executable]
+/// 6. global_config
+/// 7. treasury                     [mut]  // Receives `GlobalConfig::creation_fee` lamports
+/// 8. ..                           // The mints, in ascending order
+///
+/// Parameters:
+///
+/// 1. seed:          [u64]
+/// 2. fee:           [u16]           // One of `FEE_TIERS`
+/// 3. bump:          [u8]
+/// 4. lp_bump:       [u8]
+/// 5. lp_decimals:   [u8]            // `DEFAULT_LP_DECIMALS` uses the largest of the mints' decimals
+pub struct InitializeMultiPoolAccounts<'a> {
+    pub initializer: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub multi_pool: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub treasury: &'a AccountInfo,
+    pub mints: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeMultiPoolAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [initializer, mint_lp, multi_pool, _system_program, _token_program, global_config, treasury, mints @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(initializer)?;
+        [initializer, mint_lp, multi_pool, treasury].into_iter().try_for_each(check_writable)?;
+
+        if mints.len() < 2 || mints.len() > MultiPool::MAX_TOKENS {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            initializer,
+            mint_lp,
+            multi_pool,
+            global_config,
+            treasury,
+            mints,
+        })
+    }
+}
+
+pub struct InitializeMultiPoolInstructionData {
+    pub seed: u64,
+    pub fee: u16,
+    pub bump: [u8; 1],
+    pub lp_bump: [u8; 1],
+    pub lp_decimals: u8,
+}
+
+impl InitializeMultiPoolInstructionData {
+    /// `lp_decimals` value requesting the largest of the mints' decimals
+    pub const DEFAULT_LP_DECIMALS: u8 = u8::MAX;
+}
+
+impl TryFrom<&[u8]> for InitializeMultiPoolInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const INITIALIZE_MULTI_POOL_DATA_LEN: usize = size_of::<u64>() + size_of::<u16>() + size_of::<u8>() * 3;
+
+        if data.len() != INITIALIZE_MULTI_POOL_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let fee = u16::from_le_bytes(data[8..10].try_into().unwrap());
+        let bump: [u8; 1] = data[10..11].try_into().unwrap();
+        let lp_bump: [u8; 1] = data[11..12].try_into().unwrap();
+        let lp_decimals = data[12];
+
+        Ok(Self {
+            seed,
+            fee,
+            bump,
+            lp_bump,
+            lp_decimals,
+        })
+    }
+}
+
+pub struct InitializeMultiPool<'a> {
+    pub accounts: InitializeMultiPoolAccounts<'a>,
+    pub instruction_data: InitializeMultiPoolInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeMultiPool<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = InitializeMultiPoolAccounts::try_from(accounts)?;
+        let instruction_data = InitializeMultiPoolInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> InitializeMultiPool<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &25;
+
+    /// `sha256("global:initialize_multi_pool")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [78, 78, 68, 56, 116, 213, 188, 215];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Fail early with a clear error instead of deep inside CreateAccount
+        for account in [self.accounts.multi_pool, self.accounts.mint_lp] {
+            if !account.data_is_empty() || account.owner().ne(&pinocchio_system::ID) {
+                return Err(AmmError::PoolAlreadyExists.into());
+            }
+        }
+
+        // Pool creation is halted while the protocol is paused or frozen for an upgrade
+        let creation_fee = {
+            let global_config = GlobalConfig::load(self.accounts.global_config)?;
+
+            if global_config.is_paused() {
+                return Err(AmmError::ProtocolPaused.into());
+            }
+
+            global_config.check_not_frozen()?;
+
+            global_config.check_treasury(self.accounts.treasury.key())?;
+
+            global_config.creation_fee()
+        };
+
+        // Charge the pool creation fee to deter spam pools
+        if creation_fee > 0 {
+            Transfer {
+                from: self.accounts.initializer,
+                to: self.accounts.treasury,
+                lamports: creation_fee,
+            }
+            .invoke()?;
+        }
+
+        // Create the multi_pool account, the mints and their order are checked when pushed
+        let seed_bytes = self.instruction_data.seed.to_le_bytes();
+        let mints = self.accounts.mints;
+        let multi_pool_seeds: [Seed; MultiPool::MAX_TOKENS + 3] = array::from_fn(|index| match index {
+            0 => Seed::from(b"multi_pool"),
+            1 => Seed::from(&seed_bytes),
+            index if index - 2 < mints.len() => Seed::from(mints[index - 2].key()),
+            _ => Seed::from(&self.instruction_data.bump),
+        });
+
+        CreateAccount {
+            from: self.accounts.initializer,
+            to: self.accounts.multi_pool,
+            lamports: Rent::get()?.minimum_balance(MultiPool::LEN),
+            space: MultiPool::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&multi_pool_seeds[..mints.len() + 3])])?;
+
+        let mut multi_pool = MultiPool::init(self.accounts.multi_pool)?;
+
+        for mint in mints {
+            // `load_mint` checks that each mint is owned by SPL Token or Token-2022
+            let decimals = load_mint(mint)?.decimals();
+            multi_pool.push_token(*mint.key(), *mint.owner(), decimals)?;
+        }
+
+        let lp_decimals = match self.instruction_data.lp_decimals {
+            InitializeMultiPoolInstructionData::DEFAULT_LP_DECIMALS => {
+                (0..mints.len()).map(|index| multi_pool.decimals(index)).max().unwrap_or_default()
+            }
+            lp_decimals => lp_decimals,
+        };
+
+        multi_pool.set_inner(
+            self.instruction_data.seed,
+            self.instruction_data.fee,
+            self.instruction_data.bump,
+            lp_decimals,
+        )?;
+
+        drop(multi_pool);
+
+        // Create the mint_lp account
+        let mint_lp_seeds = [
+            Seed::from(b"mint_lp"),
+            Seed::from(self.accounts.multi_pool.key()),
+            Seed::from(&self.instruction_data.lp_bump),
+        ];
+
+        CreateAccount {
+            from: self.accounts.initializer,
+            to: self.accounts.mint_lp,
+            lamports: Rent::get()?.minimum_balance(Mint::LEN),
+            space: Mint::LEN as u64,
+            owner: &pinocchio_token::ID,
+        }
+        .invoke_signed(&[Signer::from(&mint_lp_seeds)])?;
+
+        InitializeMint2 {
+            mint: self.accounts.mint_lp,
+            decimals: lp_decimals,
+            mint_authority: self.accounts.multi_pool.key(),
+            freeze_authority: None,
+        }
+        .invoke_signed(&[Signer::from(&mint_lp_seeds)])
+    }
+}
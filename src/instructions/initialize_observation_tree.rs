@@ -0,0 +1,158 @@
+use crate::state::{
+    find_extension, get_extension_mut, Config, ConfigExtension, Extension, GlobalConfig, ObservationTree,
+};
+use crate::{bump_sequence, check_signer, check_writable, AmmError, InitEmptyMerkleTree};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::Transfer;
+
+/// #InitializeObservationTree
+///
+/// Start the compressed observation history of a pool: initialize an SPL Account Compression
+/// merkle tree with the config as its authority and store it in the config, as the
+/// `ObservationTree` extension
+///
+/// The tree account is created beforehand in the same transaction, owned by the compression
+/// program and sized for `ObservationTree::MAX_DEPTH` and `ObservationTree::MAX_BUFFER_SIZE`, with
+/// any canopy. It is only done once per pool.
+///
+/// Accounts:
+///
+/// 1. authority:                   [signer, mut]   // Pays the extra config rent
+/// 2. config                       [mut]
+/// 3. merkle_tree                  [mut]
+/// 4. compression_program          [executable]
+/// 5. noop_program                 [executable]
+/// 6. system_program               [executable]
+/// 7. global_config
+pub struct InitializeObservationTreeAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub merkle_tree: &'a AccountInfo,
+    pub noop_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeObservationTreeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, merkle_tree, _compression_program, noop_program, _system_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        [authority, config, merkle_tree].into_iter().try_for_each(check_writable)?;
+
+        GlobalConfig::load_unfrozen(global_config)?;
+
+        // Check if the authority is the correct authority
+        if Config::load(config)?.has_authority().ne(&Some(*authority.key())) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            authority,
+            config,
+            merkle_tree,
+            noop_program,
+            global_config,
+        })
+    }
+}
+
+pub struct InitializeObservationTree<'a> {
+    pub accounts: InitializeObservationTreeAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeObservationTree<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = InitializeObservationTreeAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> InitializeObservationTree<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &54;
+
+    /// `sha256("global:initialize_observation_tree")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [204, 192, 179, 214, 18, 30, 107, 17];
+
+    pub fn process(&mut self) -> ProgramResult {
+        {
+            let config = Config::load(self.accounts.config)?;
+
+            // The tree is only initialized once
+            let config_data = self.accounts.config.try_borrow_data()?;
+            if find_extension(&config_data, ConfigExtension::ObservationTree as u16)?.is_some() {
+                return Err(AmmError::ExtensionAlreadyExists.into());
+            }
+
+            // The config signs as the tree authority, so only this program appends to it
+            let config_bump = config.config_bump();
+            let seeds = [
+                Seed::from(b"config"),
+                Seed::from(config.seed_bytes()),
+                Seed::from(config.mint_x()),
+                Seed::from(config.mint_y()),
+                Seed::from(&config_bump),
+            ];
+
+            InitEmptyMerkleTree {
+                merkle_tree: self.accounts.merkle_tree,
+                authority: self.accounts.config,
+                noop_program: self.accounts.noop_program,
+                max_depth: ObservationTree::MAX_DEPTH,
+                max_buffer_size: ObservationTree::MAX_BUFFER_SIZE,
+            }
+            .invoke_signed(&[Signer::from(&seeds)])?;
+        }
+
+        // Append the tree to the config, as `ExtendConfig` does
+        let length = ObservationTree::LEN;
+        let offset = self.accounts.config.data_len();
+        let new_len = offset + ConfigExtension::HEADER_LEN + length;
+
+        let lamports = Rent::get()?.minimum_balance(new_len).saturating_sub(self.accounts.config.lamports());
+
+        if lamports > 0 {
+            Transfer {
+                from: self.accounts.authority,
+                to: self.accounts.config,
+                lamports,
+            }
+            .invoke()?;
+        }
+
+        self.accounts.config.realloc(new_len, true)?;
+
+        {
+            let mut data = self.accounts.config.try_borrow_mut_data()?;
+            data[offset..offset + 2].copy_from_slice(&(ConfigExtension::ObservationTree as u16).to_le_bytes());
+            data[offset + 2..offset + 4].copy_from_slice(&(length as u16).to_le_bytes());
+
+            get_extension_mut::<ObservationTree>(&mut data)?
+                .ok_or(ProgramError::InvalidAccountData)?
+                .set_inner(*self.accounts.merkle_tree.key());
+        }
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        Ok(())
+    }
+}
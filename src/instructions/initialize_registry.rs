@@ -0,0 +1,134 @@
+use crate::state::{GlobalConfig, Registry};
+use crate::{check_signer, check_writable, derive_registry};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #InitializeRegistry
+///
+/// Create a new page of the pool Registry
+///
+/// Accounts:
+///
+/// 1. payer:                       [signer, mut]
+/// 2. registry:                    [init]          // [b"registry", page], see `derive_registry`
+/// 3. system_program               [executable]
+/// 4. global_config
+///
+/// Parameters:
+///
+/// 1. page:          [u32]
+/// 2. registry_bump: [u8]           // Canonical bump of the page
+pub struct InitializeRegistryAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub registry: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeRegistryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, registry, _system_program, global_config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(payer)?;
+        [payer, registry].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            payer,
+            registry,
+            global_config,
+        })
+    }
+}
+
+pub struct InitializeRegistryInstructionData {
+    pub page: u32,
+    pub registry_bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for InitializeRegistryInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u32>() + size_of::<[u8; 1]>())) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let page = u32::from_le_bytes(data[0..4].try_into().unwrap());
+        let registry_bump: [u8; 1] = data[4..5].try_into().unwrap();
+
+        Ok(Self {
+            page,
+            registry_bump,
+        })
+    }
+}
+
+pub struct InitializeRegistry<'a> {
+    pub accounts: InitializeRegistryAccounts<'a>,
+    pub instruction_data: InitializeRegistryInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeRegistry<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = InitializeRegistryAccounts::try_from(accounts)?;
+        let instruction_data = InitializeRegistryInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> InitializeRegistry<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &5;
+
+    /// `sha256("global:initialize_registry")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [189, 181, 20, 17, 174, 57, 249, 59];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // A single account per page number, so indexers can trust the page numbering
+        let (registry_key, registry_bump) = derive_registry(self.instruction_data.page);
+
+        if registry_key.ne(self.accounts.registry.key()) || [registry_bump].ne(&self.instruction_data.registry_bump) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the registry page
+        let page_binding = self.instruction_data.page.to_le_bytes();
+        let registry_seeds = [
+            Seed::from(b"registry"),
+            Seed::from(&page_binding),
+            Seed::from(&self.instruction_data.registry_bump),
+        ];
+
+        let registry_lamports = Rent::get()?.minimum_balance(Registry::LEN);
+
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.registry,
+            lamports: registry_lamports,
+            space: Registry::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&registry_seeds)])?;
+
+        let mut registry = Registry::init(self.accounts.registry)?;
+
+        registry.set_inner(self.instruction_data.page, self.instruction_data.registry_bump);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{
+    check_signer, check_writable, Deposit, DepositAccounts, DepositInstructionData, Initialize, InitializeAccounts,
+    InitializeInstructionData, InitializeMint2, MintTo,
+};
+use core::mem::size_of;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult};
+use pinocchio_associated_token_account::instructions::Create;
+use pinocchio_system::instructions::CreateAccount;
+use pinocchio_token::state::Mint;
+
+/// #InitializeTestPool
+///
+/// Create two SPL Token mints, a pool of them with its vaults, and fund the payer, in one
+/// instruction, to set up integration tests on a local validator
+///
+/// Only built with the `testing` feature, never deploy it. The payer is the mint authority of both
+/// mints and the pool authority. With a non-zero `lp`, the minted balances are deposited in full
+/// for the first `lp` LP tokens.
+///
+/// Accounts:
+///
+/// 1. payer:                       [signer, mut]
+/// 2. mint_lp                      [init]
+/// 3. config                       [init]
+/// 4. mint_x                       [signer, init]  // Must sort before mint_y, see `sort_mints`
+/// 5. mint_y                       [signer, init]
+/// 6. vault_x                      [init]  // ATA of config for mint_x
+/// 7. vault_y                      [init]  // ATA of config for mint_y
+/// 8. payer_x_ata                  [init]
+/// 9. payer_y_ata                  [init]
+/// 10. payer_lp_ata                [init]
+/// 11. system_program              [executable]
+/// 12. token_program               [executable]  // SPL Token
+/// 13. associated_token_program    [executable]
+/// 14. global_config
+/// 15. treasury                    [mut]
+///
+/// Parameters:
+///
+/// 1. seed: u64,                   // See `Initialize`
+/// 2. fee: u16,
+/// 3. config_bump: u8,
+/// 4. lp_bump: u8,
+/// 5. decimals_x: u8,
+/// 6. decimals_y: u8,
+/// 7. amount_x: u64,               // Minted to the payer
+/// 8. amount_y: u64,
+/// 9. lp: u64,                     // LP of the first deposit, zero to skip it
+pub struct InitializeTestPoolAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub payer_x_ata: &'a AccountInfo,
+    pub payer_y_ata: &'a AccountInfo,
+    pub payer_lp_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub treasury: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeTestPoolAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, mint_lp, config, mint_x, mint_y, vault_x, vault_y, payer_x_ata, payer_y_ata, payer_lp_ata, system_program, token_program, _associated_token_program, global_config, treasury] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        [payer, mint_x, mint_y].into_iter().try_for_each(check_signer)?;
+        [
+            payer,
+            mint_lp,
+            config,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            payer_x_ata,
+            payer_y_ata,
+            payer_lp_ata,
+            treasury,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        if token_program.key().ne(&pinocchio_token::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            payer,
+            mint_lp,
+            config,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            payer_x_ata,
+            payer_y_ata,
+            payer_lp_ata,
+            system_program,
+            token_program,
+            global_config,
+            treasury,
+        })
+    }
+}
+
+pub struct InitializeTestPoolInstructionData {
+    pub seed: u64,
+    pub fee: u16,
+    pub config_bump: [u8; 1],
+    pub lp_bump: [u8; 1],
+    pub decimals_x: u8,
+    pub decimals_y: u8,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp: u64,
+}
+
+impl TryFrom<&[u8]> for InitializeTestPoolInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const INITIALIZE_TEST_POOL_DATA_LEN: usize =
+            size_of::<u64>() + size_of::<u16>() + size_of::<u8>() * 4 + size_of::<u64>() * 3;
+
+        if data.len() != INITIALIZE_TEST_POOL_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            seed: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+            fee: u16::from_le_bytes(data[8..10].try_into().unwrap()),
+            config_bump: [data[10]],
+            lp_bump: [data[11]],
+            decimals_x: data[12],
+            decimals_y: data[13],
+            amount_x: u64::from_le_bytes(data[14..22].try_into().unwrap()),
+            amount_y: u64::from_le_bytes(data[22..30].try_into().unwrap()),
+            lp: u64::from_le_bytes(data[30..38].try_into().unwrap()),
+        })
+    }
+}
+
+pub struct InitializeTestPool<'a> {
+    pub accounts: InitializeTestPoolAccounts<'a>,
+    pub instruction_data: InitializeTestPoolInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeTestPool<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = InitializeTestPoolAccounts::try_from(accounts)?;
+        let instruction_data = InitializeTestPoolInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> InitializeTestPool<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &46;
+
+    /// `sha256("global:initialize_test_pool")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [212, 31, 148, 75, 200, 222, 163, 48];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Create the mints, both minted by the payer
+        let mint_lamports = Rent::get()?.minimum_balance(Mint::LEN);
+
+        for (mint, decimals) in [
+            (self.accounts.mint_x, self.instruction_data.decimals_x),
+            (self.accounts.mint_y, self.instruction_data.decimals_y),
+        ] {
+            CreateAccount {
+                from: self.accounts.payer,
+                to: mint,
+                lamports: mint_lamports,
+                space: Mint::LEN as u64,
+                owner: &pinocchio_token::ID,
+            }
+            .invoke()?;
+
+            InitializeMint2 {
+                mint,
+                decimals,
+                mint_authority: self.accounts.payer.key(),
+                freeze_authority: None,
+                token_program: &pinocchio_token::ID,
+            }
+            .invoke()?;
+        }
+
+        // Create the pool, `Initialize` checks the mint order
+        Initialize {
+            accounts: InitializeAccounts {
+                initializer: self.accounts.payer,
+                mint_lp: self.accounts.mint_lp,
+                config: self.accounts.config,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                global_config: self.accounts.global_config,
+                treasury: self.accounts.treasury,
+                registry: None,
+                token_badge_x: None,
+                token_badge_y: None,
+            },
+            instruction_data: InitializeInstructionData {
+                seed: self.instruction_data.seed,
+                fee: self.instruction_data.fee,
+                config_bump: self.instruction_data.config_bump,
+                lp_bump: self.instruction_data.lp_bump,
+                flags: 0,
+                lp_decimals: InitializeInstructionData::DEFAULT_LP_DECIMALS,
+                authority: Some(*self.accounts.payer.key()),
+            },
+        }
+        .process()?;
+
+        // Create the vaults and the payer's token accounts
+        for (account, wallet, mint) in [
+            (self.accounts.vault_x, self.accounts.config, self.accounts.mint_x),
+            (self.accounts.vault_y, self.accounts.config, self.accounts.mint_y),
+            (self.accounts.payer_x_ata, self.accounts.payer, self.accounts.mint_x),
+            (self.accounts.payer_y_ata, self.accounts.payer, self.accounts.mint_y),
+            (self.accounts.payer_lp_ata, self.accounts.payer, self.accounts.mint_lp),
+        ] {
+            Create {
+                funding_account: self.accounts.payer,
+                account,
+                wallet,
+                mint,
+                system_program: self.accounts.system_program,
+                token_program: self.accounts.token_program,
+            }
+            .invoke()?;
+        }
+
+        // Fund the payer
+        for (mint, account, amount) in [
+            (self.accounts.mint_x, self.accounts.payer_x_ata, self.instruction_data.amount_x),
+            (self.accounts.mint_y, self.accounts.payer_y_ata, self.instruction_data.amount_y),
+        ] {
+            MintTo {
+                mint,
+                account,
+                mint_authority: self.accounts.payer,
+                amount,
+                token_program: &pinocchio_token::ID,
+            }
+            .invoke()?;
+        }
+
+        if self.instruction_data.lp == 0 {
+            return Ok(());
+        }
+
+        // Seed the pool with everything minted
+        Deposit {
+            accounts: DepositAccounts {
+                user: self.accounts.payer,
+                mint_lp: self.accounts.mint_lp,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                user_x_ata: self.accounts.payer_x_ata,
+                user_y_ata: self.accounts.payer_y_ata,
+                user_lp_ata: self.accounts.payer_lp_ata,
+                config: self.accounts.config,
+                token_program: self.accounts.token_program,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                token_program_x: self.accounts.token_program,
+                token_program_y: self.accounts.token_program,
+                global_config: self.accounts.global_config,
+                position: None,
+                payer: self.accounts.payer,
+            },
+            instruction_data: DepositInstructionData {
+                amount: self.instruction_data.lp,
+                max_x: self.instruction_data.amount_x,
+                max_y: self.instruction_data.amount_y,
+                expiration: i64::MAX,
+                flags: 0,
+            },
+        }
+        .process()
+    }
+}
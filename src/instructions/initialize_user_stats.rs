@@ -0,0 +1,117 @@
+use crate::state::{Config, GlobalConfig, UserStats};
+use crate::{check_signer, check_writable, derive_user_stats};
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #InitializeUserStats
+///
+/// Create the `UserStats` of a user on a pool, so their swaps count toward its `VolumeDiscount`
+/// tiers and loyalty programs
+///
+/// Anyone can pay for the statistics of any user, they start empty.
+///
+/// Accounts:
+///
+/// 1. payer:                       [signer, mut]   // Pays the user stats rent
+/// 2. user
+/// 3. config
+/// 4. user_stats                   [init]          // [b"user_stats", config, user]
+/// 5. system_program               [executable]
+/// 6. global_config
+pub struct InitializeUserStatsAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub user: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub user_stats: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeUserStatsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, user, config, user_stats, _system_program, global_config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(payer)?;
+        [payer, user_stats].into_iter().try_for_each(check_writable)?;
+
+        GlobalConfig::load_unfrozen(global_config)?;
+
+        // Statistics are only kept for pools
+        Config::load(config)?;
+
+        // Return the accounts
+        Ok(Self {
+            payer,
+            user,
+            config,
+            user_stats,
+            global_config,
+        })
+    }
+}
+
+pub struct InitializeUserStats<'a> {
+    pub accounts: InitializeUserStatsAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeUserStats<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = InitializeUserStatsAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> InitializeUserStats<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &66;
+
+    /// `sha256("global:initialize_user_stats")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [254, 243, 72, 98, 251, 130, 168, 213];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Check if the user stats are the user's on this pool
+        let (user_stats_key, user_stats_bump) =
+            derive_user_stats(self.accounts.config.key(), self.accounts.user.key());
+
+        if user_stats_key.ne(self.accounts.user_stats.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let user_stats_bump = [user_stats_bump];
+        let user_stats_seeds = [
+            Seed::from(b"user_stats"),
+            Seed::from(self.accounts.config.key()),
+            Seed::from(self.accounts.user.key()),
+            Seed::from(&user_stats_bump),
+        ];
+
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.user_stats,
+            lamports: Rent::get()?.minimum_balance(UserStats::LEN),
+            space: UserStats::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&user_stats_seeds)])?;
+
+        UserStats::init(self.accounts.user_stats)?.set_inner(
+            *self.accounts.config.key(),
+            *self.accounts.user.key(),
+            user_stats_bump,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use crate::state::{find_extension, get_extension_mut, Config, ConfigExtension, Extension, GlobalConfig, VaultBumps};
+use crate::{
+    bump_sequence, check_signer, check_writable, derive_pda_vault, token_account_len, AmmError, InitializeAccount3,
+    VAULT_SEED,
+};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::{CreateAccount, Transfer};
+
+/// #InitializeVaults
+///
+/// Create the program-derived vaults of a `Config::FLAG_PDA_VAULTS` pool and store their bumps in
+/// the config, as the `VaultBumps` extension
+///
+/// The pool refuses every instruction that takes its vaults until this ran, and anyone can run it.
+///
+/// Accounts:
+///
+/// 1. payer:                       [signer, mut]   // Pays for the vaults and the extra config rent
+/// 2. config                       [mut]
+/// 3. vault_x                      [init]  // [b"vault", config, mint_x]
+/// 4. vault_y                      [init]  // [b"vault", config, mint_y]
+/// 5. mint_x
+/// 6. mint_y
+/// 7. token_program_x              [executable]
+/// 8. token_program_y              [executable]
+/// 9. system_program               [executable]
+/// 10. global_config
+pub struct InitializeVaultsAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeVaultsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, config, vault_x, vault_y, mint_x, mint_y, _token_program_x, _token_program_y, _system_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(payer)?;
+        [payer, config, vault_x, vault_y].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            payer,
+            config,
+            vault_x,
+            vault_y,
+            mint_x,
+            mint_y,
+            global_config,
+        })
+    }
+}
+
+pub struct InitializeVaults<'a> {
+    pub accounts: InitializeVaultsAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeVaults<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = InitializeVaultsAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> InitializeVaults<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &34;
+
+    /// `sha256("global:initialize_vaults")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [234, 84, 214, 3, 128, 195, 81, 125];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // Deserialize the config account
+        let config = Config::load(self.accounts.config)?;
+
+        if !config.has_pda_vaults() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if self.accounts.mint_x.key().ne(config.mint_x()) || self.accounts.mint_y.key().ne(config.mint_y()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // The vaults are only created once
+        if find_extension(&self.accounts.config.try_borrow_data()?, ConfigExtension::VaultBumps as u16)?.is_some() {
+            return Err(AmmError::ExtensionAlreadyExists.into());
+        }
+
+        let rent = Rent::get()?;
+        let mut bumps = [[0u8; 1]; 2];
+
+        for (index, (vault, mint, token_program)) in [
+            (self.accounts.vault_x, self.accounts.mint_x, config.token_program_x()),
+            (self.accounts.vault_y, self.accounts.mint_y, config.token_program_y()),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (vault_key, vault_bump) = derive_pda_vault(self.accounts.config.key(), mint.key());
+            if vault_key.ne(vault.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            bumps[index] = [vault_bump];
+            let vault_seeds = [
+                Seed::from(VAULT_SEED),
+                Seed::from(self.accounts.config.key()),
+                Seed::from(mint.key()),
+                Seed::from(&bumps[index]),
+            ];
+
+            // Token-2022 mints may need room for account extensions
+            let space = token_account_len(mint)?;
+
+            CreateAccount {
+                from: self.accounts.payer,
+                to: vault,
+                lamports: rent.minimum_balance(space),
+                space: space as u64,
+                owner: token_program,
+            }
+            .invoke_signed(&[Signer::from(&vault_seeds)])?;
+
+            InitializeAccount3 {
+                account: vault,
+                mint,
+                owner: self.accounts.config.key(),
+                token_program,
+            }
+            .invoke()?;
+        }
+
+        drop(config);
+
+        // Append the bumps to the config, as `ExtendConfig` does
+        let length = VaultBumps::LEN;
+        let offset = self.accounts.config.data_len();
+        let new_len = offset + ConfigExtension::HEADER_LEN + length;
+
+        let lamports = rent.minimum_balance(new_len).saturating_sub(self.accounts.config.lamports());
+
+        if lamports > 0 {
+            Transfer {
+                from: self.accounts.payer,
+                to: self.accounts.config,
+                lamports,
+            }
+            .invoke()?;
+        }
+
+        self.accounts.config.realloc(new_len, true)?;
+
+        {
+            let mut data = self.accounts.config.try_borrow_mut_data()?;
+            data[offset..offset + 2].copy_from_slice(&(ConfigExtension::VaultBumps as u16).to_le_bytes());
+            data[offset + 2..offset + 4].copy_from_slice(&(length as u16).to_le_bytes());
+
+            get_extension_mut::<VaultBumps>(&mut data)?
+                .ok_or(ProgramError::InvalidAccountData)?
+                .set_inner(bumps[0], bumps[1]);
+        }
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use crate::state::{AmmState, Config, GlobalConfig, DISCRIMINATOR_LEN};
+use crate::{check_signer, check_writable, CONFIG_SEED};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::create_program_address, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::Transfer;
+
+/// #MigrateConfig
+///
+/// Prefix a config created before account discriminators with `Config::DISCRIMINATOR`
+///
+/// The data, extensions included, moves back by `DISCRIMINATOR_LEN` bytes. Every other
+/// instruction refuses the pool until it is migrated, so anyone can run it.
+///
+/// Accounts:
+///
+/// 1. payer:                       [signer, mut]   // Pays the extra rent
+/// 2. config                       [mut]
+/// 3. global_config
+/// 4. system_program               [executable]
+pub struct MigrateConfigAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MigrateConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, config, global_config, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(payer)?;
+        [payer, config].into_iter().try_for_each(check_writable)?;
+
+        if config.owner().ne(&crate::ID) || config.data_len() < Config::LEN - DISCRIMINATOR_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Legacy configs start with their state, which no discriminator starts with
+        if config.try_borrow_data()?[0] > AmmState::WithdrawOnly as u8 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        // Return the accounts
+        Ok(Self { payer, config, global_config })
+    }
+}
+
+pub struct MigrateConfig<'a> {
+    pub accounts: MigrateConfigAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MigrateConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = MigrateConfigAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> MigrateConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &30;
+
+    /// `sha256("global:migrate_config")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [92, 131, 58, 105, 210, 154, 224, 193];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let legacy_len = self.accounts.config.data_len();
+        let new_len = legacy_len + DISCRIMINATOR_LEN;
+
+        // Top up the rent for the new size
+        let lamports = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(self.accounts.config.lamports());
+
+        if lamports > 0 {
+            Transfer {
+                from: self.accounts.payer,
+                to: self.accounts.config,
+                lamports,
+            }
+            .invoke()?;
+        }
+
+        // Grow the account and shift the data behind the discriminator
+        self.accounts.config.realloc(new_len, false)?;
+
+        {
+            let mut data = self.accounts.config.try_borrow_mut_data()?;
+            data.copy_within(..legacy_len, DISCRIMINATOR_LEN);
+            data[..DISCRIMINATOR_LEN].copy_from_slice(&Config::DISCRIMINATOR);
+        }
+
+        // Only real pools can be migrated: the shifted data must derive the config address
+        let config = Config::load(self.accounts.config)?;
+        let config_bump = config.config_bump();
+
+        let address = create_program_address(
+            &[CONFIG_SEED, config.seed_bytes(), config.mint_x(), config.mint_y(), &config_bump],
+            &crate::ID,
+        )?;
+
+        if address.ne(self.accounts.config.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+use crate::state::{get_extension, Caps, GlobalConfig, Whitelist};
+use crate::{
+    bump_sequence, check_expiration, check_signer, check_writable, deposit_amounts, mul_div, set_return_amounts,
+    validate_pool_accounts, withdraw_amounts, AmmError, AmmState, BurnChecked, CheckedLpMint, CheckedUserAta, MintTo, PoolAccounts, Rounding, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #MigrateLiquidity
+///
+/// Withdraw LP from pool A and deposit the proceeds into pool B of the same mint pair in one
+/// instruction, e.g. after a fee-tier change or to move to a newer pool
+///
+/// The withdrawal goes through the user's token accounts, which keep whatever doesn't fit pool B's
+/// ratio. Pool B must already hold liquidity, and both pools must have soulbound LP or neither.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]        // Owner of the token accounts, or their delegate
+/// 2. config_a                     [mut with a `Sequence`]
+/// 3. mint_lp_a                    [mut]
+/// 4. vault_x_a                    [mut]
+/// 5. vault_y_a                    [mut]
+/// 6. user_lp_a_ata                [mut]
+/// 7. config_b                     [mut with a `Sequence`]
+/// 8. mint_lp_b                    [mut]
+/// 9. vault_x_b                    [mut]
+/// 10. vault_y_b                   [mut]
+/// 11. user_lp_b_ata               [mut]
+/// 12. user_x_ata                  [mut]
+/// 13. user_y_ata                  [mut]
+/// 14. mint_x
+/// 15. mint_y
+/// 16. token_program               [executable]  // LP token program of both pools, see `Config::lp_token_program`
+/// 17. token_program_x             [executable]
+/// 18. token_program_y             [executable]
+/// 19. global_config
+///
+/// Parameters:
+///
+/// 1. amount: u64,        // Amount of pool A LP token to burn
+/// 2. min_lp: u64,        // Min amount of pool B LP token we are willing to receive
+/// 3. expiration: i64     // Expiration of the offer
+///
+/// Return data:
+///
+/// 1. x: u64,             // Amount of X withdrawn from pool A
+/// 2. y: u64,             // Amount of Y withdrawn from pool A
+/// 3. x_deposited: u64,   // Amount of X deposited into pool B
+/// 4. y_deposited: u64,   // Amount of Y deposited into pool B
+/// 5. lp: u64,            // Amount of pool B LP token minted
+pub struct MigrateLiquidityAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub config_a: &'a AccountInfo,
+    pub mint_lp_a: &'a AccountInfo,
+    pub vault_x_a: &'a AccountInfo,
+    pub vault_y_a: &'a AccountInfo,
+    pub user_lp_a_ata: &'a AccountInfo,
+    pub config_b: &'a AccountInfo,
+    pub mint_lp_b: &'a AccountInfo,
+    pub vault_x_b: &'a AccountInfo,
+    pub vault_y_b: &'a AccountInfo,
+    pub user_lp_b_ata: &'a AccountInfo,
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MigrateLiquidityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, config_a, mint_lp_a, vault_x_a, vault_y_a, user_lp_a_ata, config_b, mint_lp_b, vault_x_b, vault_y_b, user_lp_b_ata, user_x_ata, user_y_ata, mint_x, mint_y, _token_program, token_program_x, token_program_y, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [
+            mint_lp_a,
+            vault_x_a,
+            vault_y_a,
+            user_lp_a_ata,
+            mint_lp_b,
+            vault_x_b,
+            vault_y_b,
+            user_lp_b_ata,
+            user_x_ata,
+            user_y_ata,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Migrating a pool into itself would only cost fees
+        if config_a.key().eq(config_b.key()) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            config_a,
+            mint_lp_a,
+            vault_x_a,
+            vault_y_a,
+            user_lp_a_ata,
+            config_b,
+            mint_lp_b,
+            vault_x_b,
+            vault_y_b,
+            user_lp_b_ata,
+            user_x_ata,
+            user_y_ata,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            global_config,
+        })
+    }
+}
+
+pub struct MigrateLiquidityInstructionData {
+    pub amount: u64,
+    pub min_lp: u64,
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for MigrateLiquidityInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const MIGRATE_LIQUIDITY_DATA_LEN: usize = size_of::<u64>() * 2 + size_of::<i64>();
+
+        if data.len() != MIGRATE_LIQUIDITY_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let min_lp = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[16..24].try_into().unwrap());
+
+        if amount == 0 || min_lp == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_expiration(expiration, 0)?;
+
+        Ok(Self {
+            amount,
+            min_lp,
+            expiration,
+        })
+    }
+}
+
+pub struct MigrateLiquidity<'a> {
+    pub accounts: MigrateLiquidityAccounts<'a>,
+    pub instruction_data: MigrateLiquidityInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MigrateLiquidity<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = MigrateLiquidityAccounts::try_from(accounts)?;
+        let instruction_data = MigrateLiquidityInstructionData::try_from(data)?;
+
+        // Return the initialized struct
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> MigrateLiquidity<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &29;
+
+    /// `sha256("global:migrate_liquidity")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [131, 168, 36, 158, 107, 147, 151, 163];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // Check both pools, passing the same mints for both checks that they share the mint pair
+        let PoolAccounts { config: config_a, vault_x: vault_x_a, vault_y: vault_y_a, deployed: deployed_a } =
+            validate_pool_accounts(
+                self.accounts.config_a,
+                (self.accounts.vault_x_a, self.accounts.vault_y_a),
+                (self.accounts.mint_x, self.accounts.mint_y),
+                (self.accounts.token_program_x, self.accounts.token_program_y),
+                &[AmmState::Initialized, AmmState::WithdrawOnly],
+            )?;
+        let PoolAccounts { config: config_b, vault_x: vault_x_b, vault_y: vault_y_b, deployed: deployed_b } =
+            validate_pool_accounts(
+                self.accounts.config_b,
+                (self.accounts.vault_x_b, self.accounts.vault_y_b),
+                (self.accounts.mint_x, self.accounts.mint_y),
+                (self.accounts.token_program_x, self.accounts.token_program_y),
+                &[AmmState::Initialized],
+            )?;
+
+        // Only one LP token program is passed
+        if config_a.lp_token_program().ne(config_b.lp_token_program()) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let user_x_ata = CheckedUserAta::new(self.accounts.user_x_ata, self.accounts.mint_x.key())?;
+        let user_y_ata = CheckedUserAta::new(self.accounts.user_y_ata, self.accounts.mint_y.key())?;
+
+        // Check the LP mints of both pools
+        let mint_lp_a = CheckedLpMint::new(self.accounts.mint_lp_a, self.accounts.config_a.key())?;
+        let mint_lp_b = CheckedLpMint::new(self.accounts.mint_lp_b, self.accounts.config_b.key())?;
+
+        // Withdraw from pool A, uncollected protocol fees don't back the LP supply
+        let (reserve_x_a, reserve_y_a) = config_a.reserves(vault_x_a.amount(), vault_y_a.amount(), deployed_a);
+
+        let (x, y) = withdraw_amounts(reserve_x_a, reserve_y_a, mint_lp_a.supply(), self.instruction_data.amount)?;
+
+        let config_a_bump = config_a.config_bump();
+        let seeds_a = [
+            Seed::from(b"config"),
+            Seed::from(config_a.seed_bytes()),
+            Seed::from(config_a.mint_x()),
+            Seed::from(config_a.mint_y()),
+            Seed::from(&config_a_bump),
+        ];
+        let signer_seeds_a = [Signer::from(&seeds_a)];
+
+        let (user_x_before, user_y_before) = (user_x_ata.amount(), user_y_ata.amount());
+
+        TransferChecked {
+            from: self.accounts.vault_x_a,
+            mint: self.accounts.mint_x,
+            to: self.accounts.user_x_ata,
+            authority: self.accounts.config_a,
+            amount: x,
+            decimals: config_a.decimals_x(),
+            token_program: config_a.token_program_x(),
+        }
+        .invoke_signed(&signer_seeds_a)?;
+
+        TransferChecked {
+            from: self.accounts.vault_y_a,
+            mint: self.accounts.mint_y,
+            to: self.accounts.user_y_ata,
+            authority: self.accounts.config_a,
+            amount: y,
+            decimals: config_a.decimals_y(),
+            token_program: config_a.token_program_y(),
+        }
+        .invoke_signed(&signer_seeds_a)?;
+
+        BurnChecked {
+            account: self.accounts.user_lp_a_ata,
+            mint: self.accounts.mint_lp_a,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount,
+            decimals: config_a.lp_decimals(),
+            token_program: config_a.lp_token_program(),
+        }
+        .invoke()?;
+
+        // Only what the user actually received is migrated, net of transfer fees
+        let received_x = user_x_ata.amount() - user_x_before;
+        let received_y = user_y_ata.amount() - user_y_before;
+
+        // Deposit into pool B at its ratio, the LP is capped by the scarcer side
+        let (reserve_x_b, reserve_y_b) = config_b.reserves(vault_x_b.amount(), vault_y_b.amount(), deployed_b);
+        let supply = mint_lp_b.supply();
+
+        if supply == 0 || reserve_x_b == 0 || reserve_y_b == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let lp = mul_div(received_x, supply, reserve_x_b, Rounding::Down)?
+            .min(mul_div(received_y, supply, reserve_y_b, Rounding::Down)?);
+
+        // Pay for the LP at the pool ratio, rounding up in favor of the pool
+        let (x_deposited, y_deposited) = deposit_amounts(reserve_x_b, reserve_y_b, supply, lp)?;
+
+        // Apply the optional deposit restrictions of pool B
+        {
+            let config_data = self.accounts.config_b.try_borrow_data()?;
+
+            if let Some(whitelist) = get_extension::<Whitelist>(&config_data)? {
+                if !whitelist.contains(self.accounts.user.key()) {
+                    return Err(AmmError::DepositorNotWhitelisted.into());
+                }
+            }
+
+            if let Some(caps) = get_extension::<Caps>(&config_data)? {
+                caps.check(reserve_x_b.saturating_add(x_deposited), reserve_y_b.saturating_add(y_deposited))?;
+            }
+        }
+
+        let (vault_x_before, vault_y_before) = (vault_x_b.amount(), vault_y_b.amount());
+
+        TransferChecked {
+            from: self.accounts.user_x_ata,
+            mint: self.accounts.mint_x,
+            to: self.accounts.vault_x_b,
+            authority: self.accounts.user,
+            amount: x_deposited,
+            decimals: config_b.decimals_x(),
+            token_program: config_b.token_program_x(),
+        }
+        .invoke()?;
+
+        TransferChecked {
+            from: self.accounts.user_y_ata,
+            mint: self.accounts.mint_y,
+            to: self.accounts.vault_y_b,
+            authority: self.accounts.user,
+            amount: y_deposited,
+            decimals: config_b.decimals_y(),
+            token_program: config_b.token_program_y(),
+        }
+        .invoke()?;
+
+        // With Token-2022 transfer fees the vaults receive less than was sent, so only mint
+        // the LP that the received amounts are worth
+        let backed_lp = mul_div(vault_x_b.amount() - vault_x_before, supply, reserve_x_b, Rounding::Down)?
+            .min(mul_div(vault_y_b.amount() - vault_y_before, supply, reserve_y_b, Rounding::Down)?);
+        let lp = lp.min(backed_lp);
+
+        // Check for slippage over the whole migration
+        if lp < self.instruction_data.min_lp {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let config_b_bump = config_b.config_bump();
+        let seeds_b = [
+            Seed::from(b"config"),
+            Seed::from(config_b.seed_bytes()),
+            Seed::from(config_b.mint_x()),
+            Seed::from(config_b.mint_y()),
+            Seed::from(&config_b_bump),
+        ];
+
+        MintTo {
+            mint: self.accounts.mint_lp_b,
+            account: self.accounts.user_lp_b_ata,
+            mint_authority: self.accounts.config_b,
+            amount: lp,
+            token_program: config_b.lp_token_program(),
+        }
+        .invoke_signed(&[Signer::from(&seeds_b)])?;
+
+        drop(config_a);
+        drop(config_b);
+
+        // Order the changes to both pools for off-chain consumers
+        bump_sequence(self.accounts.config_a, *Self::DISCRIMINATOR)?;
+        bump_sequence(self.accounts.config_b, *Self::DISCRIMINATOR)?;
+
+        set_return_amounts(&[x, y, x_deposited, y_deposited, lp]);
+
+        Ok(())
+    }
+}
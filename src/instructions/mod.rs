@@ -12,3 +12,194 @@ pub use swap::*;
 
 pub mod update_config;
 pub use update_config::*;
+
+pub mod initialize_registry;
+pub use initialize_registry::*;
+
+pub mod open_position;
+pub use open_position::*;
+
+pub mod increase_position;
+pub use increase_position::*;
+
+pub mod decrease_position;
+pub use decrease_position::*;
+
+pub mod initialize_global;
+pub use initialize_global::*;
+
+pub mod update_global_config;
+pub use update_global_config::*;
+
+pub mod collect_protocol_fees;
+pub use collect_protocol_fees::*;
+
+pub mod withdraw_treasury;
+pub use withdraw_treasury::*;
+
+pub mod extend_config;
+pub use extend_config::*;
+
+pub mod initialize_launch;
+pub use initialize_launch::*;
+
+pub mod buy_launch;
+pub use buy_launch::*;
+
+pub mod sell_launch;
+pub use sell_launch::*;
+
+pub mod graduate_launch;
+pub use graduate_launch::*;
+
+pub mod swap_with_quote;
+pub use swap_with_quote::*;
+
+pub mod withdraw_maker_escrow;
+pub use withdraw_maker_escrow::*;
+
+pub mod approve_strategy;
+pub use approve_strategy::*;
+
+pub mod execute_strategy;
+pub use execute_strategy::*;
+
+pub mod rebalance;
+pub use rebalance::*;
+
+pub mod deposit_to_strategy;
+pub use deposit_to_strategy::*;
+
+pub mod withdraw_from_strategy;
+pub use withdraw_from_strategy::*;
+
+pub mod initialize_multi_pool;
+pub use initialize_multi_pool::*;
+
+pub mod multi_deposit;
+pub use multi_deposit::*;
+
+pub mod multi_withdraw;
+pub use multi_withdraw::*;
+
+pub mod multi_swap;
+pub use multi_swap::*;
+
+pub mod migrate_liquidity;
+pub use migrate_liquidity::*;
+
+pub mod migrate_config;
+pub use migrate_config::*;
+
+pub mod emergency_withdraw_protocol_side;
+pub use emergency_withdraw_protocol_side::*;
+
+pub mod claim_recovery;
+pub use claim_recovery::*;
+
+pub mod raydium_swap;
+pub use raydium_swap::*;
+
+pub mod token_swap;
+pub use token_swap::*;
+
+pub mod initialize_vaults;
+pub use initialize_vaults::*;
+
+pub mod initialize_fee_distributor;
+pub use initialize_fee_distributor::*;
+
+pub mod checkpoint_fees;
+pub use checkpoint_fees::*;
+
+pub mod stake_governance;
+pub use stake_governance::*;
+
+pub mod unstake_governance;
+pub use unstake_governance::*;
+
+pub mod claim_staking_rewards;
+pub use claim_staking_rewards::*;
+
+pub mod router_swap;
+pub use router_swap::*;
+
+pub mod set_trusted_router;
+pub use set_trusted_router::*;
+
+pub mod assert_pool_healthy;
+pub use assert_pool_healthy::*;
+
+pub mod set_pool_metadata;
+pub use set_pool_metadata::*;
+
+pub mod swap_and_deposit;
+pub use swap_and_deposit::*;
+
+pub mod withdraw_and_swap;
+pub use withdraw_and_swap::*;
+
+#[cfg(feature = "testing")]
+pub mod initialize_test_pool;
+#[cfg(feature = "testing")]
+pub use initialize_test_pool::*;
+
+pub mod set_trading_calendar;
+pub use set_trading_calendar::*;
+
+pub mod withdraw_insurance;
+pub use withdraw_insurance::*;
+
+pub mod get_pool_info;
+pub use get_pool_info::*;
+
+pub mod get_fair_lp_value;
+pub use get_fair_lp_value::*;
+
+pub mod initialize_lbp;
+pub use initialize_lbp::*;
+
+pub mod swap_lbp;
+pub use swap_lbp::*;
+
+pub mod exit_lbp;
+pub use exit_lbp::*;
+
+pub mod initialize_observation_tree;
+pub use initialize_observation_tree::*;
+
+pub mod append_observation;
+pub use append_observation::*;
+
+pub mod verify_observation;
+pub use verify_observation::*;
+
+pub mod arbitrage;
+pub use arbitrage::*;
+
+pub mod set_token_badge;
+pub use set_token_badge::*;
+
+pub mod swap_batch;
+pub use swap_batch::*;
+
+pub mod open_long_term_order;
+pub use open_long_term_order::*;
+
+pub mod execute_long_term_order;
+pub use execute_long_term_order::*;
+
+pub mod close_long_term_order;
+pub use close_long_term_order::*;
+
+pub mod assert_balance_change;
+pub use assert_balance_change::*;
+
+pub mod deposit_with_permit;
+pub use deposit_with_permit::*;
+
+pub mod initialize_user_stats;
+pub use initialize_user_stats::*;
+
+pub mod snapshot_balance;
+pub use snapshot_balance::*;
@@ -0,0 +1,275 @@
+use crate::state::{GlobalConfig, MultiPool};
+use crate::{
+    check_expiration, check_signer, check_writable, derive_lp_mint, set_return_amounts, validate_multi_pool_token,
+    AmmError, CheckedVault, TransferChecked,
+};
+use core::{array, mem::size_of};
+use pinocchio::instruction::Signer;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_token::{instructions::MintTo, state::Mint};
+
+/// #MultiDeposit
+///
+/// Deposit every asset of a `MultiPool` at the pool ratio for LP tokens
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]        // Owner of the token accounts, or their delegate
+/// 2. multi_pool
+/// 3. mint_lp                      [mut]
+/// 4. user_lp_ata                  [mut]
+/// 5. token_program                [executable]    // SPL Token, for the LP mint
+/// 6. global_config
+/// 7. ..                           // Per pool token, in order: mint, vault [mut], user_ata [mut], token_program
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Amount of LP token to claim
+/// 2. expiration: i64,             // Expiration of the offer
+/// 3. max_amounts: [u64],          // Max amount of each token we are willing to deposit, in pool order
+///
+/// Return data: the amount of each token deposited, then the LP minted
+pub struct MultiLiquidityAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub multi_pool: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub user_lp_ata: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub tokens: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MultiLiquidityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, multi_pool, mint_lp, user_lp_ata, token_program, global_config, tokens @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if tokens.len() % Self::TOKEN_ACCOUNTS != 0 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [mint_lp, user_lp_ata].into_iter().try_for_each(check_writable)?;
+        for token in tokens.chunks_exact(Self::TOKEN_ACCOUNTS) {
+            [&token[1], &token[2]].into_iter().try_for_each(check_writable)?;
+        }
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            multi_pool,
+            mint_lp,
+            user_lp_ata,
+            token_program,
+            global_config,
+            tokens,
+        })
+    }
+}
+
+impl<'a> MultiLiquidityAccounts<'a> {
+    /// Accounts passed for each pool token
+    pub const TOKEN_ACCOUNTS: usize = 4;
+
+    /// Check the LP mint and the accounts of every token of `pool`, returning the vaults
+    pub fn validate(
+        &self,
+        pool: &MultiPool,
+    ) -> Result<[Option<CheckedVault<'a>>; MultiPool::MAX_TOKENS], ProgramError> {
+        if self.tokens.len() != pool.token_count() * Self::TOKEN_ACCOUNTS {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        if derive_lp_mint(self.multi_pool.key()).0.ne(self.mint_lp.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut vaults = array::from_fn(|_| None);
+        for (index, token) in self.tokens.chunks_exact(Self::TOKEN_ACCOUNTS).enumerate() {
+            vaults[index] = Some(validate_multi_pool_token(
+                self.multi_pool,
+                pool,
+                index,
+                &token[0],
+                &token[1],
+                &token[3],
+            )?);
+        }
+
+        Ok(vaults)
+    }
+}
+
+pub struct MultiLiquidityInstructionData<'a> {
+    pub amount: u64,
+    pub expiration: i64,
+    /// Little-endian u64 per pool token
+    pub token_amounts: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for MultiLiquidityInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const MULTI_LIQUIDITY_HEADER_LEN: usize = size_of::<u64>() + size_of::<i64>();
+
+        // The token amounts are checked against the pool by `check_token_count`
+        if data.len() < MULTI_LIQUIDITY_HEADER_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_expiration(expiration, 0)?;
+
+        Ok(Self {
+            amount,
+            expiration,
+            token_amounts: &data[16..],
+        })
+    }
+}
+
+impl MultiLiquidityInstructionData<'_> {
+    /// Amount passed for the `index` pool token
+    #[inline(always)]
+    pub fn token_amount(&self, index: usize) -> u64 {
+        u64::from_le_bytes(self.token_amounts[index * 8..index * 8 + 8].try_into().unwrap())
+    }
+
+    /// Check that an amount was passed for each of the `token_count` pool tokens
+    #[inline(always)]
+    pub fn check_token_count(&self, token_count: usize) -> Result<(), ProgramError> {
+        if self.token_amounts.len() != token_count * size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(())
+    }
+}
+
+pub struct MultiDeposit<'a> {
+    pub accounts: MultiLiquidityAccounts<'a>,
+    pub instruction_data: MultiLiquidityInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MultiDeposit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = MultiLiquidityAccounts::try_from(accounts)?;
+        let instruction_data = MultiLiquidityInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> MultiDeposit<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &26;
+
+    /// `sha256("global:multi_deposit")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [203, 248, 94, 140, 107, 91, 128, 121];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let pool = MultiPool::load(self.accounts.multi_pool)?;
+        let token_count = pool.token_count();
+
+        self.instruction_data.check_token_count(token_count)?;
+        let vaults = self.accounts.validate(&pool)?;
+
+        // Deserialize the LP mint
+        let supply = unsafe { Mint::from_account_info_unchecked(self.accounts.mint_lp)? }.supply() as u128;
+
+        let mut reserves = [0u64; MultiPool::MAX_TOKENS];
+        for (reserve, vault) in reserves.iter_mut().zip(vaults.iter().flatten()) {
+            *reserve = vault.amount();
+        }
+
+        let is_empty = supply == 0 && reserves.iter().all(|reserve| *reserve == 0);
+
+        // Pay for the LP at the pool ratio, rounding up in favor of the pool
+        let mut amounts = [0u64; MultiPool::MAX_TOKENS];
+        for (index, amount) in amounts.iter_mut().enumerate().take(token_count) {
+            let max = self.instruction_data.token_amount(index);
+
+            *amount = match is_empty {
+                true => max,
+                false => {
+                    if supply == 0 || reserves[index] == 0 {
+                        return Err(ProgramError::InvalidArgument);
+                    }
+
+                    u64::try_from((self.instruction_data.amount as u128 * reserves[index] as u128).div_ceil(supply))
+                        .map_err(|_| AmmError::MathOverflow)?
+                }
+            };
+
+            // Check for slippage
+            if *amount == 0 || *amount > max {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        // Transfer the tokens to the vaults
+        let mut lp = self.instruction_data.amount as u128;
+        for (index, token) in self.accounts.tokens.chunks_exact(MultiLiquidityAccounts::TOKEN_ACCOUNTS).enumerate() {
+            let vault = vaults[index].as_ref().ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            TransferChecked {
+                from: &token[2],
+                mint: &token[0],
+                to: &token[1],
+                authority: self.accounts.user,
+                amount: amounts[index],
+                decimals: pool.decimals(index),
+                token_program: pool.token_program(index),
+            }
+            .invoke()?;
+
+            // With Token-2022 transfer fees the vaults receive less than was sent, so only mint
+            // the LP that the received amounts are worth
+            let received = vault.amount() - reserves[index];
+            if !is_empty && received != amounts[index] {
+                lp = lp.min(received as u128 * supply / reserves[index] as u128);
+            }
+        }
+
+        let lp = lp as u64;
+        if lp == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Mint the LP tokens to the user
+        let seeds = pool.signer_seeds();
+
+        MintTo {
+            mint: self.accounts.mint_lp,
+            account: self.accounts.user_lp_ata,
+            mint_authority: self.accounts.multi_pool,
+            amount: lp,
+        }
+        .invoke_signed(&[Signer::from(&seeds[..token_count + 3])])?;
+
+        let mut return_amounts = [0u64; MultiPool::MAX_TOKENS + 1];
+        return_amounts[..token_count].copy_from_slice(&amounts[..token_count]);
+        return_amounts[token_count] = lp;
+
+        set_return_amounts(&return_amounts[..token_count + 1]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use crate::state::{GlobalConfig, MultiPool};
+use crate::{
+    check_expiration, check_signer, check_writable, set_return_amounts, validate_multi_pool_token, AmmError,
+    CheckedUserAta, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::Signer;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #MultiSwap
+///
+/// Swap one asset of a `MultiPool` for another
+///
+/// Only the reserves of the two tokens move, so the other tokens' accounts aren't needed.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]        // Owner of the input account, or a delegate approved for `amount`
+/// 2. multi_pool
+/// 3. global_config
+/// 4. mint_in
+/// 5. vault_in                     [mut]
+/// 6. user_in                      [mut]
+/// 7. token_program_in             [executable]
+/// 8. mint_out
+/// 9. vault_out                    [mut]
+/// 10. user_out                    [mut]
+/// 11. token_program_out           [executable]
+///
+/// Parameters:
+///
+/// 1. index_in:                    [u8]    // Index of mint_in in `MultiPool::mints`
+/// 2. index_out:                   [u8]
+/// 3. amount:                      [u64]
+/// 4. min:                         [u64]
+/// 5. expiration:                  [i64]
+///
+/// Return data:
+///
+/// 1. amount_in:                   [u64]   // Received by the vault
+/// 2. amount_out:                  [u64]   // Received by the user, net of transfer fees
+pub struct MultiSwapAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub multi_pool: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub mint_in: &'a AccountInfo,
+    pub vault_in: &'a AccountInfo,
+    pub user_in: &'a AccountInfo,
+    pub token_program_in: &'a AccountInfo,
+    pub mint_out: &'a AccountInfo,
+    pub vault_out: &'a AccountInfo,
+    pub user_out: &'a AccountInfo,
+    pub token_program_out: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MultiSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, multi_pool, global_config, mint_in, vault_in, user_in, token_program_in, mint_out, vault_out, user_out, token_program_out] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [vault_in, user_in, vault_out, user_out].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            multi_pool,
+            global_config,
+            mint_in,
+            vault_in,
+            user_in,
+            token_program_in,
+            mint_out,
+            vault_out,
+            user_out,
+            token_program_out,
+        })
+    }
+}
+
+pub struct MultiSwapInstructionData {
+    pub index_in: usize,
+    pub index_out: usize,
+    pub amount: u64,
+    pub min: u64,
+    pub expiration: i64,
+}
+
+impl TryFrom<&[u8]> for MultiSwapInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const MULTI_SWAP_DATA_LEN: usize = size_of::<u8>() * 2 + size_of::<u64>() * 2 + size_of::<i64>();
+
+        if data.len() != MULTI_SWAP_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let index_in = data[0] as usize;
+        let index_out = data[1] as usize;
+        let amount = u64::from_le_bytes(data[2..10].try_into().unwrap());
+        let min = u64::from_le_bytes(data[10..18].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[18..26].try_into().unwrap());
+
+        if index_in == index_out || amount == 0 || min == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_expiration(expiration, 0)?;
+
+        Ok(Self {
+            index_in,
+            index_out,
+            amount,
+            min,
+            expiration,
+        })
+    }
+}
+
+pub struct MultiSwap<'a> {
+    pub accounts: MultiSwapAccounts<'a>,
+    pub instruction_data: MultiSwapInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MultiSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = MultiSwapAccounts::try_from(accounts)?;
+        let instruction_data = MultiSwapInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> MultiSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &28;
+
+    /// `sha256("global:multi_swap")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [78, 214, 33, 88, 21, 5, 219, 16];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Trading is halted while the protocol is paused or frozen for an upgrade
+        let global_config = GlobalConfig::load(self.accounts.global_config)?;
+
+        if global_config.is_paused() {
+            return Err(AmmError::ProtocolPaused.into());
+        }
+
+        global_config.check_not_frozen()?;
+
+        let pool = MultiPool::load(self.accounts.multi_pool)?;
+        let (index_in, index_out) = (self.instruction_data.index_in, self.instruction_data.index_out);
+
+        // Check the mints, the token programs and the vaults of both tokens
+        let vault_in = validate_multi_pool_token(
+            self.accounts.multi_pool,
+            &pool,
+            index_in,
+            self.accounts.mint_in,
+            self.accounts.vault_in,
+            self.accounts.token_program_in,
+        )?;
+        let vault_out = validate_multi_pool_token(
+            self.accounts.multi_pool,
+            &pool,
+            index_out,
+            self.accounts.mint_out,
+            self.accounts.vault_out,
+            self.accounts.token_program_out,
+        )?;
+        let user_out = CheckedUserAta::new(self.accounts.user_out, self.accounts.mint_out.key())?;
+
+        // Refuse empty and dust pools up front
+        global_config.check_swap_reserves(vault_in.amount(), vault_out.amount())?;
+
+        // Take the input first: with Token-2022 transfer fees the vault receives less than was sent,
+        // so the balance delta, not the instruction amount, is what gets priced
+        let vault_in_before = vault_in.amount();
+
+        TransferChecked {
+            from: self.accounts.user_in,
+            mint: self.accounts.mint_in,
+            to: self.accounts.vault_in,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount,
+            decimals: pool.decimals(index_in),
+            token_program: pool.token_program(index_in),
+        }
+        .invoke()?;
+
+        let amount_in = vault_in.amount() - vault_in_before;
+
+        // The fee stays in the vault as part of the reserves
+        let (withdraw, _fee) = pool.quote_swap(vault_in_before, vault_out.amount(), amount_in)?;
+
+        if withdraw == 0 {
+            return Err(AmmError::ZeroSwapOutput.into());
+        }
+
+        // Check for slippage on the quote, then on what the user receives
+        if withdraw < self.instruction_data.min {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let user_out_before = user_out.amount();
+        let seeds = pool.signer_seeds();
+
+        TransferChecked {
+            from: self.accounts.vault_out,
+            mint: self.accounts.mint_out,
+            to: self.accounts.user_out,
+            authority: self.accounts.multi_pool,
+            amount: withdraw,
+            decimals: pool.decimals(index_out),
+            token_program: pool.token_program(index_out),
+        }
+        .invoke_signed(&[Signer::from(&seeds[..pool.token_count() + 3])])?;
+
+        // Check for slippage on what the user actually received
+        let amount_out = user_out.amount() - user_out_before;
+        if amount_out < self.instruction_data.min {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        set_return_amounts(&[amount_in, amount_out]);
+
+        Ok(())
+    }
+}
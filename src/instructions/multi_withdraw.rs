@@ -0,0 +1,105 @@
+use crate::state::MultiPool;
+use crate::{set_return_amounts, MultiLiquidityAccounts, MultiLiquidityInstructionData, TransferChecked};
+use pinocchio::instruction::Signer;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_token::{instructions::Burn, state::Mint};
+
+/// #MultiWithdraw
+///
+/// Burn LP tokens of a `MultiPool` for their share of every asset
+///
+/// Accounts: same as `MultiDeposit`, the global config isn't read as withdrawals run while the
+/// program is upgrade frozen
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Amount of LP token to burn
+/// 2. expiration: i64,             // Expiration of the offer
+/// 3. min_amounts: [u64],          // Min amount of each token we are willing to withdraw, in pool order
+///
+/// Return data: the amount of each token withdrawn
+pub struct MultiWithdraw<'a> {
+    pub accounts: MultiLiquidityAccounts<'a>,
+    pub instruction_data: MultiLiquidityInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MultiWithdraw<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = MultiLiquidityAccounts::try_from(accounts)?;
+        let instruction_data = MultiLiquidityInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> MultiWithdraw<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &27;
+
+    /// `sha256("global:multi_withdraw")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [13, 236, 1, 43, 190, 152, 3, 57];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let pool = MultiPool::load(self.accounts.multi_pool)?;
+        let token_count = pool.token_count();
+
+        self.instruction_data.check_token_count(token_count)?;
+        let vaults = self.accounts.validate(&pool)?;
+
+        // Deserialize the LP mint
+        let supply = unsafe { Mint::from_account_info_unchecked(self.accounts.mint_lp)? }.supply() as u128;
+
+        if supply == 0 || self.instruction_data.amount as u128 > supply {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Pay out the LP share of each reserve, rounding down in favor of the pool
+        let mut amounts = [0u64; MultiPool::MAX_TOKENS];
+        for (index, vault) in vaults.iter().flatten().enumerate() {
+            amounts[index] = (self.instruction_data.amount as u128 * vault.amount() as u128 / supply) as u64;
+
+            // Check for slippage
+            if amounts[index] < self.instruction_data.token_amount(index) {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        // Burn the LP tokens first
+        Burn {
+            mint: self.accounts.mint_lp,
+            account: self.accounts.user_lp_ata,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount,
+        }
+        .invoke()?;
+
+        // Transfer the tokens out of the vaults
+        let seeds = pool.signer_seeds();
+        let signer = [Signer::from(&seeds[..token_count + 3])];
+
+        for (index, token) in self.accounts.tokens.chunks_exact(MultiLiquidityAccounts::TOKEN_ACCOUNTS).enumerate() {
+            if amounts[index] == 0 {
+                continue;
+            }
+
+            TransferChecked {
+                from: &token[1],
+                mint: &token[0],
+                to: &token[2],
+                authority: self.accounts.multi_pool,
+                amount: amounts[index],
+                decimals: pool.decimals(index),
+                token_program: pool.token_program(index),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        set_return_amounts(&amounts[..token_count]);
+
+        Ok(())
+    }
+}
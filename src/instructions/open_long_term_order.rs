@@ -0,0 +1,266 @@
+use crate::state::{Config, GlobalConfig, LongTermOrder};
+use crate::{check_signer, check_writable, derive_long_term_order, set_return_amounts, CheckedUserAta, TransferChecked};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{clock::Clock, rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_associated_token_account::instructions::CreateIdempotent;
+use pinocchio_system::instructions::CreateAccount;
+
+/// #OpenLongTermOrder
+///
+/// Escrow `amount` of one side of a pool in a `LongTermOrder` selling it evenly over `duration`
+/// slots, so a treasury can execute a large order with little price impact
+///
+/// Anyone can crank the order with `ExecuteLongTermOrder`, and the owner takes the proceeds and
+/// whatever is left unsold with `CloseLongTermOrder`, at any time.
+///
+/// Accounts:
+///
+/// 1. owner:                       [signer, mut]   // Pays the order and escrow rent
+/// 2. config
+/// 3. long_term_order              [init]          // [b"long_term_order", config, owner, nonce]
+/// 4. owner_in                     [mut]           // Owner's token account of the input mint
+/// 5. escrow_x                     [init_if_needed]    // ATA of the order for mint_x
+/// 6. escrow_y                     [init_if_needed]    // ATA of the order for mint_y
+/// 7. mint_x
+/// 8. mint_y
+/// 9. token_program_x              [executable]
+/// 10. token_program_y             [executable]
+/// 11. system_program              [executable]
+/// 12. associated_token_program    [executable]
+/// 13. global_config
+///
+/// Parameters:
+///
+/// 1. nonce: u64,                  // Tells apart the orders of an owner on a pool
+/// 2. is_x: bool,                  // Sell X for Y, or Y for X
+/// 3. amount: u64,                 // Input to sell
+/// 4. min_amount_out: u64,         // Least output over the whole order, enforced pro rata
+/// 5. duration: u64,               // Slots to sell over
+///
+/// Return data:
+///
+/// 1. amount: u64,                 // Input escrowed, net of transfer fees
+/// 2. end_slot: u64,
+pub struct OpenLongTermOrderAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub long_term_order: &'a AccountInfo,
+    pub owner_in: &'a AccountInfo,
+    pub escrow_x: &'a AccountInfo,
+    pub escrow_y: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for OpenLongTermOrderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, config, long_term_order, owner_in, escrow_x, escrow_y, mint_x, mint_y, token_program_x, token_program_y, system_program, _associated_token_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(owner)?;
+        [owner, long_term_order, owner_in, escrow_x, escrow_y]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            owner,
+            config,
+            long_term_order,
+            owner_in,
+            escrow_x,
+            escrow_y,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            system_program,
+            global_config,
+        })
+    }
+}
+
+pub struct OpenLongTermOrderInstructionData {
+    pub nonce: u64,
+    pub is_x: bool,
+    pub amount: u64,
+    pub min_amount_out: u64,
+    pub duration: u64,
+}
+
+impl TryFrom<&[u8]> for OpenLongTermOrderInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const OPEN_LONG_TERM_ORDER_DATA_LEN: usize = size_of::<u64>() * 4 + size_of::<bool>();
+
+        if data.len().ne(&OPEN_LONG_TERM_ORDER_DATA_LEN) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let nonce = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let is_x = data[8] == 1;
+        let amount = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let min_amount_out = u64::from_le_bytes(data[17..25].try_into().unwrap());
+        let duration = u64::from_le_bytes(data[25..33].try_into().unwrap());
+
+        if amount == 0 || duration == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            nonce,
+            is_x,
+            amount,
+            min_amount_out,
+            duration,
+        })
+    }
+}
+
+pub struct OpenLongTermOrder<'a> {
+    pub accounts: OpenLongTermOrderAccounts<'a>,
+    pub instruction_data: OpenLongTermOrderInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for OpenLongTermOrder<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = OpenLongTermOrderAccounts::try_from(accounts)?;
+        let instruction_data = OpenLongTermOrderInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> OpenLongTermOrder<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &61;
+
+    /// `sha256("global:open_long_term_order")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [22, 38, 211, 37, 230, 32, 173, 129];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let is_x = self.instruction_data.is_x;
+
+        // The order sells one side of the pool for the other
+        let (decimals_in, token_program_in) = {
+            let config = Config::load(self.accounts.config)?;
+
+            if config.mint_x().ne(self.accounts.mint_x.key())
+                || config.mint_y().ne(self.accounts.mint_y.key())
+                || config.token_program_x().ne(self.accounts.token_program_x.key())
+                || config.token_program_y().ne(self.accounts.token_program_y.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            match is_x {
+                true => (config.decimals_x(), *config.token_program_x()),
+                false => (config.decimals_y(), *config.token_program_y()),
+            }
+        };
+
+        // Check if the order is valid
+        let (long_term_order_key, long_term_order_bump) = derive_long_term_order(
+            self.accounts.config.key(),
+            self.accounts.owner.key(),
+            self.instruction_data.nonce,
+        );
+
+        if long_term_order_key.ne(self.accounts.long_term_order.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let nonce = self.instruction_data.nonce.to_le_bytes();
+        let long_term_order_bump = [long_term_order_bump];
+        let long_term_order_seeds = [
+            Seed::from(b"long_term_order"),
+            Seed::from(self.accounts.config.key()),
+            Seed::from(self.accounts.owner.key()),
+            Seed::from(&nonce),
+            Seed::from(&long_term_order_bump),
+        ];
+
+        CreateAccount {
+            from: self.accounts.owner,
+            to: self.accounts.long_term_order,
+            lamports: Rent::get()?.minimum_balance(LongTermOrder::LEN),
+            space: LongTermOrder::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&long_term_order_seeds)])?;
+
+        // The order holds both sides until it's closed
+        for (escrow, mint, token_program) in [
+            (self.accounts.escrow_x, self.accounts.mint_x, self.accounts.token_program_x),
+            (self.accounts.escrow_y, self.accounts.mint_y, self.accounts.token_program_y),
+        ] {
+            CreateIdempotent {
+                funding_account: self.accounts.owner,
+                account: escrow,
+                wallet: self.accounts.long_term_order,
+                mint,
+                system_program: self.accounts.system_program,
+                token_program,
+            }
+            .invoke()?;
+        }
+
+        let (escrow_in_ata, mint_in) = match is_x {
+            true => (self.accounts.escrow_x, self.accounts.mint_x),
+            false => (self.accounts.escrow_y, self.accounts.mint_y),
+        };
+        let escrow_in = CheckedUserAta::new(escrow_in_ata, mint_in.key())?;
+        let escrow_in_before = escrow_in.amount();
+
+        TransferChecked {
+            from: self.accounts.owner_in,
+            mint: mint_in,
+            to: escrow_in_ata,
+            authority: self.accounts.owner,
+            amount: self.instruction_data.amount,
+            decimals: decimals_in,
+            token_program: &token_program_in,
+        }
+        .invoke()?;
+
+        // Sell what the escrow received, transfer fees aside
+        let amount = escrow_in.amount() - escrow_in_before;
+        let start_slot = Clock::get()?.slot;
+        let end_slot = start_slot.saturating_add(self.instruction_data.duration);
+
+        LongTermOrder::init(self.accounts.long_term_order)?.set_inner(
+            *self.accounts.owner.key(),
+            *self.accounts.config.key(),
+            self.instruction_data.nonce,
+            is_x,
+            amount,
+            self.instruction_data.min_amount_out,
+            start_slot,
+            end_slot,
+            long_term_order_bump,
+        );
+
+        set_return_amounts(&[amount, end_slot]);
+
+        Ok(())
+    }
+}
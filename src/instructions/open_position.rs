@@ -0,0 +1,235 @@
+use crate::state::{Config, GlobalConfig, NftPosition};
+use crate::{check_signer, check_writable, derive_lp_mint, AmmError, AmmState};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_associated_token_account::instructions::Create;
+use pinocchio_system::instructions::CreateAccount;
+use pinocchio_token::{
+    instructions::{AuthorityType, InitializeMint2, MintTo, SetAuthority},
+    state::Mint,
+};
+
+/// #OpenPosition
+///
+/// Open an NFT liquidity position on a pool created with `Config::FLAG_NFT_POSITIONS`
+///
+/// Accounts:
+///
+/// 1. owner:                       [signer, mut]
+/// 2. config
+/// 3. mint_lp
+/// 4. position_mint                [signer, init]
+/// 5. position                     [init]
+/// 6. position_lp_vault            [init]      // LP ATA of the position PDA
+/// 7. owner_position_ata           [init]      // Position NFT ATA of the owner
+/// 8. system_program               [executable]
+/// 9. token_program                [executable]
+/// 10. associated_token_program    [executable]
+/// 11. global_config
+///
+/// Parameters:
+///
+/// 1. position_bump: [u8]
+pub struct OpenPositionAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub position_mint: &'a AccountInfo,
+    pub position: &'a AccountInfo,
+    pub position_lp_vault: &'a AccountInfo,
+    pub owner_position_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for OpenPositionAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, config, mint_lp, position_mint, position, position_lp_vault, owner_position_ata, system_program, token_program, _associated_token_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(owner)?;
+        check_signer(position_mint)?;
+        [
+            owner,
+            position_mint,
+            position,
+            position_lp_vault,
+            owner_position_ata,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            owner,
+            config,
+            mint_lp,
+            position_mint,
+            position,
+            position_lp_vault,
+            owner_position_ata,
+            system_program,
+            token_program,
+            global_config,
+        })
+    }
+}
+
+pub struct OpenPositionInstructionData {
+    pub position_bump: [u8; 1],
+}
+
+impl<'a> TryFrom<&'a [u8]> for OpenPositionInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<[u8; 1]>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            position_bump: [data[0]],
+        })
+    }
+}
+
+pub struct OpenPosition<'a> {
+    pub accounts: OpenPositionAccounts<'a>,
+    pub instruction_data: OpenPositionInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for OpenPosition<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = OpenPositionAccounts::try_from(accounts)?;
+        let instruction_data = OpenPositionInstructionData::try_from(data)?;
+
+        // Return the initialized struct
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> OpenPosition<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &6;
+
+    /// `sha256("global:open_position")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [135, 128, 47, 77, 15, 152, 240, 49];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // Deserialize the config account
+        let config = Config::load(self.accounts.config)?;
+
+        // Check if we can open positions on the Amm
+        if config.amm_state()?.ne(&AmmState::Initialized) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !config.has_nft_positions() {
+            return Err(AmmError::NftPositionsDisabled.into());
+        }
+
+        // Check if the mint_lp is valid
+        let (mint_lp, _) = derive_lp_mint(self.accounts.config.key());
+
+        if mint_lp.ne(self.accounts.mint_lp.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let position_seeds = [
+            Seed::from(b"nft_position"),
+            Seed::from(self.accounts.position_mint.key()),
+            Seed::from(&self.instruction_data.position_bump),
+        ];
+        let signer_seeds = [Signer::from(&position_seeds)];
+
+        // Create the position account
+        CreateAccount {
+            from: self.accounts.owner,
+            to: self.accounts.position,
+            lamports: Rent::get()?.minimum_balance(NftPosition::LEN),
+            space: NftPosition::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&signer_seeds)?;
+
+        // Create the position NFT mint, with the position PDA as temporary mint authority
+        CreateAccount {
+            from: self.accounts.owner,
+            to: self.accounts.position_mint,
+            lamports: Rent::get()?.minimum_balance(Mint::LEN),
+            space: Mint::LEN as u64,
+            owner: &pinocchio_token::ID,
+        }
+        .invoke()?;
+
+        InitializeMint2 {
+            mint: self.accounts.position_mint,
+            decimals: 0,
+            mint_authority: self.accounts.position.key(),
+            freeze_authority: None,
+        }
+        .invoke()?;
+
+        // Create the LP escrow owned by the position
+        Create {
+            funding_account: self.accounts.owner,
+            account: self.accounts.position_lp_vault,
+            wallet: self.accounts.position,
+            mint: self.accounts.mint_lp,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.token_program,
+        }
+        .invoke()?;
+
+        // Mint the NFT to the owner and fix the supply at 1
+        Create {
+            funding_account: self.accounts.owner,
+            account: self.accounts.owner_position_ata,
+            wallet: self.accounts.owner,
+            mint: self.accounts.position_mint,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.token_program,
+        }
+        .invoke()?;
+
+        MintTo {
+            mint: self.accounts.position_mint,
+            account: self.accounts.owner_position_ata,
+            mint_authority: self.accounts.position,
+            amount: 1,
+        }
+        .invoke_signed(&signer_seeds)?;
+
+        SetAuthority {
+            account: self.accounts.position_mint,
+            authority: self.accounts.position,
+            authority_type: AuthorityType::MintTokens,
+            new_authority: None,
+        }
+        .invoke_signed(&signer_seeds)?;
+
+        NftPosition::init(self.accounts.position)?.set_inner(
+            *self.accounts.config.key(),
+            *self.accounts.position_mint.key(),
+            *self.accounts.position_lp_vault.key(),
+            self.instruction_data.position_bump,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use crate::state::{get_extension, Config, MevTax};
+use crate::{
+    check_signer, check_writable, validate_pool_accounts, AmmError, AmmState, MAX_FEE_BPS, PoolAccounts, Swap,
+    SwapAccounts, SwapInstructionData,
+};
+use core::mem::size_of;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #RaydiumSwap
+///
+/// Swap with the account layout and data encoding of Raydium CPMM's `swap_base_input` and
+/// `swap_base_output`, so routers built for it can point at this program with a new program id
+///
+/// Both run `Swap` underneath, with no expiration. The accounts Raydium keeps apart map onto the
+/// pool's: its authority and pool state are both the config, and its amm config is the global
+/// config. Exact output swaps are priced from the reserves before the trade, rounding the input up
+/// in favor of the pool, so an input mint with a transfer fee makes them fail the slippage check.
+///
+/// Accounts:
+///
+/// 1. payer:                       [signer]        // Owner of the input account, or a delegate
+/// 2. authority                                    // The config, owner of the vaults
+/// 3. amm_config                                   // The global config
+/// 4. pool_state                   [mut]           // The config
+/// 5. input_token_account          [mut]
+/// 6. output_token_account         [mut]
+/// 7. input_vault                  [mut]
+/// 8. output_vault                 [mut]
+/// 9. input_token_program          [executable]
+/// 10. output_token_program        [executable]
+/// 11. input_token_mint
+/// 12. output_token_mint
+/// 13. observation_state                           // Unused, any account
+/// 14. host_fee                    [mut, optional] // Then the other optional accounts of `Swap`
+///
+/// Parameters:
+///
+/// 1. discriminator:               [u8; 8] // `SWAP_BASE_INPUT` or `SWAP_BASE_OUTPUT`
+/// 2. amount_in:                   [u64]   // The maximum input with `SWAP_BASE_OUTPUT`
+/// 3. amount_out:                  [u64]   // The minimum output with `SWAP_BASE_INPUT`
+///
+/// Return data:
+///
+/// 1. amount_in:                   [u64]   // Sent by the user
+/// 2. amount_out:                  [u64]   // Received by the user, net of transfer fees
+pub struct RaydiumSwapAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub amm_config: &'a AccountInfo,
+    pub pool_state: &'a AccountInfo,
+    pub input_token_account: &'a AccountInfo,
+    pub output_token_account: &'a AccountInfo,
+    pub input_vault: &'a AccountInfo,
+    pub output_vault: &'a AccountInfo,
+    pub input_token_program: &'a AccountInfo,
+    pub output_token_program: &'a AccountInfo,
+    pub input_token_mint: &'a AccountInfo,
+    pub output_token_mint: &'a AccountInfo,
+    pub remaining: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RaydiumSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, authority, amm_config, pool_state, input_token_account, output_token_account, input_vault, output_vault, input_token_program, output_token_program, input_token_mint, output_token_mint, _observation_state, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(payer)?;
+        [pool_state, input_token_account, output_token_account, input_vault, output_vault]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        // The vaults are owned by the config itself
+        if authority.key().ne(pool_state.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            payer,
+            amm_config,
+            pool_state,
+            input_token_account,
+            output_token_account,
+            input_vault,
+            output_vault,
+            input_token_program,
+            output_token_program,
+            input_token_mint,
+            output_token_mint,
+            remaining,
+        })
+    }
+}
+
+pub struct RaydiumSwapInstructionData {
+    pub base_output: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+impl TryFrom<&[u8]> for RaydiumSwapInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const RAYDIUM_SWAP_DATA_LEN: usize = size_of::<[u8; 8]>() + size_of::<u64>() * 2;
+
+        if data.len() != RAYDIUM_SWAP_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let base_output = match data[..8].try_into().unwrap() {
+            RaydiumSwap::SWAP_BASE_INPUT => false,
+            RaydiumSwap::SWAP_BASE_OUTPUT => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let amount_in = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let amount_out = u64::from_le_bytes(data[16..24].try_into().unwrap());
+
+        if amount_in == 0 || amount_out == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            base_output,
+            amount_in,
+            amount_out,
+        })
+    }
+}
+
+pub struct RaydiumSwap<'a> {
+    pub accounts: RaydiumSwapAccounts<'a>,
+    pub instruction_data: RaydiumSwapInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for RaydiumSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = RaydiumSwapAccounts::try_from(accounts)?;
+        let instruction_data = RaydiumSwapInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> RaydiumSwap<'a> {
+    /// `sha256("global:swap_base_input")[..8]`
+    pub const SWAP_BASE_INPUT: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+
+    /// `sha256("global:swap_base_output")[..8]`
+    pub const SWAP_BASE_OUTPUT: [u8; 8] = [55, 217, 98, 86, 163, 74, 180, 173];
+
+    /// First byte of `SWAP_BASE_INPUT`, kept clear of the legacy single-byte discriminators
+    pub const BASE_INPUT_DISCRIMINATOR: &'a u8 = &143;
+
+    /// First byte of `SWAP_BASE_OUTPUT`, kept clear of the legacy single-byte discriminators
+    pub const BASE_OUTPUT_DISCRIMINATOR: &'a u8 = &55;
+
+    /// Index of pool_state, the config, in the accounts
+    pub const CONFIG_INDEX: usize = 3;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Find the direction from the input mint
+        let is_x = {
+            let config = Config::load(self.accounts.pool_state)?;
+
+            match (self.accounts.input_token_mint.key(), self.accounts.output_token_mint.key()) {
+                (input, output) if input.eq(config.mint_x()) && output.eq(config.mint_y()) => true,
+                (input, output) if input.eq(config.mint_y()) && output.eq(config.mint_x()) => false,
+                _ => return Err(ProgramError::InvalidAccountData),
+            }
+        };
+
+        let (amount, min) = match self.instruction_data.base_output {
+            true => {
+                let amount = self.quote_exact_output(is_x)?;
+
+                if amount > self.instruction_data.amount_in {
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                (amount, self.instruction_data.amount_out)
+            }
+            false => (self.instruction_data.amount_in, self.instruction_data.amount_out),
+        };
+
+        // Hand over to `Swap` with its own account order
+        let (user_x, user_y, vault_x, vault_y, mint_x, mint_y, token_program_x, token_program_y) = match is_x {
+            true => (
+                self.accounts.input_token_account,
+                self.accounts.output_token_account,
+                self.accounts.input_vault,
+                self.accounts.output_vault,
+                self.accounts.input_token_mint,
+                self.accounts.output_token_mint,
+                self.accounts.input_token_program,
+                self.accounts.output_token_program,
+            ),
+            false => (
+                self.accounts.output_token_account,
+                self.accounts.input_token_account,
+                self.accounts.output_vault,
+                self.accounts.input_vault,
+                self.accounts.output_token_mint,
+                self.accounts.input_token_mint,
+                self.accounts.output_token_program,
+                self.accounts.input_token_program,
+            ),
+        };
+
+        let mut optional = self
+            .accounts
+            .remaining
+            .iter()
+            .map(|account| account.key().ne(&crate::ID).then_some(account));
+        let host_fee = optional.next().flatten();
+
+        if let Some(host_fee) = host_fee {
+            check_writable(host_fee)?;
+        }
+
+        Swap {
+            accounts: SwapAccounts {
+                user: self.accounts.payer,
+                user_x,
+                user_y,
+                vault_x,
+                vault_y,
+                config: self.accounts.pool_state,
+                mint_x,
+                mint_y,
+                token_program_x,
+                token_program_y,
+                global_config: self.accounts.amm_config,
+                host_fee,
+                oracle: optional.next().flatten(),
+                instructions_sysvar: optional.next().flatten(),
+                mint_lp: optional.next().flatten(),
+                memo_program: optional.next().flatten(),
+                trading_calendar: None,
+                insurance_vault: None,
+                tip_account: None,
+                system_program: None,
+                user_stats: None,
+            },
+            instruction_data: SwapInstructionData {
+                is_x,
+                amount,
+                min,
+                expiration: i64::MAX,
+                flags: 0,
+                tip: 0,
+                memo: &[],
+            },
+        }
+        .process()
+    }
+
+    /// Input needed for `amount_out` at the current reserves, rounding up in favor of the pool
+    fn quote_exact_output(&self, is_x: bool) -> Result<u64, ProgramError> {
+        let (vaults, mints, token_programs) = match is_x {
+            true => (
+                (self.accounts.input_vault, self.accounts.output_vault),
+                (self.accounts.input_token_mint, self.accounts.output_token_mint),
+                (self.accounts.input_token_program, self.accounts.output_token_program),
+            ),
+            false => (
+                (self.accounts.output_vault, self.accounts.input_vault),
+                (self.accounts.output_token_mint, self.accounts.input_token_mint),
+                (self.accounts.output_token_program, self.accounts.input_token_program),
+            ),
+        };
+
+        let PoolAccounts { config, vault_x, vault_y, deployed } = validate_pool_accounts(
+            self.accounts.pool_state,
+            vaults,
+            mints,
+            token_programs,
+            &[AmmState::Initialized, AmmState::WithdrawOnly],
+        )?;
+
+        let (reserve_x, reserve_y) = config.reserves(vault_x.amount(), vault_y.amount(), deployed);
+        let (reserve_in, reserve_out) = match is_x {
+            true => (reserve_x as u128, reserve_y as u128),
+            false => (reserve_y as u128, reserve_x as u128),
+        };
+
+        let amount_out = self.instruction_data.amount_out as u128;
+        if amount_out >= reserve_out {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Invert the curve, then gross the input up for the fee, `MevTax` surcharge included
+        let swap_fee = match get_extension::<MevTax>(&self.accounts.pool_state.try_borrow_data()?)? {
+            Some(mev_tax) => mev_tax.swap_fee(config.swap_fee()?, Clock::get()?.slot)?,
+            None => config.swap_fee()?,
+        };
+        let fee_remainder = (MAX_FEE_BPS - swap_fee.bps()) as u128;
+        if fee_remainder == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let amount_in_after_fee = (reserve_in * amount_out).div_ceil(reserve_out - amount_out);
+        let amount_in = amount_in_after_fee
+            .checked_mul(MAX_FEE_BPS as u128)
+            .ok_or(AmmError::MathOverflow)?
+            .div_ceil(fee_remainder);
+
+        u64::try_from(amount_in).map_err(|_| AmmError::MathOverflow.into())
+    }
+}
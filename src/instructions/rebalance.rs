@@ -0,0 +1,263 @@
+use crate::state::{get_extension, get_extension_mut, Config, GlobalConfig, VaultStrategy};
+use crate::{
+    bump_sequence, check_signer, check_writable, set_return_amounts, validate_pool_accounts, AmmError, AmmState,
+    CheckedUserAta, PoolAccounts, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #Rebalance
+///
+/// Move reserves between a vault and its `VaultStrategy` account, without fees or price impact
+///
+/// Deployed amounts keep counting as reserves, up to `VaultStrategy::max_deployed_bps`.
+/// Returning more than was deployed credits the yield to the LPs, and emptying the strategy
+/// account settles it, any shortfall being a loss to the LPs.
+///
+/// Accounts:
+///
+/// 1. authority:                   [signer]        // Owner or delegate of the strategy account
+/// 2. config                       [mut]
+/// 3. vault_x                      [mut]
+/// 4. vault_y                      [mut]
+/// 5. strategy                     [mut]           // `VaultStrategy::strategy_x` or `strategy_y`
+/// 6. mint_x
+/// 7. mint_y
+/// 8. token_program_x              [executable]
+/// 9. token_program_y              [executable]
+/// 10. global_config
+///
+/// Parameters:
+///
+/// 1. is_x: [bool]
+/// 2. deploy: [bool]               // Vault to strategy if set, strategy to vault otherwise
+/// 3. amount: [u64]
+///
+/// Return data:
+///
+/// 1. deployed: u64,               // Amount of the side now deployed
+pub struct RebalanceAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub strategy: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RebalanceAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, vault_x, vault_y, strategy, mint_x, mint_y, token_program_x, token_program_y, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        [config, vault_x, vault_y, strategy].into_iter().try_for_each(check_writable)?;
+
+        // Deserialize the config account
+        let config_data = Config::load(config)?;
+
+        // Check if the authority is the correct authority
+        if config_data.has_authority().ne(&Some(*authority.key())) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            authority,
+            config,
+            vault_x,
+            vault_y,
+            strategy,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            global_config,
+        })
+    }
+}
+
+pub struct RebalanceInstructionData {
+    pub is_x: bool,
+    pub deploy: bool,
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for RebalanceInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const REBALANCE_DATA_LEN: usize = size_of::<bool>() * 2 + size_of::<u64>();
+
+        if data.len() != REBALANCE_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let is_x = data[0] > 0;
+        let deploy = data[1] > 0;
+        let amount = u64::from_le_bytes(data[2..10].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { is_x, deploy, amount })
+    }
+}
+
+pub struct Rebalance<'a> {
+    pub accounts: RebalanceAccounts<'a>,
+    pub instruction_data: RebalanceInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Rebalance<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = RebalanceAccounts::try_from(accounts)?;
+        let instruction_data = RebalanceInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Rebalance<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &22;
+
+    /// `sha256("global:rebalance")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [108, 158, 77, 9, 210, 52, 88, 62];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let is_x = self.instruction_data.is_x;
+
+        // Check the config, the pool mints and the vaults, deployed reserves can always be recalled
+        let PoolAccounts { config, vault_x, vault_y, .. } = validate_pool_accounts(
+            self.accounts.config,
+            (self.accounts.vault_x, self.accounts.vault_y),
+            (self.accounts.mint_x, self.accounts.mint_y),
+            (self.accounts.token_program_x, self.accounts.token_program_y),
+            &[AmmState::Initialized, AmmState::Disabled, AmmState::WithdrawOnly],
+        )?;
+
+        let (vault, vault_account, mint, decimals, token_program, protocol_fees) = match is_x {
+            true => (
+                self.accounts.vault_x,
+                vault_x,
+                self.accounts.mint_x,
+                config.decimals_x(),
+                *config.token_program_x(),
+                config.protocol_fees_x(),
+            ),
+            false => (
+                self.accounts.vault_y,
+                vault_y,
+                self.accounts.mint_y,
+                config.decimals_y(),
+                *config.token_program_y(),
+                config.protocol_fees_y(),
+            ),
+        };
+
+        // Check the strategy account designated for this side
+        {
+            let config_data = self.accounts.config.try_borrow_data()?;
+            let vault_strategy = get_extension::<VaultStrategy>(&config_data)?.ok_or(AmmError::MissingExtension)?;
+            let strategy = match is_x {
+                true => vault_strategy.strategy_x(),
+                false => vault_strategy.strategy_y(),
+            };
+
+            if strategy.ne(self.accounts.strategy.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        let strategy_account = CheckedUserAta::new(self.accounts.strategy, mint.key())?;
+        let vault_before = vault_account.amount();
+
+        if self.instruction_data.deploy {
+            // Uncollected protocol fees don't back the curve and stay in the vault
+            if self.instruction_data.amount > vault_before.saturating_sub(protocol_fees) {
+                return Err(ProgramError::InsufficientFunds);
+            }
+
+            // Create the signer seeds
+            let config_bump = config.config_bump();
+            let seeds = [
+                Seed::from(b"config"),
+                Seed::from(config.seed_bytes()),
+                Seed::from(config.mint_x()),
+                Seed::from(config.mint_y()),
+                Seed::from(&config_bump),
+            ];
+
+            TransferChecked {
+                from: vault,
+                mint,
+                to: self.accounts.strategy,
+                authority: self.accounts.config,
+                amount: self.instruction_data.amount,
+                decimals,
+                token_program: &token_program,
+            }
+            .invoke_signed(&[Signer::from(&seeds)])?;
+        } else {
+            TransferChecked {
+                from: self.accounts.strategy,
+                mint,
+                to: vault,
+                authority: self.accounts.authority,
+                amount: self.instruction_data.amount,
+                decimals,
+                token_program: &token_program,
+            }
+            .invoke()?;
+        }
+
+        drop(config);
+
+        // Track what the vault actually lost or received, transfer fees included
+        let deployed = {
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+            let vault_strategy =
+                get_extension_mut::<VaultStrategy>(&mut config_data)?.ok_or(AmmError::MissingExtension)?;
+
+            if self.instruction_data.deploy {
+                vault_strategy.deploy(is_x, vault_before - vault_account.amount())?;
+                vault_strategy.check_cap(is_x, vault_account.amount().saturating_sub(protocol_fees))?;
+            } else if strategy_account.amount() == 0 {
+                vault_strategy.settle(is_x);
+            } else {
+                vault_strategy.recall(is_x, vault_account.amount() - vault_before);
+            }
+
+            match is_x {
+                true => vault_strategy.deployed_x(),
+                false => vault_strategy.deployed_y(),
+            }
+        };
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        set_return_amounts(&[deployed]);
+
+        Ok(())
+    }
+}
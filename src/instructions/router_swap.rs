@@ -0,0 +1,67 @@
+use crate::state::TrustedRouter;
+use crate::{check_signer, AmmError, Swap, SwapAccounts, SwapInstructionData};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #RouterSwap
+///
+/// `Swap` for routers in the `TrustedRouter` registry, which skips deriving the vault addresses
+/// to save compute on high-frequency aggregator flow
+///
+/// The vaults must still be token accounts of the pool mints owned by the config, but which ones
+/// is left to the router. Everyone else goes through `Swap`.
+///
+/// Accounts:
+///
+/// 1. router:                      [signer]
+/// 2. trusted_router               // [b"trusted_router", router]
+/// 3. ..                           // The `Swap` accounts
+///
+/// Parameters: the `Swap` parameters
+///
+/// Return data: the `Swap` return data
+pub struct RouterSwap<'a> {
+    pub swap: Swap<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for RouterSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let [router, trusted_router, swap_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the router registry entry
+        check_signer(router)?;
+
+        let trusted_router = TrustedRouter::load(trusted_router)?;
+        if trusted_router.router().ne(router.key()) || !trusted_router.is_trusted() {
+            return Err(AmmError::UntrustedRouter.into());
+        }
+
+        let accounts = SwapAccounts::try_from(swap_accounts)?;
+        let instruction_data = SwapInstructionData::try_from(data)?;
+
+        // Return the initialized struct
+        Ok(Self {
+            swap: Swap {
+                accounts,
+                instruction_data,
+            },
+        })
+    }
+}
+
+impl<'a> RouterSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &40;
+
+    /// `sha256("global:router_swap")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [98, 80, 88, 210, 149, 106, 65, 167];
+
+    /// Index of the config among the accounts, see `Swap`
+    pub const CONFIG_INDEX: usize = 7;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.swap.process_trusted()
+    }
+}
@@ -0,0 +1,113 @@
+use crate::state::{GlobalConfig, Launch};
+use crate::{
+    set_return_amounts, AmmError, CheckedVault, LaunchTradeAccounts, LaunchTradeInstructionData,
+    TransferChecked,
+};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #SellLaunch
+///
+/// Sell launched tokens back to the bonding curve for the quote token
+///
+/// Accounts: same as `BuyLaunch`
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Tokens to sell
+/// 2. min_out: u64,                // Minimum quote to receive
+///
+/// Return data:
+///
+/// 1. tokens_in: u64,              // Tokens received by the curve
+/// 2. quote_out: u64,              // Quote sent to the user
+pub struct SellLaunch<'a> {
+    pub accounts: LaunchTradeAccounts<'a>,
+    pub instruction_data: LaunchTradeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SellLaunch<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = LaunchTradeAccounts::try_from(accounts)?;
+        let instruction_data = LaunchTradeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SellLaunch<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &16;
+
+    /// `sha256("global:sell_launch")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [168, 98, 60, 65, 121, 178, 74, 76];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let (tokens_in, quote_out) = {
+            let launch = Launch::load(self.accounts.launch)?;
+
+            if !launch.is_trading() {
+                return Err(AmmError::LaunchNotTrading.into());
+            }
+
+            let launch_token_vault = CheckedVault::new(
+                self.accounts.launch_token_vault,
+                launch.mint(),
+                self.accounts.launch.key(),
+                launch.token_program(),
+            )?;
+            let tokens_before = launch_token_vault.amount();
+
+            TransferChecked {
+                from: self.accounts.user_token_ata,
+                mint: self.accounts.mint,
+                to: self.accounts.launch_token_vault,
+                authority: self.accounts.user,
+                amount: self.instruction_data.amount,
+                decimals: launch.token_decimals(),
+                token_program: launch.token_program(),
+            }
+            .invoke()?;
+
+            // Price the sell on what the vault received, in case of transfer fees
+            let tokens_in = launch_token_vault.amount() - tokens_before;
+            let quote_out = launch.quote_sell(tokens_in)?;
+
+            if quote_out == 0 || quote_out < self.instruction_data.min_out {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let launch_bump = launch.bump();
+            let launch_seeds = [
+                Seed::from(b"launch"),
+                Seed::from(launch.mint()),
+                Seed::from(&launch_bump),
+            ];
+
+            TransferChecked {
+                from: self.accounts.launch_quote_vault,
+                mint: self.accounts.quote_mint,
+                to: self.accounts.user_quote_ata,
+                authority: self.accounts.launch,
+                amount: quote_out,
+                decimals: launch.quote_decimals(),
+                token_program: launch.quote_program(),
+            }
+            .invoke_signed(&[Signer::from(&launch_seeds)])?;
+
+            (tokens_in, quote_out)
+        };
+
+        Launch::load_mut(self.accounts.launch)?.record_sell(tokens_in, quote_out)?;
+
+        set_return_amounts(&[tokens_in, quote_out]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use crate::state::{Config, GlobalConfig, PoolMetadata};
+use crate::{check_signer, check_writable, derive_pool_metadata};
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #SetPoolMetadata
+///
+/// Create or overwrite the `PoolMetadata` of a pool
+///
+/// Accounts:
+///
+/// 1. authority:                   [signer, mut]   // Pool authority, pays for the metadata
+/// 2. config
+/// 3. pool_metadata                [init_if_needed]    // [b"pool_metadata", config]
+/// 4. global_config
+/// 5. system_program               [executable]
+///
+/// Parameters:
+///
+/// 1. name: [u8; 32],              // UTF-8, zero-padded like the fields below
+/// 2. project_url: [u8; 128],
+/// 3. logo_uri: [u8; 128],
+/// 4. tags: [[u8; 16]; 4],         // Unused tags are all zeros
+pub struct SetPoolMetadataAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub pool_metadata: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetPoolMetadataAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, pool_metadata, global_config, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        [authority, pool_metadata].into_iter().try_for_each(check_writable)?;
+
+        // Check if the authority is the correct authority
+        if Config::load(config)?.has_authority().ne(&Some(*authority.key())) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            authority,
+            config,
+            pool_metadata,
+            global_config,
+        })
+    }
+}
+
+pub struct SetPoolMetadataInstructionData<'a> {
+    pub fields: &'a [u8; PoolMetadata::FIELDS_LEN],
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetPoolMetadataInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let fields: &[u8; PoolMetadata::FIELDS_LEN] =
+            data.try_into().map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        // Every field must be text followed only by padding
+        let (name, rest) = fields.split_at(PoolMetadata::NAME_LEN);
+        let (uris, tags) = rest.split_at(PoolMetadata::URI_LEN * 2);
+
+        [name]
+            .into_iter()
+            .chain(uris.chunks_exact(PoolMetadata::URI_LEN))
+            .chain(tags.chunks_exact(PoolMetadata::TAG_LEN))
+            .try_for_each(check_padded_text)?;
+
+        Ok(Self { fields })
+    }
+}
+
+/// Check that `field` is UTF-8 text without zeros, followed by zero padding.
+#[inline(always)]
+fn check_padded_text(field: &[u8]) -> Result<(), ProgramError> {
+    let len = field.iter().position(|byte| *byte == 0).unwrap_or(field.len());
+    let (text, padding) = field.split_at(len);
+
+    if padding.iter().any(|byte| *byte != 0) || core::str::from_utf8(text).is_err() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(())
+}
+
+pub struct SetPoolMetadata<'a> {
+    pub accounts: SetPoolMetadataAccounts<'a>,
+    pub instruction_data: SetPoolMetadataInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetPoolMetadata<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetPoolMetadataAccounts::try_from(accounts)?;
+        let instruction_data = SetPoolMetadataInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetPoolMetadata<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &43;
+
+    /// `sha256("global:set_pool_metadata")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [52, 196, 44, 60, 201, 162, 55, 8];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // Check if the metadata account is valid
+        let (pool_metadata_key, pool_metadata_bump) = derive_pool_metadata(self.accounts.config.key());
+
+        if pool_metadata_key.ne(self.accounts.pool_metadata.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the metadata the first time it is set
+        let is_new = self.accounts.pool_metadata.data_is_empty();
+        if is_new {
+            let pool_metadata_bump = [pool_metadata_bump];
+            let pool_metadata_seeds = [
+                Seed::from(b"pool_metadata"),
+                Seed::from(self.accounts.config.key()),
+                Seed::from(&pool_metadata_bump),
+            ];
+
+            CreateAccount {
+                from: self.accounts.authority,
+                to: self.accounts.pool_metadata,
+                lamports: Rent::get()?.minimum_balance(PoolMetadata::LEN),
+                space: PoolMetadata::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&pool_metadata_seeds)])?;
+        }
+
+        let mut pool_metadata = match is_new {
+            true => PoolMetadata::init(self.accounts.pool_metadata)?,
+            false => PoolMetadata::load_mut(self.accounts.pool_metadata)?,
+        };
+
+        pool_metadata.set_inner(
+            *self.accounts.config.key(),
+            self.instruction_data.fields,
+            [pool_metadata_bump],
+        );
+
+        Ok(())
+    }
+}
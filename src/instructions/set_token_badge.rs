@@ -0,0 +1,144 @@
+use crate::state::{GlobalConfig, TokenBadge};
+use crate::{check_signer, check_writable, derive_token_badge};
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #SetTokenBadge
+///
+/// Issue a `TokenBadge` to a mint, or revoke it, so new pools can hold it while the `GlobalConfig`
+/// requires badges
+///
+/// Accounts:
+///
+/// 1. admin:                       [signer, mut]   // `GlobalConfig` admin, pays for the badge
+/// 2. global_config
+/// 3. mint
+/// 4. token_badge                  [init_if_needed]    // [b"token_badge", mint]
+/// 5. system_program               [executable]
+///
+/// Parameters:
+///
+/// 1. is_badged: bool,             // False to revoke
+pub struct SetTokenBadgeAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub token_badge: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetTokenBadgeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, global_config, mint, token_badge, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(admin)?;
+        [admin, token_badge].into_iter().try_for_each(check_writable)?;
+
+        // Check if the admin is the correct admin
+        let global_config = GlobalConfig::load(global_config)?;
+
+        if global_config.admin().ne(admin.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        global_config.check_not_frozen()?;
+
+        // Return the accounts
+        Ok(Self {
+            admin,
+            mint,
+            token_badge,
+        })
+    }
+}
+
+pub struct SetTokenBadgeInstructionData {
+    pub is_badged: bool,
+}
+
+impl TryFrom<&[u8]> for SetTokenBadgeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let is_badged = match data {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { is_badged })
+    }
+}
+
+pub struct SetTokenBadge<'a> {
+    pub accounts: SetTokenBadgeAccounts<'a>,
+    pub instruction_data: SetTokenBadgeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetTokenBadge<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetTokenBadgeAccounts::try_from(accounts)?;
+        let instruction_data = SetTokenBadgeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetTokenBadge<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &59;
+
+    /// `sha256("global:set_token_badge")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [219, 215, 237, 121, 146, 125, 229, 221];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Check if the badge is the mint's
+        let (token_badge_key, token_badge_bump) = derive_token_badge(self.accounts.mint.key());
+
+        if token_badge_key.ne(self.accounts.token_badge.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the badge the first time the mint is badged
+        let is_new = self.accounts.token_badge.data_is_empty();
+        if is_new {
+            let token_badge_bump = [token_badge_bump];
+            let token_badge_seeds = [
+                Seed::from(b"token_badge"),
+                Seed::from(self.accounts.mint.key()),
+                Seed::from(&token_badge_bump),
+            ];
+
+            CreateAccount {
+                from: self.accounts.admin,
+                to: self.accounts.token_badge,
+                lamports: Rent::get()?.minimum_balance(TokenBadge::LEN),
+                space: TokenBadge::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&token_badge_seeds)])?;
+        }
+
+        let mut token_badge = match is_new {
+            true => TokenBadge::init(self.accounts.token_badge)?,
+            false => TokenBadge::load_mut(self.accounts.token_badge)?,
+        };
+
+        token_badge.set_inner(
+            *self.accounts.mint.key(),
+            self.instruction_data.is_badged,
+            [token_badge_bump],
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use crate::state::{GlobalConfig, TradingCalendar};
+use crate::{check_signer, check_writable, derive_trading_calendar};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #SetTradingCalendar
+///
+/// Create or overwrite a `TradingCalendar`, which pools follow through their `TradingHours` extension
+///
+/// Accounts:
+///
+/// 1. authority:                   [signer, mut]   // Owns the calendar, pays for it
+/// 2. trading_calendar             [init_if_needed]    // [b"trading_calendar", authority, seed]
+/// 3. global_config
+/// 4. system_program               [executable]
+///
+/// Parameters:
+///
+/// 1. seed: u64,                   // Numbers the calendars of an authority
+/// 2. utc_offset: i32,             // Seconds, within a day
+/// 3. sessions: [(u32, u32); 7],   // (open, close) from Monday, at most a day, open == close when closed
+/// 4. holidays: [u32; 32],         // Local days since the Unix epoch, zero when unused
+pub struct SetTradingCalendarAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub trading_calendar: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetTradingCalendarAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, trading_calendar, global_config, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        [authority, trading_calendar].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            authority,
+            trading_calendar,
+            global_config,
+        })
+    }
+}
+
+pub struct SetTradingCalendarInstructionData<'a> {
+    pub seed: [u8; 8],
+    pub schedule: &'a [u8; TradingCalendar::SCHEDULE_LEN],
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetTradingCalendarInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() + TradingCalendar::SCHEDULE_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let (seed, schedule) = data.split_at(size_of::<u64>());
+        let schedule: &[u8; TradingCalendar::SCHEDULE_LEN] = schedule.try_into().unwrap();
+
+        let utc_offset = i32::from_le_bytes(schedule[0..4].try_into().unwrap()) as i64;
+        if utc_offset.abs() >= TradingCalendar::SECONDS_PER_DAY {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Sessions can't close before they open, nor after midnight
+        let sessions = &schedule[4..4 + size_of::<u32>() * 2 * 7];
+        if sessions.chunks_exact(8).any(|session| {
+            let open = u32::from_le_bytes(session[0..4].try_into().unwrap());
+            let close = u32::from_le_bytes(session[4..8].try_into().unwrap());
+
+            open > close || close as i64 > TradingCalendar::SECONDS_PER_DAY
+        }) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            seed: seed.try_into().unwrap(),
+            schedule,
+        })
+    }
+}
+
+pub struct SetTradingCalendar<'a> {
+    pub accounts: SetTradingCalendarAccounts<'a>,
+    pub instruction_data: SetTradingCalendarInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetTradingCalendar<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetTradingCalendarAccounts::try_from(accounts)?;
+        let instruction_data = SetTradingCalendarInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetTradingCalendar<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &47;
+
+    /// `sha256("global:set_trading_calendar")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [50, 119, 77, 228, 5, 77, 49, 209];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // Check if the calendar account is valid, only its authority derives it
+        let (trading_calendar_key, trading_calendar_bump) =
+            derive_trading_calendar(self.accounts.authority.key(), &self.instruction_data.seed);
+
+        if trading_calendar_key.ne(self.accounts.trading_calendar.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the calendar the first time it is set
+        let is_new = self.accounts.trading_calendar.data_is_empty();
+        if is_new {
+            let trading_calendar_bump = [trading_calendar_bump];
+            let trading_calendar_seeds = [
+                Seed::from(b"trading_calendar"),
+                Seed::from(self.accounts.authority.key()),
+                Seed::from(&self.instruction_data.seed),
+                Seed::from(&trading_calendar_bump),
+            ];
+
+            CreateAccount {
+                from: self.accounts.authority,
+                to: self.accounts.trading_calendar,
+                lamports: Rent::get()?.minimum_balance(TradingCalendar::LEN),
+                space: TradingCalendar::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&trading_calendar_seeds)])?;
+        }
+
+        let mut trading_calendar = match is_new {
+            true => TradingCalendar::init(self.accounts.trading_calendar)?,
+            false => TradingCalendar::load_mut(self.accounts.trading_calendar)?,
+        };
+
+        trading_calendar.set_inner(
+            *self.accounts.authority.key(),
+            self.instruction_data.seed,
+            self.instruction_data.schedule,
+            [trading_calendar_bump],
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::state::{GlobalConfig, TrustedRouter};
+use crate::{check_signer, check_writable, derive_trusted_router};
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #SetTrustedRouter
+///
+/// Add a router to the `TrustedRouter` registry, or revoke it, so it can swap through `RouterSwap`
+///
+/// Accounts:
+///
+/// 1. admin:                       [signer, mut]   // `GlobalConfig` admin, pays for the entry
+/// 2. global_config
+/// 3. router
+/// 4. trusted_router               [init_if_needed]    // [b"trusted_router", router]
+/// 5. system_program               [executable]
+///
+/// Parameters:
+///
+/// 1. is_trusted: bool,            // False to revoke
+pub struct SetTrustedRouterAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub router: &'a AccountInfo,
+    pub trusted_router: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetTrustedRouterAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, global_config, router, trusted_router, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(admin)?;
+        [admin, trusted_router].into_iter().try_for_each(check_writable)?;
+
+        // Check if the admin is the correct admin
+        let global_config = GlobalConfig::load(global_config)?;
+
+        if global_config.admin().ne(admin.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        global_config.check_not_frozen()?;
+
+        // Return the accounts
+        Ok(Self {
+            admin,
+            router,
+            trusted_router,
+        })
+    }
+}
+
+pub struct SetTrustedRouterInstructionData {
+    pub is_trusted: bool,
+}
+
+impl TryFrom<&[u8]> for SetTrustedRouterInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let is_trusted = match data {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { is_trusted })
+    }
+}
+
+pub struct SetTrustedRouter<'a> {
+    pub accounts: SetTrustedRouterAccounts<'a>,
+    pub instruction_data: SetTrustedRouterInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetTrustedRouter<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SetTrustedRouterAccounts::try_from(accounts)?;
+        let instruction_data = SetTrustedRouterInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetTrustedRouter<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &41;
+
+    /// `sha256("global:set_trusted_router")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [17, 109, 144, 226, 44, 121, 177, 162];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Check if the registry entry is valid
+        let (trusted_router_key, trusted_router_bump) = derive_trusted_router(self.accounts.router.key());
+
+        if trusted_router_key.ne(self.accounts.trusted_router.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the entry the first time the router is trusted
+        let is_new = self.accounts.trusted_router.data_is_empty();
+        if is_new {
+            let trusted_router_bump = [trusted_router_bump];
+            let trusted_router_seeds = [
+                Seed::from(b"trusted_router"),
+                Seed::from(self.accounts.router.key()),
+                Seed::from(&trusted_router_bump),
+            ];
+
+            CreateAccount {
+                from: self.accounts.admin,
+                to: self.accounts.trusted_router,
+                lamports: Rent::get()?.minimum_balance(TrustedRouter::LEN),
+                space: TrustedRouter::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&trusted_router_seeds)])?;
+        }
+
+        let mut trusted_router = match is_new {
+            true => TrustedRouter::init(self.accounts.trusted_router)?,
+            false => TrustedRouter::load_mut(self.accounts.trusted_router)?,
+        };
+
+        trusted_router.set_inner(
+            *self.accounts.router.key(),
+            self.instruction_data.is_trusted,
+            [trusted_router_bump],
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use crate::state::BalanceSnapshot;
+use crate::{check_signer, check_writable, derive_balance_snapshot, split_discriminator, AmmError, CheckedUserAta};
+use pinocchio::sysvars::instructions::{Instructions, IntrospectedInstruction};
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, pubkey::Pubkey, sysvars::{rent::Rent, Sysvar}, ProgramResult
+};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #SnapshotBalance
+///
+/// Record the balance of a token account into its `BalanceSnapshot`, for an `AssertBalanceChange`
+/// later in the same transaction
+///
+/// It must be a top-level instruction, so `AssertBalanceChange` can find it in the Instructions
+/// sysvar and know the snapshot was taken in its own transaction. Anyone can run it, the snapshot
+/// is created on the first call.
+///
+/// Accounts:
+///
+/// 1. payer:                       [signer, mut]       // Pays the snapshot rent
+/// 2. token_account
+/// 3. mint
+/// 4. snapshot                     [init_if_needed]    // [b"balance_snapshot", token_account]
+/// 5. instructions_sysvar
+/// 6. system_program               [executable]
+pub struct SnapshotBalanceAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub token_account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub snapshot: &'a AccountInfo,
+    pub instructions_sysvar: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SnapshotBalanceAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, token_account, mint, snapshot, instructions_sysvar, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(payer)?;
+        [payer, snapshot].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            payer,
+            token_account,
+            mint,
+            snapshot,
+            instructions_sysvar,
+        })
+    }
+}
+
+pub struct SnapshotBalance<'a> {
+    pub accounts: SnapshotBalanceAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SnapshotBalance<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = SnapshotBalanceAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> SnapshotBalance<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &67;
+
+    /// `sha256("global:snapshot_balance")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [103, 80, 86, 114, 5, 1, 234, 53];
+
+    /// Index of the snapshot among the accounts
+    pub const SNAPSHOT_INDEX: usize = 3;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Only a top-level call is visible to `AssertBalanceChange`
+        let instructions = Instructions::try_from(self.accounts.instructions_sysvar)?;
+        let index = instructions.load_current_index();
+
+        if !Self::is_snapshot_into(&instructions.load_instruction_at(index as usize)?, self.accounts.snapshot.key()) {
+            return Err(AmmError::InvalidBalanceSnapshot.into());
+        }
+
+        let amount = CheckedUserAta::new(self.accounts.token_account, self.accounts.mint.key())?.amount();
+
+        // Check if the snapshot is the token account's
+        let (snapshot_key, snapshot_bump) = derive_balance_snapshot(self.accounts.token_account.key());
+
+        if snapshot_key.ne(self.accounts.snapshot.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Create the snapshot on the first call
+        let is_new = self.accounts.snapshot.data_is_empty();
+        if is_new {
+            let snapshot_bump = [snapshot_bump];
+            let snapshot_seeds = [
+                Seed::from(b"balance_snapshot"),
+                Seed::from(self.accounts.token_account.key()),
+                Seed::from(&snapshot_bump),
+            ];
+
+            CreateAccount {
+                from: self.accounts.payer,
+                to: self.accounts.snapshot,
+                lamports: Rent::get()?.minimum_balance(BalanceSnapshot::LEN),
+                space: BalanceSnapshot::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&snapshot_seeds)])?;
+        }
+
+        let mut snapshot = match is_new {
+            true => BalanceSnapshot::init(self.accounts.snapshot)?,
+            false => BalanceSnapshot::load_mut(self.accounts.snapshot)?,
+        };
+
+        snapshot.set_inner(*self.accounts.token_account.key(), amount, index, [snapshot_bump]);
+
+        Ok(())
+    }
+
+    /// Whether `instruction` is a `SnapshotBalance` of this program into `snapshot`
+    pub fn is_snapshot_into(instruction: &IntrospectedInstruction, snapshot: &Pubkey) -> bool {
+        instruction.get_program_id().eq(&crate::ID)
+            && matches!(split_discriminator(instruction.get_instruction_data()), Some((Self::DISCRIMINATOR, _)))
+            && matches!(instruction.get_account_meta_at(Self::SNAPSHOT_INDEX), Ok(meta) if meta.key.eq(snapshot))
+    }
+}
@@ -0,0 +1,216 @@
+use crate::state::{FeeDistributor, GlobalConfig, Staker};
+use crate::{
+    check_signer, check_writable, derive_ata, derive_staker, set_return_amounts, CheckedVault, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #StakeGovernance
+///
+/// Stake governance tokens in a `FeeDistributor` to share the rewards of its next checkpoints
+///
+/// Rewards earned so far are settled first, and the stake stays locked until a full epoch went by,
+/// see `Staker`.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer, mut]   // Pays for the staker account
+/// 2. fee_distributor              [mut]
+/// 3. staker                       [init_if_needed]    // [b"staker", fee_distributor, user]
+/// 4. user_governance_ata          [mut]
+/// 5. stake_vault                  [mut]   // ATA of fee_distributor for the governance mint
+/// 6. governance_mint
+/// 7. token_program                [executable]
+/// 8. system_program               [executable]
+/// 9. global_config
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Amount of governance token to stake
+///
+/// Return data:
+///
+/// 1. staked: u64,                 // Received by the stake vault, net of transfer fees
+pub struct StakeGovernanceAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub fee_distributor: &'a AccountInfo,
+    pub staker: &'a AccountInfo,
+    pub user_governance_ata: &'a AccountInfo,
+    pub stake_vault: &'a AccountInfo,
+    pub governance_mint: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for StakeGovernanceAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, fee_distributor, staker, user_governance_ata, stake_vault, governance_mint, _token_program, _system_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [user, fee_distributor, staker, user_governance_ata, stake_vault]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            fee_distributor,
+            staker,
+            user_governance_ata,
+            stake_vault,
+            governance_mint,
+            global_config,
+        })
+    }
+}
+
+pub struct StakeGovernanceInstructionData {
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for StakeGovernanceInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u64>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data.try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount })
+    }
+}
+
+pub struct StakeGovernance<'a> {
+    pub accounts: StakeGovernanceAccounts<'a>,
+    pub instruction_data: StakeGovernanceInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for StakeGovernance<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = StakeGovernanceAccounts::try_from(accounts)?;
+        let instruction_data = StakeGovernanceInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> StakeGovernance<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &37;
+
+    /// `sha256("global:stake_governance")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [8, 128, 78, 38, 168, 14, 182, 234];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let fee_distributor = FeeDistributor::load(self.accounts.fee_distributor)?;
+
+        // Check the governance mint and the stake vault
+        if self.accounts.governance_mint.key().ne(fee_distributor.governance_mint())
+            || derive_ata(
+                self.accounts.fee_distributor.key(),
+                fee_distributor.governance_token_program(),
+                fee_distributor.governance_mint(),
+            )
+            .ne(self.accounts.stake_vault.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let stake_vault = CheckedVault::new(
+            self.accounts.stake_vault,
+            fee_distributor.governance_mint(),
+            self.accounts.fee_distributor.key(),
+            fee_distributor.governance_token_program(),
+        )?;
+
+        // Create the staker on the first stake, it only earns from the current reward per token on
+        if self.accounts.staker.data_is_empty() {
+            let (staker_key, staker_bump) =
+                derive_staker(self.accounts.fee_distributor.key(), self.accounts.user.key());
+            if staker_key.ne(self.accounts.staker.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let staker_bump = [staker_bump];
+            let seeds = [
+                Seed::from(b"staker"),
+                Seed::from(self.accounts.fee_distributor.key()),
+                Seed::from(self.accounts.user.key()),
+                Seed::from(&staker_bump),
+            ];
+
+            CreateAccount {
+                from: self.accounts.user,
+                to: self.accounts.staker,
+                lamports: Rent::get()?.minimum_balance(Staker::LEN),
+                space: Staker::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&seeds)])?;
+
+            Staker::init(self.accounts.staker)?.set_inner(
+                *self.accounts.fee_distributor.key(),
+                *self.accounts.user.key(),
+                fee_distributor.reward_per_token(),
+                staker_bump,
+            );
+        }
+
+        let mut staker = Staker::load_mut(self.accounts.staker)?;
+
+        if staker.fee_distributor().ne(self.accounts.fee_distributor.key())
+            || staker.owner().ne(self.accounts.user.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Settle at the current rate before the stake changes
+        staker.settle(fee_distributor.reward_per_token());
+
+        // Only what the vault received is staked
+        let stake_vault_before = stake_vault.amount();
+
+        TransferChecked {
+            from: self.accounts.user_governance_ata,
+            mint: self.accounts.governance_mint,
+            to: self.accounts.stake_vault,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount,
+            decimals: fee_distributor.governance_decimals(),
+            token_program: fee_distributor.governance_token_program(),
+        }
+        .invoke()?;
+
+        let staked = stake_vault.amount() - stake_vault_before;
+
+        drop(fee_distributor);
+
+        FeeDistributor::load_mut(self.accounts.fee_distributor)?.stake(staked)?;
+        staker.stake(staked, Clock::get()?.epoch);
+
+        set_return_amounts(&[staked]);
+
+        Ok(())
+    }
+}
@@ -1,16 +1,23 @@
-use crate::state::Config;
-use crate::AmmState;
-use constant_product_curve::{ConstantProduct, LiquidityPair};
+use crate::state::{
+    get_extension, get_extension_mut, CallerAllowlist, CircuitBreaker, Config, ExitSwaps, FeeGrowth, GlobalConfig,
+    InsuranceFund, MaintenanceWindow, MevTax, OracleState, SameSlotGuard, Stats, TradingCalendar, TradingHours,
+    UserStats, VolumeDiscount,
+};
+use crate::{
+    bump_sequence, check_expiration, check_signer, check_writable, debug_log, derive_ata, derive_insurance,
+    mul_div, set_return_amounts, slippage_bound, split_discriminator, swap_with_fee, validate_pool_accounts,
+    validate_trusted_pool_accounts, AmmError, AmmState, Arbitrage, CheckedLpMint, CheckedUserAta, DRY_RUN,
+    ExecuteLongTermOrder, ExecuteStrategy, MAX_FEE_BPS, Memo, PoolAccounts, PythPriceUpdate, RaydiumSwap, Rounding,
+    RouterSwap, SLIPPAGE_IN_BPS, SwapAndDeposit, SwapBatch, TokenSwap, TransferChecked, WithdrawAndSwap,
+};
 use core::mem::size_of;
 use pinocchio::instruction::Signer;
-use pinocchio::pubkey::find_program_address;
-use pinocchio::sysvars::clock::Clock;
-use pinocchio::sysvars::Sysvar;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::{clock::Clock, instructions::Instructions, Sysvar};
 use pinocchio::{
     account_info::AccountInfo, instruction::Seed, program_error::ProgramError, ProgramResult,
 };
-use pinocchio_token::instructions::Transfer;
-use pinocchio_token::state::TokenAccount;
+use pinocchio_system::instructions::Transfer;
 
 /// #Swap
 ///
@@ -18,20 +25,62 @@ use pinocchio_token::state::TokenAccount;
 ///
 /// Accounts:
 ///
-/// 1. user:                        [signer, mut]
+/// 1. user:                        [signer]           // Owner of the input account, or a delegate approved for `amount`
 /// 2. user_x:                      [init_if_needed]
 /// 3. user_y:                      [init_if_needed]
 /// 4. vault_x                      [mut]
 /// 5. vault_y                      [mut]
-/// 6. config                       
-/// 7. token_program               [executable]
+/// 6. config                       [mut]
+/// 7. mint_x
+/// 8. mint_y
+/// 9. token_program_x              [executable]
+/// 10. token_program_y             [executable]
+/// 11. global_config
+/// 12. host_fee                    [mut, optional]    // Input-mint token account receiving `Config::host_fee_bps` of the fee
+/// 13. oracle                      [optional]         // `OracleState::oracle`, required with `Config::FLAG_ORACLE_GUARD`
+/// 14. instructions_sysvar         [optional]         // Required with `Config::FLAG_SINGLE_SWAP` or `CallerAllowlist`
+/// 15. mint_lp                     [optional]         // Required with the `FeeGrowth` extension
+/// 16. memo_program                [optional]         // SPL Memo, required with a memo
+/// 17. trading_calendar            [optional]         // `TradingHours::calendar`, required with `TradingHours`
+/// 18. insurance_vault             [mut, optional]    // Input-mint insurance vault, required with `InsuranceFund`
+/// 19. tip_account                 [mut, optional]    // `GlobalConfig::tip_account`, required with a tip
+/// 20. system_program              [optional]         // Required with a tip
+/// 21. user_stats                  [mut, optional]    // The user's `UserStats` on this pool
+///
+/// Optional accounts can be skipped by passing the program id in their place.
+///
+/// Pools with the `CallerAllowlist` extension only take swaps through CPI from the programs it lists.
+///
+/// Pools with the `MaintenanceWindow` extension refuse swaps inside the window, and pools with the
+/// `TradingHours` extension while their calendar's market is closed.
+///
+/// `AmmState::WithdrawOnly` pools only take swaps toward their `ExitSwaps` price, if they have one.
+///
+/// Swaps passing the user's `UserStats` add to it, and get the discount its volume earns on pools
+/// with the `VolumeDiscount` extension.
+///
+/// Pools with the `MevTax` extension charge the first swap of each slot a surcharge on the fee, and
+/// pools with the `SameSlotGuard` extension can refuse a signer swapping back within a slot.
+///
+/// The user pays nothing but the input, so a relayer can be the fee payer of the transaction while
+/// a session key approved as delegate signs as `user`. With the `TIP` flag, the user also pays a
+/// lamport tip to the tip account of the `GlobalConfig`, up to its cap, so a bundle can be tipped
+/// without a separate transfer. The user must then be writable.
 ///
 /// Parameters:
 ///
 /// 1. is_x:                        [bool]
 /// 2. amount:                      [u64]
-/// 3. min:                         [u64]
-/// 4. expiration:                  [u64]
+/// 3. min:                         [u64]   // A tolerance in bps with `SLIPPAGE_IN_BPS`
+/// 4. expiration:                  [i64]   // A slot with `EXPIRATION_IN_SLOTS`
+/// 5. flags:                       [u8]    // Optional, see the shared flags in `constants`
+/// 6. tip:                         [u64]   // Lamports, only with the `TIP` flag
+/// 7. memo:                        [u8]    // Optional trailing bytes after the flags and tip, see `Memo`
+///
+/// Return data:
+///
+/// 1. amount_in:                   [u64]   // Sent by the user
+/// 2. amount_out:                  [u64]   // Received by the user, net of transfer fees
 pub struct SwapAccounts<'a> {
     pub user: &'a AccountInfo,
     pub user_x: &'a AccountInfo,
@@ -39,17 +88,68 @@ pub struct SwapAccounts<'a> {
     pub vault_x: &'a AccountInfo,
     pub vault_y: &'a AccountInfo,
     pub config: &'a AccountInfo,
-    pub token_program: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub host_fee: Option<&'a AccountInfo>,
+    pub oracle: Option<&'a AccountInfo>,
+    pub instructions_sysvar: Option<&'a AccountInfo>,
+    pub mint_lp: Option<&'a AccountInfo>,
+    pub memo_program: Option<&'a AccountInfo>,
+    pub trading_calendar: Option<&'a AccountInfo>,
+    pub insurance_vault: Option<&'a AccountInfo>,
+    pub tip_account: Option<&'a AccountInfo>,
+    pub system_program: Option<&'a AccountInfo>,
+    pub user_stats: Option<&'a AccountInfo>,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for SwapAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [user, user_x, user_y, vault_x, vault_y, config, token_program] = accounts else {
+        let [user, user_x, user_y, vault_x, vault_y, config, mint_x, mint_y, token_program_x, token_program_y, global_config, remaining @ ..] =
+            accounts
+        else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [user_x, user_y, vault_x, vault_y, config].into_iter().try_for_each(check_writable)?;
+
+        let mut optional = remaining.iter().map(|account| account.key().ne(&crate::ID).then_some(account));
+        let host_fee = optional.next().flatten();
+
+        if let Some(host_fee) = host_fee {
+            check_writable(host_fee)?;
+        }
+
+        let oracle = optional.next().flatten();
+        let instructions_sysvar = optional.next().flatten();
+        let mint_lp = optional.next().flatten();
+        let memo_program = optional.next().flatten();
+        let trading_calendar = optional.next().flatten();
+        let insurance_vault = optional.next().flatten();
+
+        if let Some(insurance_vault) = insurance_vault {
+            check_writable(insurance_vault)?;
+        }
+
+        let tip_account = optional.next().flatten();
+        let system_program = optional.next().flatten();
+
+        if let Some(tip_account) = tip_account {
+            check_writable(tip_account)?;
+        }
+
+        let user_stats = optional.next().flatten();
+
+        if let Some(user_stats) = user_stats {
+            check_writable(user_stats)?;
+        }
+
         // Return the accounts
         Ok(Self {
             user,
@@ -58,47 +158,97 @@ impl<'a> TryFrom<&'a [AccountInfo]> for SwapAccounts<'a> {
             vault_x,
             vault_y,
             config,
-            token_program,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            global_config,
+            host_fee,
+            oracle,
+            instructions_sysvar,
+            mint_lp,
+            memo_program,
+            trading_calendar,
+            insurance_vault,
+            tip_account,
+            system_program,
+            user_stats,
         })
     }
 }
 
-pub struct SwapInstructionData {
+pub struct SwapInstructionData<'a> {
     pub is_x: bool,
     pub amount: u64,
     pub min: u64,
     pub expiration: i64,
+    pub flags: u8,
+    /// Lamports paid to the `GlobalConfig` tip account, zero without the `TIP` flag
+    pub tip: u64,
+    /// Forwarded to the memo program when not empty
+    pub memo: &'a [u8],
+}
+
+impl SwapInstructionData<'_> {
+    /* Flags */
+
+    /// The 8 bytes after the flags are a lamport tip for the `GlobalConfig` tip account
+    pub const TIP: u8 = 1 << 0;
 }
 
-impl<'a> TryFrom<&'a [u8]> for SwapInstructionData {
+impl<'a> TryFrom<&'a [u8]> for SwapInstructionData<'a> {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len().ne(&(size_of::<bool>() + size_of::<u64>() + size_of::<u64>() + size_of::<i64>())) {
-            return Err(ProgramError::InvalidInstructionData);
-        }
+        const SWAP_DATA_LEN: usize = size_of::<bool>() + size_of::<u64>() + size_of::<u64>() + size_of::<i64>();
+        const SWAP_DATA_LEN_WITH_FLAGS: usize = SWAP_DATA_LEN + size_of::<u8>();
+
+        // Anything after the flags, and the tip with the `TIP` flag, is the memo
+        let (flags, memo) = match data.len() {
+            SWAP_DATA_LEN => (0, &data[SWAP_DATA_LEN..]),
+            len if len >= SWAP_DATA_LEN_WITH_FLAGS => (data[SWAP_DATA_LEN], &data[SWAP_DATA_LEN_WITH_FLAGS..]),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let (tip, memo) = match flags & Self::TIP != 0 {
+            true => match memo.split_first_chunk::<8>() {
+                Some((tip, memo)) => (u64::from_le_bytes(*tip), memo),
+                None => return Err(ProgramError::InvalidInstructionData),
+            },
+            false => (0, memo),
+        };
 
         let is_x = data[0] == 1;
         let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
         let min = u64::from_le_bytes(data[9..17].try_into().unwrap());
         let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
 
-        if amount == 0 || min == 0 || expiration < Clock::get()?.unix_timestamp {
+        // A tolerance in bps may be zero, but not beyond 100%
+        let min_invalid = match flags & SLIPPAGE_IN_BPS != 0 {
+            true => min > MAX_FEE_BPS as u64,
+            false => min == 0,
+        };
+
+        if amount == 0 || min_invalid {
             return Err(ProgramError::InvalidInstructionData);
         }
 
+        check_expiration(expiration, flags)?;
+
         Ok(Self {
             is_x,
             amount,
             min,
             expiration,
+            flags,
+            tip,
+            memo,
         })
     }
 }
 
 pub struct Swap<'a> {
     pub accounts: SwapAccounts<'a>,
-    pub instruction_data: SwapInstructionData,
+    pub instruction_data: SwapInstructionData<'a>,
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Swap<'a> {
@@ -115,121 +265,577 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Swap<'a> {
         })
     }
 }
+/// Reject the transaction if it holds more than one Swap instruction against `config`.
+///
+/// Only top-level instructions are visible in the Instructions sysvar, so swaps issued through
+/// CPI by another program are not counted.
+fn check_single_swap(instructions_sysvar: &AccountInfo, config: &Pubkey) -> ProgramResult {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+
+    let mut swaps = 0;
+    for index in 0..instructions.num_instructions() {
+        let instruction = instructions.load_instruction_at(index)?;
+
+        if instruction.get_program_id().ne(&crate::ID) {
+            continue;
+        }
+
+        let Some(config_indexes) = swap_config_indexes(instruction.get_instruction_data()) else {
+            continue;
+        };
+
+        for config_index in config_indexes {
+            if matches!(instruction.get_account_meta_at(*config_index), Ok(meta) if meta.key.eq(config)) {
+                swaps += 1;
+            }
+        }
+
+        if swaps > 1 {
+            return Err(AmmError::MultipleSwaps.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Indexes of the pool configs among the accounts of an instruction of this program that swaps.
+///
+/// The config is the 6th Swap account, the 8th of a `RouterSwap`, a `SwapAndDeposit`, a
+/// `WithdrawAndSwap` or an `ExecuteStrategy` running a `Swap`, the first of a `TokenSwap` swap, the
+/// pool state of a `RaydiumSwap`, either pool of an `Arbitrage`, every pool of a `SwapBatch` and the
+/// pool of an `ExecuteLongTermOrder`
+fn swap_config_indexes(data: &[u8]) -> Option<&'static [usize]> {
+    let config_indexes: &[usize] = match split_discriminator(data) {
+        Some((Swap::DISCRIMINATOR, _)) => &[5],
+        Some((RouterSwap::DISCRIMINATOR, _)) => &[RouterSwap::CONFIG_INDEX],
+        Some((SwapAndDeposit::DISCRIMINATOR, _)) => &[SwapAndDeposit::CONFIG_INDEX],
+        Some((WithdrawAndSwap::DISCRIMINATOR, _)) => &[WithdrawAndSwap::CONFIG_INDEX],
+        Some((ExecuteStrategy::DISCRIMINATOR, inner))
+            if matches!(split_discriminator(inner), Some((Swap::DISCRIMINATOR, _))) =>
+        {
+            &[ExecuteStrategy::CONFIG_INDEX]
+        }
+        Some((Arbitrage::DISCRIMINATOR, _)) => &Arbitrage::CONFIG_INDEXES,
+        Some((SwapBatch::DISCRIMINATOR, _)) => &SwapBatch::CONFIG_INDEXES,
+        Some((ExecuteLongTermOrder::DISCRIMINATOR, _)) => &[ExecuteLongTermOrder::CONFIG_INDEX],
+        Some((TokenSwap::DISCRIMINATOR, [TokenSwap::SWAP, ..])) => &[0],
+        #[cfg(not(feature = "legacy-discriminators"))]
+        _ if matches!(data, [TokenSwap::SWAP, ..]) && TokenSwap::is_token_swap(data) => &[0],
+        _ if [RaydiumSwap::SWAP_BASE_INPUT, RaydiumSwap::SWAP_BASE_OUTPUT]
+            .iter()
+            .any(|discriminator| data.starts_with(discriminator)) =>
+        {
+            &[RaydiumSwap::CONFIG_INDEX]
+        }
+        _ => return None,
+    };
+
+    Some(config_indexes)
+}
+
+/// Reject the swap unless the transaction instruction it runs under belongs to a program of the
+/// `allowlist`, which also rejects direct calls.
+fn check_caller(instructions_sysvar: &AccountInfo, allowlist: &CallerAllowlist) -> ProgramResult {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let current = instructions.load_instruction_at(instructions.load_current_index() as usize)?;
+
+    if !allowlist.contains(current.get_program_id()) {
+        return Err(AmmError::CallerNotAllowed.into());
+    }
+
+    Ok(())
+}
+
 impl<'a> Swap<'a> {
     pub const DISCRIMINATOR: &'a u8 = &3;
 
+    /// `sha256("global:swap")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
     pub fn process(&mut self) -> ProgramResult {
-        // Deserialize the config account
-        let config = Config::load(self.accounts.config)?;
-
-        // Check if we can swap in the Amm
-        if config.state().ne(&(AmmState::Initialized as u8)) {
-            return Err(ProgramError::InvalidAccountData);
-        }
-
-        // Check if the vault_x is valid
-        let (vault_x, _) = find_program_address(
-            &[
-                self.accounts.config.key(),
-                self.accounts.token_program.key(),
-                config.mint_x(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
+        self.execute(false)
+    }
 
-        if vault_x.ne(self.accounts.vault_x.key()) {
-            return Err(ProgramError::InvalidAccountData);
-        }
+    /// `process` for a `RouterSwap`, trusting the vaults passed by the router
+    #[inline(always)]
+    pub fn process_trusted(&mut self) -> ProgramResult {
+        self.execute(true)
+    }
 
-        // Check if the vault_y is valid
-        let (vault_y, _) = find_program_address(
-            &[
-                self.accounts.config.key(),
-                self.accounts.token_program.key(),
-                config.mint_y(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
+    fn execute(&mut self, trusted_vaults: bool) -> ProgramResult {
+        // Check if the protocol allows swaps
+        let global_config = GlobalConfig::load(self.accounts.global_config)?;
 
-        if vault_y.ne(self.accounts.vault_y.key()) {
-            return Err(ProgramError::InvalidAccountData);
+        if global_config.is_paused() {
+            return Err(AmmError::ProtocolPaused.into());
         }
 
-        // Deserialize the token accounts
-        let vault_x = unsafe { TokenAccount::from_account_info_unchecked(self.accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_info_unchecked(self.accounts.vault_y)? };
+        global_config.check_not_frozen()?;
 
-        // Swap Calculations
-        let mut curve = ConstantProduct::init(
-            vault_x.amount(),
-            vault_y.amount(),
-            vault_x.amount(),
-            config.fee(),
-            None,
-        )
-        .map_err(|_| ProgramError::Custom(1))?;
-
-        let p = match self.instruction_data.is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
+        // Check the config, the pool mints and the vaults
+        let validate = match trusted_vaults {
+            true => validate_trusted_pool_accounts,
+            false => validate_pool_accounts,
         };
+        let PoolAccounts { config, vault_x, vault_y, deployed } = validate(
+            self.accounts.config,
+            (self.accounts.vault_x, self.accounts.vault_y),
+            (self.accounts.mint_x, self.accounts.mint_y),
+            (self.accounts.token_program_x, self.accounts.token_program_y),
+            &[AmmState::Initialized, AmmState::WithdrawOnly],
+        )?;
 
-        let swap_result = curve
-            .swap(p, self.instruction_data.amount, self.instruction_data.min)
-            .map_err(|_| ProgramError::Custom(1))?;
+        // Only one top-level swap against this pool per transaction
+        if config.has_single_swap() {
+            let instructions_sysvar = self
+                .accounts
+                .instructions_sysvar
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
 
-        // Check for correct values
-        if swap_result.deposit == 0 || swap_result.withdraw == 0 {
-            return Err(ProgramError::InvalidArgument);
+            check_single_swap(instructions_sysvar, self.accounts.config.key())?;
+        }
+
+        // Permissioned pools only take swaps from their approved programs
+        if let Some(allowlist) = get_extension::<CallerAllowlist>(&self.accounts.config.try_borrow_data()?)? {
+            let instructions_sysvar = self
+                .accounts
+                .instructions_sysvar
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            check_caller(instructions_sysvar, allowlist)?;
+        }
+
+        // RWA pools close with their off-chain market
+        if let Some(window) = get_extension::<MaintenanceWindow>(&self.accounts.config.try_borrow_data()?)? {
+            window.check(Clock::get()?.unix_timestamp)?;
+        }
+
+        if let Some(trading_hours) = get_extension::<TradingHours>(&self.accounts.config.try_borrow_data()?)? {
+            if trading_hours.is_enabled() {
+                let trading_calendar = self.accounts.trading_calendar.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+                if trading_calendar.key().ne(trading_hours.calendar()) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                if !TradingCalendar::load(trading_calendar)?.is_open(Clock::get()?.unix_timestamp) {
+                    return Err(AmmError::MarketClosed.into());
+                }
+            }
+        }
+
+        let slot = Clock::get()?.slot;
+
+        // Don't let a signer trade back within the slot, as the back-run of a sandwich would
+        if let Some(guard) = get_extension::<SameSlotGuard>(&self.accounts.config.try_borrow_data()?)? {
+            guard.check(slot, self.accounts.user.key(), self.instruction_data.is_x)?;
         }
 
+        // Uncollected protocol fees sit in the vaults but don't back the curve
+        let (reserve_x, reserve_y) = config.reserves(vault_x.amount(), vault_y.amount(), deployed);
+
+        debug_log!(Self::DISCRIMINATOR, 0, reserve_x, reserve_y, deployed.0, deployed.1);
+
+        // Refuse empty and dust pools up front, the swap math would fail without a clear error
+        let (reserve_in, reserve_out) = match self.instruction_data.is_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+
+        global_config.check_swap_reserves(reserve_in, reserve_out)?;
+
         // Create the signer seeds
-        let seed_binding = config.seed().to_le_bytes();
         let config_bump = config.config_bump();
         let seeds = [
             Seed::from("config".as_bytes()),
-            Seed::from(&seed_binding),
+            Seed::from(config.seed_bytes()),
             Seed::from(config.mint_x()),
             Seed::from(config.mint_y()),
             Seed::from(&config_bump),
         ];
         let signer_seeds = [Signer::from(&seeds)];
 
-        // Swap the tokens
-        if self.instruction_data.is_x {
-            Transfer {
-                from: self.accounts.user_x,
-                to: self.accounts.vault_x,
-                authority: self.accounts.user,
-                amount: swap_result.deposit,
+        // Route the transfers by direction
+        let (user_in, vault_in, mint_in, vault_out, mint_out, user_out) = match self.instruction_data.is_x {
+            true => (
+                self.accounts.user_x,
+                self.accounts.vault_x,
+                self.accounts.mint_x,
+                self.accounts.vault_y,
+                self.accounts.mint_y,
+                self.accounts.user_y,
+            ),
+            false => (
+                self.accounts.user_y,
+                self.accounts.vault_y,
+                self.accounts.mint_y,
+                self.accounts.vault_x,
+                self.accounts.mint_x,
+                self.accounts.user_x,
+            ),
+        };
+        let (decimals_in, token_program_in, decimals_out, token_program_out) = match self.instruction_data.is_x {
+            true => (config.decimals_x(), config.token_program_x(), config.decimals_y(), config.token_program_y()),
+            false => (config.decimals_y(), config.token_program_y(), config.decimals_x(), config.token_program_x()),
+        };
+        let vault_in_account = if self.instruction_data.is_x { &vault_x } else { &vault_y };
+        let dry_run = self.instruction_data.flags & DRY_RUN != 0;
+
+        // Take the input first: with Token-2022 transfer fees the vault receives less than was sent,
+        // so the balance delta, not the instruction amount, is what gets priced
+        let amount_in = match dry_run {
+            true => self.instruction_data.amount,
+            false => {
+                let vault_in_before = vault_in_account.amount();
+
+                TransferChecked {
+                    from: user_in,
+                    mint: mint_in,
+                    to: vault_in,
+                    authority: self.accounts.user,
+                    amount: self.instruction_data.amount,
+                    decimals: decimals_in,
+                    token_program: token_program_in,
+                }
+                .invoke()?;
+
+                vault_in_account.amount().checked_sub(vault_in_before).ok_or(AmmError::MathOverflow)?
             }
-            .invoke()?;
+        };
 
-            Transfer {
-                from: self.accounts.vault_y,
-                to: self.accounts.user_y,
+        debug_log!(Self::DISCRIMINATOR, 1, self.instruction_data.amount, amount_in);
+
+        // Bound the output below the input at the spot price
+        if self.instruction_data.flags & SLIPPAGE_IN_BPS != 0 {
+            let fair = amount_in as u128 * reserve_out as u128 / reserve_in as u128;
+
+            self.instruction_data.min = slippage_bound(fair, self.instruction_data.min, false).max(1);
+        }
+
+        if amount_in == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Swap Calculations
+        //
+        // Apart from the optional host and protocol cuts, the fee stays in the vault as part of the
+        // reserves, so it compounds into the LP share value on every trade without a keeper crank.
+        // The fee rounds up and the output down, see `Rounding`.
+        let pool_swap_fee = match self.accounts.user_stats {
+            Some(user_stats) => {
+                let user_stats = UserStats::load(user_stats)?;
+                user_stats.check_owner(self.accounts.config.key(), self.accounts.user.key())?;
+
+                // High-volume traders pay a discounted pool fee
+                match get_extension::<VolumeDiscount>(&self.accounts.config.try_borrow_data()?)? {
+                    Some(discount) => config.swap_fee()?.discounted(discount.discount_for(user_stats.volume_y())),
+                    None => config.swap_fee()?,
+                }
+            }
+            None => config.swap_fee()?,
+        };
+        let swap_fee = match get_extension::<MevTax>(&self.accounts.config.try_borrow_data()?)? {
+            Some(mev_tax) => mev_tax.swap_fee(pool_swap_fee, slot)?,
+            None => pool_swap_fee,
+        };
+
+        // Inputs too small to buy anything once the fee is taken are refused too
+        let (withdraw, fee) = swap_with_fee(reserve_in, reserve_out, amount_in, swap_fee)?;
+
+        // The host, protocol and insurance cuts are shares of the pool fee, not of a `MevTax` surcharge
+        let pool_fee = match swap_fee.bps() {
+            0 => 0,
+            bps => mul_div(fee, pool_swap_fee.bps() as u64, bps as u64, Rounding::Down)?,
+        };
+
+        debug_log!(Self::DISCRIMINATOR, 2, amount_in, withdraw, fee);
+
+        // Check for slippage on the quote, then on what the user receives. The pricing helpers know
+        // nothing of `min`, so this is the only place it is enforced before any output leaves.
+        if withdraw < self.instruction_data.min {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Winding down pools only trade back toward their exit price. The host and protocol cuts
+        // are counted as reserves, which can only reject a swap early.
+        if config.amm_state()? == AmmState::WithdrawOnly {
+            let config_data = self.accounts.config.try_borrow_data()?;
+            let exit_swaps = get_extension::<ExitSwaps>(&config_data)?.ok_or(AmmError::ExitSwapRejected)?;
+
+            // The output is always below the reserve it leaves
+            let reserves_after = match self.instruction_data.is_x {
+                true => (reserve_x.checked_add(amount_in).ok_or(AmmError::MathOverflow)?, reserve_y - withdraw),
+                false => (reserve_x - withdraw, reserve_y.checked_add(amount_in).ok_or(AmmError::MathOverflow)?),
+            };
+
+            exit_swaps.check_swap(self.instruction_data.is_x, (reserve_x, reserve_y), reserves_after)?;
+        }
+
+        // Pay the host its share of the fee out of the input
+        let host_fee = match self.accounts.host_fee {
+            Some(host_fee_account) => {
+                let host_fee = mul_div(pool_fee, config.host_fee_bps() as u64, MAX_FEE_BPS as u64, Rounding::Down)?;
+
+                if host_fee > 0 && !dry_run {
+                    TransferChecked {
+                        from: vault_in,
+                        mint: mint_in,
+                        to: host_fee_account,
+                        authority: self.accounts.config,
+                        amount: host_fee,
+                        decimals: decimals_in,
+                        token_program: token_program_in,
+                    }
+                    .invoke_signed(&signer_seeds)?;
+                }
+
+                host_fee
+            }
+            None => 0,
+        };
+
+        // Reject trades too far from the external price
+        if config.has_oracle_guard() {
+            let oracle = self.accounts.oracle.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            let config_data = self.accounts.config.try_borrow_data()?;
+            let oracle_state = get_extension::<OracleState>(&config_data)?.ok_or(AmmError::MissingExtension)?;
+
+            if oracle.key().ne(oracle_state.oracle()) {
+                return Err(AmmError::InvalidOracle.into());
+            }
+
+            let oracle_price = PythPriceUpdate::read(oracle, Clock::get()?.unix_timestamp)?;
+
+            // Execution price, excluding the fee
+            let amount_in_after_fee = amount_in - fee;
+            let (x, y) = match self.instruction_data.is_x {
+                true => (amount_in_after_fee, withdraw),
+                false => (withdraw, amount_in_after_fee),
+            };
+
+            oracle_price.check_deviation(
+                x,
+                y,
+                config.decimals_x(),
+                config.decimals_y(),
+                oracle_state.max_deviation_bps(),
+            )?;
+        }
+
+        // The protocol share stays in the vault, tracked in the config until collected
+        let lp_fee = fee.checked_sub(host_fee).ok_or(AmmError::MathOverflow)?;
+        let protocol_fee =
+            mul_div(pool_fee, config.protocol_fee_bps() as u64, MAX_FEE_BPS as u64, Rounding::Down)?.min(lp_fee);
+        let lp_fee = lp_fee - protocol_fee;
+
+        // The insurance share leaves the vault, also out of the LP share
+        let insurance_fee = match get_extension::<InsuranceFund>(&self.accounts.config.try_borrow_data()?)? {
+            Some(insurance_fund) => insurance_fund.skim(pool_fee)?.min(lp_fee),
+            None => 0,
+        };
+        let lp_fee = lp_fee - insurance_fee;
+
+        if insurance_fee > 0 && !dry_run {
+            let insurance_vault = self.accounts.insurance_vault.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            if derive_ata(&derive_insurance(self.accounts.config.key()).0, token_program_in, mint_in.key())
+                .ne(insurance_vault.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            TransferChecked {
+                from: vault_in,
+                mint: mint_in,
+                to: insurance_vault,
                 authority: self.accounts.config,
-                amount: swap_result.withdraw,
+                amount: insurance_fee,
+                decimals: decimals_in,
+                token_program: token_program_in,
             }
             .invoke_signed(&signer_seeds)?;
-        } else {
+        }
+
+        debug_log!(Self::DISCRIMINATOR, 3, host_fee, protocol_fee, insurance_fee);
+
+        // Report the quote and roll back, the user accounts may not even exist yet
+        if dry_run {
+            set_return_amounts(&[self.instruction_data.amount, withdraw]);
+            return Err(AmmError::SimulationComplete.into());
+        }
+
+        let user_out_account = CheckedUserAta::new(user_out, mint_out.key())?;
+        let user_out_before = user_out_account.amount();
+
+        TransferChecked {
+            from: vault_out,
+            mint: mint_out,
+            to: user_out,
+            authority: self.accounts.config,
+            amount: withdraw,
+            decimals: decimals_out,
+            token_program: token_program_out,
+        }
+        .invoke_signed(&signer_seeds)?;
+
+        // Check for slippage on what the user actually received
+        let amount_out = user_out_account
+            .amount()
+            .checked_sub(user_out_before)
+            .ok_or(AmmError::MathOverflow)?;
+        if amount_out < self.instruction_data.min {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        drop(config);
+
+        if protocol_fee > 0 {
+            Config::load_mut(self.accounts.config)?
+                .accrue_protocol_fee(self.instruction_data.is_x, protocol_fee)?;
+        }
+
+        // Update the lifetime statistics of pools that track them
+        {
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+
+            if let Some(stats) = get_extension_mut::<Stats>(&mut config_data)? {
+                stats.record_swap(
+                    self.instruction_data.is_x,
+                    amount_in,
+                    withdraw,
+                    fee,
+                );
+            }
+        }
+
+        // Same for the user's own statistics
+        if let Some(user_stats) = self.accounts.user_stats {
+            UserStats::load_mut(user_stats)?.record_swap(self.instruction_data.is_x, amount_in, withdraw, fee);
+        }
+
+        // Spread the LP share of the fee over the LP supply for pools that track it
+        {
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+
+            if let Some(fee_growth) = get_extension_mut::<FeeGrowth>(&mut config_data)? {
+                let mint_lp = self.accounts.mint_lp.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                let mint_lp = CheckedLpMint::new(mint_lp, self.accounts.config.key())?;
+
+                fee_growth.accrue(
+                    self.instruction_data.is_x,
+                    lp_fee,
+                    mint_lp.supply(),
+                    Clock::get()?.unix_timestamp,
+                );
+            }
+        }
+
+        // Later swaps in this slot skip the `MevTax` surcharge
+        {
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+
+            if let Some(mev_tax) = get_extension_mut::<MevTax>(&mut config_data)? {
+                mev_tax.record_swap(slot);
+            }
+        }
+
+        // Remember the signer and direction for the `SameSlotGuard`
+        {
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+
+            if let Some(guard) = get_extension_mut::<SameSlotGuard>(&mut config_data)? {
+                guard.record_swap(slot, self.accounts.user.key(), self.instruction_data.is_x);
+            }
+        }
+
+        // Trip the circuit breaker on extreme price moves within a slot
+        {
+            let (reserve_x_after, reserve_y_after) =
+                Config::load(self.accounts.config)?.reserves(vault_x.amount(), vault_y.amount(), deployed);
+
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+
+            if let Some(circuit_breaker) = get_extension_mut::<CircuitBreaker>(&mut config_data)? {
+                circuit_breaker.observe(slot, reserve_x, reserve_y);
+
+                if circuit_breaker.is_tripped(reserve_x_after, reserve_y_after) {
+                    unsafe { Config::from_bytes_unchecked_mut(&mut config_data) }.set_withdraw_only();
+                }
+            }
+        }
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        // Tip the bundle or relayer out of the user's lamports, within the protocol's cap
+        if self.instruction_data.tip > 0 {
+            let tip_account = self.accounts.tip_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+            self.accounts.system_program.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            global_config.check_tip(tip_account.key(), self.instruction_data.tip)?;
+            check_writable(self.accounts.user)?;
+
             Transfer {
-                from: self.accounts.user_y,
-                to: self.accounts.vault_y,
-                authority: self.accounts.user,
-                amount: swap_result.deposit,
+                from: self.accounts.user,
+                to: tip_account,
+                lamports: self.instruction_data.tip,
             }
             .invoke()?;
+        }
 
-            Transfer {
-                from: self.accounts.vault_x,
-                to: self.accounts.user_x,
-                authority: self.accounts.config,
-                amount: swap_result.withdraw,
+        // Tag the fill for the user's reconciliation
+        if !self.instruction_data.memo.is_empty() {
+            Memo {
+                signer: self.accounts.user,
+                memo: self.instruction_data.memo,
+                memo_program: self.accounts.memo_program.ok_or(ProgramError::NotEnoughAccountKeys)?,
             }
-            .invoke_signed(&signer_seeds)?;
+            .invoke()?;
         }
 
+        debug_log!(Self::DISCRIMINATOR, 4, amount_out, vault_x.amount(), vault_y.amount());
+
+        set_return_amounts(&[self.instruction_data.amount, amount_out]);
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Deposit;
+
+    fn data(parts: &[&[u8]]) -> Vec<u8> {
+        parts.concat()
+    }
+
+    #[cfg(not(feature = "legacy-discriminators"))]
+    #[test]
+    fn swap_config_indexes_sees_raw_token_swaps() {
+        let swap = data(&[&[TokenSwap::SWAP], &[1; 16]]);
+        let deposit = data(&[&[TokenSwap::DEPOSIT_ALL_TOKEN_TYPES], &[1; 24]]);
+
+        assert_eq!(swap_config_indexes(&swap), Some(&[0][..]));
+        assert_eq!(swap_config_indexes(&data(&[&TokenSwap::HASH_DISCRIMINATOR, &swap])), Some(&[0][..]));
+        assert_eq!(swap_config_indexes(&deposit), None);
+    }
+
+    #[test]
+    fn swap_config_indexes_sees_swaps_run_by_a_strategy() {
+        let swap = data(&[&Swap::HASH_DISCRIMINATOR, &[0; 8]]);
+        let deposit = data(&[&Deposit::HASH_DISCRIMINATOR, &[0; 8]]);
+
+        assert_eq!(swap_config_indexes(&swap), Some(&[5][..]));
+        assert_eq!(
+            swap_config_indexes(&data(&[&ExecuteStrategy::HASH_DISCRIMINATOR, &swap])),
+            Some(&[ExecuteStrategy::CONFIG_INDEX][..]),
+        );
+        assert_eq!(ExecuteStrategy::CONFIG_INDEX, 7);
+
+        // A strategy deposit doesn't swap
+        assert_eq!(swap_config_indexes(&data(&[&ExecuteStrategy::HASH_DISCRIMINATOR, &deposit])), None);
+        assert_eq!(swap_config_indexes(&deposit), None);
+    }
+}
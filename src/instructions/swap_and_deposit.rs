@@ -0,0 +1,299 @@
+use crate::{
+    check_expiration, check_signer, check_writable, set_return_amounts, validate_pool_accounts, zap_swap_amount,
+    AmmError, AmmState, CheckedUserAta, Deposit, DepositAccounts, DepositInstructionData, EXPIRATION_IN_SLOTS,
+    PoolAccounts, Swap, SwapAccounts, SwapInstructionData,
+};
+use core::mem::size_of;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #SwapAndDeposit
+///
+/// Deposit a single token: swap the part of it that balances the rest at the pool ratio, then
+/// deposit both sides, in one instruction (a zap)
+///
+/// The swap and the deposit run exactly as `Swap` and `Deposit` with `EXACT_AMOUNTS`, only the LP
+/// minted is bounded. Dust the deposit can't use stays in the user's token accounts.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]        // Owner of the token accounts, or their delegate
+/// 2. mint_lp                      [mut]
+/// 3. vault_x                      [mut]
+/// 4. vault_y                      [mut]
+/// 5. user_x_ata                   [mut]
+/// 6. user_y_ata                   [mut]
+/// 7. user_lp_ata                  [mut]
+/// 8. config                       [mut]
+/// 9. token_program                [executable]  // LP token program, see `Config::lp_token_program`
+/// 10. mint_x
+/// 11. mint_y
+/// 12. token_program_x             [executable]
+/// 13. token_program_y             [executable]
+/// 14. global_config
+/// 15. oracle                      [optional]    // As in `Swap`
+/// 16. instructions_sysvar         [optional]    // As in `Swap`
+///
+/// Optional accounts can be skipped by passing the program id in their place.
+///
+/// Parameters:
+///
+/// 1. is_x: bool,                  // The deposit is paid in X
+/// 2. amount: u64,                 // Amount of the input token to deposit
+/// 3. min_lp: u64,                 // Minimum LP to receive, the only slippage bound
+/// 4. expiration: i64,             // Unix timestamp (a slot with `EXPIRATION_IN_SLOTS`)
+/// 5. flags: u8,                   // Optional, only `EXPIRATION_IN_SLOTS`
+///
+/// Return data:
+///
+/// 1. swapped: u64,                // Amount of the input token swapped
+/// 2. amount_out: u64,             // Received from the swap, net of transfer fees
+/// 3. lp: u64,                     // Amount of LP token minted
+pub struct SwapAndDepositAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+    pub user_lp_ata: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub oracle: Option<&'a AccountInfo>,
+    pub instructions_sysvar: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SwapAndDepositAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program, mint_x, mint_y, token_program_x, token_program_y, global_config, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [
+            mint_lp,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            user_lp_ata,
+            config,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        let mut optional = remaining.iter().map(|account| account.key().ne(&crate::ID).then_some(account));
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            mint_lp,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            user_lp_ata,
+            config,
+            token_program,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            global_config,
+            oracle: optional.next().flatten(),
+            instructions_sysvar: optional.next().flatten(),
+        })
+    }
+}
+
+pub struct SwapAndDepositInstructionData {
+    pub is_x: bool,
+    pub amount: u64,
+    pub min_lp: u64,
+    pub expiration: i64,
+    pub flags: u8,
+}
+
+impl TryFrom<&[u8]> for SwapAndDepositInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const SWAP_AND_DEPOSIT_DATA_LEN: usize = size_of::<bool>() + size_of::<u64>() * 2 + size_of::<i64>();
+        const SWAP_AND_DEPOSIT_DATA_LEN_WITH_FLAGS: usize = SWAP_AND_DEPOSIT_DATA_LEN + size_of::<u8>();
+
+        let flags = match data.len() {
+            SWAP_AND_DEPOSIT_DATA_LEN => 0,
+            SWAP_AND_DEPOSIT_DATA_LEN_WITH_FLAGS => data[SWAP_AND_DEPOSIT_DATA_LEN],
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let is_x = data[0] == 1;
+        let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let min_lp = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
+
+        if amount == 0 || min_lp == 0 || flags & !EXPIRATION_IN_SLOTS != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_expiration(expiration, flags)?;
+
+        Ok(Self {
+            is_x,
+            amount,
+            min_lp,
+            expiration,
+            flags,
+        })
+    }
+}
+
+pub struct SwapAndDeposit<'a> {
+    pub accounts: SwapAndDepositAccounts<'a>,
+    pub instruction_data: SwapAndDepositInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SwapAndDeposit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SwapAndDepositAccounts::try_from(accounts)?;
+        let instruction_data = SwapAndDepositInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SwapAndDeposit<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &44;
+
+    /// `sha256("global:swap_and_deposit")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [50, 21, 140, 74, 249, 6, 205, 24];
+
+    /// Index of the config among the accounts, see `Swap`
+    pub const CONFIG_INDEX: usize = 7;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let is_x = self.instruction_data.is_x;
+
+        // Size the swap from the current reserves
+        let swapped = {
+            let PoolAccounts { config, vault_x, vault_y, deployed } = validate_pool_accounts(
+                self.accounts.config,
+                (self.accounts.vault_x, self.accounts.vault_y),
+                (self.accounts.mint_x, self.accounts.mint_y),
+                (self.accounts.token_program_x, self.accounts.token_program_y),
+                &[AmmState::Initialized],
+            )?;
+
+            let (reserve_x, reserve_y) = config.reserves(vault_x.amount(), vault_y.amount(), deployed);
+            let reserve_in = if is_x { reserve_x } else { reserve_y };
+
+            zap_swap_amount(reserve_in, self.instruction_data.amount, config.fee())?
+        };
+
+        if swapped == 0 || swapped == self.instruction_data.amount {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (user_out, mint_out) = match is_x {
+            true => (self.accounts.user_y_ata, self.accounts.mint_y),
+            false => (self.accounts.user_x_ata, self.accounts.mint_x),
+        };
+        let user_out = CheckedUserAta::new(user_out, mint_out.key())?;
+        let user_lp = CheckedUserAta::new(self.accounts.user_lp_ata, self.accounts.mint_lp.key())?;
+
+        // The final LP bound covers the swap
+        let user_out_before = user_out.amount();
+
+        Swap {
+            accounts: SwapAccounts {
+                user: self.accounts.user,
+                user_x: self.accounts.user_x_ata,
+                user_y: self.accounts.user_y_ata,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                config: self.accounts.config,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                token_program_x: self.accounts.token_program_x,
+                token_program_y: self.accounts.token_program_y,
+                global_config: self.accounts.global_config,
+                host_fee: None,
+                oracle: self.accounts.oracle,
+                instructions_sysvar: self.accounts.instructions_sysvar,
+                mint_lp: Some(self.accounts.mint_lp),
+                memo_program: None,
+                trading_calendar: None,
+                insurance_vault: None,
+                tip_account: None,
+                system_program: None,
+                user_stats: None,
+            },
+            instruction_data: SwapInstructionData {
+                is_x,
+                amount: swapped,
+                min: 1,
+                expiration: self.instruction_data.expiration,
+                flags: self.instruction_data.flags,
+                tip: 0,
+                memo: &[],
+            },
+        }
+        .process()?;
+
+        let amount_out = user_out.amount().checked_sub(user_out_before).ok_or(AmmError::MathOverflow)?;
+        let rest = self.instruction_data.amount - swapped;
+        let (max_x, max_y) = if is_x { (rest, amount_out) } else { (amount_out, rest) };
+
+        // Deposit both sides for as much LP as they are worth, at least `min_lp`
+        let user_lp_before = user_lp.amount();
+
+        Deposit {
+            accounts: DepositAccounts {
+                user: self.accounts.user,
+                mint_lp: self.accounts.mint_lp,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                user_x_ata: self.accounts.user_x_ata,
+                user_y_ata: self.accounts.user_y_ata,
+                user_lp_ata: self.accounts.user_lp_ata,
+                config: self.accounts.config,
+                token_program: self.accounts.token_program,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                token_program_x: self.accounts.token_program_x,
+                token_program_y: self.accounts.token_program_y,
+                global_config: self.accounts.global_config,
+                position: None,
+                payer: self.accounts.user,
+            },
+            instruction_data: DepositInstructionData {
+                amount: self.instruction_data.min_lp,
+                max_x,
+                max_y,
+                expiration: self.instruction_data.expiration,
+                flags: DepositInstructionData::EXACT_AMOUNTS | self.instruction_data.flags,
+            },
+        }
+        .process()?;
+
+        let lp = user_lp.amount().checked_sub(user_lp_before).ok_or(AmmError::MathOverflow)?;
+
+        set_return_amounts(&[swapped, amount_out, lp]);
+
+        Ok(())
+    }
+}
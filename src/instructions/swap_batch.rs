@@ -0,0 +1,226 @@
+use crate::{
+    check_expiration, check_signer, check_writable, set_return_amounts, AmmError, CheckedUserAta, Swap, SwapAccounts,
+    SwapInstructionData,
+};
+use core::mem::size_of;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// Most swaps in a `SwapBatch`, one return amount each
+pub const MAX_BATCH_SWAPS: usize = 8;
+
+/// #SwapBatch
+///
+/// Run several independent swaps, each on its own pool with its own parameters, in one instruction
+///
+/// Each swap runs exactly as `Swap`, and the batch fails as a whole if any of them does. It saves
+/// the transaction space of repeating the shared accounts and the instruction headers, e.g. for a
+/// portfolio rebalancer. Pools needing a host fee, a memo, a `TradingHours` calendar or an
+/// `InsuranceFund` vault can't be swapped in a batch.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]        // Owner of the input accounts, or their delegate
+/// 2. global_config
+/// 3. instructions_sysvar          [optional]      // As in `Swap`, for every pool
+/// 4. ..                           // Accounts of each swap, see `SwapBatch::SWAP_ACCOUNTS`
+///
+/// Optional accounts can be skipped by passing the program id in their place. A batch holds up to
+/// `MAX_BATCH_SWAPS` swaps.
+///
+/// Parameters:
+///
+/// 1. expiration: i64,             // Shared by every swap, a slot with `EXPIRATION_IN_SLOTS`
+/// 2. flags: u8,                   // Shared by every swap, see the shared flags in `constants`
+/// 3. swaps: [..],                 // Per swap, in account order: is_x [bool], amount [u64], min [u64]
+///
+/// Return data: the amount received by the user from each swap, net of transfer fees
+pub struct SwapBatchAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub instructions_sysvar: Option<&'a AccountInfo>,
+    pub swaps: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SwapBatchAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, global_config, instructions_sysvar, swaps @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if swaps.is_empty() || swaps.len() % SwapBatch::SWAP_ACCOUNTS != 0 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        if swaps.len() / SwapBatch::SWAP_ACCOUNTS > MAX_BATCH_SWAPS {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        for swap in swaps.chunks_exact(SwapBatch::SWAP_ACCOUNTS) {
+            swap[..5].iter().try_for_each(check_writable)?;
+        }
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            global_config,
+            instructions_sysvar: instructions_sysvar.key().ne(&crate::ID).then_some(instructions_sysvar),
+            swaps,
+        })
+    }
+}
+
+pub struct SwapBatchInstructionData<'a> {
+    pub expiration: i64,
+    pub flags: u8,
+    /// `SwapBatch::SWAP_DATA_LEN` bytes per swap, checked by `Swap` as each one runs
+    pub swaps: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for SwapBatchInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const SWAP_BATCH_HEADER_LEN: usize = size_of::<i64>() + size_of::<u8>();
+
+        let Some(swaps) = data.get(SWAP_BATCH_HEADER_LEN..) else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        if swaps.is_empty() || swaps.len() % SwapBatch::SWAP_DATA_LEN != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let expiration = i64::from_le_bytes(data[0..8].try_into().unwrap());
+        let flags = data[8];
+
+        check_expiration(expiration, flags)?;
+
+        Ok(Self {
+            expiration,
+            flags,
+            swaps,
+        })
+    }
+}
+
+pub struct SwapBatch<'a> {
+    pub accounts: SwapBatchAccounts<'a>,
+    pub instruction_data: SwapBatchInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SwapBatch<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SwapBatchAccounts::try_from(accounts)?;
+        let instruction_data = SwapBatchInstructionData::try_from(data)?;
+
+        // One set of parameters per set of accounts
+        if accounts.swaps.len() / Self::SWAP_ACCOUNTS != instruction_data.swaps.len() / Self::SWAP_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SwapBatch<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &60;
+
+    /// `sha256("global:swap_batch")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [235, 224, 148, 207, 6, 216, 198, 55];
+
+    /// Accounts passed for each swap, in order: user_x [mut], user_y [mut], vault_x [mut], vault_y [mut],
+    /// config [mut], mint_x, mint_y, token_program_x, token_program_y, oracle [optional] and mint_lp
+    /// [optional], as in `Swap`
+    pub const SWAP_ACCOUNTS: usize = 11;
+
+    /// Parameters passed for each swap: is_x, amount and min
+    pub const SWAP_DATA_LEN: usize = size_of::<bool>() + size_of::<u64>() * 2;
+
+    /// Indexes of the config of each swap among the accounts, see `Swap`
+    pub const CONFIG_INDEXES: [usize; MAX_BATCH_SWAPS] = {
+        let mut indexes = [0; MAX_BATCH_SWAPS];
+        let mut index = 0;
+        while index < MAX_BATCH_SWAPS {
+            indexes[index] = 3 + index * Self::SWAP_ACCOUNTS + 4;
+            index += 1;
+        }
+        indexes
+    };
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut amounts_out = [0u64; MAX_BATCH_SWAPS];
+
+        let swaps = self
+            .accounts
+            .swaps
+            .chunks_exact(Self::SWAP_ACCOUNTS)
+            .zip(self.instruction_data.swaps.chunks_exact(Self::SWAP_DATA_LEN));
+
+        for (index, (accounts, params)) in swaps.enumerate() {
+            let [user_x, user_y, vault_x, vault_y, config, mint_x, mint_y, token_program_x, token_program_y, oracle, mint_lp] =
+                accounts
+            else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            // The `Swap` encoding of the parameters, with the shared expiration and flags
+            let mut data = [0u8; Self::SWAP_DATA_LEN + size_of::<i64>() + size_of::<u8>()];
+            let (params_data, shared_data) = data.split_at_mut(Self::SWAP_DATA_LEN);
+            params_data.copy_from_slice(params);
+            shared_data[..8].copy_from_slice(&self.instruction_data.expiration.to_le_bytes());
+            shared_data[8] = self.instruction_data.flags;
+
+            let instruction_data = SwapInstructionData::try_from(&data[..])?;
+
+            let (user_out, mint_out) = match instruction_data.is_x {
+                true => (user_y, mint_y),
+                false => (user_x, mint_x),
+            };
+            let user_out = CheckedUserAta::new(user_out, mint_out.key())?;
+            let user_out_before = user_out.amount();
+
+            Swap {
+                accounts: SwapAccounts {
+                    user: self.accounts.user,
+                    user_x,
+                    user_y,
+                    vault_x,
+                    vault_y,
+                    config,
+                    mint_x,
+                    mint_y,
+                    token_program_x,
+                    token_program_y,
+                    global_config: self.accounts.global_config,
+                    host_fee: None,
+                    oracle: oracle.key().ne(&crate::ID).then_some(oracle),
+                    instructions_sysvar: self.accounts.instructions_sysvar,
+                    mint_lp: mint_lp.key().ne(&crate::ID).then_some(mint_lp),
+                    memo_program: None,
+                    trading_calendar: None,
+                    insurance_vault: None,
+                    tip_account: None,
+                    system_program: None,
+                    user_stats: None,
+                },
+                instruction_data,
+            }
+            .process()?;
+
+            amounts_out[index] = user_out.amount().checked_sub(user_out_before).ok_or(AmmError::MathOverflow)?;
+        }
+
+        set_return_amounts(&amounts_out[..self.accounts.swaps.len() / Self::SWAP_ACCOUNTS]);
+
+        Ok(())
+    }
+}
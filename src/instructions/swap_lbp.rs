@@ -0,0 +1,241 @@
+use crate::state::{GlobalConfig, Lbp};
+use crate::{
+    check_signer, check_writable, derive_ata, set_return_amounts, AmmError, CheckedVault, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #SwapLbp
+///
+/// Buy or sell the token of a liquidity bootstrapping pool at its current weights, open to anyone
+/// between `Lbp::start_time` and `Lbp::end_time`
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]
+/// 2. lbp                          [mut]
+/// 3. lbp_token_vault              [mut]
+/// 4. lbp_quote_vault              [mut]
+/// 5. user_token_ata               [mut]
+/// 6. user_quote_ata               [mut]
+/// 7. mint
+/// 8. quote_mint
+/// 9. token_program                [executable]
+/// 10. quote_program               [executable]
+/// 11. global_config
+///
+/// Parameters:
+///
+/// 1. buy: bool,                   // Pay the quote token for the token, or the reverse
+/// 2. amount: u64,                 // Exact input
+/// 3. min_out: u64,
+///
+/// Return data:
+///
+/// 1. amount_in: u64,              // Received by the pool
+/// 2. amount_out: u64,             // Sent to the user
+pub struct SwapLbpAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub lbp: &'a AccountInfo,
+    pub lbp_token_vault: &'a AccountInfo,
+    pub lbp_quote_vault: &'a AccountInfo,
+    pub user_token_ata: &'a AccountInfo,
+    pub user_quote_ata: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub quote_mint: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SwapLbpAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, lbp, lbp_token_vault, lbp_quote_vault, user_token_ata, user_quote_ata, mint, quote_mint, token_program, quote_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [
+            lbp,
+            lbp_token_vault,
+            lbp_quote_vault,
+            user_token_ata,
+            user_quote_ata,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        // Deserialize the lbp account
+        let lbp_data = Lbp::load(lbp)?;
+
+        // Check the mints and their token programs
+        if mint.key().ne(lbp_data.mint())
+            || quote_mint.key().ne(lbp_data.quote_mint())
+            || token_program.key().ne(lbp_data.token_program())
+            || quote_program.key().ne(lbp_data.quote_program())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check if the lbp vaults are valid
+        if derive_ata(lbp.key(), token_program.key(), mint.key()).ne(lbp_token_vault.key())
+            || derive_ata(lbp.key(), quote_program.key(), quote_mint.key()).ne(lbp_quote_vault.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            lbp,
+            lbp_token_vault,
+            lbp_quote_vault,
+            user_token_ata,
+            user_quote_ata,
+            mint,
+            quote_mint,
+            global_config,
+        })
+    }
+}
+
+pub struct SwapLbpInstructionData {
+    pub buy: bool,
+    pub amount: u64,
+    pub min_out: u64,
+}
+
+impl TryFrom<&[u8]> for SwapLbpInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u8>() + size_of::<u64>() * 2)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let buy = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let min_out = u64::from_le_bytes(data[9..17].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { buy, amount, min_out })
+    }
+}
+
+pub struct SwapLbp<'a> {
+    pub accounts: SwapLbpAccounts<'a>,
+    pub instruction_data: SwapLbpInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SwapLbp<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SwapLbpAccounts::try_from(accounts)?;
+        let instruction_data = SwapLbpInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SwapLbp<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &52;
+
+    /// `sha256("global:swap_lbp")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [182, 172, 53, 96, 172, 146, 179, 246];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let buy = self.instruction_data.buy;
+
+        let (amount_in, amount_out) = {
+            let lbp = Lbp::load(self.accounts.lbp)?;
+
+            if !lbp.is_trading(now) {
+                return Err(AmmError::LbpNotTrading.into());
+            }
+
+            let token_side = (
+                self.accounts.lbp_token_vault,
+                self.accounts.user_token_ata,
+                self.accounts.mint,
+                lbp.token_decimals(),
+                lbp.token_program(),
+            );
+            let quote_side = (
+                self.accounts.lbp_quote_vault,
+                self.accounts.user_quote_ata,
+                self.accounts.quote_mint,
+                lbp.quote_decimals(),
+                lbp.quote_program(),
+            );
+            let (side_in, side_out) = match buy {
+                true => (quote_side, token_side),
+                false => (token_side, quote_side),
+            };
+            let (vault_in, user_in, mint_in, decimals_in, program_in) = side_in;
+            let (vault_out, user_out, mint_out, decimals_out, program_out) = side_out;
+
+            let checked_vault_in = CheckedVault::new(vault_in, mint_in.key(), self.accounts.lbp.key(), program_in)?;
+            let amount_before = checked_vault_in.amount();
+
+            TransferChecked {
+                from: user_in,
+                mint: mint_in,
+                to: vault_in,
+                authority: self.accounts.user,
+                amount: self.instruction_data.amount,
+                decimals: decimals_in,
+                token_program: program_in,
+            }
+            .invoke()?;
+
+            // Price the swap on what the vault received, in case of transfer fees
+            let amount_in = checked_vault_in.amount() - amount_before;
+            let amount_out = lbp.quote_swap(buy, amount_in, now)?;
+
+            if amount_out == 0 || amount_out < self.instruction_data.min_out {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let lbp_bump = lbp.bump();
+            let lbp_seeds = [Seed::from(b"lbp"), Seed::from(lbp.mint()), Seed::from(&lbp_bump)];
+
+            TransferChecked {
+                from: vault_out,
+                mint: mint_out,
+                to: user_out,
+                authority: self.accounts.lbp,
+                amount: amount_out,
+                decimals: decimals_out,
+                token_program: program_out,
+            }
+            .invoke_signed(&[Signer::from(&lbp_seeds)])?;
+
+            (amount_in, amount_out)
+        };
+
+        Lbp::load_mut(self.accounts.lbp)?.record_swap(buy, amount_in, amount_out)?;
+
+        set_return_amounts(&[amount_in, amount_out]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,333 @@
+use crate::state::{Config, GlobalConfig};
+use crate::{
+    check_ed25519_signature, check_expiration, check_signer, check_writable, derive_ata, derive_maker_escrow,
+    set_return_amounts, AmmState, TransferChecked,
+};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::pubkey::{find_program_address, Pubkey};
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_system::instructions::CreateAccount;
+
+/// #SwapWithQuote
+///
+/// Fill a quote signed by a market maker (RFQ) from the maker's escrow instead of the curve
+///
+/// The instruction right before this one must be an ed25519 program instruction verifying the
+/// maker's signature over `SwapWithQuoteInstructionData::message`. The escrow is the set of
+/// token accounts owned by the `[b"maker", config, maker]` PDA, and each quote can be filled
+/// once, by creating the `[b"quote", maker, nonce]` account.
+///
+/// Accounts:
+///
+/// 1. taker:                       [signer]        // Owner of the input account, or its delegate
+/// 2. payer:                       [signer, mut]   // Pays the quote_fill rent
+/// 3. maker
+/// 4. config
+/// 5. maker_escrow                 // PDA owning the escrow token accounts
+/// 6. escrow_x                     [mut]   // X ATA of maker_escrow
+/// 7. escrow_y                     [mut]   // Y ATA of maker_escrow
+/// 8. taker_x                      [mut]
+/// 9. taker_y                      [mut]
+/// 10. quote_fill                  [init]
+/// 11. mint_x
+/// 12. mint_y
+/// 13. token_program_x             [executable]
+/// 14. token_program_y             [executable]
+/// 15. instructions_sysvar
+/// 16. global_config
+/// 17. system_program              [executable]
+///
+/// Parameters:
+///
+/// 1. is_x: bool,                  // The taker sells X
+/// 2. amount_in: u64,              // Exact amount the taker pays into the escrow
+/// 3. amount_out: u64,             // Exact amount the taker receives from the escrow
+/// 4. expiration: i64,             // Unix timestamp
+/// 5. nonce: u64,
+///
+/// Return data:
+///
+/// 1. amount_in: u64,
+/// 2. amount_out: u64,
+pub struct SwapWithQuoteAccounts<'a> {
+    pub taker: &'a AccountInfo,
+    pub payer: &'a AccountInfo,
+    pub maker: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub maker_escrow: &'a AccountInfo,
+    pub escrow_x: &'a AccountInfo,
+    pub escrow_y: &'a AccountInfo,
+    pub taker_x: &'a AccountInfo,
+    pub taker_y: &'a AccountInfo,
+    pub quote_fill: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub instructions_sysvar: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SwapWithQuoteAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [taker, payer, maker, config, maker_escrow, escrow_x, escrow_y, taker_x, taker_y, quote_fill, mint_x, mint_y, token_program_x, token_program_y, instructions_sysvar, global_config, _system_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(taker)?;
+        check_signer(payer)?;
+        [payer, escrow_x, escrow_y, taker_x, taker_y, quote_fill].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            taker,
+            payer,
+            maker,
+            config,
+            maker_escrow,
+            escrow_x,
+            escrow_y,
+            taker_x,
+            taker_y,
+            quote_fill,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            instructions_sysvar,
+            global_config,
+        })
+    }
+}
+
+pub struct SwapWithQuoteInstructionData {
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub expiration: i64,
+    pub nonce: u64,
+}
+
+impl SwapWithQuoteInstructionData {
+    /// Size of the message signed by the maker
+    pub const MESSAGE_LEN: usize = size_of::<Pubkey>() + size_of::<u8>() + size_of::<u64>() * 4;
+
+    /// The message signed by the maker: config [32] | is_x [1] | amount_in [8] | amount_out [8] |
+    /// expiration [8] | nonce [8]
+    pub fn message(&self, config: &Pubkey) -> [u8; Self::MESSAGE_LEN] {
+        let mut message = [0u8; Self::MESSAGE_LEN];
+        message[0..32].copy_from_slice(config);
+        message[32] = self.is_x as u8;
+        message[33..41].copy_from_slice(&self.amount_in.to_le_bytes());
+        message[41..49].copy_from_slice(&self.amount_out.to_le_bytes());
+        message[49..57].copy_from_slice(&self.expiration.to_le_bytes());
+        message[57..65].copy_from_slice(&self.nonce.to_le_bytes());
+        message
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for SwapWithQuoteInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u8>() + size_of::<u64>() * 4)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let is_x = data[0] > 0;
+        let amount_in = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let amount_out = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
+        let nonce = u64::from_le_bytes(data[25..33].try_into().unwrap());
+
+        if amount_in == 0 || amount_out == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_expiration(expiration, 0)?;
+
+        Ok(Self {
+            is_x,
+            amount_in,
+            amount_out,
+            expiration,
+            nonce,
+        })
+    }
+}
+
+pub struct SwapWithQuote<'a> {
+    pub accounts: SwapWithQuoteAccounts<'a>,
+    pub instruction_data: SwapWithQuoteInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SwapWithQuote<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = SwapWithQuoteAccounts::try_from(accounts)?;
+        let instruction_data = SwapWithQuoteInstructionData::try_from(data)?;
+
+        // Return the initialized struct
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SwapWithQuote<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &18;
+
+    /// `sha256("global:swap_with_quote")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [105, 33, 241, 155, 205, 248, 138, 64];
+
+    pub fn process(&mut self) -> ProgramResult {
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        // Deserialize the config account
+        let config = Config::load(self.accounts.config)?;
+
+        // Quotes are only filled on live pools
+        if config.amm_state()?.ne(&AmmState::Initialized) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check the pool mints and the token program of each side
+        if self.accounts.mint_x.key().ne(config.mint_x())
+            || self.accounts.mint_y.key().ne(config.mint_y())
+            || self.accounts.token_program_x.key().ne(config.token_program_x())
+            || self.accounts.token_program_y.key().ne(config.token_program_y())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check the maker signed this exact quote
+        check_ed25519_signature(
+            self.accounts.instructions_sysvar,
+            self.accounts.maker.key(),
+            &self.instruction_data.message(self.accounts.config.key()),
+        )?;
+
+        // Check if the maker_escrow is valid
+        let (maker_escrow, maker_escrow_bump) =
+            derive_maker_escrow(self.accounts.config.key(), self.accounts.maker.key());
+
+        if maker_escrow.ne(self.accounts.maker_escrow.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check the escrow accounts, so the taker can't pay into an account of their own
+        for (escrow, token_program, mint) in [
+            (self.accounts.escrow_x, self.accounts.token_program_x, config.mint_x()),
+            (self.accounts.escrow_y, self.accounts.token_program_y, config.mint_y()),
+        ] {
+            if derive_ata(&maker_escrow, token_program.key(), mint).ne(escrow.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        // Burn the quote, creating the fill account fails if it was already used
+        let nonce = self.instruction_data.nonce.to_le_bytes();
+        let (quote_fill, quote_fill_bump) = find_program_address(
+            &[b"quote", self.accounts.maker.key(), &nonce],
+            &crate::ID,
+        );
+
+        if quote_fill.ne(self.accounts.quote_fill.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let quote_fill_bump = [quote_fill_bump];
+        let quote_fill_seeds = [
+            Seed::from(b"quote"),
+            Seed::from(self.accounts.maker.key()),
+            Seed::from(&nonce),
+            Seed::from(&quote_fill_bump),
+        ];
+
+        CreateAccount {
+            from: self.accounts.payer,
+            to: self.accounts.quote_fill,
+            lamports: Rent::get()?.minimum_balance(0),
+            space: 0,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&quote_fill_seeds)])?;
+
+        // Route the transfers by direction
+        let (taker_in, escrow_in, mint_in, decimals_in, token_program_in) = match self.instruction_data.is_x {
+            true => (
+                self.accounts.taker_x,
+                self.accounts.escrow_x,
+                self.accounts.mint_x,
+                config.decimals_x(),
+                config.token_program_x(),
+            ),
+            false => (
+                self.accounts.taker_y,
+                self.accounts.escrow_y,
+                self.accounts.mint_y,
+                config.decimals_y(),
+                config.token_program_y(),
+            ),
+        };
+        let (escrow_out, taker_out, mint_out, decimals_out, token_program_out) = match self.instruction_data.is_x {
+            true => (
+                self.accounts.escrow_y,
+                self.accounts.taker_y,
+                self.accounts.mint_y,
+                config.decimals_y(),
+                config.token_program_y(),
+            ),
+            false => (
+                self.accounts.escrow_x,
+                self.accounts.taker_x,
+                self.accounts.mint_x,
+                config.decimals_x(),
+                config.token_program_x(),
+            ),
+        };
+
+        TransferChecked {
+            from: taker_in,
+            mint: mint_in,
+            to: escrow_in,
+            authority: self.accounts.taker,
+            amount: self.instruction_data.amount_in,
+            decimals: decimals_in,
+            token_program: token_program_in,
+        }
+        .invoke()?;
+
+        let maker_escrow_bump = [maker_escrow_bump];
+        let maker_escrow_seeds = [
+            Seed::from(b"maker"),
+            Seed::from(self.accounts.config.key()),
+            Seed::from(self.accounts.maker.key()),
+            Seed::from(&maker_escrow_bump),
+        ];
+
+        TransferChecked {
+            from: escrow_out,
+            mint: mint_out,
+            to: taker_out,
+            authority: self.accounts.maker_escrow,
+            amount: self.instruction_data.amount_out,
+            decimals: decimals_out,
+            token_program: token_program_out,
+        }
+        .invoke_signed(&[Signer::from(&maker_escrow_seeds)])?;
+
+        set_return_amounts(&[self.instruction_data.amount_in, self.instruction_data.amount_out]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,386 @@
+use crate::state::Config;
+use crate::{
+    check_signer, check_writable, Deposit, DepositAccounts, DepositInstructionData, Swap, SwapAccounts,
+    SwapInstructionData, Withdraw, WithdrawAccounts, WithdrawInstructionData,
+};
+use core::mem::size_of;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #TokenSwap
+///
+/// Run the legacy SPL Token-Swap `Swap`, `DepositAllTokenTypes` and `WithdrawAllTokenTypes`
+/// encodings against the native `Swap`, `Deposit` and `Withdraw`
+///
+/// The raw Token-Swap data is dispatched as is, its tag and length keep it clear of the 8-byte
+/// discriminators, see `is_token_swap`. With `legacy-discriminators` the tags are taken by legacy
+/// discriminators, so the data, its tag included, follows the `TokenSwap` discriminator instead. The
+/// accounts are those of Token-Swap's Token-2022 aware layout, with the config as both the swap and
+/// the swap authority, followed by the global config where the native instruction reads it. Offers
+/// never expire, and the native rules on zero amounts apply. Pools with `Config::FLAG_ORACLE_GUARD`
+/// or `Config::FLAG_SINGLE_SWAP` can't be swapped through it.
+///
+/// Swap accounts:
+///
+/// 1. swap                         [mut]           // The config
+/// 2. swap_authority                               // The config
+/// 3. user_transfer_authority      [signer]
+/// 4. source                       [mut]
+/// 5. swap_source                  [mut]
+/// 6. swap_destination             [mut]
+/// 7. destination                  [mut]
+/// 8. pool_mint                                    // The LP mint, for pools with `FeeGrowth`
+/// 9. pool_fee_account                             // Unused, there is no fee account
+/// 10. source_mint
+/// 11. destination_mint
+/// 12. source_token_program        [executable]
+/// 13. destination_token_program   [executable]
+/// 14. pool_token_program          [executable]
+/// 15. host_fee                    [mut, optional] // The program id without a host fee
+/// 16. global_config                               // Past Token-Swap's accounts
+///
+/// DepositAllTokenTypes accounts:
+///
+/// 1. swap                         [mut]           // The config
+/// 2. swap_authority                               // The config
+/// 3. user_transfer_authority      [signer]
+/// 4. source_a                     [mut]
+/// 5. source_b                     [mut]
+/// 6. swap_a                       [mut]
+/// 7. swap_b                       [mut]
+/// 8. pool_mint                    [mut]
+/// 9. destination                  [mut]
+/// 10. mint_a
+/// 11. mint_b
+/// 12. token_program_a             [executable]
+/// 13. token_program_b             [executable]
+/// 14. pool_token_program          [executable]  // LP token program, see `Config::lp_token_program`
+/// 15. global_config                               // Past Token-Swap's accounts
+///
+/// WithdrawAllTokenTypes accounts:
+///
+/// 1. swap                                         // The config
+/// 2. swap_authority                               // The config
+/// 3. user_transfer_authority      [signer]
+/// 4. pool_mint                    [mut]
+/// 5. source                       [mut]
+/// 6. swap_a                       [mut]
+/// 7. swap_b                       [mut]
+/// 8. destination_a                [mut]
+/// 9. destination_b                [mut]
+/// 10. fee_account                                 // Unused, there is no withdrawal fee
+/// 11. mint_a
+/// 12. mint_b
+/// 13. pool_token_program          [executable]  // LP token program, see `Config::lp_token_program`
+/// 14. token_program_a             [executable]
+/// 15. token_program_b             [executable]
+///
+/// Parameters:
+///
+/// 1. tag:                         [u8]    // `SWAP`, `DEPOSIT_ALL_TOKEN_TYPES` or `WITHDRAW_ALL_TOKEN_TYPES`
+/// 2. amount:                      [u64]   // amount_in or pool_token_amount
+/// 3. a:                           [u64]   // minimum_amount_out, maximum_token_a_amount or minimum_token_a_amount
+/// 4. b:                           [u64]   // maximum_token_b_amount or minimum_token_b_amount, not with `SWAP`
+///
+/// Return data:
+///
+/// The return data of `Swap`, `Deposit` or `Withdraw`
+pub enum TokenSwap<'a> {
+    Swap(Swap<'a>),
+    Deposit(Deposit<'a>),
+    Withdraw(Withdraw<'a>),
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for TokenSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        match data.split_first() {
+            Some((&Self::SWAP, data)) => Self::swap(data, accounts),
+            Some((&Self::DEPOSIT_ALL_TOKEN_TYPES, data)) => Self::deposit(data, accounts),
+            Some((&Self::WITHDRAW_ALL_TOKEN_TYPES, data)) => Self::withdraw(data, accounts),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+impl<'a> TokenSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &33;
+
+    /// `sha256("global:token_swap")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [187, 192, 118, 212, 62, 109, 28, 213];
+
+    /// Token-Swap instruction tags
+    pub const SWAP: u8 = 1;
+    pub const DEPOSIT_ALL_TOKEN_TYPES: u8 = 2;
+    pub const WITHDRAW_ALL_TOKEN_TYPES: u8 = 3;
+
+    /// Length of the raw `SWAP` data, its tag included
+    pub const SWAP_DATA_LEN: usize = size_of::<u8>() + size_of::<u64>() * 2;
+
+    /// Length of the raw `DEPOSIT_ALL_TOKEN_TYPES` and `WITHDRAW_ALL_TOKEN_TYPES` data, its tag included
+    pub const LIQUIDITY_DATA_LEN: usize = size_of::<u8>() + size_of::<u64>() * 3;
+
+    /// Whether `data` is a raw Token-Swap instruction
+    ///
+    /// No 8-byte discriminator starts with a Token-Swap tag, so the raw data can't be mistaken for
+    /// another instruction, but the tags are legacy single-byte discriminators.
+    pub fn is_token_swap(data: &[u8]) -> bool {
+        match data {
+            [Self::SWAP, ..] => data.len() == Self::SWAP_DATA_LEN,
+            [Self::DEPOSIT_ALL_TOKEN_TYPES | Self::WITHDRAW_ALL_TOKEN_TYPES, ..] => {
+                data.len() == Self::LIQUIDITY_DATA_LEN
+            }
+            _ => false,
+        }
+    }
+
+    pub fn process(&mut self) -> ProgramResult {
+        match self {
+            Self::Swap(swap) => swap.process(),
+            Self::Deposit(deposit) => deposit.process(),
+            Self::Withdraw(withdraw) => withdraw.process(),
+        }
+    }
+
+    /// Read the three `u64` of every supported Token-Swap instruction
+    fn amounts(data: &[u8]) -> Result<(u64, u64, u64), ProgramError> {
+        if data.len() != size_of::<u64>() * 3 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let a = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let b = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let c = u64::from_le_bytes(data[16..24].try_into().unwrap());
+
+        if a == 0 || b == 0 || c == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok((a, b, c))
+    }
+
+    fn swap(data: &[u8], accounts: &'a [AccountInfo]) -> Result<Self, ProgramError> {
+        let [swap, swap_authority, user_transfer_authority, source, swap_source, swap_destination, destination, pool_mint, _pool_fee_account, source_mint, destination_mint, source_token_program, destination_token_program, _pool_token_program, host_fee, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user_transfer_authority)?;
+        [swap, source, swap_source, swap_destination, destination].into_iter().try_for_each(check_writable)?;
+
+        if swap_authority.key().ne(swap.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Token-Swap only has the amount in and the minimum out
+        if data.len() != size_of::<u64>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let min = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        if amount == 0 || min == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Find the direction from the source mint
+        let is_x = source_mint.key().eq(Config::load(swap)?.mint_x());
+
+        let (user_x, user_y, vault_x, vault_y, mint_x, mint_y, token_program_x, token_program_y) = match is_x {
+            true => (
+                source,
+                destination,
+                swap_source,
+                swap_destination,
+                source_mint,
+                destination_mint,
+                source_token_program,
+                destination_token_program,
+            ),
+            false => (
+                destination,
+                source,
+                swap_destination,
+                swap_source,
+                destination_mint,
+                source_mint,
+                destination_token_program,
+                source_token_program,
+            ),
+        };
+
+        let host_fee = Some(host_fee).filter(|host_fee| host_fee.key().ne(&crate::ID));
+
+        if let Some(host_fee) = host_fee {
+            check_writable(host_fee)?;
+        }
+
+        Ok(Self::Swap(Swap {
+            accounts: SwapAccounts {
+                user: user_transfer_authority,
+                user_x,
+                user_y,
+                vault_x,
+                vault_y,
+                config: swap,
+                mint_x,
+                mint_y,
+                token_program_x,
+                token_program_y,
+                global_config,
+                host_fee,
+                oracle: None,
+                instructions_sysvar: None,
+                mint_lp: Some(pool_mint),
+                memo_program: None,
+                trading_calendar: None,
+                insurance_vault: None,
+                tip_account: None,
+                system_program: None,
+                user_stats: None,
+            },
+            instruction_data: SwapInstructionData {
+                is_x,
+                amount,
+                min,
+                expiration: i64::MAX,
+                flags: 0,
+                tip: 0,
+                memo: &[],
+            },
+        }))
+    }
+
+    fn deposit(data: &[u8], accounts: &'a [AccountInfo]) -> Result<Self, ProgramError> {
+        let [swap, swap_authority, user_transfer_authority, source_a, source_b, swap_a, swap_b, pool_mint, destination, mint_a, mint_b, token_program_a, token_program_b, pool_token_program, global_config] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user_transfer_authority)?;
+        [swap, source_a, source_b, swap_a, swap_b, pool_mint, destination]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        if swap_authority.key().ne(swap.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (amount, max_x, max_y) = Self::amounts(data)?;
+
+        Ok(Self::Deposit(Deposit {
+            accounts: DepositAccounts {
+                user: user_transfer_authority,
+                mint_lp: pool_mint,
+                vault_x: swap_a,
+                vault_y: swap_b,
+                user_x_ata: source_a,
+                user_y_ata: source_b,
+                user_lp_ata: destination,
+                config: swap,
+                token_program: pool_token_program,
+                mint_x: mint_a,
+                mint_y: mint_b,
+                token_program_x: token_program_a,
+                token_program_y: token_program_b,
+                global_config,
+                position: None,
+                payer: user_transfer_authority,
+            },
+            instruction_data: DepositInstructionData {
+                amount,
+                max_x,
+                max_y,
+                expiration: i64::MAX,
+                flags: 0,
+            },
+        }))
+    }
+
+    fn withdraw(data: &[u8], accounts: &'a [AccountInfo]) -> Result<Self, ProgramError> {
+        let [swap, swap_authority, user_transfer_authority, pool_mint, source, swap_a, swap_b, destination_a, destination_b, _fee_account, mint_a, mint_b, pool_token_program, token_program_a, token_program_b] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user_transfer_authority)?;
+        [pool_mint, source, swap_a, swap_b, destination_a, destination_b]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        if swap_authority.key().ne(swap.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (amount, min_x, min_y) = Self::amounts(data)?;
+
+        Ok(Self::Withdraw(Withdraw {
+            accounts: WithdrawAccounts {
+                user: user_transfer_authority,
+                mint_lp: pool_mint,
+                vault_x: swap_a,
+                vault_y: swap_b,
+                user_x_ata: destination_a,
+                user_y_ata: destination_b,
+                user_lp_ata: source,
+                config: swap,
+                token_program: pool_token_program,
+                mint_x: mint_a,
+                mint_y: mint_b,
+                token_program_x: token_program_a,
+                token_program_y: token_program_b,
+                position: None,
+            },
+            instruction_data: WithdrawInstructionData {
+                amount,
+                min_x,
+                min_y,
+                expiration: i64::MAX,
+                flags: 0,
+            },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RaydiumSwap;
+
+    #[test]
+    fn is_token_swap_checks_the_tag_and_length() {
+        let swap = [&[TokenSwap::SWAP][..], &[1; 16]].concat();
+        let deposit = [&[TokenSwap::DEPOSIT_ALL_TOKEN_TYPES][..], &[1; 24]].concat();
+        let withdraw = [&[TokenSwap::WITHDRAW_ALL_TOKEN_TYPES][..], &[1; 24]].concat();
+
+        assert!(TokenSwap::is_token_swap(&swap));
+        assert!(TokenSwap::is_token_swap(&deposit));
+        assert!(TokenSwap::is_token_swap(&withdraw));
+
+        assert!(!TokenSwap::is_token_swap(&swap[..swap.len() - 1]));
+        assert!(!TokenSwap::is_token_swap(&deposit[..TokenSwap::SWAP_DATA_LEN]));
+        assert!(!TokenSwap::is_token_swap(&[&[0][..], &[1; 16]].concat()));
+        assert!(!TokenSwap::is_token_swap(&[&[4][..], &[1; 24]].concat()));
+        assert!(!TokenSwap::is_token_swap(&[]));
+    }
+
+    #[test]
+    fn token_swap_tags_start_no_discriminator() {
+        let tags = [TokenSwap::SWAP, TokenSwap::DEPOSIT_ALL_TOKEN_TYPES, TokenSwap::WITHDRAW_ALL_TOKEN_TYPES];
+        let discriminators = crate::DISCRIMINATORS
+            .iter()
+            .map(|(hash, _)| *hash)
+            .chain([&RaydiumSwap::SWAP_BASE_INPUT, &RaydiumSwap::SWAP_BASE_OUTPUT]);
+
+        for discriminator in discriminators {
+            assert!(!tags.contains(&discriminator[0]), "{discriminator:?}");
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::state::{FeeDistributor, Staker};
+use crate::{check_signer, check_writable, derive_ata, AmmError, TransferChecked};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #UnstakeGovernance
+///
+/// Withdraw governance tokens staked in a `FeeDistributor`, once a full epoch went by since the
+/// stake was last added
+///
+/// Rewards earned so far are settled first and stay claimable.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer]
+/// 2. fee_distributor              [mut]
+/// 3. staker                       [mut]   // [b"staker", fee_distributor, user]
+/// 4. user_governance_ata          [mut]
+/// 5. stake_vault                  [mut]   // ATA of fee_distributor for the governance mint
+/// 6. governance_mint
+/// 7. token_program                [executable]
+///
+/// Parameters:
+///
+/// 1. amount: u64,                 // Amount of governance token to unstake
+pub struct UnstakeGovernanceAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub fee_distributor: &'a AccountInfo,
+    pub staker: &'a AccountInfo,
+    pub user_governance_ata: &'a AccountInfo,
+    pub stake_vault: &'a AccountInfo,
+    pub governance_mint: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UnstakeGovernanceAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, fee_distributor, staker, user_governance_ata, stake_vault, governance_mint, _token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [fee_distributor, staker, user_governance_ata, stake_vault]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            fee_distributor,
+            staker,
+            user_governance_ata,
+            stake_vault,
+            governance_mint,
+        })
+    }
+}
+
+pub struct UnstakeGovernanceInstructionData {
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for UnstakeGovernanceInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u64>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data.try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount })
+    }
+}
+
+pub struct UnstakeGovernance<'a> {
+    pub accounts: UnstakeGovernanceAccounts<'a>,
+    pub instruction_data: UnstakeGovernanceInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UnstakeGovernance<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = UnstakeGovernanceAccounts::try_from(accounts)?;
+        let instruction_data = UnstakeGovernanceInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UnstakeGovernance<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &38;
+
+    /// `sha256("global:unstake_governance")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [71, 37, 255, 197, 176, 134, 149, 39];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut fee_distributor = FeeDistributor::load_mut(self.accounts.fee_distributor)?;
+        let mut staker = Staker::load_mut(self.accounts.staker)?;
+
+        // Check the staker, the governance mint and the stake vault
+        if staker.fee_distributor().ne(self.accounts.fee_distributor.key())
+            || staker.owner().ne(self.accounts.user.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if self.accounts.governance_mint.key().ne(fee_distributor.governance_mint())
+            || derive_ata(
+                self.accounts.fee_distributor.key(),
+                fee_distributor.governance_token_program(),
+                fee_distributor.governance_mint(),
+            )
+            .ne(self.accounts.stake_vault.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !staker.is_unlocked(Clock::get()?.epoch) {
+            return Err(AmmError::StakeLocked.into());
+        }
+
+        // Settle at the current rate before the stake changes
+        staker.settle(fee_distributor.reward_per_token());
+        staker.unstake(self.instruction_data.amount)?;
+        fee_distributor.unstake(self.instruction_data.amount);
+
+        // Release the mutable borrow before the distributor signs the transfer
+        drop(fee_distributor);
+        let fee_distributor = FeeDistributor::load(self.accounts.fee_distributor)?;
+
+        // Create the signer seeds
+        let bump = fee_distributor.bump();
+        let seeds = [
+            Seed::from(b"fee_distributor"),
+            Seed::from(fee_distributor.governance_mint()),
+            Seed::from(fee_distributor.reward_mint()),
+            Seed::from(&bump),
+        ];
+
+        TransferChecked {
+            from: self.accounts.stake_vault,
+            mint: self.accounts.governance_mint,
+            to: self.accounts.user_governance_ata,
+            authority: self.accounts.fee_distributor,
+            amount: self.instruction_data.amount,
+            decimals: fee_distributor.governance_decimals(),
+            token_program: fee_distributor.governance_token_program(),
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        Ok(())
+    }
+}
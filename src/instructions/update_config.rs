@@ -1,4 +1,8 @@
-use crate::state::Config;
+use crate::state::{
+    find_extension, get_extension, get_extension_mut, AmmState, Config, ConfigExtension, GlobalConfig, OracleState,
+    VaultStrategy,
+};
+use crate::{bump_sequence, check_signer, check_writable, AmmError};
 use core::mem::size_of;
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
 
@@ -10,20 +14,30 @@ use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramR
 ///
 /// 1. authority:                 [signer]
 /// 2. config:                      [mut]
+/// 3. global_config
 ///
+/// Parameters:
+///
+/// 1. field:                       [u8]    // See `ConfigField`
+/// 2. value:                       [..]    // Encoding depends on the field
 pub struct UpdateConfigAccounts<'a> {
     pub authority: &'a AccountInfo,
     pub config: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for UpdateConfigAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [authority, config] = accounts else {
+        let [authority, config, global_config] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        check_writable(config)?;
+
         // Deserialize the config account
         let config_data = Config::load(config)?;
 
@@ -32,13 +46,35 @@ impl<'a> TryFrom<&'a [AccountInfo]> for UpdateConfigAccounts<'a> {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        // Check if the authority has signed the transaction
-        if !authority.is_signer() {
-            return Err(ProgramError::InvalidAccountData);
-        }
-
         // Return the accounts
-        Ok(Self { authority, config })
+        Ok(Self { authority, config, global_config })
+    }
+}
+
+/// Config field selected by the first byte of the UpdateConfig data
+#[repr(u8)]
+pub enum ConfigField {
+    Status = 0u8,
+    Fee = 1u8,
+    Authority = 2u8,
+    HostFee = 3u8,
+    OracleGuard = 4u8,
+    Extension = 5u8,
+}
+
+impl TryFrom<u8> for ConfigField {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ConfigField::Status),
+            1 => Ok(ConfigField::Fee),
+            2 => Ok(ConfigField::Authority),
+            3 => Ok(ConfigField::HostFee),
+            4 => Ok(ConfigField::OracleGuard),
+            5 => Ok(ConfigField::Extension),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
     }
 }
 
@@ -51,7 +87,9 @@ impl<'a> TryFrom<&'a [u8]> for UpdateConfigAuthorityInstructionData {
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         Ok(Self {
-            authority: data.try_into().unwrap(),
+            authority: data
+                .try_into()
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
         })
     }
 }
@@ -65,7 +103,10 @@ impl<'a> TryFrom<&'a [u8]> for UpdateConfigFeeInstructionData {
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         Ok(Self {
-            fee: u16::from_le_bytes(data.try_into().unwrap()),
+            fee: u16::from_le_bytes(
+                data.try_into()
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            ),
         })
     }
 }
@@ -78,10 +119,76 @@ impl<'a> TryFrom<&'a [u8]> for UpdateConfigStatusInstructionData {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u8>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
         Ok(Self { status: data[0] })
     }
 }
 
+pub struct UpdateConfigHostFeeInstructionData {
+    pub host_fee_bps: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateConfigHostFeeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            host_fee_bps: u16::from_le_bytes(
+                data.try_into()
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            ),
+        })
+    }
+}
+
+pub struct UpdateConfigOracleGuardInstructionData {
+    pub oracle: [u8; 32],
+    pub max_deviation_bps: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateConfigOracleGuardInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<[u8; 32]>() + size_of::<u16>())) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            oracle: data[0..32].try_into().unwrap(),
+            max_deviation_bps: u16::from_le_bytes(data[32..34].try_into().unwrap()),
+        })
+    }
+}
+
+pub struct UpdateConfigExtensionInstructionData<'a> {
+    pub extension_type: ConfigExtension,
+    pub value: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateConfigExtensionInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().lt(&size_of::<u16>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let extension_type = ConfigExtension::try_from(u16::from_le_bytes(data[0..2].try_into().unwrap()))?;
+        let value = &data[2..];
+
+        extension_type.check_value(value)?;
+
+        Ok(Self {
+            extension_type,
+            value,
+        })
+    }
+}
+
 pub struct UpdateConfig<'a> {
     pub accounts: UpdateConfigAccounts<'a>,
     pub data: &'a [u8],
@@ -101,21 +208,33 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpdateConfig<'a> {
 impl<'a> UpdateConfig<'a> {
     pub const DISCRIMINATOR: &'a u8 = &4;
 
+    /// `sha256("global:update_config")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [29, 158, 252, 191, 10, 83, 219, 99];
+
     pub fn process(&mut self) -> ProgramResult {
-        match self.data.len() {
-            len if len == size_of::<UpdateConfigStatusInstructionData>() => {
-                self.process_update_status()
-            }
-            len if len == size_of::<UpdateConfigFeeInstructionData>() => self.process_update_fee(),
-            len if len == size_of::<UpdateConfigAuthorityInstructionData>() => {
-                self.process_update_authority()
-            }
-            _ => Err(ProgramError::InvalidInstructionData),
+        GlobalConfig::load_unfrozen(self.accounts.global_config)?;
+
+        let Some((field, data)) = self.data.split_first() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        match ConfigField::try_from(*field)? {
+            ConfigField::Status => self.process_update_status(data)?,
+            ConfigField::Fee => self.process_update_fee(data)?,
+            ConfigField::Authority => self.process_update_authority(data)?,
+            ConfigField::HostFee => self.process_update_host_fee(data)?,
+            ConfigField::OracleGuard => self.process_update_oracle_guard(data)?,
+            ConfigField::Extension => self.process_update_extension(data)?,
         }
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        Ok(())
     }
 
-    pub fn process_update_authority(&mut self) -> ProgramResult {
-        let instruction_data = UpdateConfigAuthorityInstructionData::try_from(self.data)?;
+    pub fn process_update_authority(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateConfigAuthorityInstructionData::try_from(data)?;
 
         let mut config = Config::load_mut(self.accounts.config)?;
 
@@ -124,8 +243,8 @@ impl<'a> UpdateConfig<'a> {
         Ok(())
     }
 
-    pub fn process_update_fee(&mut self) -> ProgramResult {
-        let instruction_data = UpdateConfigFeeInstructionData::try_from(self.data)?;
+    pub fn process_update_fee(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateConfigFeeInstructionData::try_from(data)?;
 
         let mut config = Config::load_mut(self.accounts.config)?;
 
@@ -134,12 +253,69 @@ impl<'a> UpdateConfig<'a> {
         Ok(())
     }
 
-    pub fn process_update_status(&mut self) -> ProgramResult {
-        let instruction_data = UpdateConfigStatusInstructionData::try_from(self.data)?;
+    pub fn process_update_status(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateConfigStatusInstructionData::try_from(data)?;
 
         let mut config = Config::load_mut(self.accounts.config)?;
 
-        config.set_state(instruction_data.status)?;
+        config.set_state(AmmState::try_from(instruction_data.status)?)?;
+
+        Ok(())
+    }
+
+    pub fn process_update_host_fee(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateConfigHostFeeInstructionData::try_from(data)?;
+
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        config.set_host_fee_bps(instruction_data.host_fee_bps)?;
+
+        Ok(())
+    }
+
+    /// A default (all zero) oracle disables the guard, the pool needs the `OracleState` extension
+    pub fn process_update_oracle_guard(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateConfigOracleGuardInstructionData::try_from(data)?;
+
+        let mut data = self.accounts.config.try_borrow_mut_data()?;
+
+        get_extension_mut::<OracleState>(&mut data)?
+            .ok_or(AmmError::MissingExtension)?
+            .set_inner(instruction_data.oracle, instruction_data.max_deviation_bps)?;
+
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+
+        config.set_oracle_guard(instruction_data.oracle.ne(&[0u8; 32]));
+
+        Ok(())
+    }
+
+    /// Overwrite the value of an extension added with `ExtendConfig`
+    pub fn process_update_extension(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateConfigExtensionInstructionData::try_from(data)?;
+
+        // The oracle feed goes through `ConfigField::OracleGuard`, which keeps the flag in sync
+        if instruction_data.extension_type == ConfigExtension::OracleState {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut data = self.accounts.config.try_borrow_mut_data()?;
+
+        // Replacing the strategy accounts would lose track of what is deployed in them
+        if instruction_data.extension_type == ConfigExtension::VaultStrategy
+            && get_extension::<VaultStrategy>(&data)?.is_some_and(|strategy| strategy.is_deployed())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (offset, length) = find_extension(&data, instruction_data.extension_type as u16)?
+            .ok_or(AmmError::MissingExtension)?;
+
+        if length.ne(&instruction_data.value.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        data[offset..offset + length].copy_from_slice(instruction_data.value);
 
         Ok(())
     }
@@ -0,0 +1,339 @@
+use crate::state::GlobalConfig;
+use crate::{check_signer, check_writable};
+use core::mem::size_of;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #UpdateGlobalConfig
+///
+/// Update the program-wide GlobalConfig
+///
+/// Accounts:
+///
+/// 1. admin:                       [signer]
+/// 2. global_config:               [mut]
+///
+/// Parameters:
+///
+/// 1. field:                       [u8]    // See `GlobalConfigField`
+/// 2. value:                       [..]    // Encoding depends on the field
+pub struct UpdateGlobalConfigAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateGlobalConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, global_config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(admin)?;
+        check_writable(global_config)?;
+
+        // Deserialize the global config account
+        let global_config_data = GlobalConfig::load(global_config)?;
+
+        // Check if the admin is the correct admin
+        if global_config_data.admin().ne(admin.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            admin,
+            global_config,
+        })
+    }
+}
+
+/// GlobalConfig field selected by the first byte of the UpdateGlobalConfig data
+#[repr(u8)]
+pub enum GlobalConfigField {
+    Admin = 0u8,
+    ProtocolFee = 1u8,
+    CreationFee = 2u8,
+    Flags = 3u8,
+    MinSwapReserve = 4u8,
+    PoolFeeBounds = 5u8,
+    AllowedTokenPrograms = 6u8,
+    AllowedPoolFlags = 7u8,
+    Tip = 8u8,
+}
+
+impl TryFrom<u8> for GlobalConfigField {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(GlobalConfigField::Admin),
+            1 => Ok(GlobalConfigField::ProtocolFee),
+            2 => Ok(GlobalConfigField::CreationFee),
+            3 => Ok(GlobalConfigField::Flags),
+            4 => Ok(GlobalConfigField::MinSwapReserve),
+            5 => Ok(GlobalConfigField::PoolFeeBounds),
+            6 => Ok(GlobalConfigField::AllowedTokenPrograms),
+            7 => Ok(GlobalConfigField::AllowedPoolFlags),
+            8 => Ok(GlobalConfigField::Tip),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+pub struct UpdateGlobalConfigAdminInstructionData {
+    pub admin: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateGlobalConfigAdminInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            admin: data
+                .try_into()
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        })
+    }
+}
+
+pub struct UpdateGlobalConfigProtocolFeeInstructionData {
+    pub protocol_fee_bps: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateGlobalConfigProtocolFeeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            protocol_fee_bps: u16::from_le_bytes(
+                data.try_into()
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            ),
+        })
+    }
+}
+
+pub struct UpdateGlobalConfigCreationFeeInstructionData {
+    pub creation_fee: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateGlobalConfigCreationFeeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            creation_fee: u64::from_le_bytes(
+                data.try_into()
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            ),
+        })
+    }
+}
+
+pub struct UpdateGlobalConfigFlagsInstructionData {
+    pub flags: u8,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateGlobalConfigFlagsInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u8>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { flags: data[0] })
+    }
+}
+
+pub struct UpdateGlobalConfigMinSwapReserveInstructionData {
+    pub min_swap_reserve: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateGlobalConfigMinSwapReserveInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            min_swap_reserve: u64::from_le_bytes(
+                data.try_into()
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            ),
+        })
+    }
+}
+
+pub struct UpdateGlobalConfigPoolFeeBoundsInstructionData {
+    pub min_pool_fee_bps: u16,
+    pub max_pool_fee_bps: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateGlobalConfigPoolFeeBoundsInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<u16>() * 2)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            min_pool_fee_bps: u16::from_le_bytes(data[0..2].try_into().unwrap()),
+            max_pool_fee_bps: u16::from_le_bytes(data[2..4].try_into().unwrap()),
+        })
+    }
+}
+
+pub struct UpdateGlobalConfigTipInstructionData {
+    pub tip_account: [u8; 32],
+    pub max_tip: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateGlobalConfigTipInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&(size_of::<[u8; 32]>() + size_of::<u64>())) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            tip_account: data[0..32].try_into().unwrap(),
+            max_tip: u64::from_le_bytes(data[32..40].try_into().unwrap()),
+        })
+    }
+}
+
+pub struct UpdateGlobalConfig<'a> {
+    pub accounts: UpdateGlobalConfigAccounts<'a>,
+    pub data: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpdateGlobalConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = UpdateGlobalConfigAccounts::try_from(accounts)?;
+
+        // Return the initialized struct
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> UpdateGlobalConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &10;
+
+    /// `sha256("global:update_global_config")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [164, 84, 130, 189, 111, 58, 250, 200];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let Some((field, data)) = self.data.split_first() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        match GlobalConfigField::try_from(*field)? {
+            GlobalConfigField::Admin => self.process_update_admin(data),
+            GlobalConfigField::ProtocolFee => self.process_update_protocol_fee(data),
+            GlobalConfigField::CreationFee => self.process_update_creation_fee(data),
+            GlobalConfigField::Flags => self.process_update_flags(data),
+            GlobalConfigField::MinSwapReserve => self.process_update_min_swap_reserve(data),
+            GlobalConfigField::PoolFeeBounds => self.process_update_pool_fee_bounds(data),
+            GlobalConfigField::AllowedTokenPrograms => self.process_update_allowed_token_programs(data),
+            GlobalConfigField::AllowedPoolFlags => self.process_update_allowed_pool_flags(data),
+            GlobalConfigField::Tip => self.process_update_tip(data),
+        }
+    }
+
+    pub fn process_update_admin(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateGlobalConfigAdminInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_admin(instruction_data.admin);
+
+        Ok(())
+    }
+
+    pub fn process_update_protocol_fee(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateGlobalConfigProtocolFeeInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_protocol_fee_bps(instruction_data.protocol_fee_bps)?;
+
+        Ok(())
+    }
+
+    pub fn process_update_creation_fee(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateGlobalConfigCreationFeeInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_creation_fee(instruction_data.creation_fee);
+
+        Ok(())
+    }
+
+    pub fn process_update_flags(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateGlobalConfigFlagsInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_flags(instruction_data.flags);
+
+        Ok(())
+    }
+
+    pub fn process_update_min_swap_reserve(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateGlobalConfigMinSwapReserveInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_min_swap_reserve(instruction_data.min_swap_reserve);
+
+        Ok(())
+    }
+
+    pub fn process_update_pool_fee_bounds(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateGlobalConfigPoolFeeBoundsInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_pool_fee_bounds(instruction_data.min_pool_fee_bps, instruction_data.max_pool_fee_bps)?;
+
+        Ok(())
+    }
+
+    pub fn process_update_allowed_token_programs(&mut self, data: &[u8]) -> ProgramResult {
+        // Same single byte encoding as the flags
+        let instruction_data = UpdateGlobalConfigFlagsInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_allowed_token_programs(instruction_data.flags);
+
+        Ok(())
+    }
+
+    pub fn process_update_allowed_pool_flags(&mut self, data: &[u8]) -> ProgramResult {
+        // Same single byte encoding as the flags
+        let instruction_data = UpdateGlobalConfigFlagsInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_allowed_pool_flags(instruction_data.flags);
+
+        Ok(())
+    }
+
+    pub fn process_update_tip(&mut self, data: &[u8]) -> ProgramResult {
+        let instruction_data = UpdateGlobalConfigTipInstructionData::try_from(data)?;
+
+        let mut global_config = GlobalConfig::load_mut(self.accounts.global_config)?;
+
+        global_config.set_tip(instruction_data.tip_account, instruction_data.max_tip);
+
+        Ok(())
+    }
+}
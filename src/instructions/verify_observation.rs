@@ -0,0 +1,162 @@
+use crate::state::{get_extension, Config, ObservationTree};
+use crate::{set_return_amounts, AmmError, VerifyLeaf};
+use core::mem::size_of;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// Number of accounts of the `verify_leaf` CPI, the tree and a full proof
+const VERIFY_ACCOUNTS: usize = ObservationTree::MAX_DEPTH as usize + 1;
+
+/// #VerifyObservation
+///
+/// Check a past observation of a pool against its `ObservationTree` and publish it in the return
+/// data
+///
+/// Read-only, so on-chain consumers (e.g. TWAP oracles) can CPI into it with observations and
+/// proofs fetched off-chain, instead of trusting them. The proof is always the full
+/// `ObservationTree::MAX_DEPTH` nodes, whatever the tree's canopy.
+///
+/// Accounts:
+///
+/// 1. config
+/// 2. merkle_tree                  // `ObservationTree::merkle_tree`
+/// 3. compression_program          [executable]
+/// 4. ..                           // Proof nodes, from the leaf up
+///
+/// Parameters:
+///
+/// 1. root: [u8; 32]               // Current root of the tree, or one still in its buffer
+/// 2. index: [u32]                 // Leaf index, returned by `AppendObservation`
+/// 3. slot: [u64]
+/// 4. unix_timestamp: [i64]
+/// 5. reserve_x: [u64]
+/// 6. reserve_y: [u64]
+///
+/// Return data:
+///
+/// 1. slot: u64,
+/// 2. unix_timestamp: u64,         // As an i64
+/// 3. reserve_x: u64,
+/// 4. reserve_y: u64,
+pub struct VerifyObservationAccounts<'a> {
+    pub config: &'a AccountInfo,
+    pub merkle_tree: &'a AccountInfo,
+    pub proof: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for VerifyObservationAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, merkle_tree, _compression_program, proof @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if proof.len() + 1 != VERIFY_ACCOUNTS {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            config,
+            merkle_tree,
+            proof,
+        })
+    }
+}
+
+pub struct VerifyObservationInstructionData {
+    pub root: [u8; 32],
+    pub index: u32,
+    pub slot: u64,
+    pub unix_timestamp: i64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl TryFrom<&[u8]> for VerifyObservationInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const VERIFY_OBSERVATION_DATA_LEN: usize = size_of::<[u8; 32]>() + size_of::<u32>() + size_of::<u64>() * 4;
+
+        if data.len().ne(&VERIFY_OBSERVATION_DATA_LEN) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let root = data[0..32].try_into().unwrap();
+        let index = u32::from_le_bytes(data[32..36].try_into().unwrap());
+        let slot = u64::from_le_bytes(data[36..44].try_into().unwrap());
+        let unix_timestamp = i64::from_le_bytes(data[44..52].try_into().unwrap());
+        let reserve_x = u64::from_le_bytes(data[52..60].try_into().unwrap());
+        let reserve_y = u64::from_le_bytes(data[60..68].try_into().unwrap());
+
+        Ok(Self {
+            root,
+            index,
+            slot,
+            unix_timestamp,
+            reserve_x,
+            reserve_y,
+        })
+    }
+}
+
+pub struct VerifyObservation<'a> {
+    pub accounts: VerifyObservationAccounts<'a>,
+    pub instruction_data: VerifyObservationInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for VerifyObservation<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = VerifyObservationAccounts::try_from(accounts)?;
+        let instruction_data = VerifyObservationInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> VerifyObservation<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &57;
+
+    /// `sha256("global:verify_observation")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [32, 225, 54, 163, 189, 239, 51, 111];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let data = &self.instruction_data;
+
+        {
+            // Deserialize the config account
+            Config::load(self.accounts.config)?;
+
+            let config_data = self.accounts.config.try_borrow_data()?;
+            let observations = get_extension::<ObservationTree>(&config_data)?.ok_or(AmmError::MissingExtension)?;
+
+            // The tree must be the pool's, and the leaf one it appended
+            if observations.merkle_tree().ne(self.accounts.merkle_tree.key())
+                || data.index as u64 >= observations.observation_count()
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        let leaf = ObservationTree::leaf(data.slot, data.unix_timestamp, data.reserve_x, data.reserve_y);
+
+        VerifyLeaf {
+            merkle_tree: self.accounts.merkle_tree,
+            proof: self.accounts.proof,
+            root: &data.root,
+            leaf: &leaf,
+            index: data.index,
+        }
+        .invoke::<VERIFY_ACCOUNTS>()?;
+
+        set_return_amounts(&[data.slot, data.unix_timestamp as u64, data.reserve_x, data.reserve_y]);
+
+        Ok(())
+    }
+}
@@ -1,14 +1,13 @@
-use crate::state::Config;
-use crate::AmmState;
-use constant_product_curve::ConstantProduct;
+use crate::state::{get_extension, Config, ExitPenalty, FeeState, Position};
+use crate::{
+    bump_sequence, check_expiration, check_signer, check_writable, debug_log, derive_position, mul_div,
+    set_return_amounts, slippage_bound, validate_pool_accounts, withdraw_amounts, AmmError, AmmState, BurnChecked,
+    CheckedLpMint, CheckedUserAta, CloseAccount, DRY_RUN, MAX_FEE_BPS, PoolAccounts, Rounding, SLIPPAGE_IN_BPS, TransferChecked,
+};
 use core::mem::size_of;
 use pinocchio::instruction::{Seed, Signer};
-use pinocchio::pubkey::find_program_address;
-use pinocchio::sysvars::clock::Clock;
-use pinocchio::sysvars::Sysvar;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
-use pinocchio_token::instructions::{Burn, Transfer};
-use pinocchio_token::state::{Mint, TokenAccount};
 
 /// #Withdraw
 ///
@@ -16,22 +15,38 @@ use pinocchio_token::state::{Mint, TokenAccount};
 ///
 /// Accounts:
 ///
-/// 1. user:                        [signer, mut]   
+/// 1. user:                        [signer, mut]   // Owner of user_lp_ata, or its delegate
 /// 2. mint_lp                      [mut]
 /// 3. vault_x                      [mut]
 /// 4. vault_y                      [mut]
 /// 5. user_x_ata                   [init_if_needed]
 /// 6. user_y_ata                   [init_if_needed]
 /// 7. user_lp_ata                  [mut]
-/// 8. config                       
-/// 9. token_program                [executable]
+/// 8. config                       [mut with a `FeeState` withdraw fee or a `Sequence`]
+/// 9. token_program                [executable]  // LP token program, see `Config::lp_token_program`
+/// 10. mint_x
+/// 11. mint_y
+/// 12. token_program_x             [executable]
+/// 13. token_program_y             [executable]
+/// 14. position                    [optional]    // [b"position", config, user], see `ExitPenalty`
+///
+/// Pools with the `FeeState` extension hold `withdraw_fee_bps` of the amounts back as protocol fees,
+/// and pools with the `ExitPenalty` extension the early-exit penalty of the user's position as
+/// reserves. The amounts below are net of both.
 ///
 /// Parameters:
 ///
-/// 1. amount: u64,        // Amount of LP token to claim
-/// 2. min_x: u64,         // Min amount of X we are willing to receive
-/// 3. min_y: u64,         // Min amount of Y we are willing to receive
-/// 4. expiration: i64     // Expiration of the offer
+/// 1. amount: u64,        // Amount of LP token to burn (max LP with `EXACT_AMOUNTS`), see `ALL_LP`
+/// 2. min_x: u64,         // Min amount of X to receive (exact with `EXACT_AMOUNTS`, in bps with `SLIPPAGE_IN_BPS`)
+/// 3. min_y: u64,         // Min amount of Y to receive (exact with `EXACT_AMOUNTS`, in bps with `SLIPPAGE_IN_BPS`)
+/// 4. expiration: i64     // Expiration of the offer (a slot with `EXPIRATION_IN_SLOTS`)
+/// 5. flags: u8           // Optional, see `WithdrawInstructionData` and the shared flags in `constants`
+///
+/// Return data:
+///
+/// 1. x: u64,             // Amount of X withdrawn
+/// 2. y: u64,             // Amount of Y withdrawn
+/// 3. lp: u64,            // Amount of LP token burned
 pub struct WithdrawAccounts<'a> {
     pub user: &'a AccountInfo,
     pub mint_lp: &'a AccountInfo,
@@ -42,18 +57,37 @@ pub struct WithdrawAccounts<'a> {
     pub user_lp_ata: &'a AccountInfo,
     pub config: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub position: Option<&'a AccountInfo>,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program] =
+        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program, mint_x, mint_y, token_program_x, token_program_y, remaining @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [
+            user,
+            mint_lp,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            user_lp_ata,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
         // Return the accounts
         Ok(Self {
             user,
@@ -65,6 +99,11 @@ impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawAccounts<'a> {
             user_lp_ata,
             config,
             token_program,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            position: remaining.first(),
         })
     }
 }
@@ -74,34 +113,59 @@ pub struct WithdrawInstructionData {
     pub min_x: u64,
     pub min_y: u64,
     pub expiration: i64,
+    pub flags: u8,
+}
+
+impl WithdrawInstructionData {
+    /// Close the user's LP token account once the withdrawal leaves it empty, needs the owner
+    pub const CLOSE_LP_ATA: u8 = 1 << 0;
+    /// Close (and unwrap) the user's WSOL token accounts after receiving the withdrawal, needs the owner
+    pub const UNWRAP_SOL: u8 = 1 << 1;
+    /// Receive exactly `min_x`/`min_y` and burn the LP they are worth, at most `amount`
+    pub const EXACT_AMOUNTS: u8 = 1 << 2;
+
+    /// `amount` burning the whole balance of the user's LP token account, read on execution
+    pub const ALL_LP: u64 = u64::MAX;
 }
 
 impl<'a> TryFrom<&'a [u8]> for WithdrawInstructionData {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len().ne(&size_of::<WithdrawInstructionData>()) {
-            return Err(ProgramError::InvalidInstructionData);
-        }
+        const WITHDRAW_DATA_LEN: usize = size_of::<u64>() * 3 + size_of::<i64>();
+        const WITHDRAW_DATA_LEN_WITH_FLAGS: usize = WITHDRAW_DATA_LEN + size_of::<u8>();
+
+        let flags = match data.len() {
+            WITHDRAW_DATA_LEN => 0,
+            WITHDRAW_DATA_LEN_WITH_FLAGS => data[WITHDRAW_DATA_LEN],
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
 
         let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
         let min_x = u64::from_le_bytes(data[8..16].try_into().unwrap());
         let min_y = u64::from_le_bytes(data[16..24].try_into().unwrap());
         let expiration = i64::from_le_bytes(data[24..32].try_into().unwrap());
 
-        if amount == 0
-            || min_x == 0
-            || min_y == 0
-            || expiration < Clock::get()?.unix_timestamp
-        {
+        // An exact withdrawal may ask for a single side, tolerances in bps may be zero but not beyond 100%
+        let min_out_invalid = match (flags & Self::EXACT_AMOUNTS != 0, flags & SLIPPAGE_IN_BPS != 0) {
+            (true, true) => true,
+            (true, false) => min_x == 0 && min_y == 0,
+            (false, true) => min_x > MAX_FEE_BPS as u64 || min_y > MAX_FEE_BPS as u64,
+            (false, false) => min_x == 0 || min_y == 0,
+        };
+
+        if amount == 0 || min_out_invalid {
             return Err(ProgramError::InvalidInstructionData);
         }
 
+        check_expiration(expiration, flags)?;
+
         Ok(Self {
             amount,
             min_x,
             min_y,
             expiration,
+            flags,
         })
     }
 }
@@ -129,104 +193,246 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Withdraw<'a> {
 impl<'a> Withdraw<'a> {
     pub const DISCRIMINATOR: &'a u8 = &2;
 
+    /// `sha256("global:withdraw")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+
     pub fn process(&mut self) -> ProgramResult {
-        // Deserialize the config account
-        let config = Config::load(self.accounts.config)?;
+        // Check the config, the pool mints and the vaults
+        let PoolAccounts { config, vault_x, vault_y, deployed } = validate_pool_accounts(
+            self.accounts.config,
+            (self.accounts.vault_x, self.accounts.vault_y),
+            (self.accounts.mint_x, self.accounts.mint_y),
+            (self.accounts.token_program_x, self.accounts.token_program_y),
+            &[AmmState::Initialized, AmmState::WithdrawOnly],
+        )?;
 
-        // Check if we can deposit to the Amm
-        if config.state().ne(&(AmmState::Initialized as u8)) {
-            return Err(ProgramError::InvalidAccountData);
-        }
+        // Check the LP mint, its supply prices the LP
+        let mint_lp = CheckedLpMint::new(self.accounts.mint_lp, self.accounts.config.key())?;
 
-        // Check if the vault_x is valid
-        let (vault_x, _) = find_program_address(
-            &[
-                self.accounts.config.key(),
-                self.accounts.token_program.key(),
-                config.mint_x(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-
-        if vault_x.ne(self.accounts.vault_x.key()) {
-            return Err(ProgramError::InvalidAccountData);
+        // Uncollected protocol fees sit in the vaults but don't back the LP supply
+        let (reserve_x, reserve_y) = config.reserves(vault_x.amount(), vault_y.amount(), deployed);
+
+        debug_log!(Self::DISCRIMINATOR, 0, reserve_x, reserve_y, mint_lp.supply());
+
+        // Resolve the whole balance now, it may have moved since the transaction was built
+        if self.instruction_data.amount == WithdrawInstructionData::ALL_LP {
+            let balance = CheckedUserAta::new(self.accounts.user_lp_ata, self.accounts.mint_lp.key())?.amount();
+
+            if balance == 0 {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            self.instruction_data.amount = balance;
         }
 
-        // Check if the vault_y is valid
-        let (vault_y, _) = find_program_address(
-            &[
-                self.accounts.config.key(),
-                self.accounts.token_program.key(),
-                config.mint_y(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-
-        if vault_y.ne(self.accounts.vault_y.key()) {
-            return Err(ProgramError::InvalidAccountData);
+        // Bound the withdrawal around the pool share of the LP at these reserves
+        if self.instruction_data.flags & SLIPPAGE_IN_BPS != 0 {
+            if mint_lp.supply() == 0 {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let lp = self.instruction_data.amount as u128;
+            let supply = mint_lp.supply() as u128;
+            let (fair_x, fair_y) = (lp * reserve_x as u128 / supply, lp * reserve_y as u128 / supply);
+
+            self.instruction_data.min_x = slippage_bound(fair_x, self.instruction_data.min_x, false);
+            self.instruction_data.min_y = slippage_bound(fair_y, self.instruction_data.min_y, false);
         }
 
-        // Deserialize the token accounts
-        let mint_lp = unsafe { Mint::from_account_info_unchecked(self.accounts.mint_lp)? };
-        let vault_x = unsafe { TokenAccount::from_account_info_unchecked(self.accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_info_unchecked(self.accounts.vault_y)? };
+        // The withdraw fee and the early-exit penalty are held back from the amounts paid out, the
+        // fee owed to the protocol and the penalty left in the reserves
+        let (withdraw_fee_bps, held_bps) = {
+            let config_data = self.accounts.config.try_borrow_data()?;
+
+            let withdraw_fee_bps = match get_extension::<FeeState>(&config_data)? {
+                Some(fee_state) => fee_state.withdraw_fee_bps() as u64,
+                None => 0,
+            };
+            let penalty_bps = match get_extension::<ExitPenalty>(&config_data)? {
+                Some(exit_penalty) if exit_penalty.is_enabled() => {
+                    exit_penalty.penalty_at(self.last_deposit()?, Clock::get()?.unix_timestamp) as u64
+                }
+                _ => 0,
+            };
+
+            (withdraw_fee_bps, (withdraw_fee_bps + penalty_bps).min(MAX_FEE_BPS as u64))
+        };
+
+        let (x, y, lp, fee_x, fee_y) = match self.instruction_data.flags & WithdrawInstructionData::EXACT_AMOUNTS != 0 {
+            true => {
+                let supply = mint_lp.supply();
+
+                if reserve_x == 0 || reserve_y == 0 {
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                // Gross the amounts up for what is held back, then burn enough LP to cover the
+                // larger side, both rounding up in favor of the pool
+                let fee_remainder = MAX_FEE_BPS as u64 - held_bps;
+                let gross_x = mul_div(self.instruction_data.min_x, MAX_FEE_BPS as u64, fee_remainder, Rounding::Up)?;
+                let gross_y = mul_div(self.instruction_data.min_y, MAX_FEE_BPS as u64, fee_remainder, Rounding::Up)?;
+
+                let lp = mul_div(gross_x, supply, reserve_x, Rounding::Up)?
+                    .max(mul_div(gross_y, supply, reserve_y, Rounding::Up)?);
+
+                // Check for slippage
+                if lp > self.instruction_data.amount {
+                    return Err(ProgramError::InvalidArgument);
+                }
 
-        let (x, y) = match mint_lp.supply() == self.instruction_data.amount {
-            true => (vault_x.amount(), vault_y.amount()),
+                // The fee is its share of what is held back
+                let (fee_x, fee_y) = match held_bps {
+                    0 => (0, 0),
+                    _ => (
+                        mul_div(gross_x - self.instruction_data.min_x, withdraw_fee_bps, held_bps, Rounding::Down)?,
+                        mul_div(gross_y - self.instruction_data.min_y, withdraw_fee_bps, held_bps, Rounding::Down)?,
+                    ),
+                };
+
+                (self.instruction_data.min_x, self.instruction_data.min_y, lp, fee_x, fee_y)
+            }
             false => {
-                let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
-                    vault_x.amount(),
-                    vault_y.amount(),
-                    mint_lp.supply(),
-                    self.instruction_data.amount,
-                    6,
-                )
-                .map_err(|_| ProgramError::InvalidArgument)?;
-
-                (amounts.x, amounts.y)
+                let (x, y) = withdraw_amounts(reserve_x, reserve_y, mint_lp.supply(), self.instruction_data.amount)?;
+
+                // What is held back rounds up in favor of the pool
+                let (held_x, held_y) = (
+                    mul_div(x, held_bps, MAX_FEE_BPS as u64, Rounding::Up)?,
+                    mul_div(y, held_bps, MAX_FEE_BPS as u64, Rounding::Up)?,
+                );
+                let fee_x = mul_div(x, withdraw_fee_bps, MAX_FEE_BPS as u64, Rounding::Up)?.min(held_x);
+                let fee_y = mul_div(y, withdraw_fee_bps, MAX_FEE_BPS as u64, Rounding::Up)?.min(held_y);
+                let (x, y) = (x - held_x, y - held_y);
+
+                // Check for slippage
+                if x < self.instruction_data.min_x || y < self.instruction_data.min_y {
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                (x, y, self.instruction_data.amount, fee_x, fee_y)
             }
         };
 
-        // Check for slippage
-        if !(x <= self.instruction_data.min_x && y <= self.instruction_data.min_y) {
-            return Err(ProgramError::InvalidArgument);
+        debug_log!(Self::DISCRIMINATOR, 1, x, y, lp);
+
+        // Report the quote and roll back before any transfer
+        if self.instruction_data.flags & DRY_RUN != 0 {
+            set_return_amounts(&[x, y, lp]);
+            return Err(AmmError::SimulationComplete.into());
         }
 
-        let seed_binding = config.seed().to_le_bytes();
         let config_bump = config.config_bump();
         let seeds = [
             Seed::from("config".as_bytes()),
-            Seed::from(&seed_binding),
+            Seed::from(config.seed_bytes()),
             Seed::from(config.mint_x()),
             Seed::from(config.mint_y()),
             Seed::from(&config_bump),
         ];
         let signer_seeds = [Signer::from(&seeds)];
 
-        Transfer {
+        TransferChecked {
             from: self.accounts.vault_x,
+            mint: self.accounts.mint_x,
             to: self.accounts.user_x_ata,
             authority: self.accounts.config,
             amount: x,
+            decimals: config.decimals_x(),
+            token_program: config.token_program_x(),
         }
         .invoke_signed(&signer_seeds)?;
 
-        Transfer {
+        TransferChecked {
             from: self.accounts.vault_y,
+            mint: self.accounts.mint_y,
             to: self.accounts.user_y_ata,
             authority: self.accounts.config,
             amount: y,
+            decimals: config.decimals_y(),
+            token_program: config.token_program_y(),
         }
         .invoke_signed(&signer_seeds)?;
 
-        Burn {
-            mint: self.accounts.mint_lp,
+        BurnChecked {
             account: self.accounts.user_lp_ata,
+            mint: self.accounts.mint_lp,
             authority: self.accounts.user,
-            amount: self.instruction_data.amount,
+            amount: lp,
+            decimals: config.lp_decimals(),
+            token_program: config.lp_token_program(),
         }
         .invoke()?;
 
+        // Return the LP token account rent if the position is fully exited
+        if self.instruction_data.flags & WithdrawInstructionData::CLOSE_LP_ATA != 0 {
+            let user_lp_ata = CheckedUserAta::new(self.accounts.user_lp_ata, self.accounts.mint_lp.key())?;
+
+            if user_lp_ata.amount() == 0 {
+                CloseAccount {
+                    account: self.accounts.user_lp_ata,
+                    destination: self.accounts.user,
+                    authority: self.accounts.user,
+                    token_program: config.lp_token_program(),
+                }
+                .invoke()?;
+            }
+        }
+
+        // Unwrap WSOL by closing the native token accounts back to the user
+        if self.instruction_data.flags & WithdrawInstructionData::UNWRAP_SOL != 0 {
+            for (user_ata, mint) in [
+                (self.accounts.user_x_ata, self.accounts.mint_x),
+                (self.accounts.user_y_ata, self.accounts.mint_y),
+            ] {
+                let token_account = CheckedUserAta::new(user_ata, mint.key())?;
+
+                // Wrapped SOL lives in SPL Token
+                if token_account.is_native() && user_ata.owner().eq(&pinocchio_token::ID) {
+                    CloseAccount {
+                        account: user_ata,
+                        destination: self.accounts.user,
+                        authority: self.accounts.user,
+                        token_program: &pinocchio_token::ID,
+                    }
+                    .invoke()?;
+                }
+            }
+        }
+
+        // The fee never left the vaults, only its owner changes
+        drop(config);
+
+        if fee_x > 0 || fee_y > 0 {
+            check_writable(self.accounts.config)?;
+
+            let mut config = Config::load_mut(self.accounts.config)?;
+            config.accrue_protocol_fee(true, fee_x)?;
+            config.accrue_protocol_fee(false, fee_y)?;
+        }
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        debug_log!(Self::DISCRIMINATOR, 2, vault_x.amount(), vault_y.amount());
+
+        set_return_amounts(&[x, y, lp]);
+
         Ok(())
     }
+
+    /// Last deposit of the user, from their position if they passed it and it exists
+    fn last_deposit(&self) -> Result<Option<i64>, ProgramError> {
+        let Some(position) = self.accounts.position else {
+            return Ok(None);
+        };
+
+        if derive_position(self.accounts.config.key(), self.accounts.user.key()).0.ne(position.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if position.data_is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Position::load(position)?.last_deposit_timestamp()))
+    }
 }
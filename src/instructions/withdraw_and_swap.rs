@@ -0,0 +1,291 @@
+use crate::{
+    check_expiration, check_signer, check_writable, set_return_amounts, AmmError, CheckedUserAta, Swap, SwapAccounts,
+    SwapInstructionData, Withdraw, WithdrawAccounts, WithdrawInstructionData, EXPIRATION_IN_SLOTS,
+};
+use core::mem::size_of;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #WithdrawAndSwap
+///
+/// Exit a pool into a single token: burn LP for both sides, then swap the other side into the
+/// chosen one, in one instruction
+///
+/// The withdrawal and the swap run exactly as `Withdraw` and `Swap`, only the total received is
+/// bounded.
+///
+/// Accounts:
+///
+/// 1. user:                        [signer, mut]   // Owner of user_lp_ata, or its delegate
+/// 2. mint_lp                      [mut]
+/// 3. vault_x                      [mut]
+/// 4. vault_y                      [mut]
+/// 5. user_x_ata                   [mut]
+/// 6. user_y_ata                   [mut]
+/// 7. user_lp_ata                  [mut]
+/// 8. config                       [mut]
+/// 9. token_program                [executable]  // LP token program, see `Config::lp_token_program`
+/// 10. mint_x
+/// 11. mint_y
+/// 12. token_program_x             [executable]
+/// 13. token_program_y             [executable]
+/// 14. global_config
+/// 15. oracle                      [optional]    // As in `Swap`
+/// 16. instructions_sysvar         [optional]    // As in `Swap`
+///
+/// Optional accounts can be skipped by passing the program id in their place.
+///
+/// Parameters:
+///
+/// 1. is_x: bool,                  // Exit into X
+/// 2. amount: u64,                 // Amount of LP token to burn, see `WithdrawInstructionData::ALL_LP`
+/// 3. min_out: u64,                // Minimum total to receive, the only slippage bound
+/// 4. expiration: i64,             // Unix timestamp (a slot with `EXPIRATION_IN_SLOTS`)
+/// 5. flags: u8,                   // Optional, only `EXPIRATION_IN_SLOTS`
+///
+/// Return data:
+///
+/// 1. lp: u64,                     // Amount of LP token burned
+/// 2. swapped: u64,                // Amount of the other token withdrawn and swapped
+/// 3. amount_out: u64,             // Total received, net of transfer fees
+pub struct WithdrawAndSwapAccounts<'a> {
+    pub user: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+    pub user_lp_ata: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub global_config: &'a AccountInfo,
+    pub oracle: Option<&'a AccountInfo>,
+    pub instructions_sysvar: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawAndSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program, mint_x, mint_y, token_program_x, token_program_y, global_config, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(user)?;
+        [
+            user,
+            mint_lp,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            user_lp_ata,
+            config,
+        ]
+        .into_iter()
+        .try_for_each(check_writable)?;
+
+        let mut optional = remaining.iter().map(|account| account.key().ne(&crate::ID).then_some(account));
+
+        // Return the accounts
+        Ok(Self {
+            user,
+            mint_lp,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            user_lp_ata,
+            config,
+            token_program,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            global_config,
+            oracle: optional.next().flatten(),
+            instructions_sysvar: optional.next().flatten(),
+        })
+    }
+}
+
+pub struct WithdrawAndSwapInstructionData {
+    pub is_x: bool,
+    pub amount: u64,
+    pub min_out: u64,
+    pub expiration: i64,
+    pub flags: u8,
+}
+
+impl TryFrom<&[u8]> for WithdrawAndSwapInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const WITHDRAW_AND_SWAP_DATA_LEN: usize = size_of::<bool>() + size_of::<u64>() * 2 + size_of::<i64>();
+        const WITHDRAW_AND_SWAP_DATA_LEN_WITH_FLAGS: usize = WITHDRAW_AND_SWAP_DATA_LEN + size_of::<u8>();
+
+        let flags = match data.len() {
+            WITHDRAW_AND_SWAP_DATA_LEN => 0,
+            WITHDRAW_AND_SWAP_DATA_LEN_WITH_FLAGS => data[WITHDRAW_AND_SWAP_DATA_LEN],
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let is_x = data[0] == 1;
+        let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let min_out = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
+
+        if amount == 0 || min_out == 0 || flags & !EXPIRATION_IN_SLOTS != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_expiration(expiration, flags)?;
+
+        Ok(Self {
+            is_x,
+            amount,
+            min_out,
+            expiration,
+            flags,
+        })
+    }
+}
+
+pub struct WithdrawAndSwap<'a> {
+    pub accounts: WithdrawAndSwapAccounts<'a>,
+    pub instruction_data: WithdrawAndSwapInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawAndSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawAndSwapAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawAndSwapInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawAndSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &45;
+
+    /// `sha256("global:withdraw_and_swap")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [25, 233, 54, 181, 102, 54, 49, 83];
+
+    /// Index of the config among the accounts, see `Swap`
+    pub const CONFIG_INDEX: usize = 7;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let is_x = self.instruction_data.is_x;
+
+        // The side kept and the side swapped into it
+        let (user_out, mint_out, user_other, mint_other) = match is_x {
+            true => (
+                self.accounts.user_x_ata,
+                self.accounts.mint_x,
+                self.accounts.user_y_ata,
+                self.accounts.mint_y,
+            ),
+            false => (
+                self.accounts.user_y_ata,
+                self.accounts.mint_y,
+                self.accounts.user_x_ata,
+                self.accounts.mint_x,
+            ),
+        };
+        let user_out = CheckedUserAta::new(user_out, mint_out.key())?;
+        let user_other = CheckedUserAta::new(user_other, mint_other.key())?;
+        let user_lp = CheckedUserAta::new(self.accounts.user_lp_ata, self.accounts.mint_lp.key())?;
+
+        let (user_out_before, user_other_before, user_lp_before) =
+            (user_out.amount(), user_other.amount(), user_lp.amount());
+
+        // The final bound covers the withdrawal
+        Withdraw {
+            accounts: WithdrawAccounts {
+                user: self.accounts.user,
+                mint_lp: self.accounts.mint_lp,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                user_x_ata: self.accounts.user_x_ata,
+                user_y_ata: self.accounts.user_y_ata,
+                user_lp_ata: self.accounts.user_lp_ata,
+                config: self.accounts.config,
+                token_program: self.accounts.token_program,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                token_program_x: self.accounts.token_program_x,
+                token_program_y: self.accounts.token_program_y,
+                position: None,
+            },
+            instruction_data: WithdrawInstructionData {
+                amount: self.instruction_data.amount,
+                min_x: 1,
+                min_y: 1,
+                expiration: self.instruction_data.expiration,
+                flags: self.instruction_data.flags,
+            },
+        }
+        .process()?;
+
+        let lp = user_lp_before.checked_sub(user_lp.amount()).ok_or(AmmError::MathOverflow)?;
+        let swapped = user_other.amount().checked_sub(user_other_before).ok_or(AmmError::MathOverflow)?;
+
+        // Swap everything received on the other side
+        Swap {
+            accounts: SwapAccounts {
+                user: self.accounts.user,
+                user_x: self.accounts.user_x_ata,
+                user_y: self.accounts.user_y_ata,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                config: self.accounts.config,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                token_program_x: self.accounts.token_program_x,
+                token_program_y: self.accounts.token_program_y,
+                global_config: self.accounts.global_config,
+                host_fee: None,
+                oracle: self.accounts.oracle,
+                instructions_sysvar: self.accounts.instructions_sysvar,
+                mint_lp: Some(self.accounts.mint_lp),
+                memo_program: None,
+                trading_calendar: None,
+                insurance_vault: None,
+                tip_account: None,
+                system_program: None,
+                user_stats: None,
+            },
+            instruction_data: SwapInstructionData {
+                is_x: !is_x,
+                amount: swapped,
+                min: 1,
+                expiration: self.instruction_data.expiration,
+                flags: self.instruction_data.flags,
+                tip: 0,
+                memo: &[],
+            },
+        }
+        .process()?;
+
+        // Check for slippage on the total received
+        let amount_out = user_out.amount().checked_sub(user_out_before).ok_or(AmmError::MathOverflow)?;
+        if amount_out < self.instruction_data.min_out {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        set_return_amounts(&[lp, swapped, amount_out]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::state::{get_extension_mut, VaultStrategy};
+use crate::{bump_sequence, set_return_amounts, AmmError, StrategyCpiAccounts, StrategyCpiInstructionData};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #WithdrawFromStrategy
+///
+/// Bring lent reserves of one side back from the `VaultStrategy` lending program
+///
+/// What the vault receives is taken off the deployed amount, anything above it being yield for
+/// the LPs. Settling clears what remains deployed once the position is closed, any shortfall
+/// being a loss to the LPs.
+///
+/// Accounts: same as `DepositToStrategy`, the global config isn't read as withdrawals run while the
+/// program is upgrade frozen
+///
+/// Parameters:
+///
+/// 1. is_x: [bool]
+/// 2. settle: [bool]               // Clear the rest of the deployed amount
+/// 3. instruction: [u8]            // Data of the lending instruction
+///
+/// Return data:
+///
+/// 1. deployed: u64,               // Amount of the side still deployed
+pub struct WithdrawFromStrategy<'a> {
+    pub accounts: StrategyCpiAccounts<'a>,
+    pub instruction_data: StrategyCpiInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawFromStrategy<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = StrategyCpiAccounts::try_from(accounts)?;
+        let instruction_data = StrategyCpiInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawFromStrategy<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &24;
+
+    /// `sha256("global:withdraw_from_strategy")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [140, 239, 65, 54, 125, 128, 223, 125];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let is_x = self.instruction_data.is_x;
+
+        let (before, after) = self.accounts.invoke_lending(is_x, self.instruction_data.lending_data)?;
+        let received = after.checked_sub(before).ok_or(ProgramError::InvalidAccountData)?;
+
+        let deployed = {
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+            let vault_strategy =
+                get_extension_mut::<VaultStrategy>(&mut config_data)?.ok_or(AmmError::MissingExtension)?;
+
+            if self.instruction_data.settle {
+                vault_strategy.settle(is_x);
+            } else {
+                vault_strategy.recall(is_x, received);
+            }
+
+            match is_x {
+                true => vault_strategy.deployed_x(),
+                false => vault_strategy.deployed_y(),
+            }
+        };
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        set_return_amounts(&[deployed]);
+
+        Ok(())
+    }
+}
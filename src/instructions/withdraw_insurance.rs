@@ -0,0 +1,238 @@
+use crate::state::{get_extension_mut, Config, InsuranceFund};
+use crate::{bump_sequence, check_signer, check_writable, derive_ata, derive_insurance, AmmError, TransferChecked};
+use core::mem::size_of;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, ProgramResult
+};
+
+/// #WithdrawInsurance
+///
+/// Request a withdrawal from the insurance vaults of a pool, then execute it once the
+/// `InsuranceFund` timelock has passed
+///
+/// Accounts:
+///
+/// 1. authority:                   [signer]
+/// 2. config                       [mut]
+/// 3. insurance                    // [b"insurance", config]
+/// 4. insurance_x                  [mut]   // ATA of insurance
+/// 5. insurance_y                  [mut]   // ATA of insurance
+/// 6. destination_x                [mut]
+/// 7. destination_y                [mut]
+/// 8. mint_x
+/// 9. mint_y
+/// 10. token_program_x             [executable]
+/// 11. token_program_y             [executable]
+///
+/// Parameters:
+///
+/// 1. action: u8,                  // `REQUEST` or `EXECUTE`
+/// 2. amount_x: u64,               // `REQUEST` only, zero amounts cancel the pending withdrawal
+/// 3. amount_y: u64,
+pub struct WithdrawInsuranceAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub insurance: &'a AccountInfo,
+    pub insurance_x: &'a AccountInfo,
+    pub insurance_y: &'a AccountInfo,
+    pub destination_x: &'a AccountInfo,
+    pub destination_y: &'a AccountInfo,
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub insurance_bump: [u8; 1],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawInsuranceAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, insurance, insurance_x, insurance_y, destination_x, destination_y, mint_x, mint_y, token_program_x, token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(authority)?;
+        [config, insurance_x, insurance_y, destination_x, destination_y]
+            .into_iter()
+            .try_for_each(check_writable)?;
+
+        // Deserialize the config account
+        let config_data = Config::load(config)?;
+
+        // Check if the authority is the correct authority
+        if config_data.has_authority().ne(&Some(*authority.key())) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check the mints, the insurance authority and its vaults
+        if mint_x.key().ne(config_data.mint_x())
+            || mint_y.key().ne(config_data.mint_y())
+            || token_program_x.key().ne(config_data.token_program_x())
+            || token_program_y.key().ne(config_data.token_program_y())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (insurance_key, insurance_bump) = derive_insurance(config.key());
+
+        if insurance_key.ne(insurance.key())
+            || derive_ata(insurance.key(), token_program_x.key(), mint_x.key()).ne(insurance_x.key())
+            || derive_ata(insurance.key(), token_program_y.key(), mint_y.key()).ne(insurance_y.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Return the accounts
+        Ok(Self {
+            authority,
+            config,
+            insurance,
+            insurance_x,
+            insurance_y,
+            destination_x,
+            destination_y,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            insurance_bump: [insurance_bump],
+        })
+    }
+}
+
+pub struct WithdrawInsuranceInstructionData {
+    pub action: u8,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+impl TryFrom<&[u8]> for WithdrawInsuranceInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const REQUEST_DATA_LEN: usize = size_of::<u8>() + size_of::<u64>() * 2;
+
+        match (data.first().copied(), data.len()) {
+            (Some(WithdrawInsurance::REQUEST), REQUEST_DATA_LEN) => Ok(Self {
+                action: WithdrawInsurance::REQUEST,
+                amount_x: u64::from_le_bytes(data[1..9].try_into().unwrap()),
+                amount_y: u64::from_le_bytes(data[9..17].try_into().unwrap()),
+            }),
+            (Some(WithdrawInsurance::EXECUTE), 1) => Ok(Self {
+                action: WithdrawInsurance::EXECUTE,
+                amount_x: 0,
+                amount_y: 0,
+            }),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+pub struct WithdrawInsurance<'a> {
+    pub accounts: WithdrawInsuranceAccounts<'a>,
+    pub instruction_data: WithdrawInsuranceInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawInsurance<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawInsuranceAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawInsuranceInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawInsurance<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &48;
+
+    /// `sha256("global:withdraw_insurance")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [201, 133, 145, 118, 235, 89, 90, 189];
+
+    /// Start the timelock of a withdrawal, replacing any pending one
+    pub const REQUEST: u8 = 0;
+    /// Withdraw the pending amounts once the timelock has passed
+    pub const EXECUTE: u8 = 1;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+
+        // Order the change for off-chain consumers
+        bump_sequence(self.accounts.config, *Self::DISCRIMINATOR)?;
+
+        let (x, y) = {
+            let mut config_data = self.accounts.config.try_borrow_mut_data()?;
+            let insurance_fund =
+                get_extension_mut::<InsuranceFund>(&mut config_data)?.ok_or(AmmError::MissingExtension)?;
+
+            if self.instruction_data.action == Self::REQUEST {
+                return insurance_fund.request_withdrawal(
+                    self.instruction_data.amount_x,
+                    self.instruction_data.amount_y,
+                    now,
+                );
+            }
+
+            insurance_fund.take_withdrawal(now)?
+        };
+
+        let (decimals_x, decimals_y) = {
+            let config = Config::load(self.accounts.config)?;
+
+            (config.decimals_x(), config.decimals_y())
+        };
+
+        // Create the signer seeds
+        let seeds = [
+            Seed::from(b"insurance"),
+            Seed::from(self.accounts.config.key()),
+            Seed::from(&self.accounts.insurance_bump),
+        ];
+        let signer_seeds = [Signer::from(&seeds)];
+
+        for (amount, from, mint, to, decimals, token_program) in [
+            (
+                x,
+                self.accounts.insurance_x,
+                self.accounts.mint_x,
+                self.accounts.destination_x,
+                decimals_x,
+                self.accounts.token_program_x,
+            ),
+            (
+                y,
+                self.accounts.insurance_y,
+                self.accounts.mint_y,
+                self.accounts.destination_y,
+                decimals_y,
+                self.accounts.token_program_y,
+            ),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+
+            TransferChecked {
+                from,
+                mint,
+                to,
+                authority: self.accounts.insurance,
+                amount,
+                decimals,
+                token_program: token_program.key(),
+            }
+            .invoke_signed(&signer_seeds)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::state::Config;
+use crate::{check_signer, check_writable, derive_maker_escrow, load_mint, TransferChecked};
+use core::mem::size_of;
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// #WithdrawMakerEscrow
+///
+/// Move tokens out of a maker's RFQ escrow, see `SwapWithQuote`
+///
+/// Makers fund the escrow with a plain transfer to the ATA of their escrow PDA.
+///
+/// Accounts:
+///
+/// 1. maker:                       [signer]
+/// 2. config
+/// 3. maker_escrow                 // [b"maker", config, maker]
+/// 4. escrow                       [mut]   // ATA of maker_escrow
+/// 5. maker_ata                    [mut]
+/// 6. mint                         // mint_x or mint_y of the pool
+/// 7. token_program                [executable]
+///
+/// Parameters:
+///
+/// 1. amount: u64,
+pub struct WithdrawMakerEscrowAccounts<'a> {
+    pub maker: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub maker_escrow: &'a AccountInfo,
+    pub escrow: &'a AccountInfo,
+    pub maker_ata: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawMakerEscrowAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [maker, config, maker_escrow, escrow, maker_ata, mint, token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(maker)?;
+        [escrow, maker_ata].into_iter().try_for_each(check_writable)?;
+
+        // Return the accounts
+        Ok(Self {
+            maker,
+            config,
+            maker_escrow,
+            escrow,
+            maker_ata,
+            mint,
+            token_program,
+        })
+    }
+}
+
+pub struct WithdrawMakerEscrowInstructionData {
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for WithdrawMakerEscrowInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u64>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data.try_into().unwrap());
+
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount })
+    }
+}
+
+pub struct WithdrawMakerEscrow<'a> {
+    pub accounts: WithdrawMakerEscrowAccounts<'a>,
+    pub instruction_data: WithdrawMakerEscrowInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawMakerEscrow<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawMakerEscrowAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawMakerEscrowInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawMakerEscrow<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &19;
+
+    /// `sha256("global:withdraw_maker_escrow")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [179, 204, 10, 141, 30, 61, 30, 202];
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Deserialize the config account
+        let config = Config::load(self.accounts.config)?;
+
+        // Only the pool mints are escrowed, each with its own token program
+        let token_program = if self.accounts.mint.key().eq(config.mint_x()) {
+            config.token_program_x()
+        } else if self.accounts.mint.key().eq(config.mint_y()) {
+            config.token_program_y()
+        } else {
+            return Err(ProgramError::InvalidAccountData);
+        };
+
+        if self.accounts.token_program.key().ne(token_program) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Check if the maker_escrow is valid
+        let (maker_escrow, maker_escrow_bump) =
+            derive_maker_escrow(self.accounts.config.key(), self.accounts.maker.key());
+
+        if maker_escrow.ne(self.accounts.maker_escrow.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let maker_escrow_bump = [maker_escrow_bump];
+        let maker_escrow_seeds = [
+            Seed::from(b"maker"),
+            Seed::from(self.accounts.config.key()),
+            Seed::from(self.accounts.maker.key()),
+            Seed::from(&maker_escrow_bump),
+        ];
+
+        // The token program checks that escrow is owned by maker_escrow
+        TransferChecked {
+            from: self.accounts.escrow,
+            mint: self.accounts.mint,
+            to: self.accounts.maker_ata,
+            authority: self.accounts.maker_escrow,
+            amount: self.instruction_data.amount,
+            decimals: load_mint(self.accounts.mint)?.decimals(),
+            token_program,
+        }
+        .invoke_signed(&[Signer::from(&maker_escrow_seeds)])
+    }
+}
@@ -0,0 +1,125 @@
+use crate::state::GlobalConfig;
+use crate::{check_signer, check_writable};
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, instruction::{Seed, Signer}, program_error::ProgramError, ProgramResult
+};
+use pinocchio_system::instructions::Transfer;
+
+/// #WithdrawTreasury
+///
+/// Move pool creation fees out of the treasury PDA
+///
+/// Accounts:
+///
+/// 1. admin:                       [signer]
+/// 2. global_config
+/// 3. treasury                     [mut]
+/// 4. destination                  [mut]
+/// 5. system_program               [executable]
+///
+/// Parameters:
+///
+/// 1. lamports: [u64]
+pub struct WithdrawTreasuryAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub treasury: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub treasury_bump: [u8; 1],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawTreasuryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, global_config, treasury, destination, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Check the signers and the writable accounts
+        check_signer(admin)?;
+        [treasury, destination].into_iter().try_for_each(check_writable)?;
+
+        // Deserialize the global config account
+        let global_config = GlobalConfig::load(global_config)?;
+
+        // Check if the admin is the correct admin
+        if global_config.admin().ne(admin.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        global_config.check_treasury(treasury.key())?;
+
+        // Return the accounts
+        Ok(Self {
+            admin,
+            treasury,
+            destination,
+            treasury_bump: global_config.treasury_bump(),
+        })
+    }
+}
+
+pub struct WithdrawTreasuryInstructionData {
+    pub lamports: u64,
+}
+
+impl TryFrom<&[u8]> for WithdrawTreasuryInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&size_of::<u64>()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let lamports = u64::from_le_bytes(data[0..8].try_into().unwrap());
+
+        if lamports == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { lamports })
+    }
+}
+
+pub struct WithdrawTreasury<'a> {
+    pub accounts: WithdrawTreasuryAccounts<'a>,
+    pub instruction_data: WithdrawTreasuryInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawTreasury<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawTreasuryAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawTreasuryInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawTreasury<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &12;
+
+    /// `sha256("global:withdraw_treasury")[..8]`, see `split_discriminator`
+    pub const HASH_DISCRIMINATOR: [u8; 8] = [40, 63, 122, 158, 144, 216, 83, 96];
+
+    pub fn process(&mut self) -> ProgramResult {
+        let treasury_seeds = [
+            Seed::from(b"treasury"),
+            Seed::from(&self.accounts.treasury_bump),
+        ];
+
+        Transfer {
+            from: self.accounts.treasury,
+            to: self.accounts.destination,
+            lamports: self.instruction_data.lamports,
+        }
+        .invoke_signed(&[Signer::from(&treasury_seeds)])?;
+
+        Ok(())
+    }
+}
@@ -1,8 +1,7 @@
-use pinocchio::{
-    account_info::AccountInfo, entrypoint, program_error::ProgramError, pubkey::Pubkey,
-    ProgramResult,
-};
-entrypoint!(process_instruction);
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult};
+
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
 
 pub mod instructions;
 pub use instructions::*;
@@ -13,18 +12,50 @@ pub use state::*;
 pub mod error;
 pub use error::*;
 
+pub mod constants;
+pub use constants::*;
+
+pub mod utils;
+pub use utils::*;
+
+pub mod oracle;
+pub use oracle::*;
+
+pub mod token;
+pub use token::*;
+
+pub mod fee;
+pub use fee::*;
+
+pub mod pda;
+pub use pda::*;
+
+pub mod validation;
+pub use validation::*;
+
+pub mod memo;
+pub use memo::*;
+
+pub mod compression;
+pub use compression::*;
+
+pub mod math;
+pub use math::*;
+
 // 22222222222222222222222222222222222222222222
 pub const ID: Pubkey = [
     0x0f, 0x1e, 0x6b, 0x14, 0x21, 0xc0, 0x4a, 0x07, 0x04, 0x31, 0x26, 0x5c, 0x19, 0xc5, 0xbb, 0xee,
     0x19, 0x92, 0xba, 0xe8, 0xaf, 0xd1, 0xcd, 0x07, 0x8e, 0xf8, 0xaf, 0x70, 0x47, 0xdc, 0x11, 0xf7,
 ];
 
-fn process_instruction(
+/// Dispatch an instruction on its discriminator, see `split_discriminator`, registered as the entrypoint
+/// unless `no-entrypoint` is set
+pub fn process_instruction(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    match instruction_data.split_first() {
+    match split_discriminator(instruction_data) {
         Some((Initialize::DISCRIMINATOR, data)) => {
             Initialize::try_from((data, accounts))?.process()
         }
@@ -34,6 +65,232 @@ fn process_instruction(
         Some((UpdateConfig::DISCRIMINATOR, data)) => {
             UpdateConfig::try_from((data, accounts))?.process()
         }
+        Some((InitializeRegistry::DISCRIMINATOR, data)) => {
+            InitializeRegistry::try_from((data, accounts))?.process()
+        }
+        Some((OpenPosition::DISCRIMINATOR, data)) => {
+            OpenPosition::try_from((data, accounts))?.process()
+        }
+        Some((IncreasePosition::DISCRIMINATOR, data)) => {
+            IncreasePosition::try_from((data, accounts))?.process()
+        }
+        Some((DecreasePosition::DISCRIMINATOR, data)) => {
+            DecreasePosition::try_from((data, accounts))?.process()
+        }
+        Some((InitializeGlobal::DISCRIMINATOR, data)) => {
+            InitializeGlobal::try_from((data, accounts))?.process()
+        }
+        Some((UpdateGlobalConfig::DISCRIMINATOR, data)) => {
+            UpdateGlobalConfig::try_from((data, accounts))?.process()
+        }
+        Some((CollectProtocolFees::DISCRIMINATOR, data)) => {
+            CollectProtocolFees::try_from((data, accounts))?.process()
+        }
+        Some((WithdrawTreasury::DISCRIMINATOR, data)) => {
+            WithdrawTreasury::try_from((data, accounts))?.process()
+        }
+        Some((ExtendConfig::DISCRIMINATOR, data)) => {
+            ExtendConfig::try_from((data, accounts))?.process()
+        }
+        Some((InitializeLaunch::DISCRIMINATOR, data)) => {
+            InitializeLaunch::try_from((data, accounts))?.process()
+        }
+        Some((BuyLaunch::DISCRIMINATOR, data)) => BuyLaunch::try_from((data, accounts))?.process(),
+        Some((SellLaunch::DISCRIMINATOR, data)) => SellLaunch::try_from((data, accounts))?.process(),
+        Some((GraduateLaunch::DISCRIMINATOR, data)) => {
+            GraduateLaunch::try_from((data, accounts))?.process()
+        }
+        Some((SwapWithQuote::DISCRIMINATOR, data)) => {
+            SwapWithQuote::try_from((data, accounts))?.process()
+        }
+        Some((WithdrawMakerEscrow::DISCRIMINATOR, data)) => {
+            WithdrawMakerEscrow::try_from((data, accounts))?.process()
+        }
+        Some((ApproveStrategy::DISCRIMINATOR, data)) => {
+            ApproveStrategy::try_from((data, accounts))?.process()
+        }
+        Some((ExecuteStrategy::DISCRIMINATOR, data)) => {
+            ExecuteStrategy::try_from((data, accounts))?.process()
+        }
+        Some((Rebalance::DISCRIMINATOR, data)) => Rebalance::try_from((data, accounts))?.process(),
+        Some((DepositToStrategy::DISCRIMINATOR, data)) => {
+            DepositToStrategy::try_from((data, accounts))?.process()
+        }
+        Some((WithdrawFromStrategy::DISCRIMINATOR, data)) => {
+            WithdrawFromStrategy::try_from((data, accounts))?.process()
+        }
+        Some((InitializeMultiPool::DISCRIMINATOR, data)) => {
+            InitializeMultiPool::try_from((data, accounts))?.process()
+        }
+        Some((MultiDeposit::DISCRIMINATOR, data)) => MultiDeposit::try_from((data, accounts))?.process(),
+        Some((MultiWithdraw::DISCRIMINATOR, data)) => MultiWithdraw::try_from((data, accounts))?.process(),
+        Some((MultiSwap::DISCRIMINATOR, data)) => MultiSwap::try_from((data, accounts))?.process(),
+        Some((MigrateLiquidity::DISCRIMINATOR, data)) => {
+            MigrateLiquidity::try_from((data, accounts))?.process()
+        }
+        Some((MigrateConfig::DISCRIMINATOR, data)) => MigrateConfig::try_from((data, accounts))?.process(),
+        Some((EmergencyWithdrawProtocolSide::DISCRIMINATOR, data)) => {
+            EmergencyWithdrawProtocolSide::try_from((data, accounts))?.process()
+        }
+        Some((ClaimRecovery::DISCRIMINATOR, data)) => ClaimRecovery::try_from((data, accounts))?.process(),
+        Some((TokenSwap::DISCRIMINATOR, data)) => TokenSwap::try_from((data, accounts))?.process(),
+        Some((InitializeVaults::DISCRIMINATOR, data)) => {
+            InitializeVaults::try_from((data, accounts))?.process()
+        }
+        Some((InitializeFeeDistributor::DISCRIMINATOR, data)) => {
+            InitializeFeeDistributor::try_from((data, accounts))?.process()
+        }
+        Some((CheckpointFees::DISCRIMINATOR, data)) => CheckpointFees::try_from((data, accounts))?.process(),
+        Some((StakeGovernance::DISCRIMINATOR, data)) => StakeGovernance::try_from((data, accounts))?.process(),
+        Some((UnstakeGovernance::DISCRIMINATOR, data)) => UnstakeGovernance::try_from((data, accounts))?.process(),
+        Some((ClaimStakingRewards::DISCRIMINATOR, data)) => ClaimStakingRewards::try_from((data, accounts))?.process(),
+        Some((RouterSwap::DISCRIMINATOR, data)) => RouterSwap::try_from((data, accounts))?.process(),
+        Some((SetTrustedRouter::DISCRIMINATOR, data)) => SetTrustedRouter::try_from((data, accounts))?.process(),
+        Some((AssertPoolHealthy::DISCRIMINATOR, data)) => AssertPoolHealthy::try_from((data, accounts))?.process(),
+        Some((SetPoolMetadata::DISCRIMINATOR, data)) => SetPoolMetadata::try_from((data, accounts))?.process(),
+        Some((SwapAndDeposit::DISCRIMINATOR, data)) => SwapAndDeposit::try_from((data, accounts))?.process(),
+        Some((WithdrawAndSwap::DISCRIMINATOR, data)) => WithdrawAndSwap::try_from((data, accounts))?.process(),
+        Some((SetTradingCalendar::DISCRIMINATOR, data)) => SetTradingCalendar::try_from((data, accounts))?.process(),
+        Some((WithdrawInsurance::DISCRIMINATOR, data)) => WithdrawInsurance::try_from((data, accounts))?.process(),
+        Some((GetPoolInfo::DISCRIMINATOR, data)) => GetPoolInfo::try_from((data, accounts))?.process(),
+        Some((GetFairLpValue::DISCRIMINATOR, data)) => GetFairLpValue::try_from((data, accounts))?.process(),
+        Some((InitializeLbp::DISCRIMINATOR, data)) => InitializeLbp::try_from((data, accounts))?.process(),
+        Some((SwapLbp::DISCRIMINATOR, data)) => SwapLbp::try_from((data, accounts))?.process(),
+        Some((ExitLbp::DISCRIMINATOR, data)) => ExitLbp::try_from((data, accounts))?.process(),
+        Some((InitializeObservationTree::DISCRIMINATOR, data)) => {
+            InitializeObservationTree::try_from((data, accounts))?.process()
+        }
+        Some((AppendObservation::DISCRIMINATOR, data)) => AppendObservation::try_from((data, accounts))?.process(),
+        Some((VerifyObservation::DISCRIMINATOR, data)) => VerifyObservation::try_from((data, accounts))?.process(),
+        Some((Arbitrage::DISCRIMINATOR, data)) => Arbitrage::try_from((data, accounts))?.process(),
+        Some((SetTokenBadge::DISCRIMINATOR, data)) => SetTokenBadge::try_from((data, accounts))?.process(),
+        Some((SwapBatch::DISCRIMINATOR, data)) => SwapBatch::try_from((data, accounts))?.process(),
+        Some((OpenLongTermOrder::DISCRIMINATOR, data)) => {
+            OpenLongTermOrder::try_from((data, accounts))?.process()
+        }
+        Some((ExecuteLongTermOrder::DISCRIMINATOR, data)) => {
+            ExecuteLongTermOrder::try_from((data, accounts))?.process()
+        }
+        Some((CloseLongTermOrder::DISCRIMINATOR, data)) => {
+            CloseLongTermOrder::try_from((data, accounts))?.process()
+        }
+        Some((AssertBalanceChange::DISCRIMINATOR, data)) => {
+            AssertBalanceChange::try_from((data, accounts))?.process()
+        }
+        Some((DepositWithPermit::DISCRIMINATOR, data)) => {
+            DepositWithPermit::try_from((data, accounts))?.process()
+        }
+        Some((InitializeUserStats::DISCRIMINATOR, data)) => {
+            InitializeUserStats::try_from((data, accounts))?.process()
+        }
+        Some((SnapshotBalance::DISCRIMINATOR, data)) => SnapshotBalance::try_from((data, accounts))?.process(),
+        #[cfg(feature = "testing")]
+        Some((InitializeTestPool::DISCRIMINATOR, data)) => InitializeTestPool::try_from((data, accounts))?.process(),
+        // Raydium CPMM swaps carry their own 8-byte discriminators, checked in full by `RaydiumSwap`
+        _ if [RaydiumSwap::SWAP_BASE_INPUT, RaydiumSwap::SWAP_BASE_OUTPUT]
+            .iter()
+            .any(|discriminator| instruction_data.starts_with(discriminator)) =>
+        {
+            RaydiumSwap::try_from((instruction_data, accounts))?.process()
+        }
+        // Token-Swap instructions carry a single-byte tag, a legacy discriminator otherwise
+        #[cfg(not(feature = "legacy-discriminators"))]
+        _ if TokenSwap::is_token_swap(instruction_data) => TokenSwap::try_from((instruction_data, accounts))?.process(),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
+
+/// The 8-byte discriminator of every instruction and the single byte it dispatches on
+const DISCRIMINATORS: &[(&[u8; 8], &u8)] = &[
+    (&Initialize::HASH_DISCRIMINATOR, Initialize::DISCRIMINATOR),
+    (&Deposit::HASH_DISCRIMINATOR, Deposit::DISCRIMINATOR),
+    (&Withdraw::HASH_DISCRIMINATOR, Withdraw::DISCRIMINATOR),
+    (&Swap::HASH_DISCRIMINATOR, Swap::DISCRIMINATOR),
+    (&UpdateConfig::HASH_DISCRIMINATOR, UpdateConfig::DISCRIMINATOR),
+    (&InitializeRegistry::HASH_DISCRIMINATOR, InitializeRegistry::DISCRIMINATOR),
+    (&OpenPosition::HASH_DISCRIMINATOR, OpenPosition::DISCRIMINATOR),
+    (&IncreasePosition::HASH_DISCRIMINATOR, IncreasePosition::DISCRIMINATOR),
+    (&DecreasePosition::HASH_DISCRIMINATOR, DecreasePosition::DISCRIMINATOR),
+    (&InitializeGlobal::HASH_DISCRIMINATOR, InitializeGlobal::DISCRIMINATOR),
+    (&UpdateGlobalConfig::HASH_DISCRIMINATOR, UpdateGlobalConfig::DISCRIMINATOR),
+    (&CollectProtocolFees::HASH_DISCRIMINATOR, CollectProtocolFees::DISCRIMINATOR),
+    (&WithdrawTreasury::HASH_DISCRIMINATOR, WithdrawTreasury::DISCRIMINATOR),
+    (&ExtendConfig::HASH_DISCRIMINATOR, ExtendConfig::DISCRIMINATOR),
+    (&InitializeLaunch::HASH_DISCRIMINATOR, InitializeLaunch::DISCRIMINATOR),
+    (&BuyLaunch::HASH_DISCRIMINATOR, BuyLaunch::DISCRIMINATOR),
+    (&SellLaunch::HASH_DISCRIMINATOR, SellLaunch::DISCRIMINATOR),
+    (&GraduateLaunch::HASH_DISCRIMINATOR, GraduateLaunch::DISCRIMINATOR),
+    (&SwapWithQuote::HASH_DISCRIMINATOR, SwapWithQuote::DISCRIMINATOR),
+    (&WithdrawMakerEscrow::HASH_DISCRIMINATOR, WithdrawMakerEscrow::DISCRIMINATOR),
+    (&ApproveStrategy::HASH_DISCRIMINATOR, ApproveStrategy::DISCRIMINATOR),
+    (&ExecuteStrategy::HASH_DISCRIMINATOR, ExecuteStrategy::DISCRIMINATOR),
+    (&Rebalance::HASH_DISCRIMINATOR, Rebalance::DISCRIMINATOR),
+    (&DepositToStrategy::HASH_DISCRIMINATOR, DepositToStrategy::DISCRIMINATOR),
+    (&WithdrawFromStrategy::HASH_DISCRIMINATOR, WithdrawFromStrategy::DISCRIMINATOR),
+    (&InitializeMultiPool::HASH_DISCRIMINATOR, InitializeMultiPool::DISCRIMINATOR),
+    (&MultiDeposit::HASH_DISCRIMINATOR, MultiDeposit::DISCRIMINATOR),
+    (&MultiWithdraw::HASH_DISCRIMINATOR, MultiWithdraw::DISCRIMINATOR),
+    (&MultiSwap::HASH_DISCRIMINATOR, MultiSwap::DISCRIMINATOR),
+    (&MigrateLiquidity::HASH_DISCRIMINATOR, MigrateLiquidity::DISCRIMINATOR),
+    (&MigrateConfig::HASH_DISCRIMINATOR, MigrateConfig::DISCRIMINATOR),
+    (&EmergencyWithdrawProtocolSide::HASH_DISCRIMINATOR, EmergencyWithdrawProtocolSide::DISCRIMINATOR),
+    (&ClaimRecovery::HASH_DISCRIMINATOR, ClaimRecovery::DISCRIMINATOR),
+    (&TokenSwap::HASH_DISCRIMINATOR, TokenSwap::DISCRIMINATOR),
+    (&InitializeVaults::HASH_DISCRIMINATOR, InitializeVaults::DISCRIMINATOR),
+    (&InitializeFeeDistributor::HASH_DISCRIMINATOR, InitializeFeeDistributor::DISCRIMINATOR),
+    (&CheckpointFees::HASH_DISCRIMINATOR, CheckpointFees::DISCRIMINATOR),
+    (&StakeGovernance::HASH_DISCRIMINATOR, StakeGovernance::DISCRIMINATOR),
+    (&UnstakeGovernance::HASH_DISCRIMINATOR, UnstakeGovernance::DISCRIMINATOR),
+    (&ClaimStakingRewards::HASH_DISCRIMINATOR, ClaimStakingRewards::DISCRIMINATOR),
+    (&RouterSwap::HASH_DISCRIMINATOR, RouterSwap::DISCRIMINATOR),
+    (&SetTrustedRouter::HASH_DISCRIMINATOR, SetTrustedRouter::DISCRIMINATOR),
+    (&AssertPoolHealthy::HASH_DISCRIMINATOR, AssertPoolHealthy::DISCRIMINATOR),
+    (&SetPoolMetadata::HASH_DISCRIMINATOR, SetPoolMetadata::DISCRIMINATOR),
+    (&SwapAndDeposit::HASH_DISCRIMINATOR, SwapAndDeposit::DISCRIMINATOR),
+    (&WithdrawAndSwap::HASH_DISCRIMINATOR, WithdrawAndSwap::DISCRIMINATOR),
+    (&SetTradingCalendar::HASH_DISCRIMINATOR, SetTradingCalendar::DISCRIMINATOR),
+    (&WithdrawInsurance::HASH_DISCRIMINATOR, WithdrawInsurance::DISCRIMINATOR),
+    (&GetPoolInfo::HASH_DISCRIMINATOR, GetPoolInfo::DISCRIMINATOR),
+    (&GetFairLpValue::HASH_DISCRIMINATOR, GetFairLpValue::DISCRIMINATOR),
+    (&InitializeLbp::HASH_DISCRIMINATOR, InitializeLbp::DISCRIMINATOR),
+    (&SwapLbp::HASH_DISCRIMINATOR, SwapLbp::DISCRIMINATOR),
+    (&ExitLbp::HASH_DISCRIMINATOR, ExitLbp::DISCRIMINATOR),
+    (&InitializeObservationTree::HASH_DISCRIMINATOR, InitializeObservationTree::DISCRIMINATOR),
+    (&AppendObservation::HASH_DISCRIMINATOR, AppendObservation::DISCRIMINATOR),
+    (&VerifyObservation::HASH_DISCRIMINATOR, VerifyObservation::DISCRIMINATOR),
+    (&Arbitrage::HASH_DISCRIMINATOR, Arbitrage::DISCRIMINATOR),
+    (&SetTokenBadge::HASH_DISCRIMINATOR, SetTokenBadge::DISCRIMINATOR),
+    (&SwapBatch::HASH_DISCRIMINATOR, SwapBatch::DISCRIMINATOR),
+    (&OpenLongTermOrder::HASH_DISCRIMINATOR, OpenLongTermOrder::DISCRIMINATOR),
+    (&ExecuteLongTermOrder::HASH_DISCRIMINATOR, ExecuteLongTermOrder::DISCRIMINATOR),
+    (&CloseLongTermOrder::HASH_DISCRIMINATOR, CloseLongTermOrder::DISCRIMINATOR),
+    (&AssertBalanceChange::HASH_DISCRIMINATOR, AssertBalanceChange::DISCRIMINATOR),
+    (&DepositWithPermit::HASH_DISCRIMINATOR, DepositWithPermit::DISCRIMINATOR),
+    (&InitializeUserStats::HASH_DISCRIMINATOR, InitializeUserStats::DISCRIMINATOR),
+    (&SnapshotBalance::HASH_DISCRIMINATOR, SnapshotBalance::DISCRIMINATOR),
+];
+
+/// Split the instruction data into the single-byte discriminator of the instruction and its data
+///
+/// Instructions are selected by their 8-byte `HASH_DISCRIMINATOR`, `sha256("global:<name>")[..8]`
+/// as Anchor derives it, so they can't collide as the instruction set grows. With the
+/// `legacy-discriminators` feature, data that doesn't start with one is read with the legacy
+/// single-byte `DISCRIMINATOR` instead.
+pub fn split_discriminator(data: &[u8]) -> Option<(&u8, &[u8])> {
+    if let Some((hash, rest)) = data.split_first_chunk::<8>() {
+        if let Some((_, discriminator)) = DISCRIMINATORS.iter().find(|(known, _)| known.eq(&hash)) {
+            return Some((discriminator, rest));
+        }
+
+        #[cfg(feature = "testing")]
+        if hash.eq(&InitializeTestPool::HASH_DISCRIMINATOR) {
+            return Some((InitializeTestPool::DISCRIMINATOR, rest));
+        }
+    }
+
+    #[cfg(feature = "legacy-discriminators")]
+    return data.split_first();
+
+    #[cfg(not(feature = "legacy-discriminators"))]
+    None
+}
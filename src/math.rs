@@ -0,0 +1,595 @@
+use crate::{isqrt, AmmError, Fee, MAX_FEE_BPS};
+use pinocchio::program_error::ProgramError;
+
+/// Direction a conversion rounds in, always chosen in favor of the pool
+///
+/// - What users pay in (deposit amounts, LP burned for exact amounts) rounds up
+/// - What users get out (LP minted, withdraw amounts, swap output) rounds down
+/// - So the reserves per LP token and `reserve_x * reserve_y` never decrease through rounding, and
+///   no sequence of deposits, swaps and withdrawals takes out more than was put in
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// `a * b / c` computed in u128, rounded as asked and checked to fit a u64.
+///
+/// The u128 product of two u64 can't overflow, so only a zero `c` or a quotient above
+/// `u64::MAX` fails, with `MathOverflow`.
+#[inline(always)]
+pub fn mul_div(a: u64, b: u64, c: u64, rounding: Rounding) -> Result<u64, ProgramError> {
+    if c == 0 {
+        return Err(AmmError::MathOverflow.into());
+    }
+
+    let product = a as u128 * b as u128;
+    let quotient = match rounding {
+        Rounding::Down => product / c as u128,
+        Rounding::Up => product.div_ceil(c as u128),
+    };
+
+    u64::try_from(quotient).map_err(|_| AmmError::MathOverflow.into())
+}
+
+/// X and Y to deposit for `lp` new LP tokens of a pool with `supply` LP, rounding up.
+#[inline(always)]
+pub fn deposit_amounts(reserve_x: u64, reserve_y: u64, supply: u64, lp: u64) -> Result<(u64, u64), ProgramError> {
+    if supply == 0 || reserve_x == 0 || reserve_y == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok((
+        mul_div(lp, reserve_x, supply, Rounding::Up)?,
+        mul_div(lp, reserve_y, supply, Rounding::Up)?,
+    ))
+}
+
+/// X and Y paid out for burning `lp` of the `supply` LP tokens, rounding down.
+///
+/// Burning the whole supply pays out the reserves exactly.
+#[inline(always)]
+pub fn withdraw_amounts(reserve_x: u64, reserve_y: u64, supply: u64, lp: u64) -> Result<(u64, u64), ProgramError> {
+    if lp > supply {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok((
+        mul_div(lp, reserve_x, supply, Rounding::Down)?,
+        mul_div(lp, reserve_y, supply, Rounding::Down)?,
+    ))
+}
+
+/// Value in raw Y of `lp` LP tokens at the external price `price_num / price_den` (raw Y per raw
+/// X), rounding down.
+///
+/// The spot reserves can be skewed within a transaction, so the pool is valued at the reserves its
+/// invariant `k` would hold at the external price instead, worth `2 * sqrt(k * price)` in Y. Moving
+/// the reserves along the curve doesn't change `k`, so it doesn't change the value.
+#[inline(always)]
+pub fn fair_lp_value(
+    (reserve_x, reserve_y): (u64, u64),
+    supply: u64,
+    lp: u64,
+    (price_num, price_den): (u128, u128),
+) -> Result<u64, ProgramError> {
+    if supply == 0 || price_den == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The square root of the price is taken in Q32.32, both roots fit in 64 bits
+    let price = price_num.checked_mul(1 << 64).ok_or(AmmError::MathOverflow)? / price_den;
+    let pool_value = (isqrt(reserve_x as u128 * reserve_y as u128) * isqrt(price)) >> 31;
+
+    let value = pool_value.checked_mul(lp as u128).ok_or(AmmError::MathOverflow)? / supply as u128;
+
+    u64::try_from(value).map_err(|_| AmmError::MathOverflow.into())
+}
+
+/// Part of `amount` to swap so that the rest and the swap output are in the pool ratio after the
+/// swap, for a swap-then-deposit with the fee kept in the reserves.
+///
+/// Solves `g * s^2 + r * (b + g) * s - a * r * b = 0` with `b = MAX_FEE_BPS`, `g` the bps left
+/// after the fee. Rounding only ever swaps too little, leaving dust of the input with the user.
+#[inline(always)]
+pub fn zap_swap_amount(reserve_in: u64, amount: u64, fee_bps: u16) -> Result<u64, ProgramError> {
+    if fee_bps >= MAX_FEE_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (b, g) = (MAX_FEE_BPS as u128, (MAX_FEE_BPS - fee_bps) as u128);
+    let r = reserve_in as u128;
+
+    // `r * x` is the discriminant, split in two roots when it doesn't fit
+    let x = r * (b + g) * (b + g) + 4 * g * b * amount as u128;
+    let root = match r.checked_mul(x) {
+        Some(discriminant) => isqrt(discriminant),
+        None => isqrt(r) * isqrt(x),
+    };
+
+    let swapped = root.saturating_sub(r * (b + g)) / (2 * g);
+
+    Ok(u64::try_from(swapped).map_err(|_| AmmError::MathOverflow)?.min(amount))
+}
+
+/// Output of a constant product swap of `amount_in` (fee already taken out), rounding down:
+/// `reserve_out * amount_in / (reserve_in + amount_in)`.
+#[cfg(not(feature = "legacy-curve"))]
+#[inline(always)]
+pub fn swap_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64) -> Result<u64, ProgramError> {
+    let reserve_in_after = reserve_in.checked_add(amount_in).ok_or(AmmError::MathOverflow)?;
+
+    mul_div(reserve_out, amount_in, reserve_in_after, Rounding::Down)
+}
+
+/// Same swap priced by the external `constant-product-curve` crate, kept behind `legacy-curve` to
+/// compare both paths while the internal math rolls out.
+#[cfg(feature = "legacy-curve")]
+pub fn swap_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64) -> Result<u64, ProgramError> {
+    use constant_product_curve::{ConstantProduct, CurveError, LiquidityPair};
+
+    let map_err = |e: CurveError| match e {
+        CurveError::Overflow | CurveError::Underflow => AmmError::MathOverflow.into(),
+        _ => ProgramError::InvalidArgument,
+    };
+
+    // The fee is already taken out and the LP supply plays no part in the swap. No minimum is
+    // passed either, the caller enforces its own so a change in the crate can't drop it.
+    let mut curve = ConstantProduct::init(reserve_in, reserve_out, reserve_in, 0, None).map_err(map_err)?;
+
+    Ok(curve.swap(LiquidityPair::X, amount_in, 0).map_err(map_err)?.withdraw)
+}
+
+/// Output of a swap of `amount_in` paying `swap_fee`, and the fee taken out of `amount_in`.
+///
+/// Fails with `ZeroSwapOutput` for inputs too small to buy anything once the fee is taken, which
+/// a dust pool turns most inputs into.
+#[inline(always)]
+pub fn swap_with_fee(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    swap_fee: Fee,
+) -> Result<(u64, u64), ProgramError> {
+    let amount_in_after_fee = swap_fee.remainder(amount_in);
+    let fee = amount_in.checked_sub(amount_in_after_fee).ok_or(AmmError::MathOverflow)?;
+
+    let amount_out = swap_amount_out(reserve_in, reserve_out, amount_in_after_fee)?;
+    if amount_out == 0 {
+        return Err(AmmError::ZeroSwapOutput.into());
+    }
+
+    Ok((amount_out, fee))
+}
+
+/// Fixed-point one of the weighted math, 48 fractional bits keep every product within a u128
+const WEIGHT_ONE: u128 = 1 << 48;
+
+/// Added to the fraction of the output reserve kept by a weighted swap, above the truncation error
+/// of `neg_log2` and `exp2_neg` for weight ratios up to 99
+const WEIGHT_ROUNDING_MARGIN: u128 = 1 << 16;
+
+/// `2^(-2^-i)` for `i` in 1..=48, in `WEIGHT_ONE` units, rounded down
+const EXP2_NEG_TABLE: [u128; 48] = [
+    199032864766430, 236691298899613, 258113691704612, 269541361132678,
+    275443548385834, 278442931975302, 279954849561487, 280713884160286,
+    281094172843149, 281284510335223, 281379727407067, 281427348029211,
+    281451161362436, 281463068784660, 281469022684685, 281471999681927,
+    281473488192355, 281474232450522, 281474604580343, 281474790645438,
+    281474883678031, 281474930194339, 281474953452497, 281474965081576,
+    281474970896116, 281474973803386, 281474975257021, 281474975983838,
+    281474976347247, 281474976528951, 281474976619803, 281474976665229,
+    281474976687942, 281474976699299, 281474976704977, 281474976707816,
+    281474976709236, 281474976709946, 281474976710301, 281474976710478,
+    281474976710567, 281474976710611, 281474976710633, 281474976710644,
+    281474976710650, 281474976710653, 281474976710654, 281474976710655,
+];
+
+/// `-log2(x)` of `x` in (0, 1], in `WEIGHT_ONE` units, by repeated squaring of the mantissa.
+#[inline(always)]
+fn neg_log2(x: u128) -> u128 {
+    // Normalize into [1, 2), the shift is the integer part
+    let shift = x.leading_zeros() - WEIGHT_ONE.leading_zeros();
+    let mut mantissa = x << shift;
+
+    let mut fraction = 0;
+    for bit in (0..48).rev() {
+        mantissa = (mantissa * mantissa) >> 48;
+        if mantissa >= 2 * WEIGHT_ONE {
+            mantissa >>= 1;
+            fraction |= 1 << bit;
+        }
+    }
+
+    shift as u128 * WEIGHT_ONE - fraction
+}
+
+/// `2^-e` of `e >= 0`, in `WEIGHT_ONE` units, rounding down.
+#[inline(always)]
+fn exp2_neg(e: u128) -> u128 {
+    let integer = e >> 48;
+    if integer >= 48 {
+        return 0;
+    }
+
+    let fraction = EXP2_NEG_TABLE
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| e & (1 << (47 - i)) != 0)
+        .fold(WEIGHT_ONE, |result, (_, factor)| (result * factor) >> 48);
+
+    fraction >> integer
+}
+
+/// Output of a weighted pool swap of `amount_in` (fee already taken out), rounding down:
+/// `reserve_out * (1 - (reserve_in / (reserve_in + amount_in))^(weight_in / weight_out))`.
+///
+/// Weights are in bps and must stay within a ratio of 99 of each other, past which the margin
+/// kept for rounding no longer covers the error of the power.
+pub fn weighted_swap_amount_out(
+    reserve_in: u64,
+    weight_in: u16,
+    reserve_out: u64,
+    weight_out: u16,
+    amount_in: u64,
+) -> Result<u64, ProgramError> {
+    if weight_in == 0 || weight_out == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+
+    // The base rounds up, so the output rounds down
+    let reserve_in_after = reserve_in as u128 + amount_in as u128;
+    let base = (reserve_in as u128 * WEIGHT_ONE).div_ceil(reserve_in_after);
+
+    let exponent = neg_log2(base) * weight_in as u128 / weight_out as u128;
+    let kept = (exp2_neg(exponent) + WEIGHT_ROUNDING_MARGIN).min(WEIGHT_ONE);
+
+    mul_div(reserve_out, (WEIGHT_ONE - kept) as u64, WEIGHT_ONE as u64, Rounding::Down)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Lbp;
+
+    /// `reserve_out * amount_in / (reserve_in + amount_in)` rounded down, the swap both curves price
+    fn reference_swap(reserve_in: u64, reserve_out: u64, amount_in: u64) -> u64 {
+        (reserve_out as u128 * amount_in as u128 / (reserve_in as u128 + amount_in as u128)) as u64
+    }
+
+    #[test]
+    fn mul_div_rounds_as_asked() {
+        assert_eq!(mul_div(10, 10, 5, Rounding::Down), Ok(20));
+        assert_eq!(mul_div(10, 10, 5, Rounding::Up), Ok(20));
+        assert_eq!(mul_div(7, 3, 2, Rounding::Down), Ok(10));
+        assert_eq!(mul_div(7, 3, 2, Rounding::Up), Ok(11));
+        assert_eq!(mul_div(1, 1, 3, Rounding::Down), Ok(0));
+        assert_eq!(mul_div(1, 1, 3, Rounding::Up), Ok(1));
+        assert_eq!(mul_div(0, u64::MAX, 1, Rounding::Up), Ok(0));
+    }
+
+    #[test]
+    fn mul_div_keeps_the_u128_product() {
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX, Rounding::Down), Ok(u64::MAX));
+        assert_eq!(mul_div(u64::MAX, u64::MAX - 1, u64::MAX, Rounding::Up), Ok(u64::MAX - 1));
+        assert_eq!(mul_div(u64::MAX, 2, 3, Rounding::Down), Ok(u64::MAX / 3 * 2));
+    }
+
+    #[test]
+    fn mul_div_fails_on_zero_divisor() {
+        assert_eq!(mul_div(1, 1, 0, Rounding::Down), Err(AmmError::MathOverflow.into()));
+        assert_eq!(mul_div(0, 0, 0, Rounding::Up), Err(AmmError::MathOverflow.into()));
+    }
+
+    #[test]
+    fn isqrt_is_the_floor_root() {
+        for (value, root) in [(0, 0), (1, 1), (2, 1), (3, 1), (4, 2), (8, 2), (9, 3), (99, 9), (100, 10)] {
+            assert_eq!(isqrt(value), root);
+        }
+
+        for root in [2u128, 1_000_003, u32::MAX as u128, u64::MAX as u128] {
+            assert_eq!(isqrt(root * root), root);
+            assert_eq!(isqrt(root * root - 1), root - 1);
+        }
+
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn deposit_amounts_round_up() {
+        assert_eq!(deposit_amounts(1_000, 2_000, 100, 10), Ok((100, 200)));
+        assert_eq!(deposit_amounts(1_000, 2_000, 300, 1), Ok((4, 7)));
+        assert_eq!(deposit_amounts(1, 1, u64::MAX, 1), Ok((1, 1)));
+    }
+
+    #[test]
+    fn deposit_amounts_need_a_priced_pool() {
+        assert_eq!(deposit_amounts(0, 0, 0, 1), Err(ProgramError::InvalidArgument));
+        assert_eq!(deposit_amounts(1_000, 0, 100, 1), Err(ProgramError::InvalidArgument));
+        assert_eq!(deposit_amounts(0, 1_000, 100, 1), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn withdraw_amounts_round_down() {
+        assert_eq!(withdraw_amounts(1_000, 2_000, 100, 10), Ok((100, 200)));
+        assert_eq!(withdraw_amounts(1_000, 2_000, 300, 1), Ok((3, 6)));
+        assert_eq!(withdraw_amounts(1, 1, u64::MAX, 1), Ok((0, 0)));
+    }
+
+    #[test]
+    fn withdraw_amounts_pay_the_reserves_for_the_supply() {
+        assert_eq!(withdraw_amounts(1_000, 2_000, 300, 300), Ok((1_000, 2_000)));
+        assert_eq!(withdraw_amounts(u64::MAX, u64::MAX, u64::MAX, u64::MAX), Ok((u64::MAX, u64::MAX)));
+        assert_eq!(withdraw_amounts(1_000, 2_000, 300, 301), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn swap_amount_out_rounds_down() {
+        assert_eq!(swap_amount_out(1_000, 1_000, 1_000), Ok(500));
+        assert_eq!(swap_amount_out(1_000, 1_000, 1), Ok(0));
+        assert_eq!(swap_amount_out(1_000, 2_000, 3), Ok(5));
+        assert_eq!(swap_amount_out(1_000, 1_000, 0), Ok(0));
+    }
+
+    #[test]
+    fn swap_amount_out_never_drains_the_pool() {
+        for reserve_out in [1, 2, 1_000, u64::MAX] {
+            assert!(swap_amount_out(1, reserve_out, u64::MAX - 1).unwrap() < reserve_out);
+        }
+    }
+
+    /// Holds for the internal math and the `legacy-curve` path alike, run with and without the
+    /// feature to compare them
+    #[test]
+    fn swap_amount_out_matches_the_constant_product() {
+        let amounts = [1, 2, 3, 7, 100, 997, 1_000_000, 123_456_789_012, u32::MAX as u64, 1 << 40];
+
+        for reserve_in in amounts {
+            for reserve_out in amounts {
+                for amount_in in amounts {
+                    assert_eq!(
+                        swap_amount_out(reserve_in, reserve_out, amount_in),
+                        Ok(reference_swap(reserve_in, reserve_out, amount_in)),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Pools as `(reserve_x, reserve_y, supply)`, from dust to reserves near `u64::MAX`
+    const POOLS: [(u64, u64, u64); 8] = [
+        (1, 1, 1),
+        (3, 7, 2),
+        (10, 1_000_000, 17),
+        (1_000_003, 999_983, 1_000),
+        (1, u64::MAX / 4, 1 << 20),
+        (u64::MAX / 4, u64::MAX / 4, 3),
+        (u64::MAX / 4, 5, u64::MAX / 4),
+        (u64::MAX / 2 - 1, u64::MAX / 2 - 7, u64::MAX / 2),
+    ];
+
+    const AMOUNTS: [u64; 8] = [1, 2, 3, 99, 1_000, 1 << 33, u64::MAX / 8, u64::MAX / 2];
+
+    #[test]
+    fn deposit_then_withdraw_returns_at_most_the_deposit() {
+        for (reserve_x, reserve_y, supply) in POOLS {
+            for lp in AMOUNTS {
+                let Ok((x, y)) = deposit_amounts(reserve_x, reserve_y, supply, lp) else {
+                    continue;
+                };
+                let (Some(reserve_x), Some(reserve_y), Some(supply)) =
+                    (reserve_x.checked_add(x), reserve_y.checked_add(y), supply.checked_add(lp))
+                else {
+                    continue;
+                };
+
+                let (out_x, out_y) = withdraw_amounts(reserve_x, reserve_y, supply, lp).unwrap();
+                assert!(out_x <= x && out_y <= y);
+            }
+        }
+    }
+
+    #[test]
+    fn withdraw_then_deposit_costs_at_least_the_withdrawal() {
+        for (reserve_x, reserve_y, supply) in POOLS {
+            for lp in AMOUNTS.into_iter().filter(|lp| *lp < supply) {
+                let (x, y) = withdraw_amounts(reserve_x, reserve_y, supply, lp).unwrap();
+                let Ok((in_x, in_y)) = deposit_amounts(reserve_x - x, reserve_y - y, supply - lp, lp) else {
+                    continue;
+                };
+
+                assert!(in_x >= x && in_y >= y);
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_dust_deposits_and_withdrawals_extract_nothing() {
+        for (mut reserve_x, mut reserve_y, mut supply) in POOLS {
+            let (mut paid_x, mut paid_y, mut received_x, mut received_y) = (0u128, 0u128, 0u128, 0u128);
+
+            for _ in 0..64 {
+                let Ok((x, y)) = deposit_amounts(reserve_x, reserve_y, supply, 1) else {
+                    break;
+                };
+                let (Some(next_x), Some(next_y)) = (reserve_x.checked_add(x), reserve_y.checked_add(y)) else {
+                    break;
+                };
+                (reserve_x, reserve_y, supply) = (next_x, next_y, supply + 1);
+                (paid_x, paid_y) = (paid_x + x as u128, paid_y + y as u128);
+
+                let (x, y) = withdraw_amounts(reserve_x, reserve_y, supply, 1).unwrap();
+                (reserve_x, reserve_y, supply) = (reserve_x - x, reserve_y - y, supply - 1);
+                (received_x, received_y) = (received_x + x as u128, received_y + y as u128);
+            }
+
+            assert!(received_x <= paid_x && received_y <= paid_y);
+        }
+    }
+
+    #[test]
+    fn swap_then_reverse_swap_returns_at_most_the_input() {
+        for (reserve_in, reserve_out, _) in POOLS {
+            for amount_in in AMOUNTS {
+                let Some(reserve_in_after) = reserve_in.checked_add(amount_in) else {
+                    continue;
+                };
+                let out = swap_amount_out(reserve_in, reserve_out, amount_in).unwrap();
+                let reserve_out_after = reserve_out - out;
+
+                // The invariant never decreases through rounding
+                let k = reserve_in as u128 * reserve_out as u128;
+                assert!(reserve_in_after as u128 * reserve_out_after as u128 >= k);
+
+                let Some(reserve_out_back) = reserve_out_after.checked_add(out) else {
+                    continue;
+                };
+                let back = swap_amount_out(reserve_out_after, reserve_in_after, out).unwrap();
+
+                assert!(back <= amount_in);
+                assert!((reserve_in_after - back) as u128 * reserve_out_back as u128 >= k);
+            }
+        }
+    }
+
+    #[test]
+    fn mul_div_fails_past_u64_max() {
+        assert_eq!(mul_div(u64::MAX, 2, 1, Rounding::Down), Err(AmmError::MathOverflow.into()));
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX - 1, Rounding::Down), Err(AmmError::MathOverflow.into()));
+        assert_eq!(mul_div(u64::MAX, u64::MAX - 1, u64::MAX - 1, Rounding::Up), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn swap_amount_out_fails_past_u64_max_reserves() {
+        assert_eq!(swap_amount_out(u64::MAX, u64::MAX, 1), Err(AmmError::MathOverflow.into()));
+        assert_eq!(swap_amount_out(1, u64::MAX, u64::MAX), Err(AmmError::MathOverflow.into()));
+        assert_eq!(swap_amount_out(u64::MAX - 1, u64::MAX, 1), Ok(1));
+        assert_eq!(swap_amount_out(u64::MAX / 2, u64::MAX, u64::MAX / 2 + 1), Ok(u64::MAX / 2 + 1));
+    }
+
+    #[test]
+    fn deposit_amounts_fail_past_u64_max() {
+        assert_eq!(deposit_amounts(u64::MAX, u64::MAX, 1, 2), Err(AmmError::MathOverflow.into()));
+        assert_eq!(deposit_amounts(u64::MAX, 1, 1, 2), Err(AmmError::MathOverflow.into()));
+        assert_eq!(deposit_amounts(u64::MAX, u64::MAX, u64::MAX, u64::MAX), Ok((u64::MAX, u64::MAX)));
+    }
+
+    #[test]
+    fn fair_lp_value_fails_past_u64_max() {
+        let (max_price, one) = ((u128::MAX, 1), (1, 1));
+
+        assert_eq!(fair_lp_value((1, 1), 1, 1, max_price), Err(AmmError::MathOverflow.into()));
+        assert_eq!(fair_lp_value((u64::MAX, u64::MAX), 1, u64::MAX, one), Err(AmmError::MathOverflow.into()));
+
+        // The whole pool is worth twice the Y reserve, past a u64 above half of it
+        assert_eq!(fair_lp_value((u64::MAX, u64::MAX), u64::MAX, u64::MAX, one), Err(AmmError::MathOverflow.into()));
+        assert!(fair_lp_value((u64::MAX / 2, u64::MAX / 2), u64::MAX, u64::MAX, one).is_ok());
+    }
+
+    #[test]
+    fn zap_and_weighted_swaps_hold_at_u64_max() {
+        assert!(zap_swap_amount(u64::MAX, u64::MAX, 30).is_ok());
+        assert!(zap_swap_amount(1, u64::MAX, 9_999).is_ok());
+        assert!(weighted_swap_amount_out(u64::MAX, 5_000, u64::MAX, 5_000, u64::MAX).unwrap() < u64::MAX);
+        assert!(weighted_swap_amount_out(1, 9_900, u64::MAX, 100, u64::MAX).unwrap() < u64::MAX);
+    }
+
+    /// `reserve_out * (1 - (reserve_in / (reserve_in + amount_in))^(weight_in / weight_out))` in f64,
+    /// through `ln_1p` and `exp_m1` so small swaps keep their precision
+    fn reference_weighted_swap(
+        reserve_in: u64,
+        weight_in: u16,
+        reserve_out: u64,
+        weight_out: u16,
+        amount_in: u64,
+    ) -> f64 {
+        let exponent = weight_in as f64 / weight_out as f64;
+        let log_base = -(amount_in as f64 / reserve_in as f64).ln_1p();
+
+        reserve_out as f64 * -(exponent * log_base).exp_m1()
+    }
+
+    /// Weights from `Lbp::MIN_WEIGHT` to `Lbp::MAX_WEIGHT`
+    const WEIGHTS: [u16; 9] = [Lbp::MIN_WEIGHT, 500, 1_000, 2_500, 5_000, 7_500, 9_000, 9_500, Lbp::MAX_WEIGHT];
+
+    #[test]
+    fn neg_log2_and_exp2_neg_are_inverses() {
+        assert_eq!(neg_log2(WEIGHT_ONE), 0);
+        assert_eq!(neg_log2(WEIGHT_ONE / 2), WEIGHT_ONE);
+        assert_eq!(neg_log2(WEIGHT_ONE / 8), 3 * WEIGHT_ONE);
+        assert_eq!(exp2_neg(0), WEIGHT_ONE);
+        assert_eq!(exp2_neg(WEIGHT_ONE), WEIGHT_ONE / 2);
+        assert_eq!(exp2_neg(48 * WEIGHT_ONE), 0);
+
+        for x in [1, 3, 1 << 20, WEIGHT_ONE / 3, WEIGHT_ONE / 2 + 1, WEIGHT_ONE - 1] {
+            let round_trip = exp2_neg(neg_log2(x));
+            assert!(round_trip.abs_diff(x) <= (x >> 40) + 1);
+        }
+    }
+
+    #[test]
+    fn weighted_swap_with_equal_weights_is_the_constant_product() {
+        let amounts = [1, 3, 100, 997, 1_000_000, 123_456_789_012, u32::MAX as u64, 1 << 40];
+
+        for weight in WEIGHTS {
+            for (reserve_in, reserve_out) in amounts.iter().flat_map(|a| amounts.map(|b| (*a, b))) {
+                // The margin only ever lowers the output, by its share of the reserve
+                let margin = (reserve_out as u128 * WEIGHT_ROUNDING_MARGIN).div_ceil(WEIGHT_ONE) as u64;
+
+                for amount_in in amounts {
+                    let constant_product = swap_amount_out(reserve_in, reserve_out, amount_in);
+                    let weighted = weighted_swap_amount_out(reserve_in, weight, reserve_out, weight, amount_in);
+                    let (constant_product, weighted) = (constant_product.unwrap(), weighted.unwrap());
+
+                    assert!(weighted <= constant_product);
+                    assert!(constant_product - weighted <= margin + 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weighted_swap_never_pays_more_than_the_exact_power() {
+        let amounts = [1, 3, 100, 997, 1_000_000, 123_456_789_012, 1 << 40, u64::MAX / 3];
+
+        for (weight_in, weight_out) in WEIGHTS.iter().flat_map(|a| WEIGHTS.map(|b| (*a, b))) {
+            for (reserve_in, reserve_out) in amounts.iter().flat_map(|a| amounts.map(|b| (*a, b))) {
+                for amount_in in amounts {
+                    let out = weighted_swap_amount_out(reserve_in, weight_in, reserve_out, weight_out, amount_in);
+                    let exact = reference_weighted_swap(reserve_in, weight_in, reserve_out, weight_out, amount_in);
+
+                    // Slack for the f64 reference itself, far below the rounding margin
+                    assert!(out.unwrap() as f64 <= exact * (1.0 + 1e-12));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_with_fee_takes_the_fee_off_the_input() {
+        let fee = Fee::from_bps(30).unwrap();
+
+        assert_eq!(swap_with_fee(1_000, 1_000, 1_000, fee), Ok((499, 3)));
+        assert_eq!(swap_with_fee(1_000, 1_000, 1_000, Fee::ZERO), Ok((500, 0)));
+    }
+
+    #[test]
+    fn swap_with_fee_refuses_dust_outputs() {
+        let fee = Fee::from_bps(30).unwrap();
+        let zero_output = Err(AmmError::ZeroSwapOutput.into());
+
+        // One unit out can never be bought, the output stays below the reserve it leaves
+        for amount_in in [1, 2, 1_000, u64::MAX - 1] {
+            assert_eq!(swap_with_fee(1, 1, amount_in, fee), zero_output);
+        }
+
+        // Inputs too small to buy anything once the fee is taken
+        assert_eq!(swap_with_fee(1_000, 1_000, 1, Fee::ZERO), zero_output);
+        assert_eq!(swap_with_fee(1_000, 1_000, 2, fee), zero_output);
+        assert_eq!(swap_with_fee(1_000, 1_000, 1_000, Fee::from_bps(MAX_FEE_BPS).unwrap()), zero_output);
+        assert_eq!(swap_with_fee(1_000, 1_000, 3, fee), Ok((1, 1)));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::MEMO_PROGRAM_ID;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+/// SPL Memo `Memo`, logging `memo` with `signer` attached so fills can be tagged, e.g. with an order ID
+///
+/// - The memo program rejects memos that aren't valid UTF-8
+pub struct Memo<'a> {
+    pub signer: &'a AccountInfo,
+    pub memo: &'a [u8],
+    pub memo_program: &'a AccountInfo,
+}
+
+impl Memo<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        if self.memo_program.key().ne(&MEMO_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let account_metas = [AccountMeta::readonly_signer(self.signer.key())];
+
+        // The instruction data is the memo itself
+        let instruction = Instruction {
+            program_id: &MEMO_PROGRAM_ID,
+            accounts: &account_metas,
+            data: self.memo,
+        };
+
+        invoke(&instruction, &[self.signer])
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{AmmError, MAX_FEE_BPS, ORACLE_MAX_AGE, PYTH_RECEIVER_PROGRAM_ID};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+
+/// A price read from an oracle account: `price * 10^exponent`
+pub struct OraclePrice {
+    pub price: i64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+/// Pyth pull-oracle `PriceUpdateV2` account
+///
+/// - Layout: discriminator [8] | write_authority [32] | verification_level [1 or 2] | price message
+/// - Only fully verified updates are accepted, so the price message always starts at `MESSAGE_OFFSET`
+pub struct PythPriceUpdate;
+
+impl PythPriceUpdate {
+    pub const DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+    /// `VerificationLevel::Full` borsh tag
+    const VERIFICATION_FULL: u8 = 1;
+
+    const VERIFICATION_LEVEL_OFFSET: usize = 40;
+
+    /// Price message: feed_id [32] | price i64 | conf u64 | exponent i32 | publish_time i64 | ..
+    const MESSAGE_OFFSET: usize = 41;
+
+    const PRICE_OFFSET: usize = Self::MESSAGE_OFFSET + 32;
+    const EXPONENT_OFFSET: usize = Self::PRICE_OFFSET + 16;
+    const PUBLISH_TIME_OFFSET: usize = Self::EXPONENT_OFFSET + 4;
+
+    /// Read the price from a `PriceUpdateV2` account, rejecting stale or partially verified updates.
+    pub fn read(account_info: &AccountInfo, now: i64) -> Result<OraclePrice, ProgramError> {
+        if account_info.owner().ne(&PYTH_RECEIVER_PROGRAM_ID) {
+            return Err(AmmError::InvalidOracle.into());
+        }
+
+        let data = account_info.try_borrow_data()?;
+
+        if data.len() < Self::PUBLISH_TIME_OFFSET + 8
+            || data[..8].ne(&Self::DISCRIMINATOR)
+            || data[Self::VERIFICATION_LEVEL_OFFSET] != Self::VERIFICATION_FULL
+        {
+            return Err(AmmError::InvalidOracle.into());
+        }
+
+        let price = i64::from_le_bytes(data[Self::PRICE_OFFSET..Self::PRICE_OFFSET + 8].try_into().unwrap());
+        let exponent = i32::from_le_bytes(data[Self::EXPONENT_OFFSET..Self::EXPONENT_OFFSET + 4].try_into().unwrap());
+        let publish_time = i64::from_le_bytes(
+            data[Self::PUBLISH_TIME_OFFSET..Self::PUBLISH_TIME_OFFSET + 8].try_into().unwrap(),
+        );
+
+        if price <= 0 || now.saturating_sub(publish_time) > ORACLE_MAX_AGE {
+            return Err(AmmError::InvalidOracle.into());
+        }
+
+        Ok(OraclePrice {
+            price,
+            exponent,
+            publish_time,
+        })
+    }
+}
+
+impl OraclePrice {
+    /// The price as a `(numerator, denominator)` of Y per X in raw units, the oracle quoting one
+    /// whole X in whole Y.
+    pub fn raw_ratio(&self, decimals_x: u8, decimals_y: u8) -> Result<(u128, u128), ProgramError> {
+        // Y per X in raw units is price * 10^(exponent + decimals_y - decimals_x)
+        let scale = self.exponent + decimals_y as i32 - decimals_x as i32;
+        let pow = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(AmmError::MathOverflow)?;
+
+        match scale >= 0 {
+            true => Ok(((self.price as u128).checked_mul(pow).ok_or(AmmError::MathOverflow)?, 1)),
+            false => Ok((self.price as u128, pow)),
+        }
+    }
+
+    /// Check that trading `x` against `y` (raw amounts) happens within `max_deviation_bps` of the
+    /// oracle price, which quotes one whole X in whole Y.
+    pub fn check_deviation(
+        &self,
+        x: u64,
+        y: u64,
+        decimals_x: u8,
+        decimals_y: u8,
+        max_deviation_bps: u16,
+    ) -> Result<(), ProgramError> {
+        // Y per X in raw units is price * 10^(exponent + decimals_y - decimals_x)
+        let scale = self.exponent + decimals_y as i32 - decimals_x as i32;
+        let pow = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(AmmError::MathOverflow)?;
+
+        // Compare `actual` (y) to `expected` (x * oracle price) on the same scale
+        let (actual, expected) = match scale >= 0 {
+            true => (
+                y as u128,
+                (x as u128 * self.price as u128)
+                    .checked_mul(pow)
+                    .ok_or(AmmError::MathOverflow)?,
+            ),
+            false => (
+                (y as u128).checked_mul(pow).ok_or(AmmError::MathOverflow)?,
+                x as u128 * self.price as u128,
+            ),
+        };
+
+        let max_deviation = expected
+            .checked_mul(max_deviation_bps as u128)
+            .ok_or(AmmError::MathOverflow)?
+            / MAX_FEE_BPS as u128;
+
+        if actual.abs_diff(expected) > max_deviation {
+            return Err(AmmError::OraclePriceDeviation.into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{state::MultiPool, CONFIG_SEED, VAULT_SEED};
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::{create_program_address, find_program_address, Pubkey};
+
+/// Derive the config of a pool, with `None` as the seed of canonical pools.
+#[inline(always)]
+pub fn derive_config_address(seed: Option<u64>, mint_x: &Pubkey, mint_y: &Pubkey) -> (Pubkey, u8) {
+    match seed {
+        Some(seed) => find_program_address(&[CONFIG_SEED, &seed.to_le_bytes(), mint_x, mint_y], &crate::ID),
+        None => find_program_address(&[CONFIG_SEED, mint_x, mint_y], &crate::ID),
+    }
+}
+
+/// Derive the LP mint of the pool at `config`.
+#[inline(always)]
+pub fn derive_lp_mint(config: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"mint_lp", config], &crate::ID)
+}
+
+/// Derive the `Registry` page numbered `page`.
+#[inline(always)]
+pub fn derive_registry(page: u32) -> (Pubkey, u8) {
+    find_program_address(&[b"registry", &page.to_le_bytes()], &crate::ID)
+}
+
+/// Derive the associated token account of `wallet` for `mint`.
+#[inline(always)]
+pub fn derive_ata(wallet: &Pubkey, token_program: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find_program_address(&[wallet, token_program, mint], &pinocchio_associated_token_account::ID).0
+}
+
+/// Derive the X and Y vaults of the pool at `config`, the config's ATAs of each mint.
+#[inline(always)]
+pub fn derive_vaults(
+    config: &Pubkey,
+    mint_x: &Pubkey,
+    token_program_x: &Pubkey,
+    mint_y: &Pubkey,
+    token_program_y: &Pubkey,
+) -> (Pubkey, Pubkey) {
+    (
+        derive_ata(config, token_program_x, mint_x),
+        derive_ata(config, token_program_y, mint_y),
+    )
+}
+
+/// Derive the program-derived vault of `mint` in the pool at `config`, see `Config::FLAG_PDA_VAULTS`.
+#[inline(always)]
+pub fn derive_pda_vault(config: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[VAULT_SEED, config, mint], &crate::ID)
+}
+
+/// Recompute a program-derived vault from its stored bump, without the bump search.
+#[inline(always)]
+pub fn create_pda_vault_address(config: &Pubkey, mint: &Pubkey, bump: [u8; 1]) -> Result<Pubkey, ProgramError> {
+    create_program_address(&[VAULT_SEED, config, mint, &bump], &crate::ID)
+}
+
+/// Derive the deposit history `Position` of `owner` in the pool at `config`.
+#[inline(always)]
+pub fn derive_position(config: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"position", config, owner], &crate::ID)
+}
+
+/// Derive the RFQ escrow authority of `maker` in the pool at `config`.
+#[inline(always)]
+pub fn derive_maker_escrow(config: &Pubkey, maker: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"maker", config, maker], &crate::ID)
+}
+
+/// Derive the recovery escrow authority of the pool at `config`, see `EmergencyWithdrawProtocolSide`.
+#[inline(always)]
+pub fn derive_recovery(config: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"recovery", config], &crate::ID)
+}
+
+/// Derive the insurance fund authority of the pool at `config`, owner of its insurance vaults.
+#[inline(always)]
+pub fn derive_insurance(config: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"insurance", config], &crate::ID)
+}
+
+/// Derive the `FeeDistributor` sharing `reward_mint` fees among `governance_mint` stakers.
+#[inline(always)]
+pub fn derive_fee_distributor(governance_mint: &Pubkey, reward_mint: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"fee_distributor", governance_mint, reward_mint], &crate::ID)
+}
+
+/// Derive the `Staker` of `owner` in the fee distributor at `fee_distributor`.
+#[inline(always)]
+pub fn derive_staker(fee_distributor: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"staker", fee_distributor, owner], &crate::ID)
+}
+
+/// Derive the `TrustedRouter` entry of `router`.
+#[inline(always)]
+pub fn derive_trusted_router(router: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"trusted_router", router], &crate::ID)
+}
+
+/// Derive the `TokenBadge` of `mint`.
+#[inline(always)]
+pub fn derive_token_badge(mint: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"token_badge", mint], &crate::ID)
+}
+
+/// Derive the `PoolMetadata` of the pool at `config`.
+#[inline(always)]
+pub fn derive_pool_metadata(config: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"pool_metadata", config], &crate::ID)
+}
+
+/// Derive the `TradingCalendar` of `authority` numbered `seed`.
+#[inline(always)]
+pub fn derive_trading_calendar(authority: &Pubkey, seed: &[u8; 8]) -> (Pubkey, u8) {
+    find_program_address(&[b"trading_calendar", authority, seed], &crate::ID)
+}
+
+/// Derive the strategy `Allowance` granted by `owner` to `keeper` in the pool at `config`.
+#[inline(always)]
+pub fn derive_allowance(config: &Pubkey, owner: &Pubkey, keeper: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"allowance", config, owner, keeper], &crate::ID)
+}
+
+/// Derive the delegate the owner approves for `DepositWithPermit`.
+#[inline(always)]
+pub fn derive_permit_authority(owner: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"permit", owner], &crate::ID)
+}
+
+/// Derive the account burning the `DepositWithPermit` permit of `owner` with `nonce`.
+#[inline(always)]
+pub fn derive_permit_nonce(owner: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    find_program_address(&[b"permit_nonce", owner, &nonce.to_le_bytes()], &crate::ID)
+}
+
+/// Derive the `UserStats` of `user` on the pool at `config`.
+#[inline(always)]
+pub fn derive_user_stats(config: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"user_stats", config, user], &crate::ID)
+}
+
+/// Derive the `BalanceSnapshot` of a token account.
+#[inline(always)]
+pub fn derive_balance_snapshot(token_account: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[b"balance_snapshot", token_account], &crate::ID)
+}
+
+/// Derive a `LongTermOrder` from its config, owner and nonce.
+#[inline(always)]
+pub fn derive_long_term_order(config: &Pubkey, owner: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    find_program_address(&[b"long_term_order", config, owner, &nonce.to_le_bytes()], &crate::ID)
+}
+
+/// Derive a `MultiPool` from its seed and its mints, in ascending order.
+#[inline(always)]
+pub fn derive_multi_pool(seed: u64, mints: &[Pubkey]) -> (Pubkey, u8) {
+    let seed = seed.to_le_bytes();
+    let mut seeds: [&[u8]; MultiPool::MAX_TOKENS + 2] = [&[]; MultiPool::MAX_TOKENS + 2];
+    seeds[0] = b"multi_pool";
+    seeds[1] = &seed;
+    for (index, mint) in mints.iter().take(MultiPool::MAX_TOKENS).enumerate() {
+        seeds[index + 2] = mint;
+    }
+
+    find_program_address(&seeds[..mints.len().min(MultiPool::MAX_TOKENS) + 2], &crate::ID)
+}
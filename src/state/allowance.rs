@@ -0,0 +1,199 @@
+use crate::AmmError;
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Spending allowance a user grants a keeper on one pool, see `ApproveStrategy`
+///
+/// - PDA seeds: [b"allowance", config, owner, keeper, bump]
+/// - The owner approves the PDA as delegate of its token accounts, and `ExecuteStrategy` signs
+///   for it while the remaining `amount_x`/`amount_y` and `expiration` allow
+#[repr(C)]
+pub struct Allowance {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    owner: Pubkey,
+    keeper: Pubkey,
+    config: Pubkey,
+    amount_x: [u8; 8],
+    amount_y: [u8; 8],
+    expiration: [u8; 8],
+    permissions: u8,
+    bump: [u8; 1],
+}
+
+impl Allowance {
+    /// `sha256("account:Allowance")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [144, 8, 184, 213, 49, 248, 73, 131];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<Pubkey>() * 3
+        + size_of::<u64>() * 2
+        + size_of::<i64>()
+        + size_of::<u8>() * 2;
+
+    /// The keeper may run `Swap`
+    pub const ALLOW_SWAP: u8 = 1 << 0;
+    /// The keeper may run `Deposit`
+    pub const ALLOW_DEPOSIT: u8 = 1 << 1;
+
+    /* Reading Helpers */
+
+    /// Return an `Allowance` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return an `Allowance` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Allowance`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Allowance)
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn keeper(&self) -> &Pubkey {
+        &self.keeper
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn amount_x(&self) -> u64 {
+        u64::from_le_bytes(self.amount_x)
+    }
+
+    #[inline(always)]
+    pub fn amount_y(&self) -> u64 {
+        u64::from_le_bytes(self.amount_y)
+    }
+
+    #[inline(always)]
+    pub fn expiration(&self) -> i64 {
+        i64::from_le_bytes(self.expiration)
+    }
+
+    #[inline(always)]
+    pub fn permissions(&self) -> u8 {
+        self.permissions
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Check the allowance is live at `now` and grants `permission`
+    #[inline(always)]
+    pub fn check(&self, permission: u8, now: i64) -> Result<(), ProgramError> {
+        if now > self.expiration() || self.permissions & permission == 0 {
+            return Err(AmmError::StrategyNotAllowed.into());
+        }
+
+        Ok(())
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `Allowance` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `Allowance` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `Allowance` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Allowance`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Allowance)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        owner: Pubkey,
+        keeper: Pubkey,
+        config: Pubkey,
+        amount_x: u64,
+        amount_y: u64,
+        expiration: i64,
+        permissions: u8,
+        bump: [u8; 1],
+    ) {
+        self.owner = owner;
+        self.keeper = keeper;
+        self.config = config;
+        self.amount_x = amount_x.to_le_bytes();
+        self.amount_y = amount_y.to_le_bytes();
+        self.expiration = expiration.to_le_bytes();
+        self.permissions = permissions;
+        self.bump = bump;
+    }
+
+    /// Consume what the keeper spent from the owner's accounts
+    #[inline(always)]
+    pub fn spend(&mut self, x: u64, y: u64) -> Result<(), ProgramError> {
+        let amount_x = self.amount_x().checked_sub(x).ok_or(AmmError::AllowanceExceeded)?;
+        let amount_y = self.amount_y().checked_sub(y).ok_or(AmmError::AllowanceExceeded)?;
+
+        self.amount_x = amount_x.to_le_bytes();
+        self.amount_y = amount_y.to_le_bytes();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Balance of a token account at some point of a transaction, read back by `AssertBalanceChange`
+///
+/// - PDA seeds: [b"balance_snapshot", token_account, bump], created by the first `SnapshotBalance`
+/// - Overwritten by every `SnapshotBalance` of the token account, it is only trusted in the
+///   transaction whose instruction at `index` took it
+#[repr(C)]
+pub struct BalanceSnapshot {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    token_account: Pubkey,
+    amount: [u8; 8],
+    index: [u8; 2],
+    bump: [u8; 1],
+}
+
+impl BalanceSnapshot {
+    /// `sha256("account:BalanceSnapshot")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [12, 111, 63, 108, 104, 176, 216, 28];
+
+    pub const LEN: usize =
+        DISCRIMINATOR_LEN + size_of::<Pubkey>() + size_of::<u64>() + size_of::<u16>() + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `BalanceSnapshot` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `BalanceSnapshot` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `BalanceSnapshot`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const BalanceSnapshot)
+    }
+
+    #[inline(always)]
+    pub fn token_account(&self) -> &Pubkey {
+        &self.token_account
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    /// Index of the top-level `SnapshotBalance` instruction that took the snapshot
+    #[inline(always)]
+    pub fn index(&self) -> u16 {
+        u16::from_le_bytes(self.index)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `BalanceSnapshot` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `BalanceSnapshot` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `BalanceSnapshot` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `BalanceSnapshot`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut BalanceSnapshot)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, token_account: Pubkey, amount: u64, index: u16, bump: [u8; 1]) {
+        self.token_account = token_account;
+        self.amount = amount.to_le_bytes();
+        self.index = index.to_le_bytes();
+        self.bump = bump;
+    }
+}
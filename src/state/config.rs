@@ -0,0 +1,683 @@
+use core::mem::size_of;
+use crate::{AmmError, Fee, FEE_TIERS, MAX_FEE_BPS, TOKEN_2022_PROGRAM_ID};
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Some information about reading data
+///  
+/// - We use #[repr(C)] instead of #[repr(packed)] for safety
+/// - Multi-byte integers stored as byte arrays to avoid alignment issues
+/// - All field access is safe with no UB risk
+#[repr(C)]
+pub struct Config {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    state: u8,
+    seed: [u8; 8],
+    authority: Pubkey,
+    mint_x: Pubkey,
+    mint_y: Pubkey,
+    fee: [u8; 2],
+    config_bump: [u8; 1],
+    flags: u8,
+    host_fee_bps: [u8; 2],
+    lp_decimals: u8,
+    risk_flags: u8,
+    protocol_fee_bps: [u8; 2],
+    protocol_fees_x: [u8; 8],
+    protocol_fees_y: [u8; 8],
+    decimals_x: u8,
+    decimals_y: u8,
+    token_program_x: Pubkey,
+    token_program_y: Pubkey,
+}
+
+// The `OFFSET_*` constants must follow the struct layout, checked at compile time instead of in tests
+const _: () = {
+    use core::mem::offset_of;
+
+    assert!(offset_of!(Config, state) == Config::OFFSET_STATE);
+    assert!(offset_of!(Config, seed) == Config::OFFSET_SEED);
+    assert!(offset_of!(Config, authority) == Config::OFFSET_AUTHORITY);
+    assert!(offset_of!(Config, mint_x) == Config::OFFSET_MINT_X);
+    assert!(offset_of!(Config, mint_y) == Config::OFFSET_MINT_Y);
+    assert!(offset_of!(Config, fee) == Config::OFFSET_FEE);
+    assert!(offset_of!(Config, config_bump) == Config::OFFSET_CONFIG_BUMP);
+    assert!(offset_of!(Config, flags) == Config::OFFSET_FLAGS);
+    assert!(offset_of!(Config, host_fee_bps) == Config::OFFSET_HOST_FEE_BPS);
+    assert!(offset_of!(Config, lp_decimals) == Config::OFFSET_LP_DECIMALS);
+    assert!(offset_of!(Config, risk_flags) == Config::OFFSET_RISK_FLAGS);
+    assert!(offset_of!(Config, protocol_fee_bps) == Config::OFFSET_PROTOCOL_FEE_BPS);
+    assert!(offset_of!(Config, protocol_fees_x) == Config::OFFSET_PROTOCOL_FEES_X);
+    assert!(offset_of!(Config, protocol_fees_y) == Config::OFFSET_PROTOCOL_FEES_Y);
+    assert!(offset_of!(Config, decimals_x) == Config::OFFSET_DECIMALS_X);
+    assert!(offset_of!(Config, decimals_y) == Config::OFFSET_DECIMALS_Y);
+    assert!(offset_of!(Config, token_program_x) == Config::OFFSET_TOKEN_PROGRAM_X);
+    assert!(offset_of!(Config, token_program_y) == Config::OFFSET_TOKEN_PROGRAM_Y);
+    assert!(Config::OFFSET_TOKEN_PROGRAM_Y + size_of::<Pubkey>() == Config::LEN);
+    assert!(size_of::<Config>() == Config::LEN);
+};
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AmmState {
+    Uninitialized = 0u8,
+    Initialized = 1u8,
+    Disabled = 2u8,
+    WithdrawOnly = 3u8,
+}
+
+impl TryFrom<u8> for AmmState {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AmmState::Uninitialized),
+            1 => Ok(AmmState::Initialized),
+            2 => Ok(AmmState::Disabled),
+            3 => Ok(AmmState::WithdrawOnly),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+impl Config {
+    /// `sha256("account:Config")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [155, 12, 170, 224, 30, 250, 204, 130];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<u8>()
+        + size_of::<u64>()
+        + size_of::<Pubkey>() * 3
+        + size_of::<u16>()
+        + size_of::<u8>()
+        + size_of::<u8>()
+        + size_of::<u16>()
+        + size_of::<u8>() * 2
+        + size_of::<u16>()
+        + size_of::<u64>() * 2
+        + size_of::<u8>() * 2
+        + size_of::<Pubkey>() * 2;
+
+    /* Offsets */
+
+    // Byte offsets of the stored fields in the account data, so indexers can build memcmp filters
+    // (e.g. on `OFFSET_MINT_X` and `OFFSET_MINT_Y` to find the pools of a pair). The reserves are
+    // the vault balances and aren't stored, only the protocol fees sitting in the vaults are.
+    pub const OFFSET_STATE: usize = DISCRIMINATOR_LEN;
+    pub const OFFSET_SEED: usize = Self::OFFSET_STATE + size_of::<u8>();
+    pub const OFFSET_AUTHORITY: usize = Self::OFFSET_SEED + size_of::<u64>();
+    pub const OFFSET_MINT_X: usize = Self::OFFSET_AUTHORITY + size_of::<Pubkey>();
+    pub const OFFSET_MINT_Y: usize = Self::OFFSET_MINT_X + size_of::<Pubkey>();
+    pub const OFFSET_FEE: usize = Self::OFFSET_MINT_Y + size_of::<Pubkey>();
+    pub const OFFSET_CONFIG_BUMP: usize = Self::OFFSET_FEE + size_of::<u16>();
+    pub const OFFSET_FLAGS: usize = Self::OFFSET_CONFIG_BUMP + size_of::<u8>();
+    pub const OFFSET_HOST_FEE_BPS: usize = Self::OFFSET_FLAGS + size_of::<u8>();
+    pub const OFFSET_LP_DECIMALS: usize = Self::OFFSET_HOST_FEE_BPS + size_of::<u16>();
+    pub const OFFSET_RISK_FLAGS: usize = Self::OFFSET_LP_DECIMALS + size_of::<u8>();
+    pub const OFFSET_PROTOCOL_FEE_BPS: usize = Self::OFFSET_RISK_FLAGS + size_of::<u8>();
+    pub const OFFSET_PROTOCOL_FEES_X: usize = Self::OFFSET_PROTOCOL_FEE_BPS + size_of::<u16>();
+    pub const OFFSET_PROTOCOL_FEES_Y: usize = Self::OFFSET_PROTOCOL_FEES_X + size_of::<u64>();
+    pub const OFFSET_DECIMALS_X: usize = Self::OFFSET_PROTOCOL_FEES_Y + size_of::<u64>();
+    pub const OFFSET_DECIMALS_Y: usize = Self::OFFSET_DECIMALS_X + size_of::<u8>();
+    pub const OFFSET_TOKEN_PROGRAM_X: usize = Self::OFFSET_DECIMALS_Y + size_of::<u8>();
+    pub const OFFSET_TOKEN_PROGRAM_Y: usize = Self::OFFSET_TOKEN_PROGRAM_X + size_of::<Pubkey>();
+
+    /* Flags */
+
+    /// Canonical pool: the config PDA is derived from the mint pair only, without a seed
+    pub const FLAG_CANONICAL: u8 = 1 << 0;
+    /// Liquidity can be wrapped into NFT positions, see `OpenPosition`
+    pub const FLAG_NFT_POSITIONS: u8 = 1 << 1;
+    /// The fee may be any value below 100% instead of one of `FEE_TIERS` (permissioned pools only)
+    pub const FLAG_CUSTOM_FEE_TIER: u8 = 1 << 2;
+    /// Refuse pool assets that can be frozen or clawed back
+    pub const FLAG_STRICT: u8 = 1 << 3;
+    /// Swaps must execute close to the `OracleState` extension price (set via UpdateConfig)
+    pub const FLAG_ORACLE_GUARD: u8 = 1 << 4;
+    /// A transaction may only contain one swap against this pool (blocks sandwich bundles)
+    pub const FLAG_SINGLE_SWAP: u8 = 1 << 5;
+    /// The LP mint is a Token-2022 non-transferable mint, so LP can only be minted and burned by the pool
+    pub const FLAG_SOULBOUND_LP: u8 = 1 << 6;
+    /// The vaults are program-derived token accounts instead of ATAs, see `InitializeVaults`
+    pub const FLAG_PDA_VAULTS: u8 = 1 << 7;
+
+    /* Risk Flags */
+
+    /// mint_x has a freeze authority, so the vault can be frozen
+    pub const RISK_FREEZABLE_X: u8 = 1 << 0;
+    /// mint_y has a freeze authority, so the vault can be frozen
+    pub const RISK_FREEZABLE_Y: u8 = 1 << 1;
+    /// mint_x has a Token-2022 permanent delegate, so vault funds can be clawed back
+    pub const RISK_CLAWBACK_X: u8 = 1 << 2;
+    /// mint_y has a Token-2022 permanent delegate, so vault funds can be clawed back
+    pub const RISK_CLAWBACK_Y: u8 = 1 << 3;
+
+    /* Reading Helpers */
+
+    // Return a `Config` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`. Extensions appended
+    /// by `ExtendConfig` are ignored, see `get_extension`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `Config` from the given account info.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, but does not
+    /// perform the borrow check.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that it is safe to borrow the account data (e.g., there are
+    /// no mutable borrows of the account data).
+    #[inline(always)]
+    pub unsafe fn load_unchecked(account_info: &AccountInfo) -> Result<&Self, ProgramError> {
+        if account_info.data_len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner() != &crate::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.borrow_data_unchecked();
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::from_bytes_unchecked(data))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Config)
+    }
+
+    #[inline(always)]
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// `state` as an `AmmState`, failing on an unknown value
+    #[inline(always)]
+    pub fn amm_state(&self) -> Result<AmmState, ProgramError> {
+        AmmState::try_from(self.state)
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn authority(&self) -> &Pubkey {
+        &self.authority
+    }
+
+    #[inline(always)]
+    pub fn mint_x(&self) -> &Pubkey {
+        &self.mint_x
+    }
+
+    #[inline(always)]
+    pub fn mint_y(&self) -> &Pubkey {
+        &self.mint_y
+    }
+
+    #[inline(always)]
+    pub fn fee(&self) -> u16 {
+        u16::from_le_bytes(self.fee)
+    }
+
+    /// `fee` as a `Fee`
+    #[inline(always)]
+    pub fn swap_fee(&self) -> Result<Fee, ProgramError> {
+        Fee::from_bps(self.fee())
+    }
+
+    #[inline(always)]
+    pub fn config_bump(&self) -> [u8; 1] {
+        self.config_bump
+    }
+
+    #[inline(always)]
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Share of the swap fee (in bps of the fee) paid to a host fee account when one is provided
+    #[inline(always)]
+    pub fn host_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.host_fee_bps)
+    }
+
+    #[inline(always)]
+    pub fn lp_decimals(&self) -> u8 {
+        self.lp_decimals
+    }
+
+    /// Freeze and clawback risks of the pool assets recorded at Initialize, see `RISK_*`
+    #[inline(always)]
+    pub fn risk_flags(&self) -> u8 {
+        self.risk_flags
+    }
+
+    /// Share of the swap fee (in bps of the fee) accrued for the protocol, set from `GlobalConfig`
+    #[inline(always)]
+    pub fn protocol_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.protocol_fee_bps)
+    }
+
+    /// Protocol fees in X held by vault_x until `CollectProtocolFees`
+    #[inline(always)]
+    pub fn protocol_fees_x(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_x)
+    }
+
+    /// Protocol fees in Y held by vault_y until `CollectProtocolFees`
+    #[inline(always)]
+    pub fn protocol_fees_y(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_y)
+    }
+
+    /// Pool reserves backing the LP supply: the vault balances plus the amounts deployed to the
+    /// `VaultStrategy` accounts, minus uncollected protocol fees.
+    #[inline(always)]
+    pub fn reserves(&self, vault_x_amount: u64, vault_y_amount: u64, deployed: (u64, u64)) -> (u64, u64) {
+        (
+            vault_x_amount.saturating_add(deployed.0).saturating_sub(self.protocol_fees_x()),
+            vault_y_amount.saturating_add(deployed.1).saturating_sub(self.protocol_fees_y()),
+        )
+    }
+
+    #[inline(always)]
+    pub fn decimals_x(&self) -> u8 {
+        self.decimals_x
+    }
+
+    #[inline(always)]
+    pub fn decimals_y(&self) -> u8 {
+        self.decimals_y
+    }
+
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    #[inline(always)]
+    pub fn token_program_x(&self) -> &Pubkey {
+        &self.token_program_x
+    }
+
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    #[inline(always)]
+    pub fn token_program_y(&self) -> &Pubkey {
+        &self.token_program_y
+    }
+
+    #[inline(always)]
+    pub fn has_oracle_guard(&self) -> bool {
+        self.flags & Self::FLAG_ORACLE_GUARD != 0
+    }
+
+    #[inline(always)]
+    pub fn has_single_swap(&self) -> bool {
+        self.flags & Self::FLAG_SINGLE_SWAP != 0
+    }
+
+    #[inline(always)]
+    pub fn is_canonical(&self) -> bool {
+        self.flags & Self::FLAG_CANONICAL != 0
+    }
+
+    #[inline(always)]
+    pub fn has_nft_positions(&self) -> bool {
+        self.flags & Self::FLAG_NFT_POSITIONS != 0
+    }
+
+    #[inline(always)]
+    pub fn has_custom_fee_tier(&self) -> bool {
+        self.flags & Self::FLAG_CUSTOM_FEE_TIER != 0
+    }
+
+    #[inline(always)]
+    pub fn has_soulbound_lp(&self) -> bool {
+        self.flags & Self::FLAG_SOULBOUND_LP != 0
+    }
+
+    #[inline(always)]
+    pub fn has_pda_vaults(&self) -> bool {
+        self.flags & Self::FLAG_PDA_VAULTS != 0
+    }
+
+    /// Token program owning the LP mint, Token-2022 for soulbound LP
+    #[inline(always)]
+    pub fn lp_token_program(&self) -> &Pubkey {
+        match self.has_soulbound_lp() {
+            true => &TOKEN_2022_PROGRAM_ID,
+            false => &pinocchio_token::ID,
+        }
+    }
+
+    /// Seed bytes used in the config PDA derivation.
+    ///
+    /// Canonical pools are derived without a seed, so this returns an empty slice for them.
+    #[inline(always)]
+    pub fn seed_bytes(&self) -> &[u8] {
+        if self.is_canonical() {
+            &[]
+        } else {
+            &self.seed
+        }
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `Config` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `Config` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `Config` from the given account info.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, but does not
+    /// perform the borrow check.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that it is safe to borrow the account data mutably (e.g., there are
+    /// no other borrows of the account data).
+    #[inline(always)]
+    pub unsafe fn load_mut_unchecked(account_info: &AccountInfo) -> Result<&mut Self, ProgramError> {
+        if account_info.data_len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner() != &crate::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.borrow_mut_data_unchecked();
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::from_bytes_unchecked_mut(data))
+    }
+
+    /// Return a mutable `Config` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Config`, and
+    /// it is properly aligned to be interpreted as an instance of `Config`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Config)
+    }
+
+    /// Move the pool to `state`, which can't go back to `Uninitialized`
+    #[inline(always)]
+    pub fn set_state(&mut self, state: AmmState) -> Result<(), ProgramError> {
+        if state == AmmState::Uninitialized {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        self.state = state as u8;
+
+        Ok(())
+    }
+
+    /// Stop swaps and deposits while letting LPs exit, see `CircuitBreaker`
+    #[inline(always)]
+    pub fn set_withdraw_only(&mut self) {
+        self.state = AmmState::WithdrawOnly as u8;
+    }
+
+    #[inline(always)]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_authority(&mut self, authority: Pubkey) {
+        self.authority = authority;
+    }
+
+    #[inline(always)]
+    pub fn set_mint_x(&mut self, mint_x: Pubkey) {
+        self.mint_x = mint_x;
+    }
+
+    #[inline(always)]
+    pub fn set_mint_y(&mut self, mint_y: Pubkey) {
+        self.mint_y = mint_y;
+    }
+
+    /// Set the swap fee, which must be one of `FEE_TIERS` unless the pool allows custom tiers.
+    #[inline(always)]
+    pub fn set_fee(&mut self, fee: u16) -> Result<(), ProgramError> {
+        if fee.ge(&MAX_FEE_BPS) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !self.has_custom_fee_tier() && !FEE_TIERS.contains(&fee) {
+            return Err(AmmError::InvalidFeeTier.into());
+        }
+
+        self.fee = fee.to_le_bytes();
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_host_fee_bps(&mut self, host_fee_bps: u16) -> Result<(), ProgramError> {
+        if host_fee_bps.gt(&MAX_FEE_BPS) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        self.host_fee_bps = host_fee_bps.to_le_bytes();
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_lp_decimals(&mut self, lp_decimals: u8) {
+        self.lp_decimals = lp_decimals;
+    }
+
+    #[inline(always)]
+    pub fn set_config_bump(&mut self, config_bump: [u8; 1]) {
+        self.config_bump = config_bump;
+    }
+
+    #[inline(always)]
+    pub fn set_flags(&mut self, flags: u8) {
+        self.flags = flags;
+    }
+
+    #[inline(always)]
+    pub fn set_risk_flags(&mut self, risk_flags: u8) {
+        self.risk_flags = risk_flags;
+    }
+
+    #[inline(always)]
+    pub fn set_protocol_fee_bps(&mut self, protocol_fee_bps: u16) -> Result<(), ProgramError> {
+        if protocol_fee_bps.gt(&MAX_FEE_BPS) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        self.protocol_fee_bps = protocol_fee_bps.to_le_bytes();
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_token_programs(&mut self, token_program_x: Pubkey, token_program_y: Pubkey) {
+        self.token_program_x = token_program_x;
+        self.token_program_y = token_program_y;
+    }
+
+    #[inline(always)]
+    pub fn set_decimals(&mut self, decimals_x: u8, decimals_y: u8) {
+        self.decimals_x = decimals_x;
+        self.decimals_y = decimals_y;
+    }
+
+    /// Toggle `FLAG_ORACLE_GUARD`, the feed itself lives in the `OracleState` extension.
+    #[inline(always)]
+    pub fn set_oracle_guard(&mut self, enabled: bool) {
+        match enabled {
+            true => self.flags |= Self::FLAG_ORACLE_GUARD,
+            false => self.flags &= !Self::FLAG_ORACLE_GUARD,
+        }
+    }
+
+    /// Add swap fees owed to the protocol on the input side of a swap
+    #[inline(always)]
+    pub fn accrue_protocol_fee(&mut self, is_x: bool, amount: u64) -> Result<(), ProgramError> {
+        let protocol_fees = match is_x {
+            true => &mut self.protocol_fees_x,
+            false => &mut self.protocol_fees_y,
+        };
+
+        *protocol_fees = u64::from_le_bytes(*protocol_fees)
+            .checked_add(amount)
+            .ok_or(AmmError::MathOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Reset the accrued protocol fees, returning the amounts that were owed as `(x, y)`
+    #[inline(always)]
+    pub fn take_protocol_fees(&mut self) -> (u64, u64) {
+        let fees = (self.protocol_fees_x(), self.protocol_fees_y());
+        self.protocol_fees_x = [0; 8];
+        self.protocol_fees_y = [0; 8];
+        fees
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        seed: u64,
+        authority: Pubkey,
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        fee: u16,
+        config_bump: [u8; 1],
+        flags: u8,
+        lp_decimals: u8,
+    ) -> Result<(), ProgramError> {
+        
+        self.set_state(AmmState::Initialized)?;
+        self.set_seed(seed);
+        self.set_authority(authority);
+        self.set_mint_x(mint_x);
+        self.set_mint_y(mint_y);
+        // Flags decide which fees are accepted, so they go first
+        self.set_flags(flags);
+        self.set_fee(fee)?;
+        self.set_config_bump(config_bump);
+        self.set_lp_decimals(lp_decimals);
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn has_authority(&self) -> Option<Pubkey> {
+        let bytes = self.authority();
+
+        // SAFETY: [u8; 32] is always aligned and sized for 4 u64s
+        let chunks: &[u64; 4] = unsafe { &*(bytes.as_ptr() as *const [u64; 4]) };
+        if chunks.iter().any(|&x| x != 0) {
+            // If any chunk is not 0, then hit an early exit and return the authority
+            Some(self.authority)
+        } else {
+            // If all chunks are 0, then return None
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accrue_protocol_fee_fails_past_u64_max() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+
+        config.accrue_protocol_fee(true, u64::MAX - 1).unwrap();
+        config.accrue_protocol_fee(true, 1).unwrap();
+        assert_eq!(config.accrue_protocol_fee(true, 1), Err(AmmError::MathOverflow.into()));
+        assert_eq!(config.protocol_fees_x(), u64::MAX);
+
+        config.accrue_protocol_fee(false, u64::MAX).unwrap();
+        assert_eq!(config.accrue_protocol_fee(false, u64::MAX), Err(AmmError::MathOverflow.into()));
+        assert_eq!(config.take_protocol_fees(), (u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn set_state_reaches_every_live_state() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+
+        for state in [AmmState::Initialized, AmmState::Disabled, AmmState::WithdrawOnly, AmmState::Initialized] {
+            config.set_state(state).unwrap();
+            assert_eq!(config.state(), state as u8);
+            assert!(config.amm_state() == Ok(state));
+        }
+    }
+
+    #[test]
+    fn set_state_rejects_uninitialized() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+
+        config.set_state(AmmState::WithdrawOnly).unwrap();
+        assert!(config.set_state(AmmState::Uninitialized) == Err(ProgramError::InvalidAccountData));
+        assert!(config.amm_state() == Ok(AmmState::WithdrawOnly));
+    }
+
+    #[test]
+    fn amm_state_rejects_unknown_bytes() {
+        for value in 0..=3u8 {
+            assert!(AmmState::try_from(value).is_ok_and(|state| state as u8 == value));
+        }
+
+        for value in 4..=u8::MAX {
+            assert!(AmmState::try_from(value) == Err(ProgramError::InvalidAccountData));
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use pinocchio::program_error::ProgramError;
+
+/// Every program account starts with an 8-byte type discriminator, so indexers can tell the
+/// account types apart with a `getProgramAccounts` memcmp filter at offset 0
+///
+/// - Each type exposes its own as `DISCRIMINATOR`, the first 8 bytes of
+///   `sha256("account:<TypeName>")`
+/// - None of them starts with a valid `AmmState`, so configs created before discriminators
+///   existed (which start with their state) can be told apart, see `MigrateConfig`
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Check that the account `data` starts with `discriminator`.
+#[inline(always)]
+pub fn check_discriminator(data: &[u8], discriminator: &[u8; DISCRIMINATOR_LEN]) -> Result<(), ProgramError> {
+    if data[..DISCRIMINATOR_LEN].ne(discriminator) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+/// Write `discriminator` to freshly created account `data`, failing if it was already initialized.
+#[inline(always)]
+pub fn write_discriminator(data: &mut [u8], discriminator: &[u8; DISCRIMINATOR_LEN]) -> Result<(), ProgramError> {
+    if data[..DISCRIMINATOR_LEN].iter().any(|byte| *byte != 0) {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    data[..DISCRIMINATOR_LEN].copy_from_slice(discriminator);
+
+    Ok(())
+}
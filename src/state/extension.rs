@@ -0,0 +1,1492 @@
+use core::mem::size_of;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{mul_div, AmmError, Fee, Rounding, MAX_FEE_BPS, MIN_INSURANCE_TIMELOCK};
+
+use super::Config;
+
+/// Optional feature blocks appended to a config account by `ExtendConfig`
+///
+/// - Layout after `Config::LEN`: (type [2] | length [2] | value [length])*
+/// - Each value is the fixed `#[repr(C)]` layout of its type, unknown types are skipped
+/// - Pools only pay rent for the extensions they enable
+#[repr(u16)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum ConfigExtension {
+    OracleState = 1u16,
+    FeeState = 2u16,
+    Caps = 3u16,
+    Whitelist = 4u16,
+    Stats = 5u16,
+    CircuitBreaker = 6u16,
+    VaultStrategy = 7u16,
+    FeeGrowth = 8u16,
+    FeeBurn = 9u16,
+    ExitSwaps = 10u16,
+    VaultBumps = 11u16,
+    FeeSplit = 12u16,
+    CallerAllowlist = 13u16,
+    MaintenanceWindow = 14u16,
+    TradingHours = 15u16,
+    InsuranceFund = 16u16,
+    MevTax = 17u16,
+    SameSlotGuard = 18u16,
+    ExitPenalty = 19u16,
+    ObservationTree = 20u16,
+    Sequence = 21u16,
+    VolumeDiscount = 22u16,
+}
+
+impl TryFrom<u16> for ConfigExtension {
+    type Error = ProgramError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(ConfigExtension::OracleState),
+            2 => Ok(ConfigExtension::FeeState),
+            3 => Ok(ConfigExtension::Caps),
+            4 => Ok(ConfigExtension::Whitelist),
+            5 => Ok(ConfigExtension::Stats),
+            6 => Ok(ConfigExtension::CircuitBreaker),
+            7 => Ok(ConfigExtension::VaultStrategy),
+            8 => Ok(ConfigExtension::FeeGrowth),
+            9 => Ok(ConfigExtension::FeeBurn),
+            10 => Ok(ConfigExtension::ExitSwaps),
+            11 => Ok(ConfigExtension::VaultBumps),
+            12 => Ok(ConfigExtension::FeeSplit),
+            13 => Ok(ConfigExtension::CallerAllowlist),
+            14 => Ok(ConfigExtension::MaintenanceWindow),
+            15 => Ok(ConfigExtension::TradingHours),
+            16 => Ok(ConfigExtension::InsuranceFund),
+            17 => Ok(ConfigExtension::MevTax),
+            18 => Ok(ConfigExtension::SameSlotGuard),
+            19 => Ok(ConfigExtension::ExitPenalty),
+            20 => Ok(ConfigExtension::ObservationTree),
+            21 => Ok(ConfigExtension::Sequence),
+            22 => Ok(ConfigExtension::VolumeDiscount),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+impl ConfigExtension {
+    /// Size of the type [2] | length [2] header in front of each value
+    pub const HEADER_LEN: usize = size_of::<u16>() * 2;
+
+    #[inline(always)]
+    pub fn value_len(self) -> usize {
+        match self {
+            ConfigExtension::OracleState => OracleState::LEN,
+            ConfigExtension::FeeState => FeeState::LEN,
+            ConfigExtension::Caps => Caps::LEN,
+            ConfigExtension::Whitelist => Whitelist::LEN,
+            ConfigExtension::Stats => Stats::LEN,
+            ConfigExtension::CircuitBreaker => CircuitBreaker::LEN,
+            ConfigExtension::VaultStrategy => VaultStrategy::LEN,
+            ConfigExtension::FeeGrowth => FeeGrowth::LEN,
+            ConfigExtension::FeeBurn => FeeBurn::LEN,
+            ConfigExtension::ExitSwaps => ExitSwaps::LEN,
+            ConfigExtension::VaultBumps => VaultBumps::LEN,
+            ConfigExtension::FeeSplit => FeeSplit::LEN,
+            ConfigExtension::CallerAllowlist => CallerAllowlist::LEN,
+            ConfigExtension::MaintenanceWindow => MaintenanceWindow::LEN,
+            ConfigExtension::TradingHours => TradingHours::LEN,
+            ConfigExtension::InsuranceFund => InsuranceFund::LEN,
+            ConfigExtension::MevTax => MevTax::LEN,
+            ConfigExtension::SameSlotGuard => SameSlotGuard::LEN,
+            ConfigExtension::ExitPenalty => ExitPenalty::LEN,
+            ConfigExtension::ObservationTree => ObservationTree::LEN,
+            ConfigExtension::Sequence => Sequence::LEN,
+            ConfigExtension::VolumeDiscount => VolumeDiscount::LEN,
+        }
+    }
+
+    /// Validate a value before it is written to the config account
+    pub fn check_value(self, value: &[u8]) -> Result<(), ProgramError> {
+        if value.len().ne(&self.value_len()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let valid = match self {
+            ConfigExtension::OracleState => {
+                u16::from_le_bytes(value[32..34].try_into().unwrap()) <= MAX_FEE_BPS
+            }
+            ConfigExtension::FeeState => {
+                u16::from_le_bytes(value[0..2].try_into().unwrap()) <= MAX_FEE_BPS
+                    && u16::from_le_bytes(value[2..4].try_into().unwrap()) <= MAX_FEE_BPS
+            }
+            ConfigExtension::Caps
+            | ConfigExtension::Whitelist
+            | ConfigExtension::ExitSwaps
+            | ConfigExtension::CallerAllowlist
+            | ConfigExtension::TradingHours => true,
+            // Only ever written by Swap
+            ConfigExtension::Stats | ConfigExtension::FeeGrowth => false,
+            // Only ever written by InitializeVaults
+            ConfigExtension::VaultBumps => false,
+            // Only ever written by InitializeObservationTree and AppendObservation
+            ConfigExtension::ObservationTree => false,
+            // Only ever written by the instructions changing the pool, see `bump_sequence`
+            ConfigExtension::Sequence => false,
+            ConfigExtension::CircuitBreaker => {
+                u16::from_le_bytes(value[0..2].try_into().unwrap()) <= MAX_FEE_BPS
+            }
+            // The deployed amounts are only ever written by Rebalance and the lending CPIs
+            ConfigExtension::VaultStrategy => {
+                value[64..80].iter().all(|byte| *byte == 0)
+                    && u16::from_le_bytes(value[112..114].try_into().unwrap()) <= MAX_FEE_BPS
+            }
+            ConfigExtension::FeeBurn => u16::from_le_bytes(value[32..34].try_into().unwrap()) <= MAX_FEE_BPS,
+            // The window can't end before it starts, equal timestamps disable it
+            ConfigExtension::MaintenanceWindow => {
+                let start = i64::from_le_bytes(value[0..8].try_into().unwrap());
+                let end = i64::from_le_bytes(value[8..16].try_into().unwrap());
+
+                start <= end
+            }
+            // The pending withdrawal is only ever written by WithdrawInsurance, so an update cancels it
+            ConfigExtension::InsuranceFund => {
+                u16::from_le_bytes(value[0..2].try_into().unwrap()) <= MAX_FEE_BPS
+                    && i64::from_le_bytes(value[2..10].try_into().unwrap()) >= MIN_INSURANCE_TIMELOCK
+                    && value[10..34].iter().all(|byte| *byte == 0)
+            }
+            // The last swap slot is only ever written by Swap
+            ConfigExtension::MevTax => {
+                u16::from_le_bytes(value[0..2].try_into().unwrap()) <= MAX_FEE_BPS
+                    && value[2..10].iter().all(|byte| *byte == 0)
+            }
+            // Only the rule is set by the authority, the recent swaps are only ever written by Swap
+            ConfigExtension::SameSlotGuard => value[0] <= 1 && value[1..].iter().all(|byte| *byte == 0),
+            ConfigExtension::ExitPenalty => {
+                u16::from_le_bytes(value[0..2].try_into().unwrap()) <= MAX_FEE_BPS
+                    && i64::from_le_bytes(value[2..10].try_into().unwrap()) >= 0
+            }
+            // Discounts can waive the whole fee, but no more
+            ConfigExtension::VolumeDiscount => value[size_of::<u64>() * VolumeDiscount::TIERS..]
+                .chunks_exact(2)
+                .all(|discount| u16::from_le_bytes([discount[0], discount[1]]) <= MAX_FEE_BPS),
+            // The weights add up to 100%, or are all zero to disable the split
+            ConfigExtension::FeeSplit => {
+                let (recipients, weights) = value.split_at(size_of::<Pubkey>() * FeeSplit::CAPACITY);
+                let mut total = 0u32;
+
+                recipients.chunks_exact(size_of::<Pubkey>()).zip(weights.chunks_exact(2)).all(|(recipient, weight)| {
+                    let weight = u16::from_le_bytes([weight[0], weight[1]]);
+                    total += weight as u32;
+
+                    weight == 0 || recipient.iter().any(|byte| *byte != 0)
+                }) && (total == 0 || total == MAX_FEE_BPS as u32)
+            }
+        };
+
+        if !valid {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(())
+    }
+}
+
+/// A fixed-size extension value
+pub trait Extension: Sized {
+    const TYPE: ConfigExtension;
+    const LEN: usize = size_of::<Self>();
+}
+
+/// Locate an extension in the config account data, returning the offset and length of its value.
+///
+/// Fails on a truncated TLV area, so walking it also validates the account.
+pub fn find_extension(data: &[u8], extension_type: u16) -> Result<Option<(usize, usize)>, ProgramError> {
+    let mut offset = Config::LEN;
+
+    while offset < data.len() {
+        let header = data
+            .get(offset..offset + ConfigExtension::HEADER_LEN)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let current_type = u16::from_le_bytes([header[0], header[1]]);
+        let length = u16::from_le_bytes([header[2], header[3]]) as usize;
+
+        let value_offset = offset + ConfigExtension::HEADER_LEN;
+        if value_offset + length > data.len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if current_type == extension_type {
+            return Ok(Some((value_offset, length)));
+        }
+
+        offset = value_offset + length;
+    }
+
+    Ok(None)
+}
+
+/// Return the `T` extension from the config account data, if the pool has it.
+pub fn get_extension<T: Extension>(data: &[u8]) -> Result<Option<&T>, ProgramError> {
+    match find_extension(data, T::TYPE as u16)? {
+        Some((offset, length)) if length == T::LEN => {
+            Ok(Some(unsafe { &*(data[offset..].as_ptr() as *const T) }))
+        }
+        Some(_) => Err(ProgramError::InvalidAccountData),
+        None => Ok(None),
+    }
+}
+
+/// Return the mutable `T` extension from the config account data, if the pool has it.
+pub fn get_extension_mut<T: Extension>(data: &mut [u8]) -> Result<Option<&mut T>, ProgramError> {
+    match find_extension(data, T::TYPE as u16)? {
+        Some((offset, length)) if length == T::LEN => {
+            Ok(Some(unsafe { &mut *(data[offset..].as_mut_ptr() as *mut T) }))
+        }
+        Some(_) => Err(ProgramError::InvalidAccountData),
+        None => Ok(None),
+    }
+}
+
+/// Price feed used by `Config::FLAG_ORACLE_GUARD`
+#[repr(C)]
+pub struct OracleState {
+    oracle: Pubkey,
+    max_deviation_bps: [u8; 2],
+}
+
+impl Extension for OracleState {
+    const TYPE: ConfigExtension = ConfigExtension::OracleState;
+}
+
+impl OracleState {
+    #[inline(always)]
+    pub fn oracle(&self) -> &Pubkey {
+        &self.oracle
+    }
+
+    /// Maximum distance (in bps) between the swap execution price and the oracle price
+    #[inline(always)]
+    pub fn max_deviation_bps(&self) -> u16 {
+        u16::from_le_bytes(self.max_deviation_bps)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, oracle: Pubkey, max_deviation_bps: u16) -> Result<(), ProgramError> {
+        if max_deviation_bps.gt(&MAX_FEE_BPS) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        self.oracle = oracle;
+        self.max_deviation_bps = max_deviation_bps.to_le_bytes();
+
+        Ok(())
+    }
+}
+
+/// Fees charged on liquidity changes, in bps of the amounts moved
+#[repr(C)]
+pub struct FeeState {
+    deposit_fee_bps: [u8; 2],
+    withdraw_fee_bps: [u8; 2],
+}
+
+impl Extension for FeeState {
+    const TYPE: ConfigExtension = ConfigExtension::FeeState;
+}
+
+impl FeeState {
+    #[inline(always)]
+    pub fn deposit_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.deposit_fee_bps)
+    }
+
+    #[inline(always)]
+    pub fn withdraw_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.withdraw_fee_bps)
+    }
+}
+
+/// Deposit caps on the pool reserves, zero means uncapped
+#[repr(C)]
+pub struct Caps {
+    max_reserve_x: [u8; 8],
+    max_reserve_y: [u8; 8],
+}
+
+impl Extension for Caps {
+    const TYPE: ConfigExtension = ConfigExtension::Caps;
+}
+
+impl Caps {
+    #[inline(always)]
+    pub fn max_reserve_x(&self) -> u64 {
+        u64::from_le_bytes(self.max_reserve_x)
+    }
+
+    #[inline(always)]
+    pub fn max_reserve_y(&self) -> u64 {
+        u64::from_le_bytes(self.max_reserve_y)
+    }
+
+    /// Check that the reserves after a deposit stay within the caps
+    #[inline(always)]
+    pub fn check(&self, reserve_x: u64, reserve_y: u64) -> Result<(), ProgramError> {
+        if (self.max_reserve_x() != 0 && reserve_x > self.max_reserve_x())
+            || (self.max_reserve_y() != 0 && reserve_y > self.max_reserve_y())
+        {
+            return Err(AmmError::DepositCapExceeded.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Users allowed to deposit, unused slots are the default pubkey (an empty list blocks deposits)
+#[repr(C)]
+pub struct Whitelist {
+    depositors: [Pubkey; Whitelist::CAPACITY],
+}
+
+impl Extension for Whitelist {
+    const TYPE: ConfigExtension = ConfigExtension::Whitelist;
+}
+
+impl Whitelist {
+    pub const CAPACITY: usize = 8;
+
+    #[inline(always)]
+    pub fn depositors(&self) -> &[Pubkey; Self::CAPACITY] {
+        &self.depositors
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, depositor: &Pubkey) -> bool {
+        depositor.ne(&Pubkey::default()) && self.depositors.contains(depositor)
+    }
+}
+
+/// Lifetime swap statistics, so dashboards don't have to replay the pool history
+///
+/// - Volumes count both legs of each swap in their own token, fees are in the input token
+/// - Counters saturate instead of failing the swap
+#[repr(C)]
+pub struct Stats {
+    volume_x: [u8; 8],
+    volume_y: [u8; 8],
+    fees_x: [u8; 8],
+    fees_y: [u8; 8],
+    swap_count: [u8; 8],
+}
+
+impl Extension for Stats {
+    const TYPE: ConfigExtension = ConfigExtension::Stats;
+}
+
+impl Stats {
+    #[inline(always)]
+    pub fn lifetime_volume_x(&self) -> u64 {
+        u64::from_le_bytes(self.volume_x)
+    }
+
+    #[inline(always)]
+    pub fn lifetime_volume_y(&self) -> u64 {
+        u64::from_le_bytes(self.volume_y)
+    }
+
+    #[inline(always)]
+    pub fn lifetime_fees_x(&self) -> u64 {
+        u64::from_le_bytes(self.fees_x)
+    }
+
+    #[inline(always)]
+    pub fn lifetime_fees_y(&self) -> u64 {
+        u64::from_le_bytes(self.fees_y)
+    }
+
+    #[inline(always)]
+    pub fn swap_count(&self) -> u64 {
+        u64::from_le_bytes(self.swap_count)
+    }
+
+    /// Record a swap of `amount_in` (fee included) for `amount_out`
+    #[inline(always)]
+    pub fn record_swap(&mut self, is_x: bool, amount_in: u64, amount_out: u64, fee: u64) {
+        let (volume_in, volume_out, fees_in) = match is_x {
+            true => (&mut self.volume_x, &mut self.volume_y, &mut self.fees_x),
+            false => (&mut self.volume_y, &mut self.volume_x, &mut self.fees_y),
+        };
+
+        *volume_in = u64::from_le_bytes(*volume_in).saturating_add(amount_in).to_le_bytes();
+        *volume_out = u64::from_le_bytes(*volume_out).saturating_add(amount_out).to_le_bytes();
+        *fees_in = u64::from_le_bytes(*fees_in).saturating_add(fee).to_le_bytes();
+        self.swap_count = self.swap_count().saturating_add(1).to_le_bytes();
+    }
+}
+
+/// Moves the pool to `AmmState::WithdrawOnly` when the price moves too far within one slot
+///
+/// - The reserves are snapshotted on the first swap of every slot
+/// - The swap that crosses `max_price_change_bps` still executes, later ones are refused until
+///   the authority sets the pool back to `AmmState::Initialized` through UpdateConfig
+/// - A zero threshold disables the breaker
+#[repr(C)]
+pub struct CircuitBreaker {
+    max_price_change_bps: [u8; 2],
+    slot: [u8; 8],
+    reference_reserve_x: [u8; 8],
+    reference_reserve_y: [u8; 8],
+}
+
+impl Extension for CircuitBreaker {
+    const TYPE: ConfigExtension = ConfigExtension::CircuitBreaker;
+}
+
+impl CircuitBreaker {
+    #[inline(always)]
+    pub fn max_price_change_bps(&self) -> u16 {
+        u16::from_le_bytes(self.max_price_change_bps)
+    }
+
+    #[inline(always)]
+    pub fn slot(&self) -> u64 {
+        u64::from_le_bytes(self.slot)
+    }
+
+    #[inline(always)]
+    pub fn reference_reserves(&self) -> (u64, u64) {
+        (
+            u64::from_le_bytes(self.reference_reserve_x),
+            u64::from_le_bytes(self.reference_reserve_y),
+        )
+    }
+
+    /// Snapshot the reserves before the first swap of `slot`
+    #[inline(always)]
+    pub fn observe(&mut self, slot: u64, reserve_x: u64, reserve_y: u64) {
+        if self.slot() != slot {
+            self.slot = slot.to_le_bytes();
+            self.reference_reserve_x = reserve_x.to_le_bytes();
+            self.reference_reserve_y = reserve_y.to_le_bytes();
+        }
+    }
+
+    /// Whether the Y/X price implied by the reserves moved more than `max_price_change_bps`
+    /// away from the snapshot
+    #[inline(always)]
+    pub fn is_tripped(&self, reserve_x: u64, reserve_y: u64) -> bool {
+        let (reference_x, reference_y) = self.reference_reserves();
+
+        if self.max_price_change_bps() == 0 || reference_x == 0 || reference_y == 0 {
+            return false;
+        }
+
+        // Compare y/x against reference_y/reference_x by cross-multiplying
+        let mut current = reserve_y as u128 * reference_x as u128;
+        let mut reference = reference_y as u128 * reserve_x as u128;
+
+        // Leave room for the bps scaling, the precision lost is negligible
+        let headroom = current.max(reference).leading_zeros();
+        if headroom < 14 {
+            current >>= 14 - headroom;
+            reference >>= 14 - headroom;
+        }
+
+        current.abs_diff(reference) * MAX_FEE_BPS as u128 > reference * self.max_price_change_bps() as u128
+    }
+}
+
+/// Where the authority puts idle reserves to work: token accounts filled with `Rebalance` and a
+/// lending program driven by `DepositToStrategy` and `WithdrawFromStrategy`
+///
+/// - What left the vaults is tracked as deployed and still counts as reserves, so moving it
+///   doesn't change the price or the LP share value
+/// - Anything returned above the deployed amount is yield and stays in the vault for the LPs
+/// - At most `max_deployed_bps` of each reserve can be deployed, zero blocks deployments
+/// - The strategy can only be replaced through UpdateConfig while nothing is deployed
+#[repr(C)]
+pub struct VaultStrategy {
+    strategy_x: Pubkey,
+    strategy_y: Pubkey,
+    deployed_x: [u8; 8],
+    deployed_y: [u8; 8],
+    lending_program: Pubkey,
+    max_deployed_bps: [u8; 2],
+}
+
+impl Extension for VaultStrategy {
+    const TYPE: ConfigExtension = ConfigExtension::VaultStrategy;
+}
+
+impl VaultStrategy {
+    /// Token account of mint_x receiving the deployed X
+    #[inline(always)]
+    pub fn strategy_x(&self) -> &Pubkey {
+        &self.strategy_x
+    }
+
+    /// Token account of mint_y receiving the deployed Y
+    #[inline(always)]
+    pub fn strategy_y(&self) -> &Pubkey {
+        &self.strategy_y
+    }
+
+    /// Program the reserves are lent to, the default pubkey if there is none
+    #[inline(always)]
+    pub fn lending_program(&self) -> &Pubkey {
+        &self.lending_program
+    }
+
+    #[inline(always)]
+    pub fn max_deployed_bps(&self) -> u16 {
+        u16::from_le_bytes(self.max_deployed_bps)
+    }
+
+    #[inline(always)]
+    pub fn deployed_x(&self) -> u64 {
+        u64::from_le_bytes(self.deployed_x)
+    }
+
+    #[inline(always)]
+    pub fn deployed_y(&self) -> u64 {
+        u64::from_le_bytes(self.deployed_y)
+    }
+
+    #[inline(always)]
+    pub fn is_deployed(&self) -> bool {
+        self.deployed_x() != 0 || self.deployed_y() != 0
+    }
+
+    /// Record `amount` leaving the vault for the strategy account
+    #[inline(always)]
+    pub fn deploy(&mut self, is_x: bool, amount: u64) -> Result<(), ProgramError> {
+        let deployed = match is_x {
+            true => &mut self.deployed_x,
+            false => &mut self.deployed_y,
+        };
+
+        *deployed = u64::from_le_bytes(*deployed)
+            .checked_add(amount)
+            .ok_or(AmmError::MathOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Check that a side deploys at most `max_deployed_bps` of its reserve, `liquid` being the part
+    /// of the reserve left in the vault
+    #[inline(always)]
+    pub fn check_cap(&self, is_x: bool, liquid: u64) -> Result<(), ProgramError> {
+        let deployed = match is_x {
+            true => self.deployed_x(),
+            false => self.deployed_y(),
+        } as u128;
+
+        if deployed * MAX_FEE_BPS as u128 > (liquid as u128 + deployed) * self.max_deployed_bps() as u128 {
+            return Err(AmmError::StrategyCapExceeded.into());
+        }
+
+        Ok(())
+    }
+
+    /// Record `amount` coming back to the vault, the excess over the deployed amount is yield
+    #[inline(always)]
+    pub fn recall(&mut self, is_x: bool, amount: u64) {
+        let deployed = match is_x {
+            true => &mut self.deployed_x,
+            false => &mut self.deployed_y,
+        };
+
+        *deployed = u64::from_le_bytes(*deployed).saturating_sub(amount).to_le_bytes();
+    }
+
+    /// Clear the deployed amount once the strategy account is empty, whatever wasn't returned is lost
+    #[inline(always)]
+    pub fn settle(&mut self, is_x: bool) {
+        match is_x {
+            true => self.deployed_x = [0u8; 8],
+            false => self.deployed_y = [0u8; 8],
+        }
+    }
+}
+
+/// Swap fees earned per LP token since the extension was added, so UIs can derive the LP fee APY
+/// from two reads of the config instead of indexing every swap
+///
+/// - Growths are Q64.64 amounts of X and Y per LP token, of the fee left to the LPs after the host
+///   and protocol cuts
+/// - They wrap on overflow, so readers should take differences with wrapping arithmetic
+/// - Swaps on these pools must pass the LP mint to read its supply
+#[repr(C)]
+pub struct FeeGrowth {
+    fee_growth_x: [u8; 16],
+    fee_growth_y: [u8; 16],
+    updated_at: [u8; 8],
+}
+
+impl Extension for FeeGrowth {
+    const TYPE: ConfigExtension = ConfigExtension::FeeGrowth;
+}
+
+impl FeeGrowth {
+    /// Q64.64 X earned per LP token
+    #[inline(always)]
+    pub fn fee_growth_x(&self) -> u128 {
+        u128::from_le_bytes(self.fee_growth_x)
+    }
+
+    /// Q64.64 Y earned per LP token
+    #[inline(always)]
+    pub fn fee_growth_y(&self) -> u128 {
+        u128::from_le_bytes(self.fee_growth_y)
+    }
+
+    /// Unix timestamp of the latest swap
+    #[inline(always)]
+    pub fn updated_at(&self) -> i64 {
+        i64::from_le_bytes(self.updated_at)
+    }
+
+    /// Spread the `fee` left to the LPs by a swap over the `lp_supply`
+    #[inline(always)]
+    pub fn accrue(&mut self, is_x: bool, fee: u64, lp_supply: u64, timestamp: i64) {
+        if lp_supply > 0 {
+            let fee_growth = match is_x {
+                true => &mut self.fee_growth_x,
+                false => &mut self.fee_growth_y,
+            };
+
+            *fee_growth = u128::from_le_bytes(*fee_growth)
+                .wrapping_add(((fee as u128) << 64) / lp_supply as u128)
+                .to_le_bytes();
+        }
+
+        self.updated_at = timestamp.to_le_bytes();
+    }
+}
+
+/// Buyback and burn: part of the protocol fees collected in the pool's governance token is burned
+/// by `CollectProtocolFees` instead of going to the treasury
+///
+/// - `burn_mint` must be one of the pool mints, the other side is always collected in full
+/// - Each burn is logged as (b"fee_burn", mint, amount) so indexers can track the burned supply
+#[repr(C)]
+pub struct FeeBurn {
+    burn_mint: Pubkey,
+    burn_bps: [u8; 2],
+}
+
+impl Extension for FeeBurn {
+    const TYPE: ConfigExtension = ConfigExtension::FeeBurn;
+}
+
+impl FeeBurn {
+    /// The governance token, burned from the protocol fees
+    #[inline(always)]
+    pub fn burn_mint(&self) -> &Pubkey {
+        &self.burn_mint
+    }
+
+    /// Share (in bps) of the `burn_mint` protocol fees burned
+    #[inline(always)]
+    pub fn burn_bps(&self) -> u16 {
+        u16::from_le_bytes(self.burn_bps)
+    }
+
+    /// Split collected protocol fees `x` and `y` into the amounts to burn on each side, rounding
+    /// down in favor of the treasury
+    #[inline(always)]
+    pub fn burn_amounts(&self, mint_x: &Pubkey, mint_y: &Pubkey, x: u64, y: u64) -> Result<(u64, u64), ProgramError> {
+        let burn = |amount: u64| (amount as u128 * self.burn_bps() as u128 / MAX_FEE_BPS as u128) as u64;
+
+        if self.burn_mint.eq(mint_x) {
+            Ok((burn(x), 0))
+        } else if self.burn_mint.eq(mint_y) {
+            Ok((0, burn(y)))
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+}
+
+/// Lets a `AmmState::WithdrawOnly` pool keep swapping during a wind-down, but only in the direction
+/// that brings its reserves back to the exit price, so LPs exit at fair value instead of at the
+/// price the pool was left at
+///
+/// - The exit price is the Y/X ratio `target_reserve_y / target_reserve_x`, set by the authority
+///   through UpdateConfig; zero targets disable the mode
+/// - Swaps must take out the side in excess and may not push the pool past the exit price
+/// - Deposits stay blocked
+#[repr(C)]
+pub struct ExitSwaps {
+    target_reserve_x: [u8; 8],
+    target_reserve_y: [u8; 8],
+}
+
+impl Extension for ExitSwaps {
+    const TYPE: ConfigExtension = ConfigExtension::ExitSwaps;
+}
+
+impl ExitSwaps {
+    #[inline(always)]
+    pub fn target_reserves(&self) -> (u64, u64) {
+        (
+            u64::from_le_bytes(self.target_reserve_x),
+            u64::from_le_bytes(self.target_reserve_y),
+        )
+    }
+
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        let (target_x, target_y) = self.target_reserves();
+
+        target_x != 0 && target_y != 0
+    }
+
+    /// Check that a swap paying in X when `is_x` only reduces the side in excess of the exit price,
+    /// without crossing it
+    #[inline(always)]
+    pub fn check_swap(
+        &self,
+        is_x: bool,
+        (reserve_x, reserve_y): (u64, u64),
+        (reserve_x_after, reserve_y_after): (u64, u64),
+    ) -> Result<(), ProgramError> {
+        let (target_x, target_y) = self.target_reserves();
+
+        // Compare y/x against target_y/target_x by cross-multiplying, `Greater` when X is in excess
+        let excess_x = |x: u64, y: u64| (x as u128 * target_y as u128).cmp(&(y as u128 * target_x as u128));
+
+        let valid = self.is_enabled()
+            && match is_x {
+                // Paying X in is only allowed while Y is in excess, and must leave it so or balanced
+                true => excess_x(reserve_x, reserve_y).is_lt() && excess_x(reserve_x_after, reserve_y_after).is_le(),
+                false => excess_x(reserve_x, reserve_y).is_gt() && excess_x(reserve_x_after, reserve_y_after).is_ge(),
+            };
+
+        if !valid {
+            return Err(AmmError::ExitSwapRejected.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Bumps of the program-derived vaults of a `Config::FLAG_PDA_VAULTS` pool, written once by
+/// `InitializeVaults`
+///
+/// - Vault seeds: [b"vault", config, mint, bump], token accounts owned by the config
+/// - Stored so the vaults are checked with `create_program_address` instead of a bump search
+#[repr(C)]
+pub struct VaultBumps {
+    vault_x_bump: [u8; 1],
+    vault_y_bump: [u8; 1],
+}
+
+impl Extension for VaultBumps {
+    const TYPE: ConfigExtension = ConfigExtension::VaultBumps;
+}
+
+impl VaultBumps {
+    #[inline(always)]
+    pub fn vault_x_bump(&self) -> [u8; 1] {
+        self.vault_x_bump
+    }
+
+    #[inline(always)]
+    pub fn vault_y_bump(&self) -> [u8; 1] {
+        self.vault_y_bump
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, vault_x_bump: [u8; 1], vault_y_bump: [u8; 1]) {
+        self.vault_x_bump = vault_x_bump;
+        self.vault_y_bump = vault_y_bump;
+    }
+}
+
+/// Recipients paid by a `FeeSplit` with their X and Y amounts, packed at the front, and their count
+pub type FeeSplitPayouts = ([(Pubkey, u64, u64); FeeSplit::CAPACITY], usize);
+
+/// Splits the protocol fees collected by `CollectProtocolFees` between several recipients, e.g. a
+/// treasury, an insurance fund and a buyback wallet
+///
+/// - Each recipient is a wallet paid in its own X and Y token accounts, in proportion to its weight
+/// - Slots with a zero weight are unused, the weights of the others add up to `MAX_FEE_BPS`
+/// - All zero weights disable the split and the fees go to the treasury in full
+/// - Applies after the `FeeBurn` cut, if any
+#[repr(C)]
+pub struct FeeSplit {
+    recipients: [Pubkey; FeeSplit::CAPACITY],
+    weights_bps: [[u8; 2]; FeeSplit::CAPACITY],
+}
+
+impl Extension for FeeSplit {
+    const TYPE: ConfigExtension = ConfigExtension::FeeSplit;
+}
+
+impl FeeSplit {
+    pub const CAPACITY: usize = 4;
+
+    #[inline(always)]
+    pub fn recipients(&self) -> &[Pubkey; Self::CAPACITY] {
+        &self.recipients
+    }
+
+    #[inline(always)]
+    pub fn weight_bps(&self, index: usize) -> u16 {
+        u16::from_le_bytes(self.weights_bps[index])
+    }
+
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        (0..Self::CAPACITY).any(|index| self.weight_bps(index) != 0)
+    }
+
+    /// Split collected protocol fees `x` and `y` by weight
+    ///
+    /// Shares round down, the dust goes to the first recipient.
+    #[inline(always)]
+    pub fn payouts(&self, x: u64, y: u64) -> Result<FeeSplitPayouts, ProgramError> {
+        let share = |amount: u64, weight: u16| mul_div(amount, weight as u64, MAX_FEE_BPS as u64, Rounding::Down);
+
+        let mut payouts = [(Pubkey::default(), 0, 0); Self::CAPACITY];
+        let mut count = 0;
+        let (mut paid_x, mut paid_y) = (0, 0);
+
+        for (index, recipient) in self.recipients.iter().enumerate() {
+            let weight = self.weight_bps(index);
+            if weight == 0 {
+                continue;
+            }
+
+            let (share_x, share_y) = (share(x, weight)?, share(y, weight)?);
+            payouts[count] = (*recipient, share_x, share_y);
+            paid_x = share_x.checked_add(paid_x).ok_or(AmmError::MathOverflow)?;
+            paid_y = share_y.checked_add(paid_y).ok_or(AmmError::MathOverflow)?;
+            count += 1;
+        }
+
+        // The weights add up to `MAX_FEE_BPS`, so the shares never exceed the amounts
+        if count > 0 {
+            payouts[0].1 += x.checked_sub(paid_x).ok_or(AmmError::MathOverflow)?;
+            payouts[0].2 += y.checked_sub(paid_y).ok_or(AmmError::MathOverflow)?;
+        }
+
+        Ok((payouts, count))
+    }
+}
+
+/// Programs allowed to swap against a permissioned pool, e.g. the approved frontends of an RWA pool,
+/// unused slots are the default pubkey (an empty list blocks swaps)
+///
+/// - Swaps must come through CPI, from a transaction instruction of one of these programs, which
+///   `Swap` reads from the Instructions sysvar
+/// - Only the top-level program is checked, not the programs it calls in between
+#[repr(C)]
+pub struct CallerAllowlist {
+    programs: [Pubkey; CallerAllowlist::CAPACITY],
+}
+
+impl Extension for CallerAllowlist {
+    const TYPE: ConfigExtension = ConfigExtension::CallerAllowlist;
+}
+
+impl CallerAllowlist {
+    pub const CAPACITY: usize = 4;
+
+    #[inline(always)]
+    pub fn programs(&self) -> &[Pubkey; Self::CAPACITY] {
+        &self.programs
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, program: &Pubkey) -> bool {
+        program.ne(&Pubkey::default()) && self.programs.contains(program)
+    }
+}
+
+/// Unix timestamps between which the pool only allows withdrawals, e.g. while the off-chain market
+/// of an RWA pool is closed, set by the authority through UpdateConfig
+///
+/// - The window is `[maintenance_start, maintenance_end)`, equal timestamps disable it
+/// - Swaps and deposits fail with `MaintenanceWindow` inside it, withdrawals are never blocked
+#[repr(C)]
+pub struct MaintenanceWindow {
+    maintenance_start: [u8; 8],
+    maintenance_end: [u8; 8],
+}
+
+impl Extension for MaintenanceWindow {
+    const TYPE: ConfigExtension = ConfigExtension::MaintenanceWindow;
+}
+
+impl MaintenanceWindow {
+    #[inline(always)]
+    pub fn maintenance_start(&self) -> i64 {
+        i64::from_le_bytes(self.maintenance_start)
+    }
+
+    #[inline(always)]
+    pub fn maintenance_end(&self) -> i64 {
+        i64::from_le_bytes(self.maintenance_end)
+    }
+
+    /// Fail with `MaintenanceWindow` if `now` falls inside the window
+    #[inline(always)]
+    pub fn check(&self, now: i64) -> Result<(), ProgramError> {
+        if (self.maintenance_start()..self.maintenance_end()).contains(&now) {
+            return Err(AmmError::MaintenanceWindow.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// The `TradingCalendar` a pool follows, swaps only go through while its market is open
+///
+/// - Set by the authority through UpdateConfig, the default pubkey disables the check
+/// - `Swap` takes the calendar as an optional account, so the wrappers building their own swap
+///   accounts (`SwapAndDeposit`, `WithdrawAndSwap`, `TokenSwap`, `RaydiumSwap`) can't trade the pool
+/// - Deposits and withdrawals are unaffected, see `MaintenanceWindow` to pause deposits
+#[repr(C)]
+pub struct TradingHours {
+    calendar: Pubkey,
+}
+
+impl Extension for TradingHours {
+    const TYPE: ConfigExtension = ConfigExtension::TradingHours;
+}
+
+impl TradingHours {
+    #[inline(always)]
+    pub fn calendar(&self) -> &Pubkey {
+        &self.calendar
+    }
+
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.calendar.ne(&Pubkey::default())
+    }
+}
+
+/// Backstop of a pegged-asset pool: a cut of every swap fee set aside in the insurance vaults, the
+/// ATAs of the `[b"insurance", config]` PDA, set by the authority through UpdateConfig
+///
+/// - `skim_bps` of the fee comes out of the LP share, like the protocol fee
+/// - `Swap` takes the vault of the input mint as an optional account, required while `skim_bps` is
+///   set, so the wrappers building their own swap accounts can't trade the pool
+/// - The authority withdraws through `WithdrawInsurance`, at least `timelock` seconds after
+///   requesting it, so LPs see a withdrawal coming
+#[repr(C)]
+pub struct InsuranceFund {
+    skim_bps: [u8; 2],
+    timelock: [u8; 8],
+    pending_x: [u8; 8],
+    pending_y: [u8; 8],
+    unlock_at: [u8; 8],
+}
+
+impl Extension for InsuranceFund {
+    const TYPE: ConfigExtension = ConfigExtension::InsuranceFund;
+}
+
+impl InsuranceFund {
+    #[inline(always)]
+    pub fn skim_bps(&self) -> u16 {
+        u16::from_le_bytes(self.skim_bps)
+    }
+
+    #[inline(always)]
+    pub fn timelock(&self) -> i64 {
+        i64::from_le_bytes(self.timelock)
+    }
+
+    /// The requested withdrawal, zero if none
+    #[inline(always)]
+    pub fn pending(&self) -> (u64, u64) {
+        (u64::from_le_bytes(self.pending_x), u64::from_le_bytes(self.pending_y))
+    }
+
+    /// When the requested withdrawal can go through, zero if none
+    #[inline(always)]
+    pub fn unlock_at(&self) -> i64 {
+        i64::from_le_bytes(self.unlock_at)
+    }
+
+    /// The skim out of a swap `fee`
+    #[inline(always)]
+    pub fn skim(&self, fee: u64) -> Result<u64, ProgramError> {
+        mul_div(fee, self.skim_bps() as u64, MAX_FEE_BPS as u64, Rounding::Down)
+    }
+
+    /// Request the withdrawal of `x` and `y` after the timelock, replacing any pending one; zero
+    /// amounts cancel it
+    #[inline(always)]
+    pub fn request_withdrawal(&mut self, x: u64, y: u64, now: i64) -> Result<(), ProgramError> {
+        let unlock_at = match x == 0 && y == 0 {
+            true => 0,
+            false => now.checked_add(self.timelock()).ok_or(AmmError::MathOverflow)?,
+        };
+
+        self.pending_x = x.to_le_bytes();
+        self.pending_y = y.to_le_bytes();
+        self.unlock_at = unlock_at.to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Clear the pending withdrawal and return its amounts, once its timelock has passed
+    #[inline(always)]
+    pub fn take_withdrawal(&mut self, now: i64) -> Result<(u64, u64), ProgramError> {
+        if self.unlock_at() == 0 || now < self.unlock_at() {
+            return Err(AmmError::InsuranceLocked.into());
+        }
+
+        let pending = self.pending();
+
+        self.pending_x = [0; 8];
+        self.pending_y = [0; 8];
+        self.unlock_at = [0; 8];
+
+        Ok(pending)
+    }
+}
+
+/// Auction-style capture of loss-versus-rebalancing: the first swap of every slot, usually the
+/// arbitrage moving the pool to the new market price, pays `first_swap_fee_bps` on top of the pool
+/// fee, set by the authority through UpdateConfig
+///
+/// - The surcharge is kept in full by the LPs, the host, protocol and insurance cuts only apply to
+///   the pool fee
+/// - `last_swap_slot` is the slot of the last executed swap, so later swaps in it pay the pool fee
+/// - The total fee is capped at `MAX_FEE_BPS`, a zero surcharge disables the tax
+#[repr(C)]
+pub struct MevTax {
+    first_swap_fee_bps: [u8; 2],
+    last_swap_slot: [u8; 8],
+}
+
+impl Extension for MevTax {
+    const TYPE: ConfigExtension = ConfigExtension::MevTax;
+}
+
+impl MevTax {
+    #[inline(always)]
+    pub fn first_swap_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.first_swap_fee_bps)
+    }
+
+    #[inline(always)]
+    pub fn last_swap_slot(&self) -> u64 {
+        u64::from_le_bytes(self.last_swap_slot)
+    }
+
+    /// Fee of a swap in `slot` on a pool charging `pool_fee`, with the surcharge if no swap went
+    /// through in that slot yet
+    #[inline(always)]
+    pub fn swap_fee(&self, pool_fee: Fee, slot: u64) -> Result<Fee, ProgramError> {
+        if self.last_swap_slot() == slot {
+            return Ok(pool_fee);
+        }
+
+        Fee::from_bps(pool_fee.bps().saturating_add(self.first_swap_fee_bps()).min(MAX_FEE_BPS))
+    }
+
+    #[inline(always)]
+    pub fn record_swap(&mut self, slot: u64) {
+        self.last_swap_slot = slot.to_le_bytes();
+    }
+}
+
+/// Tracks the recent swaps of the current slot and can reject a swap whose signer already swapped
+/// the other way in it, the back-run of a simple sandwich, set by the authority through UpdateConfig
+///
+/// - `reject_reversals` turns the rule on, otherwise the swaps are only recorded
+/// - Only the last `CAPACITY` swaps of the slot are remembered, so a sandwich padded with enough
+///   swaps from other signers in between still goes through
+/// - Directions are 1 for X in, 2 for Y in, 0 for an unused slot
+#[repr(C)]
+pub struct SameSlotGuard {
+    reject_reversals: u8,
+    last_swap_slot: [u8; 8],
+    swap_count: u8,
+    swappers: [Pubkey; SameSlotGuard::CAPACITY],
+    directions: [u8; SameSlotGuard::CAPACITY],
+}
+
+impl Extension for SameSlotGuard {
+    const TYPE: ConfigExtension = ConfigExtension::SameSlotGuard;
+}
+
+impl SameSlotGuard {
+    pub const CAPACITY: usize = 4;
+
+    #[inline(always)]
+    pub fn rejects_reversals(&self) -> bool {
+        self.reject_reversals != 0
+    }
+
+    /// Slot of the last executed swap
+    #[inline(always)]
+    pub fn last_swap_slot(&self) -> u64 {
+        u64::from_le_bytes(self.last_swap_slot)
+    }
+
+    #[inline(always)]
+    fn direction(is_x: bool) -> u8 {
+        match is_x {
+            true => 1,
+            false => 2,
+        }
+    }
+
+    /// Reject a swap by `signer` in `slot` if it reverses one of the signer's recorded swaps
+    #[inline(always)]
+    pub fn check(&self, slot: u64, signer: &Pubkey, is_x: bool) -> Result<(), ProgramError> {
+        if !self.rejects_reversals() || self.last_swap_slot() != slot {
+            return Ok(());
+        }
+
+        let reversed = Self::direction(!is_x);
+        if self
+            .swappers
+            .iter()
+            .zip(self.directions)
+            .any(|(swapper, direction)| direction == reversed && swapper.eq(signer))
+        {
+            return Err(AmmError::SameSlotReversal.into());
+        }
+
+        Ok(())
+    }
+
+    /// Record an executed swap, forgetting the swaps of earlier slots
+    #[inline(always)]
+    pub fn record_swap(&mut self, slot: u64, signer: &Pubkey, is_x: bool) {
+        if self.last_swap_slot() != slot {
+            self.last_swap_slot = slot.to_le_bytes();
+            self.swap_count = 0;
+            self.directions = [0; Self::CAPACITY];
+        }
+
+        // The count wraps at a multiple of the capacity, so the oldest swap is always overwritten
+        let index = self.swap_count as usize % Self::CAPACITY;
+        self.swappers[index] = *signer;
+        self.directions[index] = Self::direction(is_x);
+        self.swap_count = self.swap_count.wrapping_add(1);
+    }
+}
+
+/// Early-exit penalty discouraging mercenary liquidity during incentive programs, set by the
+/// authority through UpdateConfig
+///
+/// - Withdrawals hold back `penalty_bps` of the amounts right after a deposit, decaying linearly
+///   to zero over `decay_period` seconds since the `Position::last_deposit_timestamp` of the user
+/// - The penalty stays in the reserves, for the remaining LPs
+/// - Deposits must pass their position so every deposit restarts the decay, and withdrawals without
+///   the user's position pay the full penalty, as LP moved to another wallet would
+/// - The wrappers building their own deposit and withdraw accounts pass no position, so
+///   `SwapAndDeposit` and `TokenSwap` can't deposit, and `WithdrawAndSwap` and `TokenSwap`
+///   withdrawals pay the full penalty
+/// - A zero penalty or period disables it
+#[repr(C)]
+pub struct ExitPenalty {
+    penalty_bps: [u8; 2],
+    decay_period: [u8; 8],
+}
+
+impl Extension for ExitPenalty {
+    const TYPE: ConfigExtension = ConfigExtension::ExitPenalty;
+}
+
+impl ExitPenalty {
+    #[inline(always)]
+    pub fn penalty_bps(&self) -> u16 {
+        u16::from_le_bytes(self.penalty_bps)
+    }
+
+    #[inline(always)]
+    pub fn decay_period(&self) -> i64 {
+        i64::from_le_bytes(self.decay_period)
+    }
+
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.penalty_bps() != 0 && self.decay_period() != 0
+    }
+
+    /// Penalty in bps at `now` of a withdrawal by a user whose last deposit was at
+    /// `last_deposit`, the full penalty if unknown
+    #[inline(always)]
+    pub fn penalty_at(&self, last_deposit: Option<i64>, now: i64) -> u16 {
+        if !self.is_enabled() {
+            return 0;
+        }
+
+        let Some(last_deposit) = last_deposit else {
+            return self.penalty_bps();
+        };
+
+        let period = self.decay_period() as i128;
+        let remaining = period - (now as i128 - last_deposit as i128).clamp(0, period);
+
+        (self.penalty_bps() as i128 * remaining / period) as u16
+    }
+}
+
+/// Oracle observations of the pool kept as leaves of an SPL Account Compression merkle tree,
+/// written by InitializeObservationTree and AppendObservation
+///
+/// - Each leaf is an observation packed as slot [8] | unix_timestamp [8] | reserve_x [8] |
+///   reserve_y [8], unhashed, so `VerifyObservation` reads it back from a proof alone
+/// - The history lives in the noop logs and the tree only keeps its root, so the rent stays flat
+///   however many observations are appended
+/// - At most one observation per slot, the count is the index of the next leaf
+#[repr(C)]
+pub struct ObservationTree {
+    merkle_tree: Pubkey,
+    observation_count: [u8; 8],
+    last_observation_slot: [u8; 8],
+}
+
+impl Extension for ObservationTree {
+    const TYPE: ConfigExtension = ConfigExtension::ObservationTree;
+}
+
+impl ObservationTree {
+    /// Depth of the tree, room for 2^20 observations
+    pub const MAX_DEPTH: u32 = 20;
+
+    /// Recent roots kept by the tree, a proof against an older root fails
+    pub const MAX_BUFFER_SIZE: u32 = 64;
+
+    #[inline(always)]
+    pub fn merkle_tree(&self) -> &Pubkey {
+        &self.merkle_tree
+    }
+
+    #[inline(always)]
+    pub fn observation_count(&self) -> u64 {
+        u64::from_le_bytes(self.observation_count)
+    }
+
+    #[inline(always)]
+    pub fn last_observation_slot(&self) -> u64 {
+        u64::from_le_bytes(self.last_observation_slot)
+    }
+
+    /// Pack an observation into a leaf
+    #[inline(always)]
+    pub fn leaf(slot: u64, unix_timestamp: i64, reserve_x: u64, reserve_y: u64) -> [u8; 32] {
+        let mut leaf = [0u8; 32];
+        leaf[0..8].copy_from_slice(&slot.to_le_bytes());
+        leaf[8..16].copy_from_slice(&unix_timestamp.to_le_bytes());
+        leaf[16..24].copy_from_slice(&reserve_x.to_le_bytes());
+        leaf[24..32].copy_from_slice(&reserve_y.to_le_bytes());
+        leaf
+    }
+
+    /// Record an observation appended in `slot`, returning its leaf index
+    #[inline(always)]
+    pub fn record(&mut self, slot: u64) -> Result<u64, ProgramError> {
+        let index = self.observation_count();
+
+        if self.last_observation_slot() == slot && index > 0 {
+            return Err(AmmError::ObservationAlreadyRecorded.into());
+        }
+
+        self.observation_count = (index + 1).to_le_bytes();
+        self.last_observation_slot = slot.to_le_bytes();
+
+        Ok(index)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, merkle_tree: Pubkey) {
+        self.merkle_tree = merkle_tree;
+        self.observation_count = [0; 8];
+        self.last_observation_slot = [0; 8];
+    }
+}
+
+/// Changelog sequence number of the pool, bumped by every instruction that changes it and logged
+/// in its `sequence` event, so Geyser and gRPC consumers can order updates and detect missed ones
+///
+/// - Starts at zero when added by ExtendConfig, the first change logs 1
+/// - Pools with it must pass their config writable to every instruction that changes them
+#[repr(C)]
+pub struct Sequence {
+    sequence: [u8; 8],
+}
+
+impl Extension for Sequence {
+    const TYPE: ConfigExtension = ConfigExtension::Sequence;
+}
+
+impl Sequence {
+    #[inline(always)]
+    pub fn sequence(&self) -> u64 {
+        u64::from_le_bytes(self.sequence)
+    }
+
+    /// Advance to the next sequence number and return it
+    #[inline(always)]
+    pub fn bump(&mut self) -> u64 {
+        let sequence = self.sequence().wrapping_add(1);
+        self.sequence = sequence.to_le_bytes();
+        sequence
+    }
+}
+
+/// Fee tiers for high-volume traders, set by the authority through UpdateConfig: swaps passing the
+/// user's `UserStats` waive part of the pool fee once the user's Y volume reaches a tier
+///
+/// - Volumes count both legs of each swap, so every swap adds to the Y volume whatever its direction
+/// - The highest discount among the tiers reached applies, a zero minimum volume disables a tier
+/// - Discounts are in bps of the pool fee, before any `MevTax` surcharge, which is never discounted
+#[repr(C)]
+pub struct VolumeDiscount {
+    min_volumes_y: [[u8; 8]; VolumeDiscount::TIERS],
+    discounts_bps: [[u8; 2]; VolumeDiscount::TIERS],
+}
+
+impl Extension for VolumeDiscount {
+    const TYPE: ConfigExtension = ConfigExtension::VolumeDiscount;
+}
+
+impl VolumeDiscount {
+    pub const TIERS: usize = 4;
+
+    #[inline(always)]
+    pub fn min_volume_y(&self, tier: usize) -> u64 {
+        u64::from_le_bytes(self.min_volumes_y[tier])
+    }
+
+    #[inline(always)]
+    pub fn discount_bps(&self, tier: usize) -> u16 {
+        u16::from_le_bytes(self.discounts_bps[tier])
+    }
+
+    /// Discount (in bps of the pool fee) earned by a user with `volume_y` of lifetime Y volume
+    #[inline(always)]
+    pub fn discount_for(&self, volume_y: u64) -> u16 {
+        (0..Self::TIERS)
+            .filter(|tier| self.min_volume_y(*tier) > 0 && volume_y >= self.min_volume_y(*tier))
+            .map(|tier| self.discount_bps(tier))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Config account data with the given (type, length, value) entries, as `ExtendConfig` lays them out
+    fn config_data(entries: &[(u16, u16, &[u8])]) -> Vec<u8> {
+        let mut data = vec![0u8; Config::LEN];
+        for (extension_type, length, value) in entries {
+            data.extend_from_slice(&extension_type.to_le_bytes());
+            data.extend_from_slice(&length.to_le_bytes());
+            data.extend_from_slice(value);
+        }
+        data
+    }
+
+    #[test]
+    fn reads_nothing_without_extensions() {
+        let data = config_data(&[]);
+
+        assert_eq!(find_extension(&data, ConfigExtension::Caps as u16), Ok(None));
+        assert!(get_extension::<Caps>(&data).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_a_truncated_header() {
+        for extra in 1..ConfigExtension::HEADER_LEN {
+            let mut data = config_data(&[(ConfigExtension::Caps as u16, Caps::LEN as u16, &[0; Caps::LEN])]);
+            data.extend_from_slice(&[ConfigExtension::FeeState as u8, 0, 4][..extra]);
+
+            assert_eq!(find_extension(&data, ConfigExtension::FeeState as u16), Err(ProgramError::InvalidAccountData));
+            assert!(get_extension::<FeeState>(&data).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_a_length_past_the_account() {
+        let data = config_data(&[(ConfigExtension::Caps as u16, Caps::LEN as u16 + 1, &[0; Caps::LEN])]);
+
+        assert_eq!(find_extension(&data, ConfigExtension::Caps as u16), Err(ProgramError::InvalidAccountData));
+        assert!(get_extension::<Caps>(&data).is_err());
+        assert!(get_extension::<FeeState>(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_a_length_other_than_the_type_size() {
+        let data = config_data(&[(ConfigExtension::Caps as u16, Caps::LEN as u16 - 1, &[0; Caps::LEN - 1])]);
+
+        assert_eq!(find_extension(&data, ConfigExtension::Caps as u16), Ok(Some((Config::LEN + 4, Caps::LEN - 1))));
+        assert!(get_extension::<Caps>(&data).is_err());
+        assert!(get_extension_mut::<Caps>(&mut data.clone()).is_err());
+    }
+
+    #[test]
+    fn skips_unknown_types() {
+        let caps = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0];
+        let data = config_data(&[(u16::MAX, 3, &[9, 9, 9]), (ConfigExtension::Caps as u16, Caps::LEN as u16, &caps)]);
+
+        assert!(ConfigExtension::try_from(u16::MAX).is_err());
+        assert!(ConfigExtension::try_from(0).is_err());
+
+        let read = get_extension::<Caps>(&data).unwrap().unwrap();
+        assert_eq!((read.max_reserve_x(), read.max_reserve_y()), (1, 2));
+    }
+
+    #[test]
+    fn reads_the_first_of_duplicate_types() {
+        let first = [3, 0, 4, 0];
+        let data = config_data(&[
+            (ConfigExtension::FeeState as u16, FeeState::LEN as u16, &first),
+            (ConfigExtension::FeeState as u16, FeeState::LEN as u16, &[0xff; 4]),
+        ]);
+
+        // `ExtendConfig` refuses a type already found, so a later entry can never shadow the first
+        let read = get_extension::<FeeState>(&data).unwrap().unwrap();
+        assert_eq!((read.deposit_fee_bps(), read.withdraw_fee_bps()), (3, 4));
+        assert_eq!(find_extension(&data, ConfigExtension::FeeState as u16), Ok(Some((Config::LEN + 4, 4))));
+    }
+
+    #[test]
+    fn round_trips_writes() {
+        let mut data = config_data(&[
+            (ConfigExtension::OracleState as u16, OracleState::LEN as u16, &[0; OracleState::LEN]),
+            (ConfigExtension::Stats as u16, Stats::LEN as u16, &[0; Stats::LEN]),
+        ]);
+
+        get_extension_mut::<OracleState>(&mut data).unwrap().unwrap().set_inner([7; 32], 250).unwrap();
+        get_extension_mut::<Stats>(&mut data).unwrap().unwrap().record_swap(true, 100, 90, 1);
+
+        let oracle = get_extension::<OracleState>(&data).unwrap().unwrap();
+        assert_eq!((oracle.oracle(), oracle.max_deviation_bps()), (&[7; 32], 250));
+
+        let stats = get_extension::<Stats>(&data).unwrap().unwrap();
+        assert_eq!((stats.lifetime_volume_x(), stats.lifetime_volume_y(), stats.swap_count()), (100, 90, 1));
+
+        // Writes stay inside their value, the headers and the base account are untouched
+        assert!(data[..Config::LEN].iter().all(|byte| *byte == 0));
+        let stats_offset = Config::LEN + ConfigExtension::HEADER_LEN * 2 + OracleState::LEN;
+        assert_eq!(find_extension(&data, ConfigExtension::Stats as u16), Ok(Some((stats_offset, Stats::LEN))));
+    }
+
+    #[test]
+    fn checks_values_against_the_type_size() {
+        for extension_type in 1..=22 {
+            let extension = ConfigExtension::try_from(extension_type).unwrap();
+            let too_long = vec![0u8; extension.value_len() + 1];
+
+            assert_eq!(extension.check_value(&too_long), Err(ProgramError::InvalidInstructionData));
+            let too_short = &too_long[..extension.value_len() - 1];
+            assert_eq!(extension.check_value(too_short), Err(ProgramError::InvalidInstructionData));
+        }
+
+        assert_eq!(ConfigExtension::FeeState.check_value(&[0x10, 0x27, 0x10, 0x27]), Ok(()));
+        let above_max_fee = [0x11, 0x27, 0, 0];
+        assert_eq!(ConfigExtension::FeeState.check_value(&above_max_fee), Err(ProgramError::InvalidInstructionData));
+    }
+}
@@ -0,0 +1,250 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::AmmError;
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Shares the protocol fees collected in `reward_mint` among the stakers of `governance_mint`
+///
+/// - PDA seeds: [b"fee_distributor", governance_mint, reward_mint, bump], also the owner of its
+///   stake and reward vaults (ATAs)
+/// - The admin sends protocol fees to the reward vault, e.g. as the treasury of `CollectProtocolFees`
+/// - Once per epoch, `CheckpointFees` spreads what arrived since the last checkpoint over the stake,
+///   as a Q64.64 reward per staked token, and reserves it for the stakers
+#[repr(C)]
+pub struct FeeDistributor {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    governance_mint: Pubkey,
+    reward_mint: Pubkey,
+    governance_token_program: Pubkey,
+    reward_token_program: Pubkey,
+    governance_decimals: u8,
+    reward_decimals: u8,
+    total_staked: [u8; 8],
+    reward_per_token: [u8; 16],
+    reserved: [u8; 8],
+    epoch: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl FeeDistributor {
+    /// `sha256("account:FeeDistributor")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [129, 170, 37, 59, 198, 169, 135, 1];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<Pubkey>() * 4
+        + size_of::<u8>() * 2
+        + size_of::<u64>()
+        + size_of::<u128>()
+        + size_of::<u64>() * 2
+        + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `FeeDistributor` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `FeeDistributor` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `FeeDistributor`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const FeeDistributor)
+    }
+
+    #[inline(always)]
+    pub fn governance_mint(&self) -> &Pubkey {
+        &self.governance_mint
+    }
+
+    #[inline(always)]
+    pub fn reward_mint(&self) -> &Pubkey {
+        &self.reward_mint
+    }
+
+    #[inline(always)]
+    pub fn governance_token_program(&self) -> &Pubkey {
+        &self.governance_token_program
+    }
+
+    #[inline(always)]
+    pub fn reward_token_program(&self) -> &Pubkey {
+        &self.reward_token_program
+    }
+
+    #[inline(always)]
+    pub fn governance_decimals(&self) -> u8 {
+        self.governance_decimals
+    }
+
+    #[inline(always)]
+    pub fn reward_decimals(&self) -> u8 {
+        self.reward_decimals
+    }
+
+    #[inline(always)]
+    pub fn total_staked(&self) -> u64 {
+        u64::from_le_bytes(self.total_staked)
+    }
+
+    /// Q64.64 reward earned per staked token since the distributor was created
+    #[inline(always)]
+    pub fn reward_per_token(&self) -> u128 {
+        u128::from_le_bytes(self.reward_per_token)
+    }
+
+    /// Rewards in the reward vault owed to the stakers, checkpointed but not yet claimed
+    #[inline(always)]
+    pub fn reserved(&self) -> u64 {
+        u64::from_le_bytes(self.reserved)
+    }
+
+    /// Epoch of the latest checkpoint
+    #[inline(always)]
+    pub fn epoch(&self) -> u64 {
+        u64::from_le_bytes(self.epoch)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `FeeDistributor` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `FeeDistributor` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `FeeDistributor` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `FeeDistributor`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut FeeDistributor)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        governance_mint: Pubkey,
+        reward_mint: Pubkey,
+        governance_token_program: Pubkey,
+        reward_token_program: Pubkey,
+        governance_decimals: u8,
+        reward_decimals: u8,
+        epoch: u64,
+        bump: [u8; 1],
+    ) {
+        self.governance_mint = governance_mint;
+        self.reward_mint = reward_mint;
+        self.governance_token_program = governance_token_program;
+        self.reward_token_program = reward_token_program;
+        self.governance_decimals = governance_decimals;
+        self.reward_decimals = reward_decimals;
+        self.epoch = epoch.to_le_bytes();
+        self.bump = bump;
+    }
+
+    /// Spread the rewards that arrived since the last checkpoint over the stake, once per epoch.
+    ///
+    /// With nothing staked they stay unreserved until a later checkpoint. Returns the rewards
+    /// distributed.
+    #[inline(always)]
+    pub fn checkpoint(&mut self, reward_balance: u64, epoch: u64) -> Result<u64, ProgramError> {
+        if epoch <= self.epoch() {
+            return Err(AmmError::CheckpointTooEarly.into());
+        }
+
+        self.epoch = epoch.to_le_bytes();
+
+        let rewards = reward_balance.saturating_sub(self.reserved());
+        if rewards == 0 || self.total_staked() == 0 {
+            return Ok(0);
+        }
+
+        self.reward_per_token = self
+            .reward_per_token()
+            .wrapping_add(((rewards as u128) << 64) / self.total_staked() as u128)
+            .to_le_bytes();
+        self.reserved = self
+            .reserved()
+            .checked_add(rewards)
+            .ok_or(AmmError::MathOverflow)?
+            .to_le_bytes();
+
+        Ok(rewards)
+    }
+
+    #[inline(always)]
+    pub fn stake(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.total_staked = self
+            .total_staked()
+            .checked_add(amount)
+            .ok_or(AmmError::MathOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn unstake(&mut self, amount: u64) {
+        self.total_staked = (self.total_staked() - amount).to_le_bytes();
+    }
+
+    /// Release `amount` of the reserved rewards, paid to a staker
+    #[inline(always)]
+    pub fn pay(&mut self, amount: u64) {
+        self.reserved = self.reserved().saturating_sub(amount).to_le_bytes();
+    }
+}
@@ -0,0 +1,454 @@
+use core::mem::size_of;
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+};
+
+use crate::{AmmError, MAX_FEE_BPS, TOKEN_2022_PROGRAM_ID};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Program-wide settings shared by every pool
+///
+/// - PDA seeds: [b"global", bump], a single account per deployment
+/// - Read by Initialize (defaults and bounds for new pools), Swap (pause switch, tip account and
+///   cap) and every other state-mutating instruction but withdrawals (upgrade freeze switch)
+/// - The bounds only apply to permissionless creators, pools created by the admin aren't bound
+/// - Pool creation fees are paid to the system-owned treasury PDA: [b"treasury", treasury_bump]
+#[repr(C)]
+pub struct GlobalConfig {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    admin: Pubkey,
+    protocol_fee_bps: [u8; 2],
+    creation_fee: [u8; 8],
+    flags: u8,
+    bump: [u8; 1],
+    treasury_bump: [u8; 1],
+    min_swap_reserve: [u8; 8],
+    min_pool_fee_bps: [u8; 2],
+    max_pool_fee_bps: [u8; 2],
+    allowed_token_programs: u8,
+    allowed_pool_flags: u8,
+    tip_account: Pubkey,
+    max_tip: [u8; 8],
+}
+
+impl GlobalConfig {
+    /// `sha256("account:GlobalConfig")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [149, 8, 156, 202, 160, 252, 176, 217];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<Pubkey>()
+        + size_of::<u16>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + size_of::<u8>()
+        + size_of::<u8>()
+        + size_of::<u64>()
+        + size_of::<u16>() * 2
+        + size_of::<u8>() * 2
+        + size_of::<Pubkey>()
+        + size_of::<u64>();
+
+    /* Flags */
+
+    /// New pools can't be created and swaps are rejected in every pool
+    pub const FLAG_PAUSED: u8 = 1 << 0;
+    /// Set while a new program build is deployed and verified: every instruction reading the
+    /// global config is rejected, except withdrawals and the global admin's own updates
+    pub const FLAG_UPGRADE_FREEZE: u8 = 1 << 1;
+    /// New pools need a `TokenBadge` for both mints, whoever creates them
+    pub const FLAG_REQUIRE_TOKEN_BADGES: u8 = 1 << 2;
+
+    /* Token Programs */
+
+    /// New pool assets may live in SPL Token
+    pub const TOKEN_PROGRAM_SPL: u8 = 1 << 0;
+    /// New pool assets may live in Token-2022
+    pub const TOKEN_PROGRAM_2022: u8 = 1 << 1;
+
+    /* Reading Helpers */
+
+    /// Return the `GlobalConfig` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner, length and address validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        let global_config = Self::load_with_address_unchecked(account_info)?;
+        global_config.check_address(account_info.key())?;
+        Ok(global_config)
+    }
+
+    /// Return the `GlobalConfig` like `load` once checked that the deployment isn't upgrade frozen,
+    /// see `check_not_frozen`.
+    ///
+    /// The flag is read before the address is derived, so a frozen deployment fails early.
+    #[inline(always)]
+    pub fn load_unfrozen(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        let global_config = Self::load_with_address_unchecked(account_info)?;
+        global_config.check_not_frozen()?;
+        global_config.check_address(account_info.key())?;
+        Ok(global_config)
+    }
+
+    /// Owner, length and discriminator validation of `load`, the address is left to the caller
+    #[inline(always)]
+    fn load_with_address_unchecked(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return the `GlobalConfig` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `GlobalConfig`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const GlobalConfig)
+    }
+
+    /// Check that `key` is the singleton PDA, so no other account can stand in for it
+    #[inline(always)]
+    fn check_address(&self, key: &Pubkey) -> Result<(), ProgramError> {
+        if create_program_address(&[b"global", &self.bump], &crate::ID)?.ne(key) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn admin(&self) -> &Pubkey {
+        &self.admin
+    }
+
+    /// Share of the swap fee (in bps of the fee) kept for the protocol by new pools
+    #[inline(always)]
+    pub fn protocol_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.protocol_fee_bps)
+    }
+
+    /// Lamports charged to create a pool
+    #[inline(always)]
+    pub fn creation_fee(&self) -> u64 {
+        u64::from_le_bytes(self.creation_fee)
+    }
+
+    #[inline(always)]
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn treasury_bump(&self) -> [u8; 1] {
+        self.treasury_bump
+    }
+
+    /// Check that `key` is the treasury PDA
+    #[inline(always)]
+    pub fn check_treasury(&self, key: &Pubkey) -> Result<(), ProgramError> {
+        if create_program_address(&[b"treasury", &self.treasury_bump], &crate::ID)?.ne(key) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn is_paused(&self) -> bool {
+        self.flags & Self::FLAG_PAUSED != 0
+    }
+
+    #[inline(always)]
+    pub fn is_upgrade_frozen(&self) -> bool {
+        self.flags & Self::FLAG_UPGRADE_FREEZE != 0
+    }
+
+    /// Check that the deployment isn't upgrade frozen, see `FLAG_UPGRADE_FREEZE`
+    #[inline(always)]
+    pub fn check_not_frozen(&self) -> Result<(), ProgramError> {
+        if self.is_upgrade_frozen() {
+            return Err(AmmError::UpgradeFrozen.into());
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn requires_token_badges(&self) -> bool {
+        self.flags & Self::FLAG_REQUIRE_TOKEN_BADGES != 0
+    }
+
+    /// Swaps are refused unless both reserves of the pool are above this amount
+    #[inline(always)]
+    pub fn min_swap_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.min_swap_reserve)
+    }
+
+    /// Check that the reserves of a pool are deep enough to swap against
+    #[inline(always)]
+    pub fn check_swap_reserves(&self, reserve_in: u64, reserve_out: u64) -> Result<(), ProgramError> {
+        if reserve_in <= self.min_swap_reserve() || reserve_out <= self.min_swap_reserve() {
+            return Err(AmmError::InsufficientLiquidity.into());
+        }
+
+        Ok(())
+    }
+
+    /// Lowest fee (in bps) a permissionless pool may be created with
+    #[inline(always)]
+    pub fn min_pool_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.min_pool_fee_bps)
+    }
+
+    /// Highest fee (in bps) a permissionless pool may be created with
+    #[inline(always)]
+    pub fn max_pool_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.max_pool_fee_bps)
+    }
+
+    /// Token programs permissionless pool assets may live in, see `TOKEN_PROGRAM_*`
+    #[inline(always)]
+    pub fn allowed_token_programs(&self) -> u8 {
+        self.allowed_token_programs
+    }
+
+    /// `Config::FLAG_*` options permissionless creators may set
+    #[inline(always)]
+    pub fn allowed_pool_flags(&self) -> u8 {
+        self.allowed_pool_flags
+    }
+
+    /// Check the parameters of a new permissionless pool against the deployment bounds
+    ///
+    /// Token programs other than SPL Token and Token-2022 are left to `load_mint` to reject.
+    #[inline(always)]
+    pub fn check_pool_params(&self, fee: u16, flags: u8, token_programs: [&Pubkey; 2]) -> Result<(), ProgramError> {
+        if fee < self.min_pool_fee_bps() || fee > self.max_pool_fee_bps() {
+            return Err(AmmError::PoolFeeOutOfBounds.into());
+        }
+
+        if flags & !self.allowed_pool_flags != 0 {
+            return Err(AmmError::PoolFlagsNotAllowed.into());
+        }
+
+        for token_program in token_programs {
+            let required = match token_program {
+                program if program.eq(&pinocchio_token::ID) => Self::TOKEN_PROGRAM_SPL,
+                program if program.eq(&TOKEN_2022_PROGRAM_ID) => Self::TOKEN_PROGRAM_2022,
+                _ => continue,
+            };
+
+            if self.allowed_token_programs & required == 0 {
+                return Err(AmmError::TokenProgramNotAllowed.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Account receiving the lamport tips of swaps, e.g. a bundle tip account
+    #[inline(always)]
+    pub fn tip_account(&self) -> &Pubkey {
+        &self.tip_account
+    }
+
+    /// Highest tip (in lamports) a swap may pay, zero while tips are disabled
+    #[inline(always)]
+    pub fn max_tip(&self) -> u64 {
+        u64::from_le_bytes(self.max_tip)
+    }
+
+    /// Check that a swap tips `tip` lamports to the designated tip account, within the cap
+    #[inline(always)]
+    pub fn check_tip(&self, tip_account: &Pubkey, tip: u64) -> Result<(), ProgramError> {
+        if tip > self.max_tip() {
+            return Err(AmmError::TipAboveCap.into());
+        }
+
+        if tip_account.ne(&self.tip_account) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+
+    /* Writing Helpers */
+
+    /// Return the mutable `GlobalConfig` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner, length and address validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        let global_config = RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) });
+        global_config.check_address(account_info.key())?;
+        Ok(global_config)
+    }
+
+    /// Write the discriminator of a freshly created `GlobalConfig` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return the mutable `GlobalConfig` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `GlobalConfig`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut GlobalConfig)
+    }
+
+    #[inline(always)]
+    pub fn set_admin(&mut self, admin: Pubkey) {
+        self.admin = admin;
+    }
+
+    #[inline(always)]
+    pub fn set_protocol_fee_bps(&mut self, protocol_fee_bps: u16) -> Result<(), ProgramError> {
+        if protocol_fee_bps.gt(&MAX_FEE_BPS) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        self.protocol_fee_bps = protocol_fee_bps.to_le_bytes();
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_creation_fee(&mut self, creation_fee: u64) {
+        self.creation_fee = creation_fee.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_flags(&mut self, flags: u8) {
+        self.flags = flags;
+    }
+
+    #[inline(always)]
+    pub fn set_min_swap_reserve(&mut self, min_swap_reserve: u64) {
+        self.min_swap_reserve = min_swap_reserve.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_pool_fee_bounds(&mut self, min_pool_fee_bps: u16, max_pool_fee_bps: u16) -> Result<(), ProgramError> {
+        if min_pool_fee_bps.gt(&max_pool_fee_bps) || max_pool_fee_bps.gt(&MAX_FEE_BPS) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        self.min_pool_fee_bps = min_pool_fee_bps.to_le_bytes();
+        self.max_pool_fee_bps = max_pool_fee_bps.to_le_bytes();
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_allowed_token_programs(&mut self, allowed_token_programs: u8) {
+        self.allowed_token_programs = allowed_token_programs;
+    }
+
+    #[inline(always)]
+    pub fn set_allowed_pool_flags(&mut self, allowed_pool_flags: u8) {
+        self.allowed_pool_flags = allowed_pool_flags;
+    }
+
+    #[inline(always)]
+    pub fn set_tip(&mut self, tip_account: Pubkey, max_tip: u64) {
+        self.tip_account = tip_account;
+        self.max_tip = max_tip.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        admin: Pubkey,
+        protocol_fee_bps: u16,
+        creation_fee: u64,
+        bump: [u8; 1],
+        treasury_bump: [u8; 1],
+    ) -> Result<(), ProgramError> {
+        self.set_admin(admin);
+        self.set_protocol_fee_bps(protocol_fee_bps)?;
+        self.set_creation_fee(creation_fee);
+        self.set_flags(0);
+        self.set_min_swap_reserve(0);
+        // Unbounded until the admin narrows it down
+        self.set_pool_fee_bounds(0, MAX_FEE_BPS)?;
+        self.set_allowed_token_programs(Self::TOKEN_PROGRAM_SPL | Self::TOKEN_PROGRAM_2022);
+        self.set_allowed_pool_flags(u8::MAX);
+        // Tips are disabled until the admin designates an account
+        self.set_tip(Pubkey::default(), 0);
+        self.bump = bump;
+        self.treasury_bump = treasury_bump;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_swap_reserves_refuses_empty_pools() {
+        let mut data = [0u8; GlobalConfig::LEN];
+        let global_config = unsafe { GlobalConfig::from_bytes_unchecked_mut(&mut data) };
+        let insufficient_liquidity = Err(AmmError::InsufficientLiquidity.into());
+
+        assert_eq!(global_config.check_swap_reserves(0, 0), insufficient_liquidity);
+        assert_eq!(global_config.check_swap_reserves(0, u64::MAX), insufficient_liquidity);
+        assert_eq!(global_config.check_swap_reserves(u64::MAX, 0), insufficient_liquidity);
+        assert_eq!(global_config.check_swap_reserves(1, 1), Ok(()));
+    }
+
+    #[test]
+    fn check_swap_reserves_refuses_dust_pools() {
+        let mut data = [0u8; GlobalConfig::LEN];
+        let global_config = unsafe { GlobalConfig::from_bytes_unchecked_mut(&mut data) };
+        let insufficient_liquidity = Err(AmmError::InsufficientLiquidity.into());
+
+        global_config.set_min_swap_reserve(1_000);
+
+        // Both reserves must be strictly above the minimum
+        assert_eq!(global_config.check_swap_reserves(1_000, 1_000_000), insufficient_liquidity);
+        assert_eq!(global_config.check_swap_reserves(1_000_000, 1_000), insufficient_liquidity);
+        assert_eq!(global_config.check_swap_reserves(999, 999), insufficient_liquidity);
+        assert_eq!(global_config.check_swap_reserves(1_001, 1_001), Ok(()));
+        assert_eq!(global_config.check_swap_reserves(u64::MAX, u64::MAX), Ok(()));
+    }
+}
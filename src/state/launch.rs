@@ -0,0 +1,333 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::AmmError;
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Bonding-curve sale of a new token that graduates into a constant-product pool
+///
+/// - PDA seeds: [b"launch", mint, bump]
+/// - The curve trades `token_reserve` against `virtual_quote_reserve + quote_reserve`, so the
+///   starting price is set by the virtual reserve and no quote liquidity is needed up front
+/// - Trading stops once `quote_reserve` reaches `target_raise`; `GraduateLaunch` then seeds a
+///   pool with the raise and `pool_token_amount`, keeping the LP locked in the launch PDA
+#[repr(C)]
+pub struct Launch {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    state: u8,
+    creator: Pubkey,
+    mint: Pubkey,
+    quote_mint: Pubkey,
+    token_program: Pubkey,
+    quote_program: Pubkey,
+    token_decimals: u8,
+    quote_decimals: u8,
+    virtual_quote_reserve: [u8; 8],
+    quote_reserve: [u8; 8],
+    token_reserve: [u8; 8],
+    pool_token_amount: [u8; 8],
+    target_raise: [u8; 8],
+    fee: [u8; 2],
+    config: Pubkey,
+    bump: [u8; 1],
+}
+
+#[repr(u8)]
+pub enum LaunchState {
+    Uninitialized = 0u8,
+    Trading = 1u8,
+    Graduated = 2u8,
+}
+
+impl Launch {
+    /// `sha256("account:Launch")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [144, 51, 51, 163, 206, 85, 213, 38];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<u8>()
+        + size_of::<Pubkey>() * 5
+        + size_of::<u8>() * 2
+        + size_of::<u64>() * 5
+        + size_of::<u16>()
+        + size_of::<Pubkey>()
+        + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `Launch` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `Launch` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Launch`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Launch)
+    }
+
+    #[inline(always)]
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    #[inline(always)]
+    pub fn is_trading(&self) -> bool {
+        self.state == LaunchState::Trading as u8 && self.quote_reserve() < self.target_raise()
+    }
+
+    #[inline(always)]
+    pub fn creator(&self) -> &Pubkey {
+        &self.creator
+    }
+
+    /// The token being launched
+    #[inline(always)]
+    pub fn mint(&self) -> &Pubkey {
+        &self.mint
+    }
+
+    /// The token raised by the sale
+    #[inline(always)]
+    pub fn quote_mint(&self) -> &Pubkey {
+        &self.quote_mint
+    }
+
+    #[inline(always)]
+    pub fn token_program(&self) -> &Pubkey {
+        &self.token_program
+    }
+
+    #[inline(always)]
+    pub fn quote_program(&self) -> &Pubkey {
+        &self.quote_program
+    }
+
+    #[inline(always)]
+    pub fn token_decimals(&self) -> u8 {
+        self.token_decimals
+    }
+
+    #[inline(always)]
+    pub fn quote_decimals(&self) -> u8 {
+        self.quote_decimals
+    }
+
+    #[inline(always)]
+    pub fn virtual_quote_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.virtual_quote_reserve)
+    }
+
+    /// Quote actually raised and held by the launch quote vault
+    #[inline(always)]
+    pub fn quote_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.quote_reserve)
+    }
+
+    /// Tokens left for sale on the curve
+    #[inline(always)]
+    pub fn token_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.token_reserve)
+    }
+
+    /// Tokens set aside to seed the pool at graduation
+    #[inline(always)]
+    pub fn pool_token_amount(&self) -> u64 {
+        u64::from_le_bytes(self.pool_token_amount)
+    }
+
+    #[inline(always)]
+    pub fn target_raise(&self) -> u64 {
+        u64::from_le_bytes(self.target_raise)
+    }
+
+    /// Swap fee of the graduated pool, one of `FEE_TIERS`
+    #[inline(always)]
+    pub fn fee(&self) -> u16 {
+        u16::from_le_bytes(self.fee)
+    }
+
+    /// The pool created at graduation
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Return `(quote_in, tokens_out)` for a buy of up to `quote_in`, capped so the raise
+    /// doesn't overshoot the target. Rounds down in favor of the curve.
+    #[inline(always)]
+    pub fn quote_buy(&self, quote_in: u64) -> Result<(u64, u64), ProgramError> {
+        let quote_in = quote_in.min(self.target_raise().saturating_sub(self.quote_reserve()));
+
+        let quote_after = self.virtual_quote_reserve() as u128 + self.quote_reserve() as u128 + quote_in as u128;
+        let tokens_out = self.token_reserve() as u128 * quote_in as u128 / quote_after;
+
+        Ok((
+            quote_in,
+            u64::try_from(tokens_out).map_err(|_| AmmError::MathOverflow)?,
+        ))
+    }
+
+    /// Return the quote paid out for selling `tokens_in` back to the curve. Rounds down in
+    /// favor of the curve and never pays more than was raised.
+    #[inline(always)]
+    pub fn quote_sell(&self, tokens_in: u64) -> Result<u64, ProgramError> {
+        let quote_before = self.virtual_quote_reserve() as u128 + self.quote_reserve() as u128;
+        let tokens_after = self.token_reserve() as u128 + tokens_in as u128;
+        let quote_out =
+            u64::try_from(quote_before * tokens_in as u128 / tokens_after).map_err(|_| AmmError::MathOverflow)?;
+
+        if quote_out > self.quote_reserve() {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        Ok(quote_out)
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `Launch` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `Launch` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `Launch` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Launch`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Launch)
+    }
+
+    #[inline(always)]
+    pub fn record_buy(&mut self, quote_in: u64, tokens_out: u64) -> Result<(), ProgramError> {
+        self.quote_reserve = self
+            .quote_reserve()
+            .checked_add(quote_in)
+            .ok_or(AmmError::MathOverflow)?
+            .to_le_bytes();
+        self.token_reserve = self
+            .token_reserve()
+            .checked_sub(tokens_out)
+            .ok_or(ProgramError::InsufficientFunds)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn record_sell(&mut self, tokens_in: u64, quote_out: u64) -> Result<(), ProgramError> {
+        self.token_reserve = self
+            .token_reserve()
+            .checked_add(tokens_in)
+            .ok_or(AmmError::MathOverflow)?
+            .to_le_bytes();
+        self.quote_reserve = self
+            .quote_reserve()
+            .checked_sub(quote_out)
+            .ok_or(ProgramError::InsufficientFunds)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Close the sale and record the pool it graduated into
+    #[inline(always)]
+    pub fn graduate(&mut self, config: Pubkey) -> Result<(), ProgramError> {
+        if self.state != LaunchState::Trading as u8 || self.quote_reserve() < self.target_raise() {
+            return Err(AmmError::LaunchNotGraduable.into());
+        }
+
+        self.state = LaunchState::Graduated as u8;
+        self.config = config;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        creator: Pubkey,
+        mint: Pubkey,
+        quote_mint: Pubkey,
+        token_program: Pubkey,
+        quote_program: Pubkey,
+        token_decimals: u8,
+        quote_decimals: u8,
+        virtual_quote_reserve: u64,
+        token_reserve: u64,
+        pool_token_amount: u64,
+        target_raise: u64,
+        fee: u16,
+        bump: [u8; 1],
+    ) {
+        self.state = LaunchState::Trading as u8;
+        self.creator = creator;
+        self.mint = mint;
+        self.quote_mint = quote_mint;
+        self.token_program = token_program;
+        self.quote_program = quote_program;
+        self.token_decimals = token_decimals;
+        self.quote_decimals = quote_decimals;
+        self.virtual_quote_reserve = virtual_quote_reserve.to_le_bytes();
+        self.quote_reserve = 0u64.to_le_bytes();
+        self.token_reserve = token_reserve.to_le_bytes();
+        self.pool_token_amount = pool_token_amount.to_le_bytes();
+        self.target_raise = target_raise.to_le_bytes();
+        self.fee = fee.to_le_bytes();
+        self.bump = bump;
+    }
+}
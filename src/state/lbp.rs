@@ -0,0 +1,387 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{mul_div, weighted_swap_amount_out, AmmError, Rounding, MAX_FEE_BPS};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Liquidity bootstrapping pool: a weighted pool of a new token against a quote token whose
+/// weights shift over time, for price discovery of a fair launch
+///
+/// - PDA seeds: [b"lbp", mint, bump]
+/// - Only the creator adds liquidity, when creating the pool, and takes it back with `ExitLbp`
+///   after `end_time`
+/// - Anyone swaps between `start_time` and `end_time`; the token weight moves linearly from
+///   `start_weight` to `end_weight` (in bps, the quote weight is the rest), so the price drifts
+///   down unless buyers hold it up
+/// - Reserves are tracked here, donations to the vaults only go back to the creator at exit
+/// - The fee is kept in the reserves
+#[repr(C)]
+pub struct Lbp {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    state: u8,
+    creator: Pubkey,
+    mint: Pubkey,
+    quote_mint: Pubkey,
+    token_program: Pubkey,
+    quote_program: Pubkey,
+    token_decimals: u8,
+    quote_decimals: u8,
+    token_reserve: [u8; 8],
+    quote_reserve: [u8; 8],
+    start_weight: [u8; 2],
+    end_weight: [u8; 2],
+    start_time: [u8; 8],
+    end_time: [u8; 8],
+    fee: [u8; 2],
+    bump: [u8; 1],
+}
+
+#[repr(u8)]
+pub enum LbpState {
+    Uninitialized = 0u8,
+    Trading = 1u8,
+    Closed = 2u8,
+}
+
+impl Lbp {
+    /// `sha256("account:Lbp")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [149, 191, 222, 43, 244, 62, 21, 241];
+
+    /// Bounds of the token weight in bps, keeping the weight ratio within what
+    /// `weighted_swap_amount_out` supports
+    pub const MIN_WEIGHT: u16 = 100;
+    pub const MAX_WEIGHT: u16 = MAX_FEE_BPS - Self::MIN_WEIGHT;
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<u8>()
+        + size_of::<Pubkey>() * 5
+        + size_of::<u8>() * 2
+        + size_of::<u64>() * 2
+        + size_of::<u16>() * 2
+        + size_of::<i64>() * 2
+        + size_of::<u16>()
+        + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `Lbp` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `Lbp` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Lbp`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Lbp)
+    }
+
+    #[inline(always)]
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    #[inline(always)]
+    pub fn is_trading(&self, now: i64) -> bool {
+        self.state == LbpState::Trading as u8 && (self.start_time()..self.end_time()).contains(&now)
+    }
+
+    #[inline(always)]
+    pub fn creator(&self) -> &Pubkey {
+        &self.creator
+    }
+
+    /// The token being launched
+    #[inline(always)]
+    pub fn mint(&self) -> &Pubkey {
+        &self.mint
+    }
+
+    /// The token it is priced in
+    #[inline(always)]
+    pub fn quote_mint(&self) -> &Pubkey {
+        &self.quote_mint
+    }
+
+    #[inline(always)]
+    pub fn token_program(&self) -> &Pubkey {
+        &self.token_program
+    }
+
+    #[inline(always)]
+    pub fn quote_program(&self) -> &Pubkey {
+        &self.quote_program
+    }
+
+    #[inline(always)]
+    pub fn token_decimals(&self) -> u8 {
+        self.token_decimals
+    }
+
+    #[inline(always)]
+    pub fn quote_decimals(&self) -> u8 {
+        self.quote_decimals
+    }
+
+    #[inline(always)]
+    pub fn token_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.token_reserve)
+    }
+
+    #[inline(always)]
+    pub fn quote_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.quote_reserve)
+    }
+
+    /// Token weight in bps at `start_time`
+    #[inline(always)]
+    pub fn start_weight(&self) -> u16 {
+        u16::from_le_bytes(self.start_weight)
+    }
+
+    /// Token weight in bps at `end_time`
+    #[inline(always)]
+    pub fn end_weight(&self) -> u16 {
+        u16::from_le_bytes(self.end_weight)
+    }
+
+    #[inline(always)]
+    pub fn start_time(&self) -> i64 {
+        i64::from_le_bytes(self.start_time)
+    }
+
+    #[inline(always)]
+    pub fn end_time(&self) -> i64 {
+        i64::from_le_bytes(self.end_time)
+    }
+
+    /// Swap fee in bps
+    #[inline(always)]
+    pub fn fee(&self) -> u16 {
+        u16::from_le_bytes(self.fee)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Token weight in bps at `now`, interpolated between the start and end weights
+    #[inline(always)]
+    pub fn token_weight(&self, now: i64) -> u16 {
+        let (start, end) = (self.start_time(), self.end_time());
+        let elapsed = now.clamp(start, end).saturating_sub(start) as i128;
+        let duration = end.saturating_sub(start).max(1) as i128;
+
+        let (start_weight, end_weight) = (self.start_weight() as i128, self.end_weight() as i128);
+
+        (start_weight + (end_weight - start_weight) * elapsed / duration) as u16
+    }
+
+    /// Return the output of a swap of `amount_in` at `now`, buying tokens with the quote token
+    /// when `buy` and selling them for it otherwise. Rounds down in favor of the pool.
+    #[inline(always)]
+    pub fn quote_swap(&self, buy: bool, amount_in: u64, now: i64) -> Result<u64, ProgramError> {
+        let token_weight = self.token_weight(now);
+        let quote_weight = MAX_FEE_BPS - token_weight;
+
+        // The fee rounds up in favor of the pool
+        let fee = mul_div(amount_in, self.fee() as u64, MAX_FEE_BPS as u64, Rounding::Up)?;
+        let amount_in_after_fee = amount_in.checked_sub(fee).ok_or(AmmError::MathOverflow)?;
+
+        match buy {
+            true => weighted_swap_amount_out(
+                self.quote_reserve(),
+                quote_weight,
+                self.token_reserve(),
+                token_weight,
+                amount_in_after_fee,
+            ),
+            false => weighted_swap_amount_out(
+                self.token_reserve(),
+                token_weight,
+                self.quote_reserve(),
+                quote_weight,
+                amount_in_after_fee,
+            ),
+        }
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `Lbp` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `Lbp` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `Lbp` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Lbp`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Lbp)
+    }
+
+    #[inline(always)]
+    pub fn record_swap(&mut self, buy: bool, amount_in: u64, amount_out: u64) -> Result<(), ProgramError> {
+        let (reserve_in, reserve_out) = match buy {
+            true => (&mut self.quote_reserve, &mut self.token_reserve),
+            false => (&mut self.token_reserve, &mut self.quote_reserve),
+        };
+
+        *reserve_in = u64::from_le_bytes(*reserve_in)
+            .checked_add(amount_in)
+            .ok_or(AmmError::MathOverflow)?
+            .to_le_bytes();
+        *reserve_out = u64::from_le_bytes(*reserve_out)
+            .checked_sub(amount_out)
+            .ok_or(ProgramError::InsufficientFunds)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Close the pool once its sale has ended, the vaults are then returned to the creator
+    #[inline(always)]
+    pub fn close(&mut self, now: i64) -> Result<(), ProgramError> {
+        if self.state != LbpState::Trading as u8 || now < self.end_time() {
+            return Err(AmmError::LbpNotClosable.into());
+        }
+
+        self.state = LbpState::Closed as u8;
+        self.token_reserve = 0u64.to_le_bytes();
+        self.quote_reserve = 0u64.to_le_bytes();
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        creator: Pubkey,
+        mint: Pubkey,
+        quote_mint: Pubkey,
+        token_program: Pubkey,
+        quote_program: Pubkey,
+        token_decimals: u8,
+        quote_decimals: u8,
+        token_reserve: u64,
+        quote_reserve: u64,
+        start_weight: u16,
+        end_weight: u16,
+        start_time: i64,
+        end_time: i64,
+        fee: u16,
+        bump: [u8; 1],
+    ) {
+        self.state = LbpState::Trading as u8;
+        self.creator = creator;
+        self.mint = mint;
+        self.quote_mint = quote_mint;
+        self.token_program = token_program;
+        self.quote_program = quote_program;
+        self.token_decimals = token_decimals;
+        self.quote_decimals = quote_decimals;
+        self.token_reserve = token_reserve.to_le_bytes();
+        self.quote_reserve = quote_reserve.to_le_bytes();
+        self.start_weight = start_weight.to_le_bytes();
+        self.end_weight = end_weight.to_le_bytes();
+        self.start_time = start_time.to_le_bytes();
+        self.end_time = end_time.to_le_bytes();
+        self.fee = fee.to_le_bytes();
+        self.bump = bump;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Data of an `Lbp` moving the token weight from `start_weight` to `end_weight` over 100..=200
+    fn lbp_data(start_weight: u16, end_weight: u16) -> [u8; Lbp::LEN] {
+        let mut data = [0u8; Lbp::LEN];
+        let lbp = unsafe { Lbp::from_bytes_unchecked_mut(&mut data) };
+        let key = Pubkey::default();
+        lbp.set_inner(key, key, key, key, key, 6, 6, 1_000, 1_000, start_weight, end_weight, 100, 200, 0, [0]);
+        data
+    }
+
+    #[test]
+    fn token_weight_interpolates_between_the_start_and_end() {
+        let mut data = lbp_data(Lbp::MAX_WEIGHT, Lbp::MIN_WEIGHT);
+        let lbp = unsafe { Lbp::from_bytes_unchecked(&data) };
+
+        assert_eq!(lbp.token_weight(100), Lbp::MAX_WEIGHT);
+        assert_eq!(lbp.token_weight(125), 7_450);
+        assert_eq!(lbp.token_weight(150), 5_000);
+        assert_eq!(lbp.token_weight(199), 198);
+        assert_eq!(lbp.token_weight(200), Lbp::MIN_WEIGHT);
+
+        // Rising weights interpolate the same way
+        data = lbp_data(2_000, 8_000);
+        let lbp = unsafe { Lbp::from_bytes_unchecked(&data) };
+        assert_eq!(lbp.token_weight(150), 5_000);
+        assert_eq!(lbp.token_weight(101), 2_060);
+    }
+
+    #[test]
+    fn token_weight_clamps_outside_the_sale() {
+        let data = lbp_data(Lbp::MAX_WEIGHT, Lbp::MIN_WEIGHT);
+        let lbp = unsafe { Lbp::from_bytes_unchecked(&data) };
+
+        for now in [i64::MIN, 0, 99] {
+            assert_eq!(lbp.token_weight(now), Lbp::MAX_WEIGHT);
+        }
+        for now in [201, 10_000, i64::MAX] {
+            assert_eq!(lbp.token_weight(now), Lbp::MIN_WEIGHT);
+        }
+    }
+}
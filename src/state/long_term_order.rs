@@ -0,0 +1,230 @@
+use crate::{mul_div, AmmError, Rounding};
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// TWAMM order selling one side of a pool for the other evenly over a range of slots, see
+/// `OpenLongTermOrder`
+///
+/// - PDA seeds: [b"long_term_order", config, owner, nonce, bump]
+/// - The order owns its escrows, the associated token accounts of both pool mints, and signs the
+///   `Swap` of each `ExecuteLongTermOrder` crank as their `user`
+/// - By any slot, the order has sold the share of `amount` elapsed since `start_slot`, and bought
+///   at least the same share of `min_amount_out`
+#[repr(C)]
+pub struct LongTermOrder {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    owner: Pubkey,
+    config: Pubkey,
+    nonce: [u8; 8],
+    amount: [u8; 8],
+    min_amount_out: [u8; 8],
+    sold: [u8; 8],
+    bought: [u8; 8],
+    start_slot: [u8; 8],
+    end_slot: [u8; 8],
+    is_x: u8,
+    bump: [u8; 1],
+}
+
+impl LongTermOrder {
+    /// `sha256("account:LongTermOrder")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [111, 2, 153, 190, 129, 101, 56, 119];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN + size_of::<Pubkey>() * 2 + size_of::<u64>() * 7 + size_of::<u8>() * 2;
+
+    /* Reading Helpers */
+
+    /// Return a `LongTermOrder` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `LongTermOrder` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `LongTermOrder`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const LongTermOrder)
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn nonce(&self) -> u64 {
+        u64::from_le_bytes(self.nonce)
+    }
+
+    /// Input amount sold over the order, as received by its escrow
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    /// Least output the whole order must buy
+    #[inline(always)]
+    pub fn min_amount_out(&self) -> u64 {
+        u64::from_le_bytes(self.min_amount_out)
+    }
+
+    #[inline(always)]
+    pub fn sold(&self) -> u64 {
+        u64::from_le_bytes(self.sold)
+    }
+
+    #[inline(always)]
+    pub fn bought(&self) -> u64 {
+        u64::from_le_bytes(self.bought)
+    }
+
+    #[inline(always)]
+    pub fn start_slot(&self) -> u64 {
+        u64::from_le_bytes(self.start_slot)
+    }
+
+    #[inline(always)]
+    pub fn end_slot(&self) -> u64 {
+        u64::from_le_bytes(self.end_slot)
+    }
+
+    /// Whether the order sells X for Y
+    #[inline(always)]
+    pub fn is_x(&self) -> bool {
+        self.is_x == 1
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Input the order is due to sell at `slot`, pro rata of the slots elapsed since `start_slot`
+    #[inline(always)]
+    pub fn due(&self, slot: u64) -> Result<u64, ProgramError> {
+        let elapsed = slot.min(self.end_slot()).saturating_sub(self.start_slot());
+        let duration = self.end_slot() - self.start_slot();
+
+        let vested = mul_div(self.amount(), elapsed, duration, Rounding::Down)?;
+
+        Ok(vested.saturating_sub(self.sold()))
+    }
+
+    /// Least total output once `sold` of the input is sold, pro rata of `min_amount_out`
+    #[inline(always)]
+    pub fn min_bought(&self, sold: u64) -> Result<u64, ProgramError> {
+        mul_div(self.min_amount_out(), sold, self.amount(), Rounding::Up)
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `LongTermOrder` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `LongTermOrder` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `LongTermOrder` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `LongTermOrder`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut LongTermOrder)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        owner: Pubkey,
+        config: Pubkey,
+        nonce: u64,
+        is_x: bool,
+        amount: u64,
+        min_amount_out: u64,
+        start_slot: u64,
+        end_slot: u64,
+        bump: [u8; 1],
+    ) {
+        self.owner = owner;
+        self.config = config;
+        self.nonce = nonce.to_le_bytes();
+        self.is_x = is_x as u8;
+        self.amount = amount.to_le_bytes();
+        self.min_amount_out = min_amount_out.to_le_bytes();
+        self.sold = 0u64.to_le_bytes();
+        self.bought = 0u64.to_le_bytes();
+        self.start_slot = start_slot.to_le_bytes();
+        self.end_slot = end_slot.to_le_bytes();
+        self.bump = bump;
+    }
+
+    /// Record a crank that sold `sold` and bought `bought`, failing if the order falls behind its
+    /// minimum output
+    #[inline(always)]
+    pub fn fill(&mut self, sold: u64, bought: u64) -> Result<(), ProgramError> {
+        let sold = self.sold().checked_add(sold).ok_or(AmmError::MathOverflow)?;
+        let bought = self.bought().checked_add(bought).ok_or(AmmError::MathOverflow)?;
+
+        if bought < self.min_bought(sold)? {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.sold = sold.to_le_bytes();
+        self.bought = bought.to_le_bytes();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+pub mod config;
+pub use config::*;
+
+pub mod registry;
+pub use registry::*;
+
+pub mod position;
+pub use position::*;
+
+pub mod nft_position;
+pub use nft_position::*;
+
+pub mod global_config;
+pub use global_config::*;
+
+pub mod extension;
+pub use extension::*;
+
+pub mod launch;
+pub use launch::*;
+
+pub mod allowance;
+pub use allowance::*;
+
+pub mod multi_pool;
+pub use multi_pool::*;
+
+pub mod discriminator;
+pub use discriminator::*;
+
+pub mod recovery;
+pub use recovery::*;
+
+pub mod fee_distributor;
+pub use fee_distributor::*;
+
+pub mod staker;
+pub use staker::*;
+
+pub mod trusted_router;
+pub use trusted_router::*;
+
+pub mod pool_metadata;
+pub use pool_metadata::*;
+
+pub mod trading_calendar;
+pub use trading_calendar::*;
+
+pub mod lbp;
+pub use lbp::*;
+
+pub mod token_badge;
+pub use token_badge::*;
+
+pub mod long_term_order;
+pub use long_term_order::*;
+
+pub mod user_stats;
+pub use user_stats::*;
+
+pub mod balance_snapshot;
+pub use balance_snapshot::*;
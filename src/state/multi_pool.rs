@@ -0,0 +1,223 @@
+use crate::{mul_div, swap_amount_out, AmmError, Rounding, FEE_TIERS, MAX_FEE_BPS};
+use core::{array, mem::size_of};
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// A pool of 2 to `MultiPool::MAX_TOKENS` assets on an equal-weight constant-mean curve
+///
+/// - PDA seeds: [b"multi_pool", seed, mints.., bump], the mints strictly ascending
+/// - The invariant is the product of all reserves, so a swap between two tokens only moves
+///   their two reserves and prices like a constant product pair
+/// - Vaults are the pool's ATAs of each mint, the LP mint is [b"mint_lp", multi_pool]
+#[repr(C)]
+pub struct MultiPool {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    seed: [u8; 8],
+    fee: [u8; 2],
+    bump: [u8; 1],
+    lp_decimals: u8,
+    token_count: u8,
+    mints: [Pubkey; MultiPool::MAX_TOKENS],
+    token_programs: [Pubkey; MultiPool::MAX_TOKENS],
+    decimals: [u8; MultiPool::MAX_TOKENS],
+}
+
+impl MultiPool {
+    pub const MAX_TOKENS: usize = 4;
+
+    /// `sha256("account:MultiPool")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [244, 223, 68, 238, 85, 162, 221, 210];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<u64>()
+        + size_of::<u16>()
+        + size_of::<u8>() * 3
+        + size_of::<Pubkey>() * Self::MAX_TOKENS * 2
+        + size_of::<u8>() * Self::MAX_TOKENS;
+
+    /* Reading Helpers */
+
+    /// Return a `MultiPool` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `MultiPool` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `MultiPool`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const MultiPool)
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn fee(&self) -> u16 {
+        u16::from_le_bytes(self.fee)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn lp_decimals(&self) -> u8 {
+        self.lp_decimals
+    }
+
+    #[inline(always)]
+    pub fn token_count(&self) -> usize {
+        self.token_count as usize
+    }
+
+    /// The pool mints, in ascending order
+    #[inline(always)]
+    pub fn mints(&self) -> &[Pubkey] {
+        &self.mints[..self.token_count()]
+    }
+
+    #[inline(always)]
+    pub fn token_program(&self, index: usize) -> &Pubkey {
+        &self.token_programs[index]
+    }
+
+    #[inline(always)]
+    pub fn decimals(&self, index: usize) -> u8 {
+        self.decimals[index]
+    }
+
+    /// Seeds of the pool PDA, only the first `token_count() + 3` are used
+    #[inline(always)]
+    pub fn signer_seeds(&self) -> [Seed; Self::MAX_TOKENS + 3] {
+        let token_count = self.token_count();
+
+        array::from_fn(|index| match index {
+            0 => Seed::from(b"multi_pool"),
+            1 => Seed::from(&self.seed),
+            index if index - 2 < token_count => Seed::from(&self.mints[index - 2]),
+            _ => Seed::from(&self.bump),
+        })
+    }
+
+    /// Amount out of a swap of `amount_in`, and the fee kept by the pool
+    ///
+    /// With the other reserves fixed, the product invariant reduces to `reserve_in * reserve_out`.
+    #[inline(always)]
+    pub fn quote_swap(&self, reserve_in: u64, reserve_out: u64, amount_in: u64) -> Result<(u64, u64), ProgramError> {
+        if reserve_in == 0 || reserve_out == 0 {
+            return Err(AmmError::InsufficientLiquidity.into());
+        }
+
+        // The fee rounds up in favor of the pool
+        let fee = mul_div(amount_in, self.fee() as u64, MAX_FEE_BPS as u64, Rounding::Up)?;
+        let amount_in_after_fee = amount_in.checked_sub(fee).ok_or(AmmError::MathOverflow)?;
+
+        Ok((swap_amount_out(reserve_in, reserve_out, amount_in_after_fee)?, fee))
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `MultiPool` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `MultiPool` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `MultiPool` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `MultiPool`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut MultiPool)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, seed: u64, fee: u16, bump: [u8; 1], lp_decimals: u8) -> Result<(), ProgramError> {
+        if !FEE_TIERS.contains(&fee) {
+            return Err(AmmError::InvalidFeeTier.into());
+        }
+
+        self.seed = seed.to_le_bytes();
+        self.fee = fee.to_le_bytes();
+        self.bump = bump;
+        self.lp_decimals = lp_decimals;
+
+        Ok(())
+    }
+
+    /// Append a token, mints must be pushed in ascending order
+    #[inline(always)]
+    pub fn push_token(&mut self, mint: Pubkey, token_program: Pubkey, decimals: u8) -> Result<(), ProgramError> {
+        let index = self.token_count();
+
+        if index == Self::MAX_TOKENS {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if index > 0 && mint.le(&self.mints[index - 1]) {
+            return Err(AmmError::InvalidMintOrder.into());
+        }
+
+        self.mints[index] = mint;
+        self.token_programs[index] = token_program;
+        self.decimals[index] = decimals;
+        self.token_count += 1;
+
+        Ok(())
+    }
+}
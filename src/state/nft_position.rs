@@ -0,0 +1,174 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey, ProgramResult};
+use pinocchio_token::state::TokenAccount;
+
+use crate::AmmError;
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Liquidity position represented by a 1-of-1 NFT
+///
+/// - PDA seeds: [b"nft_position", position_mint, bump]
+/// - The LP tokens backing the position sit in `lp_vault`, an LP token account owned by this PDA
+/// - Whoever holds the `position_mint` NFT controls the position
+#[repr(C)]
+pub struct NftPosition {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    config: Pubkey,
+    position_mint: Pubkey,
+    lp_vault: Pubkey,
+    liquidity: [u8; 8],
+    locked_until: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl NftPosition {
+    /// `sha256("account:NftPosition")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [91, 244, 89, 214, 250, 195, 212, 147];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<Pubkey>() * 3
+        + size_of::<u64>()
+        + size_of::<i64>()
+        + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `NftPosition` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `NftPosition` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `NftPosition`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const NftPosition)
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn position_mint(&self) -> &Pubkey {
+        &self.position_mint
+    }
+
+    #[inline(always)]
+    pub fn lp_vault(&self) -> &Pubkey {
+        &self.lp_vault
+    }
+
+    #[inline(always)]
+    pub fn liquidity(&self) -> u64 {
+        u64::from_le_bytes(self.liquidity)
+    }
+
+    #[inline(always)]
+    pub fn locked_until(&self) -> i64 {
+        i64::from_le_bytes(self.locked_until)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Check that `holder_ata` is a token account of `holder` containing the position NFT
+    #[inline(always)]
+    pub fn check_holder(&self, holder_ata: &AccountInfo, holder: &Pubkey) -> ProgramResult {
+        let holder_ata = TokenAccount::from_account_info(holder_ata)?;
+
+        if holder_ata.mint().ne(&self.position_mint)
+            || holder_ata.owner().ne(holder)
+            || holder_ata.amount() != 1
+        {
+            return Err(AmmError::InvalidPositionHolder.into());
+        }
+
+        Ok(())
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `NftPosition` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `NftPosition` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `NftPosition` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `NftPosition`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut NftPosition)
+    }
+
+    #[inline(always)]
+    pub fn set_liquidity(&mut self, liquidity: u64) {
+        self.liquidity = liquidity.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_locked_until(&mut self, locked_until: i64) {
+        self.locked_until = locked_until.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: Pubkey, position_mint: Pubkey, lp_vault: Pubkey, bump: [u8; 1]) {
+        self.config = config;
+        self.position_mint = position_mint;
+        self.lp_vault = lp_vault;
+        self.set_liquidity(0);
+        self.set_locked_until(0);
+        self.bump = bump;
+    }
+}
@@ -0,0 +1,167 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Display metadata of a pool, so UIs can render it without a centralized token list
+///
+/// - PDA seeds: [b"pool_metadata", config, bump]
+/// - Optional, created and updated by the pool authority through `SetPoolMetadata`
+/// - Text fields are UTF-8, zero-padded to their fixed size
+#[repr(C)]
+pub struct PoolMetadata {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    config: Pubkey,
+    name: [u8; PoolMetadata::NAME_LEN],
+    project_url: [u8; PoolMetadata::URI_LEN],
+    logo_uri: [u8; PoolMetadata::URI_LEN],
+    tags: [[u8; PoolMetadata::TAG_LEN]; PoolMetadata::MAX_TAGS],
+    bump: [u8; 1],
+}
+
+impl PoolMetadata {
+    /// `sha256("account:PoolMetadata")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [75, 50, 227, 48, 192, 212, 141, 226];
+
+    pub const NAME_LEN: usize = 32;
+    pub const URI_LEN: usize = 128;
+    pub const TAG_LEN: usize = 16;
+    pub const MAX_TAGS: usize = 4;
+
+    /// Length of the name, project URL, logo URI and tags, as written by `set_inner`
+    pub const FIELDS_LEN: usize = Self::NAME_LEN + Self::URI_LEN * 2 + Self::TAG_LEN * Self::MAX_TAGS;
+
+    pub const LEN: usize = DISCRIMINATOR_LEN + size_of::<Pubkey>() + Self::FIELDS_LEN + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `PoolMetadata` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `PoolMetadata` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `PoolMetadata`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const PoolMetadata)
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn name(&self) -> &[u8] {
+        trim_padding(&self.name)
+    }
+
+    #[inline(always)]
+    pub fn project_url(&self) -> &[u8] {
+        trim_padding(&self.project_url)
+    }
+
+    #[inline(always)]
+    pub fn logo_uri(&self) -> &[u8] {
+        trim_padding(&self.logo_uri)
+    }
+
+    /// The tag at `index`, empty for unused slots
+    #[inline(always)]
+    pub fn tag(&self, index: usize) -> &[u8] {
+        trim_padding(&self.tags[index])
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `PoolMetadata` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `PoolMetadata` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `PoolMetadata` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `PoolMetadata`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut PoolMetadata)
+    }
+
+    /// Overwrite every field, `fields` being the zero-padded name, project URL, logo URI and tags
+    /// as laid out in the account
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: Pubkey, fields: &[u8; PoolMetadata::FIELDS_LEN], bump: [u8; 1]) {
+        let (name, rest) = fields.split_at(Self::NAME_LEN);
+        let (project_url, rest) = rest.split_at(Self::URI_LEN);
+        let (logo_uri, tags) = rest.split_at(Self::URI_LEN);
+
+        self.config = config;
+        self.name.copy_from_slice(name);
+        self.project_url.copy_from_slice(project_url);
+        self.logo_uri.copy_from_slice(logo_uri);
+        for (tag, bytes) in self.tags.iter_mut().zip(tags.chunks_exact(Self::TAG_LEN)) {
+            tag.copy_from_slice(bytes);
+        }
+        self.bump = bump;
+    }
+}
+
+/// `bytes` without its trailing zero padding
+#[inline(always)]
+fn trim_padding(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|byte| *byte != 0).map_or(0, |index| index + 1);
+
+    &bytes[..len]
+}
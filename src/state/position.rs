@@ -0,0 +1,190 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Per-user deposit history for a pool
+///
+/// - PDA seeds: [b"position", config, owner, bump]
+/// - Cumulative fields only ever grow with deposits, so UIs can compare them against the
+///   current redeemable amounts to derive impermanent loss and fee earnings
+/// - `entry_*` fields snapshot the pool right after the latest deposit
+#[repr(C)]
+pub struct Position {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    owner: Pubkey,
+    config: Pubkey,
+    deposited_x: [u8; 8],
+    deposited_y: [u8; 8],
+    lp_minted: [u8; 8],
+    entry_reserve_x: [u8; 8],
+    entry_reserve_y: [u8; 8],
+    entry_lp_supply: [u8; 8],
+    last_deposit_timestamp: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl Position {
+    /// `sha256("account:Position")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [170, 188, 143, 228, 122, 64, 247, 208];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<Pubkey>() * 2
+        + size_of::<u64>() * 6
+        + size_of::<i64>()
+        + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `Position` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `Position` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Position`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Position)
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn deposited_x(&self) -> u64 {
+        u64::from_le_bytes(self.deposited_x)
+    }
+
+    #[inline(always)]
+    pub fn deposited_y(&self) -> u64 {
+        u64::from_le_bytes(self.deposited_y)
+    }
+
+    #[inline(always)]
+    pub fn lp_minted(&self) -> u64 {
+        u64::from_le_bytes(self.lp_minted)
+    }
+
+    #[inline(always)]
+    pub fn entry_reserve_x(&self) -> u64 {
+        u64::from_le_bytes(self.entry_reserve_x)
+    }
+
+    #[inline(always)]
+    pub fn entry_reserve_y(&self) -> u64 {
+        u64::from_le_bytes(self.entry_reserve_y)
+    }
+
+    #[inline(always)]
+    pub fn entry_lp_supply(&self) -> u64 {
+        u64::from_le_bytes(self.entry_lp_supply)
+    }
+
+    #[inline(always)]
+    pub fn last_deposit_timestamp(&self) -> i64 {
+        i64::from_le_bytes(self.last_deposit_timestamp)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `Position` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `Position` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `Position` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Position`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Position)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, owner: Pubkey, config: Pubkey, bump: [u8; 1]) {
+        self.owner = owner;
+        self.config = config;
+        self.bump = bump;
+    }
+
+    /// Accumulate a deposit and snapshot the pool state right after it
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn record_deposit(
+        &mut self,
+        x: u64,
+        y: u64,
+        lp: u64,
+        reserve_x: u64,
+        reserve_y: u64,
+        lp_supply: u64,
+        timestamp: i64,
+    ) {
+        self.deposited_x = self.deposited_x().saturating_add(x).to_le_bytes();
+        self.deposited_y = self.deposited_y().saturating_add(y).to_le_bytes();
+        self.lp_minted = self.lp_minted().saturating_add(lp).to_le_bytes();
+        self.entry_reserve_x = reserve_x.to_le_bytes();
+        self.entry_reserve_y = reserve_y.to_le_bytes();
+        self.entry_lp_supply = lp_supply.to_le_bytes();
+        self.last_deposit_timestamp = timestamp.to_le_bytes();
+    }
+}
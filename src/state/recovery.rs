@@ -0,0 +1,168 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Snapshot of a pool's recovery escrow, see `EmergencyWithdrawProtocolSide`
+///
+/// - PDA seeds: [b"recovery", config, bump], also the owner of the escrow token accounts
+/// - Every emergency withdrawal retakes the snapshot from the escrow balances and the LP supply
+/// - `ClaimRecovery` pays `amount * lp / lp_supply` of each side and removes the claim from the
+///   snapshot, so later claims keep the same rate whatever else lands in the escrow
+#[repr(C)]
+pub struct Recovery {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    config: Pubkey,
+    lp_supply: [u8; 8],
+    amount_x: [u8; 8],
+    amount_y: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl Recovery {
+    /// `sha256("account:Recovery")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [242, 166, 34, 227, 71, 217, 155, 103];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<Pubkey>()
+        + size_of::<u64>() * 3
+        + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `Recovery` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `Recovery` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Recovery`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Recovery)
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    /// LP not yet claimed since the snapshot
+    #[inline(always)]
+    pub fn lp_supply(&self) -> u64 {
+        u64::from_le_bytes(self.lp_supply)
+    }
+
+    /// X owed to the unclaimed LP
+    #[inline(always)]
+    pub fn amount_x(&self) -> u64 {
+        u64::from_le_bytes(self.amount_x)
+    }
+
+    /// Y owed to the unclaimed LP
+    #[inline(always)]
+    pub fn amount_y(&self) -> u64 {
+        u64::from_le_bytes(self.amount_y)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Share of the snapshot owed for `lp`, rounding down in favor of the other holders
+    #[inline(always)]
+    pub fn quote_claim(&self, lp: u64) -> Result<(u64, u64), ProgramError> {
+        if lp > self.lp_supply() {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        let x = (self.amount_x() as u128 * lp as u128 / self.lp_supply() as u128) as u64;
+        let y = (self.amount_y() as u128 * lp as u128 / self.lp_supply() as u128) as u64;
+
+        Ok((x, y))
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `Recovery` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `Recovery` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `Recovery` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Recovery`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Recovery)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: Pubkey, bump: [u8; 1]) {
+        self.config = config;
+        self.bump = bump;
+    }
+
+    /// Retake the snapshot from the escrow balances and the outstanding LP
+    #[inline(always)]
+    pub fn snapshot(&mut self, lp_supply: u64, amount_x: u64, amount_y: u64) {
+        self.lp_supply = lp_supply.to_le_bytes();
+        self.amount_x = amount_x.to_le_bytes();
+        self.amount_y = amount_y.to_le_bytes();
+    }
+
+    /// Remove a claim of `lp` paid `x` and `y` from the snapshot
+    #[inline(always)]
+    pub fn claim(&mut self, lp: u64, x: u64, y: u64) {
+        self.lp_supply = (self.lp_supply() - lp).to_le_bytes();
+        self.amount_x = (self.amount_x() - x).to_le_bytes();
+        self.amount_y = (self.amount_y() - y).to_le_bytes();
+    }
+}
@@ -0,0 +1,187 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::AmmError;
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// A single pool listed in a `Registry` page
+///
+/// - Layout is fixed so indexers can read entries at `Registry::ENTRIES_OFFSET + i * RegistryEntry::LEN`
+#[repr(C)]
+pub struct RegistryEntry {
+    config: Pubkey,
+    mint_x: Pubkey,
+    mint_y: Pubkey,
+}
+
+impl RegistryEntry {
+    pub const LEN: usize = size_of::<Pubkey>() * 3;
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn mint_x(&self) -> &Pubkey {
+        &self.mint_x
+    }
+
+    #[inline(always)]
+    pub fn mint_y(&self) -> &Pubkey {
+        &self.mint_y
+    }
+}
+
+/// Paged list of every pool created with a registry account attached
+///
+/// - PDA seeds: [b"registry", page (u32 LE), bump]
+/// - Header: discriminator [8] | page [4] | count [2] | bump [1], followed by `CAPACITY` entries
+/// - Only the first `count` entries are populated
+#[repr(C)]
+pub struct Registry {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    page: [u8; 4],
+    count: [u8; 2],
+    bump: [u8; 1],
+    entries: [RegistryEntry; Registry::CAPACITY],
+}
+
+impl Registry {
+    /// `sha256("account:Registry")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [47, 174, 110, 246, 184, 182, 252, 218];
+
+    /// Maximum number of pools per page (keeps the account below the 10KiB CPI allocation limit)
+    pub const CAPACITY: usize = 100;
+
+    pub const ENTRIES_OFFSET: usize = DISCRIMINATOR_LEN + size_of::<u32>() + size_of::<u16>() + size_of::<u8>();
+
+    pub const LEN: usize = Self::ENTRIES_OFFSET + RegistryEntry::LEN * Self::CAPACITY;
+
+    /* Reading Helpers */
+
+    /// Return a `Registry` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `Registry` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Registry`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Registry)
+    }
+
+    #[inline(always)]
+    pub fn page(&self) -> u32 {
+        u32::from_le_bytes(self.page)
+    }
+
+    #[inline(always)]
+    pub fn count(&self) -> u16 {
+        u16::from_le_bytes(self.count)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.count() as usize >= Self::CAPACITY
+    }
+
+    /// Return the populated entries of this page
+    #[inline(always)]
+    pub fn entries(&self) -> &[RegistryEntry] {
+        &self.entries[..self.count() as usize]
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `Registry` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `Registry` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `Registry` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Registry`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Registry)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, page: u32, bump: [u8; 1]) {
+        self.page = page.to_le_bytes();
+        self.count = 0u16.to_le_bytes();
+        self.bump = bump;
+    }
+
+    /// Append a pool to this page
+    #[inline(always)]
+    pub fn push(&mut self, config: Pubkey, mint_x: Pubkey, mint_y: Pubkey) -> Result<(), ProgramError> {
+        if self.is_full() {
+            return Err(AmmError::RegistryPageFull.into());
+        }
+
+        let count = self.count();
+        self.entries[count as usize] = RegistryEntry {
+            config,
+            mint_x,
+            mint_y,
+        };
+        self.count = (count + 1).to_le_bytes();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Governance tokens staked by `owner` in a `FeeDistributor`, and the rewards they earned
+///
+/// - PDA seeds: [b"staker", fee_distributor, owner, bump]
+/// - `reward_debt` is the distributor's reward per token when the rewards were last settled, so
+///   only checkpoints after it pay out
+/// - Stake can only be withdrawn once a full epoch went by since it was last added, so joining
+///   right before a checkpoint keeps the tokens locked through the next epoch
+#[repr(C)]
+pub struct Staker {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    fee_distributor: Pubkey,
+    owner: Pubkey,
+    amount: [u8; 8],
+    reward_debt: [u8; 16],
+    owed: [u8; 8],
+    staked_epoch: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl Staker {
+    /// `sha256("account:Staker")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [171, 229, 193, 85, 67, 177, 151, 4];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + size_of::<Pubkey>() * 2
+        + size_of::<u64>()
+        + size_of::<u128>()
+        + size_of::<u64>() * 2
+        + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `Staker` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `Staker` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Staker`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Staker)
+    }
+
+    #[inline(always)]
+    pub fn fee_distributor(&self) -> &Pubkey {
+        &self.fee_distributor
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    #[inline(always)]
+    pub fn reward_debt(&self) -> u128 {
+        u128::from_le_bytes(self.reward_debt)
+    }
+
+    /// Rewards settled but not yet claimed
+    #[inline(always)]
+    pub fn owed(&self) -> u64 {
+        u64::from_le_bytes(self.owed)
+    }
+
+    /// Epoch the stake was last added in
+    #[inline(always)]
+    pub fn staked_epoch(&self) -> u64 {
+        u64::from_le_bytes(self.staked_epoch)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Whether the stake may be withdrawn in `epoch`
+    #[inline(always)]
+    pub fn is_unlocked(&self, epoch: u64) -> bool {
+        epoch > self.staked_epoch().saturating_add(1)
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `Staker` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `Staker` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `Staker` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Staker`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Staker)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, fee_distributor: Pubkey, owner: Pubkey, reward_debt: u128, bump: [u8; 1]) {
+        self.fee_distributor = fee_distributor;
+        self.owner = owner;
+        self.reward_debt = reward_debt.to_le_bytes();
+        self.bump = bump;
+    }
+
+    /// Add the rewards earned since the last settlement to `owed`, at the distributor's current
+    /// reward per token
+    #[inline(always)]
+    pub fn settle(&mut self, reward_per_token: u128) {
+        let earned = (self.amount() as u128 * reward_per_token.wrapping_sub(self.reward_debt())) >> 64;
+
+        self.owed = self.owed().saturating_add(earned as u64).to_le_bytes();
+        self.reward_debt = reward_per_token.to_le_bytes();
+    }
+
+    /// Add settled stake in `epoch`
+    #[inline(always)]
+    pub fn stake(&mut self, amount: u64, epoch: u64) {
+        self.amount = (self.amount() + amount).to_le_bytes();
+        self.staked_epoch = epoch.to_le_bytes();
+    }
+
+    /// Remove settled stake
+    #[inline(always)]
+    pub fn unstake(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.amount = self
+            .amount()
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Take the owed rewards out for a claim
+    #[inline(always)]
+    pub fn take_owed(&mut self) -> u64 {
+        let owed = self.owed();
+        self.owed = [0u8; 8];
+        owed
+    }
+}
@@ -0,0 +1,122 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// A mint the `GlobalConfig` admin vouches for, allowed in new pools of permissioned deployments
+///
+/// - PDA seeds: [b"token_badge", mint, bump]
+/// - Only required with `GlobalConfig::FLAG_REQUIRE_TOKEN_BADGES`, e.g. to vet Token-2022 mints
+///   with exotic extensions before they're pooled
+/// - Revoking keeps the account and clears `is_badged`, existing pools are unaffected
+#[repr(C)]
+pub struct TokenBadge {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    mint: Pubkey,
+    is_badged: u8,
+    bump: [u8; 1],
+}
+
+impl TokenBadge {
+    /// `sha256("account:TokenBadge")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [116, 219, 204, 229, 249, 116, 255, 150];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN + size_of::<Pubkey>() + size_of::<u8>() * 2;
+
+    /* Reading Helpers */
+
+    /// Return a `TokenBadge` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `TokenBadge` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `TokenBadge`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const TokenBadge)
+    }
+
+    #[inline(always)]
+    pub fn mint(&self) -> &Pubkey {
+        &self.mint
+    }
+
+    #[inline(always)]
+    pub fn is_badged(&self) -> bool {
+        self.is_badged == 1
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `TokenBadge` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `TokenBadge` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `TokenBadge` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `TokenBadge`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut TokenBadge)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, mint: Pubkey, is_badged: bool, bump: [u8; 1]) {
+        self.mint = mint;
+        self.is_badged = is_badged as u8;
+        self.bump = bump;
+    }
+}
@@ -0,0 +1,189 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Market hours shared by the pools that reference it through their `TradingHours` extension,
+/// e.g. the exchange calendar of the tokenized equities an issuer lists
+///
+/// - PDA seeds: [b"trading_calendar", authority, seed, bump]
+/// - Created and updated by its authority through `SetTradingCalendar`
+/// - Times are local to `utc_offset`, weekdays start on Monday
+/// - A session opens at `open` and closes at `close`, in seconds since local midnight; an empty
+///   session keeps the market closed that weekday
+/// - Holidays are local day numbers since the Unix epoch, unused slots are zero
+#[repr(C)]
+pub struct TradingCalendar {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    authority: Pubkey,
+    seed: [u8; 8],
+    utc_offset: [u8; 4],
+    sessions: [[u8; 8]; 7],
+    holidays: [[u8; 4]; TradingCalendar::MAX_HOLIDAYS],
+    bump: [u8; 1],
+}
+
+impl TradingCalendar {
+    /// `sha256("account:TradingCalendar")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [227, 138, 88, 183, 169, 149, 230, 19];
+
+    pub const MAX_HOLIDAYS: usize = 32;
+
+    pub const SECONDS_PER_DAY: i64 = 86_400;
+
+    /// Length of the UTC offset, weekly sessions and holidays, as written by `set_inner`
+    pub const SCHEDULE_LEN: usize =
+        size_of::<i32>() + size_of::<u32>() * 2 * 7 + size_of::<u32>() * Self::MAX_HOLIDAYS;
+
+    pub const LEN: usize =
+        DISCRIMINATOR_LEN + size_of::<Pubkey>() + size_of::<u64>() + Self::SCHEDULE_LEN + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `TradingCalendar` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `TradingCalendar` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `TradingCalendar`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const TradingCalendar)
+    }
+
+    #[inline(always)]
+    pub fn authority(&self) -> &Pubkey {
+        &self.authority
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn utc_offset(&self) -> i32 {
+        i32::from_le_bytes(self.utc_offset)
+    }
+
+    /// The `(open, close)` session of `weekday`, Monday being 0
+    #[inline(always)]
+    pub fn session(&self, weekday: usize) -> (u32, u32) {
+        let session = self.sessions[weekday];
+
+        (
+            u32::from_le_bytes([session[0], session[1], session[2], session[3]]),
+            u32::from_le_bytes([session[4], session[5], session[6], session[7]]),
+        )
+    }
+
+    #[inline(always)]
+    pub fn is_holiday(&self, day: u32) -> bool {
+        day != 0 && self.holidays.contains(&day.to_le_bytes())
+    }
+
+    /// Whether the market is open at the Unix timestamp `now`
+    #[inline(always)]
+    pub fn is_open(&self, now: i64) -> bool {
+        let local = now.saturating_add(self.utc_offset() as i64);
+        let day = local.div_euclid(Self::SECONDS_PER_DAY);
+        let time = local.rem_euclid(Self::SECONDS_PER_DAY) as u32;
+
+        // The epoch fell on a Thursday
+        let (open, close) = self.session((day + 3).rem_euclid(7) as usize);
+
+        (open..close).contains(&time) && !u32::try_from(day).is_ok_and(|day| self.is_holiday(day))
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `TradingCalendar` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `TradingCalendar` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `TradingCalendar` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `TradingCalendar`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut TradingCalendar)
+    }
+
+    /// Overwrite every field, `schedule` being the UTC offset, sessions and holidays as laid out
+    /// in the account
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        authority: Pubkey,
+        seed: [u8; 8],
+        schedule: &[u8; TradingCalendar::SCHEDULE_LEN],
+        bump: [u8; 1],
+    ) {
+        let (utc_offset, rest) = schedule.split_at(size_of::<i32>());
+        let (sessions, holidays) = rest.split_at(size_of::<u32>() * 2 * 7);
+
+        self.authority = authority;
+        self.seed = seed;
+        self.utc_offset.copy_from_slice(utc_offset);
+        for (session, bytes) in self.sessions.iter_mut().zip(sessions.chunks_exact(8)) {
+            session.copy_from_slice(bytes);
+        }
+        for (holiday, bytes) in self.holidays.iter_mut().zip(holidays.chunks_exact(4)) {
+            holiday.copy_from_slice(bytes);
+        }
+        self.bump = bump;
+    }
+}
@@ -0,0 +1,181 @@
+use core::mem::size_of;
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError, pubkey::Pubkey};
+
+use super::{check_discriminator, write_discriminator, DISCRIMINATOR_LEN};
+
+/// Lifetime swap statistics of a user on a pool, for loyalty programs and the `VolumeDiscount`
+/// fee tiers
+///
+/// - PDA seeds: [b"user_stats", config, user, bump], created by `InitializeUserStats`
+/// - Updated by every `Swap` of `user` that passes it, as the pool's `Stats`: volumes count both
+///   legs of each swap in their own token, fees are in the input token
+/// - Counters saturate instead of failing the swap
+#[repr(C)]
+pub struct UserStats {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    config: Pubkey,
+    user: Pubkey,
+    volume_x: [u8; 8],
+    volume_y: [u8; 8],
+    fees_x: [u8; 8],
+    fees_y: [u8; 8],
+    swap_count: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl UserStats {
+    /// `sha256("account:UserStats")[..8]`, see `DISCRIMINATOR_LEN`
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [176, 223, 136, 27, 122, 79, 32, 227];
+
+    pub const LEN: usize = DISCRIMINATOR_LEN + size_of::<Pubkey>() * 2 + size_of::<u64>() * 5 + size_of::<u8>();
+
+    /* Reading Helpers */
+
+    /// Return a `UserStats` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(Ref::map(data, |data| unsafe { Self::from_bytes_unchecked(data) }))
+    }
+
+    /// Return a `UserStats` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `UserStats`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const UserStats)
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn user(&self) -> &Pubkey {
+        &self.user
+    }
+
+    #[inline(always)]
+    pub fn volume_x(&self) -> u64 {
+        u64::from_le_bytes(self.volume_x)
+    }
+
+    #[inline(always)]
+    pub fn volume_y(&self) -> u64 {
+        u64::from_le_bytes(self.volume_y)
+    }
+
+    #[inline(always)]
+    pub fn fees_x(&self) -> u64 {
+        u64::from_le_bytes(self.fees_x)
+    }
+
+    #[inline(always)]
+    pub fn fees_y(&self) -> u64 {
+        u64::from_le_bytes(self.fees_y)
+    }
+
+    #[inline(always)]
+    pub fn swap_count(&self) -> u64 {
+        u64::from_le_bytes(self.swap_count)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Check that these are the statistics of `user` on the pool at `config`
+    #[inline(always)]
+    pub fn check_owner(&self, config: &Pubkey, user: &Pubkey) -> Result<(), ProgramError> {
+        if self.config.ne(config) || self.user.ne(user) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    /* Writing Helpers */
+
+    /// Return a mutable `UserStats` from the given account info with safe borrowing.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`.
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        check_discriminator(&data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Write the discriminator of a freshly created `UserStats` and return it mutably.
+    ///
+    /// This method performs owner and length validation on `AccountInfo`, and fails if the
+    /// account was already initialized.
+    #[inline(always)]
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data = account_info.try_borrow_mut_data()?;
+        write_discriminator(&mut data, &Self::DISCRIMINATOR)?;
+        Ok(RefMut::map(data, |data| unsafe { Self::from_bytes_unchecked_mut(data) }))
+    }
+
+    /// Return a mutable `UserStats` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `UserStats`.
+    /// This method does not perform length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut UserStats)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: Pubkey, user: Pubkey, bump: [u8; 1]) {
+        self.config = config;
+        self.user = user;
+        self.volume_x = [0; 8];
+        self.volume_y = [0; 8];
+        self.fees_x = [0; 8];
+        self.fees_y = [0; 8];
+        self.swap_count = [0; 8];
+        self.bump = bump;
+    }
+
+    /// Record a swap of `amount_in` (fee included) for `amount_out`
+    #[inline(always)]
+    pub fn record_swap(&mut self, is_x: bool, amount_in: u64, amount_out: u64, fee: u64) {
+        let (volume_in, volume_out, fees_in) = match is_x {
+            true => (&mut self.volume_x, &mut self.volume_y, &mut self.fees_x),
+            false => (&mut self.volume_y, &mut self.volume_x, &mut self.fees_y),
+        };
+
+        *volume_in = u64::from_le_bytes(*volume_in).saturating_add(amount_in).to_le_bytes();
+        *volume_out = u64::from_le_bytes(*volume_out).saturating_add(amount_out).to_le_bytes();
+        *fees_in = u64::from_le_bytes(*fees_in).saturating_add(fee).to_le_bytes();
+        self.swap_count = self.swap_count().saturating_add(1).to_le_bytes();
+    }
+}
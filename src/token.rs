@@ -0,0 +1,459 @@
+use crate::{derive_lp_mint, AmmError, TOKEN_2022_PROGRAM_ID};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction, Signer},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+/// Check that `token_program` is SPL Token or Token-2022.
+#[inline(always)]
+pub fn check_token_program(token_program: &Pubkey) -> Result<(), ProgramError> {
+    if token_program.ne(&pinocchio_token::ID) && token_program.ne(&TOKEN_2022_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Check that `account` is a token account of `mint`, owned by SPL Token or Token-2022.
+#[inline(always)]
+fn check_token_account(account: &AccountInfo, mint: &Pubkey) -> Result<(), ProgramError> {
+    check_token_program(account.owner()).map_err(|_| ProgramError::InvalidAccountOwner)?;
+
+    if account.data_len() < TokenAccount::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if token_account(account).mint().ne(mint) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+/// Read a token account checked by `check_token_account`, ignoring any Token-2022 extensions.
+#[inline(always)]
+fn token_account(account: &AccountInfo) -> &TokenAccount {
+    // SAFETY: token accounts are only written by the token programs through CPI, this program
+    // never borrows them mutably, and the owner and length were checked
+    unsafe { TokenAccount::from_bytes(&account.borrow_data_unchecked()[..TokenAccount::LEN]) }
+}
+
+/// A token account of `mint` held by a program PDA, like the pool and launch vaults.
+///
+/// Every read goes to the account data, so balances stay current across CPIs.
+pub struct CheckedVault<'a> {
+    account: &'a AccountInfo,
+}
+
+impl<'a> CheckedVault<'a> {
+    /// Check that `account` is a `token_program` account of `mint` owned by `authority`.
+    #[inline(always)]
+    pub fn new(
+        account: &'a AccountInfo,
+        mint: &Pubkey,
+        authority: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        if account.owner().ne(token_program) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        check_token_account(account, mint)?;
+
+        if token_account(account).owner().ne(authority) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self { account })
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        token_account(self.account).amount()
+    }
+}
+
+/// The LP mint of a pool, checked at its `derive_lp_mint` address.
+///
+/// Every read goes to the account data, so the supply stays current across mint and burn CPIs.
+/// Soulbound LP mints are owned by Token-2022, see `Config::FLAG_SOULBOUND_LP`.
+pub struct CheckedLpMint<'a> {
+    account: &'a AccountInfo,
+}
+
+impl<'a> CheckedLpMint<'a> {
+    /// Check that `account` is the LP mint of the pool at `config`, owned by SPL Token or Token-2022.
+    #[inline(always)]
+    pub fn new(account: &'a AccountInfo, config: &Pubkey) -> Result<Self, ProgramError> {
+        if derive_lp_mint(config).0.ne(account.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        check_token_program(account.owner()).map_err(|_| ProgramError::InvalidAccountOwner)?;
+
+        if account.data_len() < Mint::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self { account })
+    }
+
+    #[inline(always)]
+    pub fn supply(&self) -> u64 {
+        // SAFETY: mints are only written by the token programs through CPI, this program never
+        // borrows them mutably, and the owner and length were checked
+        unsafe { Mint::from_bytes(&self.account.borrow_data_unchecked()[..Mint::LEN]) }.supply()
+    }
+}
+
+/// A token account of `mint` passed by a user, owned by anyone unless checked with `with_owner`.
+///
+/// Every read goes to the account data, so balances stay current across CPIs.
+pub struct CheckedUserAta<'a> {
+    account: &'a AccountInfo,
+}
+
+impl<'a> CheckedUserAta<'a> {
+    /// Check that `account` is a token account of `mint`.
+    #[inline(always)]
+    pub fn new(account: &'a AccountInfo, mint: &Pubkey) -> Result<Self, ProgramError> {
+        check_token_account(account, mint)?;
+
+        Ok(Self { account })
+    }
+
+    /// Check that `account` is a token account of `mint` owned by `owner`.
+    #[inline(always)]
+    pub fn with_owner(account: &'a AccountInfo, mint: &Pubkey, owner: &Pubkey) -> Result<Self, ProgramError> {
+        let ata = Self::new(account, mint)?;
+
+        if ata.owner().ne(owner) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(ata)
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        token_account(self.account).owner()
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        token_account(self.account).amount()
+    }
+
+    #[inline(always)]
+    pub fn is_native(&self) -> bool {
+        token_account(self.account).is_native()
+    }
+
+    /// Check that `authority` can move `amount` out of the account, as its owner or as a delegate
+    /// approved for at least `amount`.
+    ///
+    /// The token program enforces the same, this only fails earlier with a clearer error.
+    #[inline(always)]
+    pub fn check_authority(&self, authority: &Pubkey, amount: u64) -> Result<(), ProgramError> {
+        let account = token_account(self.account);
+
+        if account.owner().eq(authority) {
+            return Ok(());
+        }
+
+        if account.delegate() != Some(authority) || account.delegated_amount() < amount {
+            return Err(AmmError::InsufficientDelegation.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// `TransferChecked` routed to the given token program.
+///
+/// `pinocchio_token` only targets SPL Token, while pool assets may live in either token program.
+/// The checked variant is used because Token-2022 requires it for mints with transfer fees.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The source account.
+///   1. `[]` The token mint.
+///   2. `[WRITE]` The destination account.
+///   3. `[SIGNER]` The source account's owner/delegate.
+pub struct TransferChecked<'a> {
+    pub from: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub to: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_program: &'a Pubkey,
+}
+
+impl TransferChecked<'_> {
+    const DISCRIMINATOR: u8 = 12;
+
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.from.key()),
+            AccountMeta::readonly(self.mint.key()),
+            AccountMeta::writable(self.to.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // discriminator [1] | amount [8] | decimals [1]
+        let mut instruction_data = [0u8; 10];
+        instruction_data[0] = Self::DISCRIMINATOR;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        instruction_data[9] = self.decimals;
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.from, self.mint, self.to, self.authority],
+            signers,
+        )
+    }
+}
+
+/// `BurnChecked` routed to the given token program, see `TransferChecked`.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The account to burn from.
+///   1. `[WRITE]` The token mint.
+///   2. `[SIGNER]` The account's owner/delegate.
+pub struct BurnChecked<'a> {
+    pub account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_program: &'a Pubkey,
+}
+
+impl BurnChecked<'_> {
+    const DISCRIMINATOR: u8 = 15;
+
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // discriminator [1] | amount [8] | decimals [1]
+        let mut instruction_data = [0u8; 10];
+        instruction_data[0] = Self::DISCRIMINATOR;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        instruction_data[9] = self.decimals;
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(&instruction, &[self.account, self.mint, self.authority], signers)
+    }
+}
+
+/// `MintTo` routed to the given token program, see `TransferChecked`.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The mint.
+///   1. `[WRITE]` The account to mint tokens to.
+///   2. `[SIGNER]` The mint's minting authority.
+pub struct MintTo<'a> {
+    pub mint: &'a AccountInfo,
+    pub account: &'a AccountInfo,
+    pub mint_authority: &'a AccountInfo,
+    pub amount: u64,
+    pub token_program: &'a Pubkey,
+}
+
+impl MintTo<'_> {
+    const DISCRIMINATOR: u8 = 7;
+
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly_signer(self.mint_authority.key()),
+        ];
+
+        // discriminator [1] | amount [8]
+        let mut instruction_data = [0u8; 9];
+        instruction_data[0] = Self::DISCRIMINATOR;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(&instruction, &[self.mint, self.account, self.mint_authority], signers)
+    }
+}
+
+/// `CloseAccount` routed to the given token program, see `TransferChecked`.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The account to close.
+///   1. `[WRITE]` The destination account.
+///   2. `[SIGNER]` The account's owner.
+pub struct CloseAccount<'a> {
+    pub account: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub token_program: &'a Pubkey,
+}
+
+impl CloseAccount<'_> {
+    const DISCRIMINATOR: u8 = 9;
+
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.destination.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &[Self::DISCRIMINATOR],
+        };
+
+        invoke_signed(&instruction, &[self.account, self.destination, self.authority], signers)
+    }
+}
+
+/// `InitializeMint2` routed to the given token program, see `TransferChecked`.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The mint to initialize.
+pub struct InitializeMint2<'a> {
+    pub mint: &'a AccountInfo,
+    pub decimals: u8,
+    pub mint_authority: &'a Pubkey,
+    pub freeze_authority: Option<&'a Pubkey>,
+    pub token_program: &'a Pubkey,
+}
+
+impl InitializeMint2<'_> {
+    const DISCRIMINATOR: u8 = 20;
+
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [AccountMeta::writable(self.mint.key())];
+
+        // discriminator [1] | decimals [1] | mint_authority [32] | option [1] | freeze_authority [32]
+        let mut instruction_data = [0u8; 67];
+        instruction_data[0] = Self::DISCRIMINATOR;
+        instruction_data[1] = self.decimals;
+        instruction_data[2..34].copy_from_slice(self.mint_authority);
+
+        if let Some(freeze_authority) = self.freeze_authority {
+            instruction_data[34] = 1;
+            instruction_data[35..67].copy_from_slice(freeze_authority);
+        }
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(&instruction, &[self.mint], &[])
+    }
+}
+
+/// Token-2022 `InitializeNonTransferableMint`, must run before `InitializeMint2`.
+///
+/// Tokens of the mint can only be minted and burned, never transferred.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The mint to initialize.
+pub struct InitializeNonTransferableMint<'a> {
+    pub mint: &'a AccountInfo,
+}
+
+impl InitializeNonTransferableMint<'_> {
+    const DISCRIMINATOR: u8 = 32;
+
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [AccountMeta::writable(self.mint.key())];
+
+        let instruction = Instruction {
+            program_id: &TOKEN_2022_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &[Self::DISCRIMINATOR],
+        };
+
+        invoke_signed(&instruction, &[self.mint], &[])
+    }
+}
+
+/// `InitializeAccount3` routed to the given token program, see `TransferChecked`.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The account to initialize.
+///   1. `[]` The mint this account will be associated with.
+pub struct InitializeAccount3<'a> {
+    pub account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub owner: &'a Pubkey,
+    pub token_program: &'a Pubkey,
+}
+
+impl InitializeAccount3<'_> {
+    const DISCRIMINATOR: u8 = 18;
+
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly(self.mint.key()),
+        ];
+
+        // discriminator [1] | owner [32]
+        let mut instruction_data = [0u8; 33];
+        instruction_data[0] = Self::DISCRIMINATOR;
+        instruction_data[1..33].copy_from_slice(self.owner);
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(&instruction, &[self.account, self.mint], &[])
+    }
+}
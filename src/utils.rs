@@ -0,0 +1,530 @@
+use core::mem::size_of;
+use crate::state::{get_extension_mut, Sequence};
+use crate::{
+    AmmError, ACCOUNT_EXTENSIONS_REQUIRED, ED25519_PROGRAM_ID, EXPIRATION_IN_SLOTS, MAX_FEE_BPS, TOKEN_2022_PROGRAM_ID,
+};
+use pinocchio::{
+    account_info::{AccountInfo, Ref}, log::sol_log_data, program::set_return_data, program_error::ProgramError, pubkey::Pubkey, sysvars::{clock::Clock, instructions::Instructions, Sysvar}, ProgramResult
+};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+/// Trace a step of an instruction with `sol_log_64` when built with the `debug-logs` feature.
+///
+/// Every diagnostic log goes through this macro, so the default build logs nothing but events.
+/// Each trace logs `discriminator << 8 | step` followed by up to four values of the step. Without
+/// the feature the arguments aren't even evaluated, so release builds pay no compute for them.
+#[macro_export]
+macro_rules! debug_log {
+    ($discriminator:expr, $step:expr $(, $value:expr)+ $(,)?) => {
+        #[cfg(feature = "debug-logs")]
+        {
+            let mut values = [0u64; 4];
+            for (slot, value) in values.iter_mut().zip([$($value as u64),+]) {
+                *slot = value;
+            }
+
+            pinocchio::log::sol_log_64(
+                ((*$discriminator as u64) << 8) | $step,
+                values[0],
+                values[1],
+                values[2],
+                values[3],
+            );
+        }
+    };
+}
+
+/// Order a mint pair the way `Initialize` expects it, returning `(mint_x, mint_y)`.
+///
+/// Pools only accept `mint_x < mint_y` (byte-wise), so callers should run their pair through
+/// this before deriving the config address or building instruction data.
+#[inline(always)]
+pub fn sort_mints<'a>(mint_a: &'a Pubkey, mint_b: &'a Pubkey) -> (&'a Pubkey, &'a Pubkey) {
+    if mint_a.le(mint_b) {
+        (mint_a, mint_b)
+    } else {
+        (mint_b, mint_a)
+    }
+}
+
+/// Load an initialized mint owned by SPL Token or Token-2022.
+#[inline(always)]
+pub fn load_mint(account: &AccountInfo) -> Result<Ref<Mint>, ProgramError> {
+    if account.owner().ne(&pinocchio_token::ID) && account.owner().ne(&TOKEN_2022_PROGRAM_ID) {
+        return Err(AmmError::InvalidMint.into());
+    }
+
+    // Token-2022 mints carry extensions after the base layout
+    if account.data_len() < Mint::LEN {
+        return Err(AmmError::InvalidMint.into());
+    }
+
+    let mint = Ref::map(account.try_borrow_data()?, |data| unsafe {
+        Mint::from_bytes(&data[..Mint::LEN])
+    });
+
+    if !mint.is_initialized() {
+        return Err(AmmError::InvalidMint.into());
+    }
+
+    Ok(mint)
+}
+
+/// Check whether a Token-2022 mint carries the given extension.
+///
+/// Extensions are stored as TLV entries (`[type u16][length u16][value]`) after the base
+/// mint, padded to the token account size, and a one byte account type.
+#[inline(always)]
+pub fn mint_has_extension(account: &AccountInfo, extension_type: u16) -> Result<bool, ProgramError> {
+    if account.owner().ne(&TOKEN_2022_PROGRAM_ID) {
+        return Ok(false);
+    }
+
+    let data = account.try_borrow_data()?;
+    let mut offset = TokenAccount::LEN + size_of::<u8>();
+
+    while offset + 4 <= data.len() {
+        let entry_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let entry_len = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+
+        if entry_type == extension_type {
+            return Ok(true);
+        }
+
+        offset += 4 + entry_len;
+    }
+
+    Ok(false)
+}
+
+/// Size of a token account for `mint`, with room for the account extensions its Token-2022 mint
+/// extensions require, see `ACCOUNT_EXTENSIONS_REQUIRED`.
+#[inline(always)]
+pub fn token_account_len(mint: &AccountInfo) -> Result<usize, ProgramError> {
+    let mut extensions_len = 0;
+
+    for (extension_type, value_len) in ACCOUNT_EXTENSIONS_REQUIRED {
+        if mint_has_extension(mint, extension_type)? {
+            extensions_len += 4 + value_len;
+        }
+    }
+
+    // Accounts with extensions carry the account type byte before their TLV area
+    match extensions_len {
+        0 => Ok(TokenAccount::LEN),
+        _ => Ok(TokenAccount::LEN + size_of::<u8>() + extensions_len),
+    }
+}
+
+/// Publish executed amounts as consecutive little-endian `u64`s in the return data (up to 8).
+#[inline(always)]
+pub fn set_return_amounts(amounts: &[u64]) {
+    let mut data = [0u8; 64];
+
+    for (chunk, amount) in data.chunks_exact_mut(8).zip(amounts) {
+        chunk.copy_from_slice(&amount.to_le_bytes());
+    }
+
+    set_return_data(&data[..amounts.len() * 8]);
+}
+
+/// Check an order deadline, read as a slot when `flags` has `EXPIRATION_IN_SLOTS` and as a unix
+/// timestamp otherwise.
+#[inline(always)]
+pub fn check_expiration(expiration: i64, flags: u8) -> ProgramResult {
+    let clock = Clock::get()?;
+
+    let expired = match flags & EXPIRATION_IN_SLOTS != 0 {
+        true => expiration < 0 || (expiration as u64) < clock.slot,
+        false => expiration < clock.unix_timestamp,
+    };
+
+    if expired {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(())
+}
+
+/// Turn a `SLIPPAGE_IN_BPS` tolerance into a bound `bps` above `fair` for a maximum, rounding up, or
+/// `bps` below it for a minimum, rounding down.
+#[inline(always)]
+pub fn slippage_bound(fair: u128, bps: u64, is_max: bool) -> u64 {
+    let bound = match is_max {
+        true => (fair * (MAX_FEE_BPS as u128 + bps as u128)).div_ceil(MAX_FEE_BPS as u128),
+        false => fair * (MAX_FEE_BPS as u128).saturating_sub(bps as u128) / MAX_FEE_BPS as u128,
+    };
+
+    u64::try_from(bound).unwrap_or(u64::MAX)
+}
+
+/// Integer square root, rounded down.
+#[inline(always)]
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    // Newton's method from an overestimate converges down to the floor
+    let mut x = value / 2 + 1;
+    let mut y = (x + value / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+
+    x
+}
+
+/// Bump the `Sequence` of the pool at `config`, if it tracks one, and log it as a
+/// `sequence` event: "sequence" | config [32] | sequence [8] | discriminator [1].
+///
+/// Returns the new sequence number, zero for pools without the extension.
+#[inline(always)]
+pub fn bump_sequence(config: &AccountInfo, discriminator: u8) -> Result<u64, ProgramError> {
+    let mut config_data = config.try_borrow_mut_data()?;
+
+    let Some(sequence) = get_extension_mut::<Sequence>(&mut config_data)? else {
+        return Ok(0);
+    };
+
+    check_writable(config)?;
+
+    let sequence = sequence.bump();
+    sol_log_data(&[b"sequence", config.key(), &sequence.to_le_bytes(), &[discriminator]]);
+
+    Ok(sequence)
+}
+
+/// Check that `account` signed the transaction.
+#[inline(always)]
+pub fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer() {
+        return Err(AmmError::AccountNotSigner.into());
+    }
+    Ok(())
+}
+
+/// Check that `account` was passed as writable.
+#[inline(always)]
+pub fn check_writable(account: &AccountInfo) -> ProgramResult {
+    if !account.is_writable() {
+        return Err(AmmError::AccountNotWritable.into());
+    }
+    Ok(())
+}
+
+/// Check that the instruction right before the current one makes the ed25519 program verify a
+/// signature of `message` by `signer`.
+///
+/// Only single-signature instructions that carry the key, signature and message in their own
+/// data are accepted: num_signatures [1] | padding [1] | offsets [14] | ..
+pub fn check_ed25519_signature(instructions_sysvar: &AccountInfo, signer: &Pubkey, message: &[u8]) -> ProgramResult {
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+    const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let instruction = instructions
+        .get_instruction_relative(-1)
+        .map_err(|_| AmmError::InvalidQuoteSignature)?;
+
+    if instruction.get_program_id().ne(&ED25519_PROGRAM_ID) {
+        return Err(AmmError::InvalidQuoteSignature.into());
+    }
+
+    let data = instruction.get_instruction_data();
+    if data.len() < OFFSETS_START + OFFSETS_LEN || data[0] != 1 {
+        return Err(AmmError::InvalidQuoteSignature.into());
+    }
+
+    let offset = |index: usize| {
+        let start = OFFSETS_START + index * size_of::<u16>();
+        u16::from_le_bytes([data[start], data[start + 1]])
+    };
+
+    // signature_offset, signature_ix, public_key_offset, public_key_ix, message_offset, message_size, message_ix
+    if offset(1) != CURRENT_INSTRUCTION || offset(3) != CURRENT_INSTRUCTION || offset(6) != CURRENT_INSTRUCTION {
+        return Err(AmmError::InvalidQuoteSignature.into());
+    }
+
+    let public_key_offset = offset(2) as usize;
+    let message_offset = offset(4) as usize;
+    let message_size = offset(5) as usize;
+
+    if data.get(public_key_offset..public_key_offset + size_of::<Pubkey>()).ne(&Some(signer.as_slice()))
+        || data.get(message_offset..message_offset + message_size).ne(&Some(message))
+    {
+        return Err(AmmError::InvalidQuoteSignature.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::*;
+    use crate::state::GlobalConfig;
+
+    /// Account as the runtime serializes it, the header `AccountInfo` points to followed by room for
+    /// `data_len` bytes of data
+    #[repr(C)]
+    struct RawAccount {
+        borrow_state: u8,
+        is_signer: u8,
+        is_writable: u8,
+        executable: u8,
+        original_data_len: u32,
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data_len: u64,
+        data: [u8; GlobalConfig::LEN],
+    }
+
+    type Parse = fn(&[AccountInfo]) -> ProgramResult;
+    type Run = fn(&[u8], &[AccountInfo]) -> ProgramResult;
+
+    /// Empty accounts with distinct keys, all of them signers and writable
+    fn raw_accounts(count: usize) -> Vec<RawAccount> {
+        (0..count)
+            .map(|index| RawAccount {
+                borrow_state: 0,
+                is_signer: 1,
+                is_writable: 1,
+                executable: 0,
+                original_data_len: 0,
+                key: [index as u8 + 1; 32],
+                owner: [0; 32],
+                lamports: 0,
+                data_len: 0,
+                data: [0; GlobalConfig::LEN],
+            })
+            .collect()
+    }
+
+    fn account_infos(raw: &mut [RawAccount]) -> Vec<AccountInfo> {
+        raw.iter_mut()
+            .map(|account| unsafe { core::mem::transmute::<*mut RawAccount, AccountInfo>(account) })
+            .collect()
+    }
+
+    /// Parse `count` accounts once per expected signer and writable account, with only that flag cleared
+    fn check_permissions(parse: Parse, count: usize, signers: &[usize], writable: &[usize]) {
+        for &index in signers {
+            let mut raw = raw_accounts(count);
+            raw[index].is_signer = 0;
+            assert_eq!(parse(&account_infos(&mut raw)), Err(AmmError::AccountNotSigner.into()), "signer {index}");
+        }
+
+        for &index in writable {
+            let mut raw = raw_accounts(count);
+            raw[index].is_writable = 0;
+            assert_eq!(parse(&account_infos(&mut raw)), Err(AmmError::AccountNotWritable.into()), "writable {index}");
+        }
+    }
+
+    /// Run an instruction on `count` signer and writable accounts, with an upgrade frozen global
+    /// config at `global_config`
+    fn check_frozen(run: Run, data: &[u8], count: usize, global_config: usize) {
+        let mut raw = raw_accounts(count);
+        let account = &mut raw[global_config];
+        account.owner = crate::ID;
+        account.data_len = GlobalConfig::LEN as u64;
+        account.data[..GlobalConfig::DISCRIMINATOR.len()].copy_from_slice(&GlobalConfig::DISCRIMINATOR);
+        let frozen = unsafe { GlobalConfig::from_bytes_unchecked_mut(&mut account.data) };
+        frozen.set_flags(GlobalConfig::FLAG_UPGRADE_FREEZE);
+
+        assert_eq!(run(data, &account_infos(&mut raw)), Err(AmmError::UpgradeFrozen.into()));
+    }
+
+    #[test]
+    fn pool_instructions_check_signers_and_writable_accounts() {
+        // Optional registry at 9, the token badges aren't written
+        check_permissions(|a| InitializeAccounts::try_from(a).map(drop), 12, &[0], &[0, 1, 2, 8, 9]);
+        check_permissions(|a| InitializeVaultsAccounts::try_from(a).map(drop), 10, &[0], &[0, 1, 2, 3]);
+        check_permissions(|a| InitializeRegistryAccounts::try_from(a).map(drop), 4, &[0], &[0, 1]);
+        check_permissions(|a| InitializeUserStatsAccounts::try_from(a).map(drop), 6, &[0], &[0, 3]);
+        check_permissions(|a| UpdateConfigAccounts::try_from(a).map(drop), 3, &[0], &[1]);
+        check_permissions(|a| ExtendConfigAccounts::try_from(a).map(drop), 4, &[0], &[0, 1]);
+        check_permissions(|a| MigrateConfigAccounts::try_from(a).map(drop), 4, &[0], &[0, 1]);
+        check_permissions(|a| SetPoolMetadataAccounts::try_from(a).map(drop), 5, &[0], &[0, 2]);
+        check_permissions(|a| SetTradingCalendarAccounts::try_from(a).map(drop), 4, &[0], &[0, 1]);
+        check_permissions(|a| RebalanceAccounts::try_from(a).map(drop), 10, &[0], &[1, 2, 3, 4]);
+        check_permissions(|a| StrategyCpiAccounts::try_from(a).map(drop), 11, &[0], &[1, 2, 3]);
+        check_permissions(|a| ApproveStrategyAccounts::try_from(a).map(drop), 6, &[0], &[0, 3]);
+        check_permissions(|a| ExecuteStrategyAccounts::try_from(a).map(drop), 2, &[0], &[1]);
+        check_permissions(|a| WithdrawInsuranceAccounts::try_from(a).map(drop), 11, &[0], &[1, 3, 4, 5, 6]);
+        check_permissions(
+            |a| EmergencyWithdrawProtocolSideAccounts::try_from(a).map(drop),
+            14,
+            &[0],
+            &[0, 2, 3, 4, 5, 6],
+        );
+        check_permissions(|a| ClaimRecoveryAccounts::try_from(a).map(drop), 14, &[0], &[2, 3, 4, 5, 6, 7, 8]);
+        check_permissions(|a| InitializeObservationTreeAccounts::try_from(a).map(drop), 7, &[0], &[0, 1, 2]);
+        check_permissions(|a| AppendObservationAccounts::try_from(a).map(drop), 8, &[], &[0, 4]);
+    }
+
+    #[test]
+    fn liquidity_instructions_check_signers_and_writable_accounts() {
+        // Optional position, system program and payer at 14 to 16
+        check_permissions(|a| DepositAccounts::try_from(a).map(drop), 17, &[0, 16], &[1, 2, 3, 4, 5, 6, 7, 14, 16]);
+        // Without a payer the user pays for the position
+        check_permissions(|a| DepositAccounts::try_from(a).map(drop), 16, &[0], &[0, 1, 2, 3, 4, 5, 6, 7, 14]);
+        check_permissions(|a| DepositWithPermitAccounts::try_from(a).map(drop), 19, &[0], &[0, 2]);
+        check_permissions(|a| WithdrawAccounts::try_from(a).map(drop), 14, &[0], &[0, 1, 2, 3, 4, 5, 6]);
+        check_permissions(|a| SwapAndDepositAccounts::try_from(a).map(drop), 16, &[0], &[1, 2, 3, 4, 5, 6, 7]);
+        check_permissions(|a| WithdrawAndSwapAccounts::try_from(a).map(drop), 16, &[0], &[0, 1, 2, 3, 4, 5, 6, 7]);
+        check_permissions(
+            |a| MigrateLiquidityAccounts::try_from(a).map(drop),
+            19,
+            &[0],
+            &[2, 3, 4, 5, 7, 8, 9, 10, 11, 12],
+        );
+        check_permissions(|a| OpenPositionAccounts::try_from(a).map(drop), 11, &[0, 3], &[0, 3, 4, 5, 6]);
+        check_permissions(|a| IncreasePositionAccounts::try_from(a).map(drop), 7, &[0], &[1, 2, 3]);
+        check_permissions(|a| DecreasePositionAccounts::try_from(a).map(drop), 6, &[0], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn swap_instructions_check_signers_and_writable_accounts() {
+        // Optional host fee, insurance vault, tip account and user stats at 11, 17, 18 and 20
+        check_permissions(|a| SwapAccounts::try_from(a).map(drop), 21, &[0], &[1, 2, 3, 4, 5, 11, 17, 18, 20]);
+        check_permissions(|a| RaydiumSwapAccounts::try_from(a).map(drop), 13, &[0], &[3, 4, 5, 6, 7]);
+        check_permissions(
+            |a| ArbitrageAccounts::try_from(a).map(drop),
+            18,
+            &[0],
+            &[1, 2, 8, 9, 10, 11, 12, 13],
+        );
+        // Two swaps of `SWAP_ACCOUNTS`, each writing its first five accounts
+        check_permissions(
+            |a| SwapBatchAccounts::try_from(a).map(drop),
+            3 + 2 * SwapBatch::SWAP_ACCOUNTS,
+            &[0],
+            &[3, 4, 5, 6, 7, 14, 15, 16, 17, 18],
+        );
+        check_permissions(|a| SwapWithQuoteAccounts::try_from(a).map(drop), 17, &[0, 1], &[1, 5, 6, 7, 8, 9]);
+        check_permissions(|a| WithdrawMakerEscrowAccounts::try_from(a).map(drop), 7, &[0], &[3, 4]);
+        check_permissions(|a| OpenLongTermOrderAccounts::try_from(a).map(drop), 13, &[0], &[0, 2, 3, 4, 5]);
+        check_permissions(|a| ExecuteLongTermOrderAccounts::try_from(a).map(drop), 16, &[], &[0]);
+        check_permissions(|a| CloseLongTermOrderAccounts::try_from(a).map(drop), 11, &[0], &[0, 2, 3, 4, 5, 6]);
+        check_permissions(|a| SnapshotBalanceAccounts::try_from(a).map(drop), 6, &[0], &[0, 3]);
+    }
+
+    #[test]
+    fn multi_pool_instructions_check_signers_and_writable_accounts() {
+        check_permissions(|a| InitializeMultiPoolAccounts::try_from(a).map(drop), 9, &[0], &[0, 1, 2, 6]);
+        // Two tokens of `TOKEN_ACCOUNTS`, each writing its vault and user account
+        check_permissions(
+            |a| MultiLiquidityAccounts::try_from(a).map(drop),
+            6 + 2 * MultiLiquidityAccounts::TOKEN_ACCOUNTS,
+            &[0],
+            &[2, 3, 7, 8, 11, 12],
+        );
+        check_permissions(|a| MultiSwapAccounts::try_from(a).map(drop), 11, &[0], &[4, 5, 8, 9]);
+    }
+
+    #[test]
+    fn launch_instructions_check_signers_and_writable_accounts() {
+        check_permissions(|a| InitializeLaunchAccounts::try_from(a).map(drop), 12, &[0], &[0, 1, 4, 5, 6]);
+        check_permissions(|a| LaunchTradeAccounts::try_from(a).map(drop), 11, &[0], &[1, 2, 3, 4, 5]);
+        check_permissions(
+            |a| GraduateLaunchAccounts::try_from(a).map(drop),
+            18,
+            &[0],
+            &[0, 1, 2, 3, 4, 5, 6, 9, 10, 14],
+        );
+        check_permissions(|a| InitializeLbpAccounts::try_from(a).map(drop), 13, &[0], &[0, 1, 4, 5, 6, 7]);
+        check_permissions(|a| SwapLbpAccounts::try_from(a).map(drop), 11, &[0], &[1, 2, 3, 4, 5]);
+        check_permissions(|a| ExitLbpAccounts::try_from(a).map(drop), 10, &[0], &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn admin_instructions_check_signers_and_writable_accounts() {
+        check_permissions(|a| InitializeGlobalAccounts::try_from(a).map(drop), 3, &[0], &[0, 1]);
+        check_permissions(|a| UpdateGlobalConfigAccounts::try_from(a).map(drop), 2, &[0], &[1]);
+        // Two fee recipients after the fixed accounts
+        check_permissions(
+            |a| CollectProtocolFeesAccounts::try_from(a).map(drop),
+            13,
+            &[0],
+            &[2, 3, 4, 5, 6, 11, 12],
+        );
+        check_permissions(|a| WithdrawTreasuryAccounts::try_from(a).map(drop), 5, &[0], &[2, 3]);
+        check_permissions(|a| SetTrustedRouterAccounts::try_from(a).map(drop), 5, &[0], &[0, 3]);
+        check_permissions(|a| SetTokenBadgeAccounts::try_from(a).map(drop), 5, &[0], &[0, 3]);
+        check_permissions(|a| InitializeFeeDistributorAccounts::try_from(a).map(drop), 11, &[0], &[0, 2, 5, 6]);
+    }
+
+    #[test]
+    fn governance_instructions_check_signers_and_writable_accounts() {
+        check_permissions(|a| StakeGovernanceAccounts::try_from(a).map(drop), 9, &[0], &[0, 1, 2, 3, 4]);
+        check_permissions(|a| UnstakeGovernanceAccounts::try_from(a).map(drop), 7, &[0], &[1, 2, 3, 4]);
+        check_permissions(|a| ClaimStakingRewardsAccounts::try_from(a).map(drop), 7, &[0], &[1, 2, 3, 4]);
+        check_permissions(|a| CheckpointFeesAccounts::try_from(a).map(drop), 3, &[], &[0]);
+    }
+
+    #[test]
+    fn open_position_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| OpenPosition::try_from((d, a))?.process(), &[255], 11, 10);
+    }
+
+    #[test]
+    fn increase_position_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| IncreasePosition::try_from((d, a))?.process(), &1u64.to_le_bytes(), 7, 6);
+    }
+
+    #[test]
+    fn approve_strategy_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| ApproveStrategy::try_from((d, a))?.process(), &[0; 25], 6, 5);
+    }
+
+    #[test]
+    fn checkpoint_fees_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| CheckpointFees::try_from((d, a))?.process(), &[], 3, 2);
+    }
+
+    #[test]
+    fn initialize_observation_tree_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| InitializeObservationTree::try_from((d, a))?.process(), &[], 7, 6);
+    }
+
+    #[test]
+    fn append_observation_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| AppendObservation::try_from((d, a))?.process(), &[], 8, 7);
+    }
+
+    #[test]
+    fn initialize_vaults_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| InitializeVaults::try_from((d, a))?.process(), &[], 10, 9);
+    }
+
+    #[test]
+    fn initialize_user_stats_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| InitializeUserStats::try_from((d, a))?.process(), &[], 6, 5);
+    }
+
+    #[test]
+    fn initialize_registry_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| InitializeRegistry::try_from((d, a))?.process(), &[0; 5], 4, 3);
+    }
+
+    #[test]
+    fn collect_protocol_fees_refuses_upgrade_frozen_deployments() {
+        check_frozen(|d, a| CollectProtocolFees::try_from((d, a))?.process(), &[], 11, 1);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn initialize_test_pool_checks_signers_and_writable_accounts() {
+        check_permissions(
+            |a| InitializeTestPoolAccounts::try_from(a).map(drop),
+            15,
+            &[0, 3, 4],
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 14],
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::state::{get_extension, AmmState, Config, MultiPool, VaultBumps, VaultStrategy};
+use crate::{create_pda_vault_address, derive_ata, derive_vaults, AmmError, CheckedLpMint, CheckedVault};
+use pinocchio::{account_info::{AccountInfo, Ref}, program_error::ProgramError, pubkey::Pubkey};
+
+/// A pool config with its vaults, checked by `validate_pool_accounts`
+pub struct PoolAccounts<'a> {
+    pub config: Ref<'a, Config>,
+    pub vault_x: CheckedVault<'a>,
+    pub vault_y: CheckedVault<'a>,
+    /// X and Y moved to the `VaultStrategy` accounts, still part of the reserves
+    pub deployed: (u64, u64),
+}
+
+/// Load the pool at `config` and check the accounts an instruction passes for it:
+///
+/// - the pool is in one of `states`
+/// - `mints` and `token_programs` are the pool's X and Y mints and their token programs
+/// - `vaults` are the config's ATAs of each mint, or its program-derived vaults with
+///   `Config::FLAG_PDA_VAULTS`
+#[inline(always)]
+pub fn validate_pool_accounts<'a>(
+    config: &'a AccountInfo,
+    vaults: (&'a AccountInfo, &'a AccountInfo),
+    mints: (&AccountInfo, &AccountInfo),
+    token_programs: (&AccountInfo, &AccountInfo),
+    states: &[AmmState],
+) -> Result<PoolAccounts<'a>, ProgramError> {
+    let pool_accounts = validate_trusted_pool_accounts(config, vaults, mints, token_programs, states)?;
+
+    // Check if the vaults are valid
+    let (vault_x, vault_y) = pool_vault_addresses(config, &pool_accounts.config)?;
+
+    if vault_x.ne(vaults.0.key()) || vault_y.ne(vaults.1.key()) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(pool_accounts)
+}
+
+/// Addresses of the X and Y vaults of the pool at `config`: its ATAs of each mint, or its
+/// program-derived vaults with `Config::FLAG_PDA_VAULTS`, checked from their stored bumps.
+#[inline(always)]
+pub fn pool_vault_addresses(config: &AccountInfo, config_data: &Config) -> Result<(Pubkey, Pubkey), ProgramError> {
+    match config_data.has_pda_vaults() {
+        true => {
+            let data = config.try_borrow_data()?;
+            let bumps = get_extension::<VaultBumps>(&data)?.ok_or(AmmError::MissingExtension)?;
+
+            Ok((
+                create_pda_vault_address(config.key(), config_data.mint_x(), bumps.vault_x_bump())?,
+                create_pda_vault_address(config.key(), config_data.mint_y(), bumps.vault_y_bump())?,
+            ))
+        }
+        false => Ok(derive_vaults(
+            config.key(),
+            config_data.mint_x(),
+            config_data.token_program_x(),
+            config_data.mint_y(),
+            config_data.token_program_y(),
+        )),
+    }
+}
+
+/// Reserves and LP supply of the pool at `config`, for read-only instructions that only take its
+/// vaults and LP mint, checked at the pool's addresses.
+#[inline(always)]
+pub fn pool_reserves(
+    config: &AccountInfo,
+    config_data: &Config,
+    (vault_x, vault_y): (&AccountInfo, &AccountInfo),
+    mint_lp: &AccountInfo,
+) -> Result<(u64, u64, u64), ProgramError> {
+    let (vault_x_key, vault_y_key) = pool_vault_addresses(config, config_data)?;
+
+    if vault_x_key.ne(vault_x.key()) || vault_y_key.ne(vault_y.key()) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let vault_x = CheckedVault::new(vault_x, config_data.mint_x(), config.key(), config_data.token_program_x())?;
+    let vault_y = CheckedVault::new(vault_y, config_data.mint_y(), config.key(), config_data.token_program_y())?;
+    let lp_supply = CheckedLpMint::new(mint_lp, config.key())?.supply();
+
+    let deployed = match get_extension::<VaultStrategy>(&config.try_borrow_data()?)? {
+        Some(strategy) => (strategy.deployed_x(), strategy.deployed_y()),
+        None => (0, 0),
+    };
+    let (reserve_x, reserve_y) = config_data.reserves(vault_x.amount(), vault_y.amount(), deployed);
+
+    Ok((reserve_x, reserve_y, lp_supply))
+}
+
+/// `validate_pool_accounts` without deriving the vault addresses, for callers vouched for by a
+/// `TrustedRouter`.
+///
+/// The vaults must still be token accounts of the pool mints owned by the config.
+#[inline(always)]
+pub fn validate_trusted_pool_accounts<'a>(
+    config: &'a AccountInfo,
+    vaults: (&'a AccountInfo, &'a AccountInfo),
+    mints: (&AccountInfo, &AccountInfo),
+    token_programs: (&AccountInfo, &AccountInfo),
+    states: &[AmmState],
+) -> Result<PoolAccounts<'a>, ProgramError> {
+    let config_data = Config::load(config)?;
+
+    // Check the pool accepts the instruction in its current state
+    if !states.contains(&config_data.amm_state()?) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check the pool mints and the token program of each side
+    if mints.0.key().ne(config_data.mint_x())
+        || mints.1.key().ne(config_data.mint_y())
+        || token_programs.0.key().ne(config_data.token_program_x())
+        || token_programs.1.key().ne(config_data.token_program_y())
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let vault_x = CheckedVault::new(vaults.0, mints.0.key(), config.key(), token_programs.0.key())?;
+    let vault_y = CheckedVault::new(vaults.1, mints.1.key(), config.key(), token_programs.1.key())?;
+
+    let deployed = match get_extension::<VaultStrategy>(&config.try_borrow_data()?)? {
+        Some(strategy) => (strategy.deployed_x(), strategy.deployed_y()),
+        None => (0, 0),
+    };
+
+    Ok(PoolAccounts {
+        config: config_data,
+        vault_x,
+        vault_y,
+        deployed,
+    })
+}
+
+/// Check the accounts a multi-asset instruction passes for the `index` token of `pool`: its mint,
+/// its token program and the pool's ATA as vault.
+#[inline(always)]
+pub fn validate_multi_pool_token<'a>(
+    pool: &AccountInfo,
+    pool_data: &MultiPool,
+    index: usize,
+    mint: &AccountInfo,
+    vault: &'a AccountInfo,
+    token_program: &AccountInfo,
+) -> Result<CheckedVault<'a>, ProgramError> {
+    let expected_mint = pool_data.mints().get(index).ok_or(ProgramError::InvalidInstructionData)?;
+
+    if mint.key().ne(expected_mint) || token_program.key().ne(pool_data.token_program(index)) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if derive_ata(pool.key(), token_program.key(), mint.key()).ne(vault.key()) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    CheckedVault::new(vault, mint.key(), pool.key(), token_program.key())
+}